@@ -0,0 +1,25 @@
+//! Feeds arbitrary source code to both engines' parsers, to make sure neither panics on malformed
+//! input. (Programs aren't run, as they could loop forever.)
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|source: &str| {
+	let flags = knightrs::env::Flags::default();
+	if let Ok(source) = knightrs::value::text::TextSlice::new(source, &flags) {
+		let mut env = knightrs::env::Environment::new(&flags);
+		let _ = knightrs::parse::Parser::new(source, &mut env).parse_program();
+	}
+
+	// SAFETY: Nothing allocated within the `gc` escapes.
+	unsafe {
+		knightrs_bytecode::Gc::default().run(|gc| {
+			use knightrs_bytecode::parser::{source_location::ProgramSource, Parser};
+
+			let mut env = knightrs_bytecode::Environment::new(Default::default(), gc);
+			if let Ok(parser) = Parser::new(&mut env, ProgramSource::ExprFlag, source) {
+				let _ = parser.parse_program();
+			}
+		})
+	}
+});
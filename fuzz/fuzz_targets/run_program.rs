@@ -0,0 +1,174 @@
+//! Runs structurally valid programs under both engines, to exercise the deeper parts of the
+//! interpreters (and especially the VM's stack manipulation) without getting stuck on parse errors.
+//!
+//! `WHILE`, `BLOCK`, and `CALL` aren't generated, so every program terminates, and integer literals
+//! are kept small so `*` can't build up huge strings and lists.
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use std::fmt::{self, Display, Formatter};
+
+/// How deeply expressions can be nested before only leaves are rendered.
+const MAX_DEPTH: usize = 6;
+
+#[derive(Debug, Arbitrary)]
+enum Expr {
+	Null,
+	Boolean(bool),
+	Integer(u8),
+	Text(Vec<Char>),
+	Variable(Var),
+	Prompt,
+	Random,
+	Unary(Unary, Box<Expr>),
+	Binary(Binary, Box<Expr>, Box<Expr>),
+	Assign(Var, Box<Expr>),
+	If(Box<Expr>, Box<Expr>, Box<Expr>),
+	Get(Box<Expr>, Box<Expr>, Box<Expr>),
+	Set(Box<Expr>, Box<Expr>, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Arbitrary)]
+enum Var {
+	A,
+	B,
+	C,
+}
+
+#[derive(Debug, Arbitrary)]
+enum Char {
+	Lower(u8),
+	Digit(u8),
+	Space,
+	Newline,
+}
+
+#[derive(Debug, Arbitrary)]
+enum Unary {
+	Quit,
+	Output,
+	Dump,
+	Length,
+	Not,
+	Negate,
+	Ascii,
+	Box,
+	Head,
+	Tail,
+}
+
+#[derive(Debug, Arbitrary)]
+enum Binary {
+	Add,
+	Sub,
+	Mul,
+	Div,
+	Mod,
+	Pow,
+	Lth,
+	Gth,
+	Eql,
+	And,
+	Or,
+	Then,
+}
+
+/// Renders an expression as Knight source code, at the given depth.
+struct Render<'a>(&'a Expr, usize);
+
+impl Render<'_> {
+	fn nested<'b>(&self, expr: &'b Expr) -> Render<'b> {
+		Render(expr, self.1 + 1)
+	}
+}
+
+impl Display for Var {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		f.write_str(match self {
+			Self::A => "a",
+			Self::B => "b",
+			Self::C => "c",
+		})
+	}
+}
+
+impl Display for Render<'_> {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		let Render(expr, depth) = *self;
+		let sub = |expr| self.nested(expr);
+
+		if depth >= MAX_DEPTH {
+			return f.write_str("0");
+		}
+
+		match expr {
+			Expr::Null => f.write_str("NULL"),
+			Expr::Boolean(b) => f.write_str(if *b { "TRUE" } else { "FALSE" }),
+			Expr::Integer(int) => write!(f, "{}", int % 16),
+			Expr::Text(chars) => {
+				f.write_str("\"")?;
+				for chr in chars {
+					match chr {
+						Char::Lower(c) => write!(f, "{}", (b'a' + c % 26) as char)?,
+						Char::Digit(c) => write!(f, "{}", c % 10)?,
+						Char::Space => f.write_str(" ")?,
+						Char::Newline => f.write_str("\n")?,
+					}
+				}
+				f.write_str("\"")
+			}
+			Expr::Variable(var) => write!(f, "{var}"),
+			Expr::Prompt => f.write_str("PROMPT"),
+			Expr::Random => f.write_str("RANDOM"),
+			Expr::Unary(op, arg) => {
+				let name = match op {
+					Unary::Quit => "QUIT",
+					Unary::Output => "OUTPUT",
+					Unary::Dump => "DUMP",
+					Unary::Length => "LENGTH",
+					Unary::Not => "!",
+					Unary::Negate => "~",
+					Unary::Ascii => "ASCII",
+					Unary::Box => ",",
+					Unary::Head => "[",
+					Unary::Tail => "]",
+				};
+				write!(f, "{name} {}", sub(arg))
+			}
+			Expr::Binary(op, lhs, rhs) => {
+				let name = match op {
+					Binary::Add => "+",
+					Binary::Sub => "-",
+					Binary::Mul => "*",
+					Binary::Div => "/",
+					Binary::Mod => "%",
+					Binary::Pow => "^",
+					Binary::Lth => "<",
+					Binary::Gth => ">",
+					Binary::Eql => "?",
+					Binary::And => "&",
+					Binary::Or => "|",
+					Binary::Then => ";",
+				};
+				write!(f, "{name} {} {}", sub(lhs), sub(rhs))
+			}
+			Expr::Assign(var, value) => write!(f, "= {var} {}", sub(value)),
+			Expr::If(cond, ift, iff) => write!(f, "IF {} {} {}", sub(cond), sub(ift), sub(iff)),
+			Expr::Get(coll, start, len) => write!(f, "GET {} {} {}", sub(coll), sub(start), sub(len)),
+			Expr::Set(coll, start, len, repl) => {
+				write!(f, "SET {} {} {} {}", sub(coll), sub(start), sub(len), sub(repl))
+			}
+		}
+	}
+}
+
+fuzz_target!(|input: (Expr, String)| {
+	let (expr, stdin) = input;
+
+	// Assign every variable first, as their values are only checked for when `check-variables` is on.
+	let source = format!("; = a 1 ; = b \"\" ; = c ,0 {}", Render(&expr, 0));
+
+	let _ = knightrs::testing::run(&source, &stdin, &Default::default());
+	let _ = knightrs_bytecode::testing::run(&source, &stdin, Default::default());
+});
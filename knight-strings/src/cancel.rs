@@ -0,0 +1,49 @@
+//! Stopping Knight programs from another thread.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A handle which can stop an interpreter's programs while they're running.
+///
+/// Both interpreters hand these out via their environments' `cancel_handle` methods. Handles can be
+/// cloned and sent to other threads (eg a SIGINT handler, or a timeout), and after
+/// [`cancel`](Self::cancel) is called, the interpreter stops its program the next time it checks
+/// (via [`take`](Self::take)).
+///
+/// # Examples
+/// ```
+/// # use knight_strings::CancelHandle;
+/// let handle = CancelHandle::default();
+/// let other = handle.clone();
+///
+/// std::thread::spawn(move || other.cancel()).join().unwrap();
+/// assert!(handle.is_cancelled());
+/// assert!(handle.take());
+/// assert!(!handle.is_cancelled());
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct CancelHandle(Arc<AtomicBool>);
+
+impl CancelHandle {
+	/// Stops the program that's currently running (or, if none is, the next one that's run).
+	pub fn cancel(&self) {
+		self.0.store(true, Ordering::Relaxed);
+	}
+
+	/// Returns whether [`cancel`](Self::cancel) was called, and the program hasn't stopped yet.
+	#[must_use]
+	pub fn is_cancelled(&self) -> bool {
+		self.0.load(Ordering::Relaxed)
+	}
+
+	/// Returns whether a cancellation was requested, resetting it.
+	///
+	/// This is what interpreters call to observe cancellations, so that they can run other programs
+	/// afterwards.
+	#[inline]
+	pub fn take(&self) -> bool {
+		// Only `swap` if it's set, so the common case doesn't need exclusive access to the cache
+		// line.
+		self.is_cancelled() && self.0.swap(false, Ordering::Relaxed)
+	}
+}
@@ -0,0 +1,57 @@
+use crate::Encoding;
+use std::fmt::{self, Display, Formatter};
+
+/// A `char` which is valid within some [`Encoding`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Character(char);
+
+impl Character {
+	/// Returns `chr` as a `Character`, if it's valid within `encoding`.
+	#[inline]
+	pub const fn new(chr: char, encoding: Encoding) -> Option<Self> {
+		if encoding.is_char_valid(chr) {
+			Some(Self(chr))
+		} else {
+			None
+		}
+	}
+
+	/// Returns `chr` as a `Character`, without checking whether it's valid.
+	///
+	/// # Safety
+	/// `chr` must be valid within whichever [`Encoding`] the caller is using.
+	#[inline]
+	pub const unsafe fn new_unchecked(chr: char) -> Self {
+		Self(chr)
+	}
+
+	/// Gets the underlying `char`.
+	#[inline]
+	pub const fn inner(self) -> char {
+		self.0
+	}
+}
+
+impl PartialEq<char> for Character {
+	fn eq(&self, rhs: &char) -> bool {
+		self.0 == *rhs
+	}
+}
+
+impl AsRef<char> for Character {
+	fn as_ref(&self) -> &char {
+		&self.0
+	}
+}
+
+impl std::borrow::Borrow<char> for Character {
+	fn borrow(&self) -> &char {
+		&self.0
+	}
+}
+
+impl Display for Character {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		Display::fmt(&self.0, f)
+	}
+}
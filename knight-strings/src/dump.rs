@@ -0,0 +1,58 @@
+use std::fmt::{self, Display, Formatter, Write};
+
+/// The representation of a string that `DUMP` writes, as returned by [`dump`].
+///
+/// The Knight spec says strings are dumped within double quotes, with only `\`, `"`, newlines,
+/// tabs, and carriage returns escaped (as `\\`, `\"`, `\n`, `\t`, and `\r`, respectively). Every
+/// other character is written as-is. This differs from Rust's [`Debug`](std::fmt::Debug) for
+/// `str`, which also escapes things like other control characters and some unicode characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dump<'a>(&'a str);
+
+/// Returns something that [`Display`]s `source` how `DUMP` is required to write it.
+///
+/// # Examples
+/// These are the examples from the Knight spec (as well as a few edge cases):
+/// ```
+/// # use knight_strings::dump;
+/// assert_eq!(dump("").to_string(), r#""""#);
+/// assert_eq!(dump("hello").to_string(), r#""hello""#);
+/// assert_eq!(dump("a\"b").to_string(), r#""a\"b""#);
+/// assert_eq!(dump("a\\b").to_string(), r#""a\\b""#);
+/// assert_eq!(dump("\r\n\t").to_string(), r#""\r\n\t""#);
+/// assert_eq!(dump("'").to_string(), r#""'""#);
+/// assert_eq!(dump("\\n").to_string(), r#""\\n""#);
+///
+/// // Nothing else is escaped, unlike Rust's `{:?}`.
+/// assert_eq!(dump("\x7F\0é").to_string(), "\"\x7F\0é\"");
+/// ```
+#[inline]
+pub const fn dump(source: &str) -> Dump<'_> {
+	Dump(source)
+}
+
+impl Display for Dump<'_> {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		f.write_char('"')?;
+
+		// Write out unescaped runs all at once, rather than a character at a time.
+		let mut start = 0;
+		for (idx, byte) in self.0.bytes().enumerate() {
+			let escaped = match byte {
+				b'\\' => r"\\",
+				b'"' => r#"\""#,
+				b'\n' => r"\n",
+				b'\t' => r"\t",
+				b'\r' => r"\r",
+				_ => continue,
+			};
+
+			f.write_str(&self.0[start..idx])?;
+			f.write_str(escaped)?;
+			start = idx + 1;
+		}
+
+		f.write_str(&self.0[start..])?;
+		f.write_char('"')
+	}
+}
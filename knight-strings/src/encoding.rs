@@ -0,0 +1,63 @@
+use std::fmt::{self, Display, Formatter};
+
+/// Encoding is the different sets of characters that Knight strings can be made of.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+	/// All UTF-8 strings are valid, i.e. any `str` is a valid Knight string.
+	#[default]
+	Utf8,
+
+	/// Only the strict Knight subset is valid.
+	Knight,
+
+	/// Only ASCII-based strings are valid; any other UTF-8 string is invalid.
+	Ascii,
+}
+
+/// The error that's returned from [`Encoding::validate`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct EncodingError {
+	pub encoding: Encoding, // todo: dont make pub lol make fns
+	pub position: usize,
+	pub character: char,
+}
+
+impl std::error::Error for EncodingError {}
+impl Display for EncodingError {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		write!(
+			f,
+			"encoding error: character {:?} at offset {} isn't valid in {:?} encoding",
+			self.character, self.position, self.encoding
+		)
+	}
+}
+
+impl Encoding {
+	/// Returns whether `chr` is allowed in this encoding.
+	pub const fn is_char_valid(self, chr: char) -> bool {
+		match self {
+			Self::Utf8 => true,
+			Self::Ascii => chr.is_ascii(),
+			Self::Knight => matches!(chr, '\r' | '\n' | '\t' | ' '..='~'),
+		}
+	}
+
+	/// Validate checks to see if `source` only contains valid characters within the encoding.
+	///
+	/// Note that this doesn't check for the length of the `source`, which is also required by Knight
+	/// compliance; see [`is_valid_length`](crate::is_valid_length) for that.
+	///
+	/// The [`position`](EncodingError::position) of the returned error is a byte offset.
+	pub fn validate(self, source: &str) -> Result<(), EncodingError> {
+		// all `str`s are valid utf8
+		if self == Self::Utf8 {
+			return Ok(());
+		}
+
+		match source.char_indices().find(|&(_, chr)| !self.is_char_valid(chr)) {
+			Some((position, character)) => Err(EncodingError { encoding: self, position, character }),
+			None => Ok(()),
+		}
+	}
+}
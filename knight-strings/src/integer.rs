@@ -0,0 +1,112 @@
+use std::fmt::{self, Display, Formatter};
+
+/// What happens when a string containing an out-of-bounds integer is converted to an integer.
+///
+/// The Knight spec leaves this undefined.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum IntegerOverflow {
+	/// Wrap around, as if the digits were accumulated with wrapping arithmetic.
+	#[default]
+	Wrap,
+
+	/// Use the smallest or largest integer, depending on the sign.
+	Saturate,
+
+	/// Return an [`IntegerOverflowError`].
+	Error,
+}
+
+/// The error that's returned from [`parse_integer`] when the integer is out of bounds and
+/// [`IntegerOverflow::Error`] is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IntegerOverflowError;
+
+impl std::error::Error for IntegerOverflowError {}
+impl Display for IntegerOverflowError {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		f.write_str("integer in string is out of bounds")
+	}
+}
+
+/// Converts `source` to an integer, as per the Knight spec's string -> integer conversion.
+///
+/// Leading ASCII whitespace is skipped, then an optional `+` or `-`, and then as many ASCII digits
+/// as possible; anything after that is ignored. If there are no digits, `0` is returned.
+///
+/// The result has to fit within a `bits`-bit signed integer (which must be within `1..=64`); if it
+/// doesn't, `overflow` determines what happens.
+///
+/// # Examples
+/// ```
+/// use knight_strings::IntegerOverflow::{Error, Saturate, Wrap};
+/// use knight_strings::{parse_integer, IntegerOverflowError};
+///
+/// assert_eq!(parse_integer("  \n+12abc", 64, Wrap), Ok(12));
+/// assert_eq!(parse_integer("- 3", 64, Wrap), Ok(0));
+/// assert_eq!(parse_integer("", 64, Wrap), Ok(0));
+///
+/// // ±2³¹, for 32-bit integers.
+/// assert_eq!(parse_integer("2147483647", 32, Error), Ok(2147483647));
+/// assert_eq!(parse_integer("-2147483648", 32, Error), Ok(-2147483648));
+/// assert_eq!(parse_integer("2147483648", 32, Error), Err(IntegerOverflowError));
+/// assert_eq!(parse_integer("-2147483649", 32, Error), Err(IntegerOverflowError));
+/// assert_eq!(parse_integer("2147483648", 32, Wrap), Ok(-2147483648));
+/// assert_eq!(parse_integer("-2147483649", 32, Wrap), Ok(2147483647));
+/// assert_eq!(parse_integer("2147483648", 32, Saturate), Ok(2147483647));
+/// assert_eq!(parse_integer("-2147483649", 32, Saturate), Ok(-2147483648));
+///
+/// // ±2⁶³, for 64-bit integers.
+/// assert_eq!(parse_integer("9223372036854775807", 64, Error), Ok(i64::MAX));
+/// assert_eq!(parse_integer("-9223372036854775808", 64, Error), Ok(i64::MIN));
+/// assert_eq!(parse_integer("9223372036854775808", 64, Error), Err(IntegerOverflowError));
+/// assert_eq!(parse_integer("-9223372036854775809", 64, Error), Err(IntegerOverflowError));
+/// assert_eq!(parse_integer("9223372036854775808", 64, Wrap), Ok(i64::MIN));
+/// assert_eq!(parse_integer("-9223372036854775809", 64, Wrap), Ok(i64::MAX));
+/// assert_eq!(parse_integer("99999999999999999999", 64, Saturate), Ok(i64::MAX));
+/// assert_eq!(parse_integer("-99999999999999999999", 64, Saturate), Ok(i64::MIN));
+/// ```
+pub fn parse_integer(
+	source: &str,
+	bits: u32,
+	overflow: IntegerOverflow,
+) -> Result<i64, IntegerOverflowError> {
+	debug_assert!((1..=64).contains(&bits), "invalid bit count: {bits}");
+
+	let shift = 64 - bits;
+	let (min, max) = (i64::MIN >> shift, i64::MAX >> shift);
+
+	let source = source.trim_start_matches(|c: char| c.is_ascii_whitespace());
+	let (is_negative, digits) = match source.as_bytes().first() {
+		Some(b'-') => (true, &source[1..]),
+		Some(b'+') => (false, &source[1..]),
+		_ => (false, source),
+	};
+
+	// Negative numbers are accumulated downwards, so that `min` itself doesn't overflow.
+	let mut wrapped = 0i64;
+	let mut exact = Some(0i64);
+	for digit in digits.bytes().take_while(u8::is_ascii_digit) {
+		let digit = i64::from(digit - b'0');
+
+		if is_negative {
+			wrapped = wrapped.wrapping_mul(10).wrapping_sub(digit);
+			exact = exact.and_then(|n| n.checked_mul(10)?.checked_sub(digit));
+		} else {
+			wrapped = wrapped.wrapping_mul(10).wrapping_add(digit);
+			exact = exact.and_then(|n| n.checked_mul(10)?.checked_add(digit));
+		}
+
+		exact = exact.filter(|n| (min..=max).contains(n));
+	}
+
+	match (exact, overflow) {
+		(Some(integer), _) => Ok(integer),
+		// Truncate `wrapped` to `bits` bits, and then sign-extend it.
+		(None, IntegerOverflow::Wrap) => Ok((wrapped << shift) >> shift),
+		(None, IntegerOverflow::Saturate) => Ok(if is_negative { min } else { max }),
+		(None, IntegerOverflow::Error) => Err(IntegerOverflowError),
+	}
+}
@@ -0,0 +1,47 @@
+//! The string rules shared by both Knight interpreters.
+//!
+//! Both `knightrs` and `knightrs-bytecode` have their own string types (as they're tied to their
+//! own memory management), but what makes a string valid is the same for both: which characters
+//! are allowed (see [`Encoding`] and [`Character`]), and how long a string can be (see
+//! [`MAX_LEN`]). Keeping those rules here means the two interpreters can't disagree about them.
+//! The same goes for how strings are written by `DUMP` (see [`dump`]), how they're converted to
+//! integers (see [`parse_integer`]), and how source code is split into tokens (see [`syntax`]).
+//!
+//! [`CancelHandle`] isn't about strings, but it lives here too, as it's the one piece of the
+//! environments that both interpreters share.
+
+mod cancel;
+mod character;
+mod dump;
+mod encoding;
+mod integer;
+pub mod syntax;
+
+pub use cancel::CancelHandle;
+pub use character::Character;
+pub use dump::{dump, Dump};
+pub use encoding::{Encoding, EncodingError};
+pub use integer::{parse_integer, IntegerOverflow, IntegerOverflowError};
+
+/// The maximum length of a Knight string (or list), when container lengths are being checked.
+///
+/// The Knight specs only require implementations to support lengths up to [`i32::MAX`].
+pub const MAX_LEN: usize = i32::MAX as usize;
+
+/// Returns whether `len` is at most [`MAX_LEN`].
+#[inline]
+pub const fn is_valid_length(len: usize) -> bool {
+	len <= MAX_LEN
+}
+
+/// Returns whether a string or list of length `len` is allowed when container lengths are being
+/// checked, with `max` as the configured maximum (or [`MAX_LEN`], if none was configured).
+///
+/// Both interpreters' `Compliance::is_valid_container_length` defer to this.
+#[inline]
+pub const fn is_valid_container_length(len: usize, max: Option<usize>) -> bool {
+	match max {
+		Some(max) => len <= max,
+		None => is_valid_length(len),
+	}
+}
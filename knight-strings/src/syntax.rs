@@ -0,0 +1,52 @@
+//! The lexical rules of Knight source code, shared by both interpreters' parsers.
+//!
+//! Whitespace, comments, and where identifiers end are easy to get subtly different between two
+//! hand-written parsers (e.g. a comment right at the end of the source, or a `#` inside a string),
+//! so the rules live here instead. Programs that exercise those edge cases are part of the
+//! `knightrs-conformance` corpus, which both interpreters are checked against.
+
+/// The character which starts a comment. Comments run until the end of the line (or source), and
+/// don't include the newline itself.
+///
+/// Within string literals, this is just a normal character.
+pub const COMMENT_START: char = '#';
+
+/// Returns whether `chr` is whitespace.
+///
+/// The Knight spec only requires tabs, newlines, carriage returns, and spaces to be supported,
+/// but as every other whitespace character is outside of the Knight [encoding](crate::Encoding)
+/// anyway, all of Unicode's whitespace is accepted.
+///
+/// Note that `(` and `)` (which are whitespace unless parens are being checked) and `:` (which is
+/// a function) aren't included.
+#[inline]
+pub fn is_whitespace(chr: char) -> bool {
+	chr.is_whitespace()
+}
+
+/// Returns whether `chr` is a parenthesis.
+#[inline]
+pub const fn is_paren(chr: char) -> bool {
+	matches!(chr, '(' | ')')
+}
+
+/// Returns whether `chr` can start a variable name.
+#[inline]
+pub fn is_variable_start(chr: char) -> bool {
+	chr.is_ascii_lowercase() || chr == '_'
+}
+
+/// Returns whether `chr` can be in a variable name after its first character.
+#[inline]
+pub fn is_variable_char(chr: char) -> bool {
+	is_variable_start(chr) || chr.is_ascii_digit()
+}
+
+/// Returns whether `chr` is part of a keyword function's name (such as `OUTPUT`).
+///
+/// Everything after a keyword function's first character is ignored, so `O`, `OUTPUT`, and
+/// `OUT_PUT` are all the same function.
+#[inline]
+pub fn is_keyword_char(chr: char) -> bool {
+	chr.is_uppercase() || chr == '_'
+}
@@ -0,0 +1,70 @@
+//! Times arithmetic- and string-heavy programs, which spend most of their time in the binary
+//! operators (`+`, `-`, `*`, `%`, `<`, `>`, and `?`).
+//!
+//! Run via `cargo bench -p knightrs-bytecode --bench operators`. Each program is run a handful of
+//! times, and the fastest run is reported, as that's the least affected by whatever else the
+//! machine is doing.
+//!
+//! These were used to check whether per-instruction inline caches (which remember the operand types
+//! an operator saw last, and skip straight to a specialized version) would be worth adding. They
+//! weren't: every workload ran within noise of the uncached VM, as the operators already check for
+//! two integers and two strings before anything else, and the time goes to the dispatch loop and
+//! allocating strings instead.
+
+use knightrs_bytecode::parser::{source_location::ProgramSource, Parser};
+use knightrs_bytecode::vm::Vm;
+use knightrs_bytecode::{Environment, Gc, Options};
+use std::time::{Duration, Instant};
+
+const RUNS: usize = 10;
+
+// Every operator sees two integers, every time.
+const ARITHMETIC: &str = "
+	; = i 0 ; = n 0
+	: WHILE < i 1000000
+		; = n % (+ (* n 3) (- i 7)) 1000003
+		; = n + n > i n
+		: = i + i 1";
+
+// Every operator sees two strings, except the loop's counter.
+const STRINGS: &str = r#"
+	; = i 0 ; = s "" ; = t "abc"
+	: WHILE < i 300000
+		; = s + GET t 0 1 GET t 1 2
+		; = t IF < s t + s "z" + t "y"
+		; = t IF ? s t "abc" GET t 0 3
+		: = i + i 1"#;
+
+// The same `+` sees integers and strings on alternate iterations.
+const MIXED: &str = r#"
+	; = i 0
+	: WHILE < i 1000000
+		; = x IF % i 2 "ab" 1
+		; = y + x IF % i 2 "cd" 2
+		: = i + i 1"#;
+
+// Parses and runs `source`, returning how long it took to run.
+fn time(source: &str) -> Duration {
+	// SAFETY: nothing allocated by the `gc` escapes it.
+	unsafe {
+		Gc::default().run(|gc| {
+			let mut env = Environment::new(Options::default(), gc);
+			let program = Parser::new(&mut env, ProgramSource::ExprFlag, source)
+				.and_then(|parser| parser.parse_program())
+				.expect("the benchmark is valid Knight");
+
+			let start = Instant::now();
+			Vm::new(&program, &mut env)
+				.run_entire_program_without_argv()
+				.expect("the benchmark runs successfully");
+			start.elapsed()
+		})
+	}
+}
+
+fn main() {
+	for (name, source) in [("arithmetic", ARITHMETIC), ("strings", STRINGS), ("mixed", MIXED)] {
+		let fastest = (0..RUNS).map(|_| time(source)).min().unwrap_or_default();
+		println!("{name:>10}: {fastest:>12?}");
+	}
+}
@@ -0,0 +1,66 @@
+//! Times print-heavy programs, to compare `OUTPUT` of string constants (which is compiled to a
+//! single `OutputConst`) against `OUTPUT` of the same string from a variable (which is a load and
+//! then an `Output`, as every other `OUTPUT` is).
+//!
+//! Run via `cargo bench -p knightrs-bytecode --bench output`. Each program is run a handful of
+//! times, and the fastest run is reported, as that's the least affected by whatever else the
+//! machine is doing. What's written is discarded, so only the interpreter is timed.
+
+use knightrs_bytecode::parser::{source_location::ProgramSource, Parser};
+use knightrs_bytecode::vm::Vm;
+use knightrs_bytecode::{Environment, Gc, Options};
+use std::time::{Duration, Instant};
+
+const RUNS: usize = 20;
+const ITERATIONS: usize = 5_000;
+const LINES_PER_ITERATION: usize = 100;
+
+// A loop which outputs `line` lots of times per iteration (so the loop itself is only a small part
+// of the run time), after running `setup`.
+fn print_heavy(setup: &str, line: &str) -> String {
+	let outputs = format!("; OUTPUT {line} ").repeat(LINES_PER_ITERATION);
+	format!("; {setup} ; = i 0 : WHILE < i {ITERATIONS} {outputs}: = i + i 1")
+}
+
+// Parses and runs `source`, returning how long it took to run.
+fn time(source: &str) -> Duration {
+	// SAFETY: nothing allocated by the `gc` escapes it.
+	unsafe {
+		Gc::default().run(|gc| {
+			let mut env = Environment::new(Options::default(), gc);
+			env.set_stdout(std::io::sink());
+
+			let program = Parser::new(&mut env, ProgramSource::ExprFlag, source)
+				.and_then(|parser| parser.parse_program())
+				.expect("the benchmark is valid Knight");
+
+			let start = Instant::now();
+			Vm::new(&program, &mut env)
+				.run_entire_program_without_argv()
+				.expect("the benchmark runs successfully");
+			start.elapsed()
+		})
+	}
+}
+
+fn bench(line: &str) {
+	let constant = print_heavy("NULL", &format!("{line:?}"));
+	let variable = print_heavy(&format!("= line {line:?}"), "line");
+
+	// The two are run alternately, so that they're equally affected if the machine slows down.
+	let (mut constant_time, mut variable_time) = (Duration::MAX, Duration::MAX);
+	for _ in 0..RUNS {
+		constant_time = constant_time.min(time(&constant));
+		variable_time = variable_time.min(time(&variable));
+	}
+
+	println!("{line:?}, {} lines:", ITERATIONS * LINES_PER_ITERATION);
+	println!("  constant  run: {constant_time:>12?}");
+	println!("  variable  run: {variable_time:>12?}");
+	println!("  speedup: {:.2}x", variable_time.as_secs_f64() / constant_time.as_secs_f64());
+}
+
+fn main() {
+	bench("hello, world");
+	bench("a string which is long enough that it has to be allocated");
+}
@@ -0,0 +1,135 @@
+//! Discovering what this build of the interpreter supports.
+
+use crate::Environment;
+#[cfg(doc)]
+use crate::Options;
+
+/// A description of which cargo features this crate was compiled with.
+///
+/// This is returned by [`capabilities`], and is intended for tooling which needs to adapt its
+/// behaviour (or print diagnostics) based on what's available. What's actually _enabled_ at runtime
+/// is controlled by [`Options`]; see [`Capabilities::active_options`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Capabilities {
+	/// Whether extensions can be enabled (`feature = "extensions"`).
+	pub extensions: bool,
+
+	/// Whether compliance checks can be enabled (`feature = "compliance"`).
+	pub compliance: bool,
+
+	/// Whether quality-of-life options can be enabled (`feature = "qol"`).
+	pub qol: bool,
+
+	/// Whether runtime errors include stacktraces (`feature = "stacktrace"`).
+	pub stacktrace: bool,
+
+	/// Whether variables can be checked for being unassigned (`feature = "check-variables"`).
+	pub check_variables: bool,
+
+	/// Whether parens can be checked (`feature = "check-parens"`).
+	pub check_parens: bool,
+
+	/// Whether embedding options are available (`feature = "embedded"`).
+	pub embedded: bool,
+
+	/// Whether embedders can define their own types (`feature = "custom-types"`).
+	pub custom_types: bool,
+
+	/// Whether floating point numbers are compiled in (`feature = "floats"`).
+	pub floats: bool,
+
+	/// Whether values are thread-safe (`feature = "multithreaded"`).
+	pub multithreaded: bool,
+
+	/// Whether Knight 2.0.1 behaviour is used (`feature = "knight_2_0_1"`).
+	pub knight_2_0_1: bool,
+
+	/// How values are garbage collected.
+	pub gc_strategy: GcStrategy,
+}
+
+/// The strategy the [`Gc`](crate::Gc) uses to reclaim values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GcStrategy {
+	/// Values reachable from roots are marked, and everything else is swept.
+	MarkAndSweep,
+}
+
+/// Returns which cargo features this crate was compiled with.
+///
+/// # Examples
+/// ```
+/// let caps = knightrs_bytecode::capabilities();
+/// assert_eq!(caps.extensions, cfg!(feature = "extensions"));
+/// ```
+pub const fn capabilities() -> Capabilities {
+	Capabilities {
+		extensions: cfg!(feature = "extensions"),
+		compliance: cfg!(feature = "compliance"),
+		qol: cfg!(feature = "qol"),
+		stacktrace: cfg!(feature = "stacktrace"),
+		check_variables: cfg!(feature = "check-variables"),
+		check_parens: cfg!(feature = "check-parens"),
+		embedded: cfg!(feature = "embedded"),
+		custom_types: cfg!(feature = "custom-types"),
+		floats: cfg!(feature = "floats"),
+		multithreaded: cfg!(feature = "multithreaded"),
+		knight_2_0_1: cfg!(feature = "knight_2_0_1"),
+		gc_strategy: GcStrategy::MarkAndSweep,
+	}
+}
+
+impl Capabilities {
+	/// Returns the names of the options that are in effect in `env`.
+	///
+	/// This is every boolean option which is enabled, followed by every option that takes a value
+	/// (along with its value). Options belonging to a feature `self` doesn't have are left out, as
+	/// are extension options when extensions are [disabled](Options::extensions_disabled). The names
+	/// are the same ones accepted by [`Options::apply_str_flags`], so the output can be fed back
+	/// into it.
+	///
+	/// # Examples
+	/// ```
+	/// # use knightrs_bytecode::{capabilities, Environment, Gc, Options};
+	/// let mut opts = Options::default();
+	/// # #[cfg(feature = "extensions")] {
+	/// opts.extensions.functions.type_of = true;
+	/// # }
+	///
+	/// // SAFETY: nothing allocated by the `gc` escapes it.
+	/// unsafe {
+	///     Gc::default().run(|gc| {
+	///         let env = Environment::new(opts, gc);
+	///         let active = capabilities().active_options(&env);
+	///         assert_eq!(
+	///             active.contains(&"extensions.functions.type-of".to_string()),
+	///             cfg!(feature = "extensions")
+	///         );
+	///
+	///         let mut caps = capabilities();
+	///         caps.extensions = false;
+	///         let active = caps.active_options(&env);
+	///         assert!(!active.iter().any(|name| name.starts_with("extensions.")));
+	///     })
+	/// }
+	/// ```
+	pub fn active_options(&self, env: &Environment<'_>) -> Vec<String> {
+		let opts = env.opts();
+		let extensions =
+			cfg_expr!(feature = "extensions", self.extensions && !opts.extensions_disabled(), false);
+
+		let mut active = opts.active_flags();
+		active.retain(|name| match name.split(['.', '=']).next().unwrap_or_default() {
+			"extensions" => extensions,
+			"compliance" => self.compliance,
+			"qol" => self.qol,
+			"embedded" => self.embedded,
+			"check-variables" => self.check_variables,
+			"check-parens" => self.check_parens,
+			_ => true,
+		});
+		active
+	}
+}
@@ -1,21 +1,276 @@
-use crate::gc::GcRoot;
-use std::io;
+use crate::gc::{GcRoot, ValueRoot};
+#[cfg(feature = "custom-types")]
+use crate::parser::{ParseError, Parser};
+use std::io::{self, Write};
 
 use crate::gc::Gc;
 use crate::options::Options;
-use crate::value::{Integer, KnString};
-use rand::{rngs::StdRng, Rng, SeedableRng};
+use crate::value::{Integer, KnString, NativeFn, Value};
+use rand::{rngs::StdRng, SeedableRng};
+#[cfg(feature = "extensions")]
+use std::collections::{HashSet, VecDeque};
+#[cfg(feature = "extensions")]
+use std::path::Path;
+use std::rc::Rc;
+
+pub mod evaluation;
+pub mod output;
+pub mod prompt;
+pub use evaluation::{Evaluation, Warning};
+pub use knight_strings::CancelHandle;
+pub use output::{FlushPolicy, Newline, Output, Stdout};
+pub use prompt::{Prompt, PromptResult, Stdin};
+
+/// The function that's used to run shell commands for `$`; it's given the command to run, and
+/// returns the command's stdout.
+#[cfg(feature = "extensions")]
+pub type System<'gc> = dyn FnMut(&str) -> crate::Result<String> + 'gc;
+
+/// The function that's used to read files for `XIMPORT`; it's given the file's name, and returns
+/// its contents.
+#[cfg(feature = "extensions")]
+pub type ReadFile<'gc> = dyn FnMut(&str) -> crate::Result<String> + 'gc;
+
+/// A function implemented in Rust, which Knight code can call; see
+/// [`Environment::register_native_fn`]. It's given the arguments it was called with.
+///
+/// What it returns is rooted, as it may have just been allocated, and would otherwise be collected
+/// by the next allocation.
+pub type NativeFnBody<'gc> =
+	dyn Fn(&[Value<'gc>], &mut Environment<'gc>) -> crate::Result<ValueRoot<'gc>> + 'gc;
+
+/// A function which parses custom syntax; see [`Environment::add_parse_fn`].
+///
+/// It returns `Ok(None)` if the source doesn't start with its syntax, in which case it shouldn't
+/// have advanced `parser` at all.
+#[cfg(feature = "custom-types")]
+pub type ParseFn<'gc> = dyn for<'src, 'path> Fn(
+		&mut Parser<'_, 'src, 'path, 'gc>,
+	) -> Result<Option<ValueRoot<'gc>>, ParseError<'path>>
+	+ 'gc;
 
 pub struct Environment<'gc> {
 	opts: Options,
 	rng: StdRng,
 	gc: &'gc Gc,
+	prompt: Prompt<'gc>,
+	output: Output<'gc>,
+	cancel: CancelHandle,
+
+	// Where `DUMP` writes to; if `None`, it's the same as `output`.
+	dump: Option<Output<'gc>>,
+
+	#[cfg(feature = "extensions")]
+	system: Box<System<'gc>>,
+
+	#[cfg(feature = "extensions")]
+	read_file: Box<ReadFile<'gc>>,
+
+	// A queue of results that'll be returned from `$` instead of running commands.
+	#[cfg(feature = "extensions")]
+	system_results: VecDeque<String>,
+
+	// Paths of `XIMPORT`ed files, which are leaked so programs can refer to them.
+	#[cfg(feature = "extensions")]
+	interned_paths: HashSet<&'static Path>,
+
+	// Indexed by `NativeFn`s. They're `Rc`s so they can be called with `self`.
+	native_fns: Vec<Rc<NativeFnBody<'gc>>>,
+
+	// Likewise, they're `Rc`s so they can be called with a parser borrowing `self`.
+	#[cfg(feature = "custom-types")]
+	parse_fns: Vec<Rc<ParseFn<'gc>>>,
 }
 
 impl<'gc> Environment<'gc> {
 	pub fn new(opts: Options, gc: &'gc Gc) -> Self {
+		#[cfg(feature = "qol")]
+		let dump_to_stderr = opts.qol.dump_to_stderr;
+
 		// TODO: allow `rng` to be supplied by callers
-		Self { opts, rng: StdRng::from_entropy(), gc }
+		let mut env = Self {
+			opts,
+			rng: StdRng::from_entropy(),
+			gc,
+			prompt: Prompt::default(),
+			output: Output::default(),
+			cancel: CancelHandle::default(),
+			dump: None,
+
+			#[cfg(feature = "extensions")]
+			system: Box::new(default_system),
+
+			#[cfg(feature = "extensions")]
+			read_file: Box::new(default_read_file),
+
+			#[cfg(feature = "extensions")]
+			system_results: VecDeque::new(),
+
+			#[cfg(feature = "extensions")]
+			interned_paths: HashSet::new(),
+
+			native_fns: Vec::new(),
+
+			#[cfg(feature = "custom-types")]
+			parse_fns: Vec::new(),
+		};
+
+		#[cfg(feature = "qol")]
+		if dump_to_stderr {
+			env.set_dump_stream(io::stderr());
+		}
+
+		env.output.set_flush_policy(env.opts.io.flush);
+		env
+	}
+
+	/// Parses and runs `source`, returning its result along with everything it wrote to stdout.
+	///
+	/// This is a convenience for the common case of running a snippet and inspecting all of its
+	/// effects. Output is captured for the duration of the call, and so isn't written to stdout. If
+	/// output was already being [captured](Output::start_capture), the program's output is also
+	/// added to that capture.
+	///
+	/// Note that `QUIT` still exits the process unless
+	/// [`dont_exit_when_quitting`](crate::options::Embedded::dont_exit_when_quitting) is set.
+	///
+	/// # Examples
+	/// ```
+	/// use knightrs_bytecode::env::{Environment, Warning};
+	/// use knightrs_bytecode::{Gc, Options};
+	///
+	/// // SAFETY: nothing allocated by the `gc` escapes it.
+	/// unsafe {
+	///     Gc::default().run(|gc| {
+	///         let mut env = Environment::new(Options::default(), gc);
+	///         let evaluation = env.evaluate("; = a 3 : OUTPUT IF TRUE a b");
+	///
+	///         assert_eq!(evaluation.stdout_lossy(), "3\n");
+	///         assert_eq!(evaluation.warnings, [Warning::NeverAssigned("b".to_string())]);
+	///         # #[cfg(feature = "stats")]
+	///         assert_eq!(evaluation.stats.variables_used, 1);
+	///     })
+	/// }
+	/// ```
+	pub fn evaluate(&mut self, source: &str) -> Evaluation<'gc> {
+		use crate::parser::source_location::ProgramSource;
+		use crate::vm::Vm;
+		use std::time::{Duration, Instant};
+
+		let previous_capture = self.output.stop_capture();
+		self.output.start_capture();
+
+		let start = Instant::now();
+		let program = crate::parser::Parser::new(self, ProgramSource::ExprFlag, source)
+			.and_then(|parser| parser.parse_program());
+		let parse_time = start.elapsed();
+
+		#[cfg(feature = "stats")]
+		let mut stats = crate::vm::Stats::default();
+
+		let mut warnings = Vec::new();
+
+		let (value, run_time) = match program {
+			Ok(program) => {
+				warnings.extend(
+					program.unassigned_variables().map(|name| Warning::NeverAssigned(name.to_string())),
+				);
+
+				let start = Instant::now();
+				let gc = self.gc;
+				let mut vm = Vm::new(&program, self);
+				let value = vm.run_entire_program_without_argv();
+
+				#[cfg(feature = "stats")]
+				{
+					stats = vm.stats();
+				}
+
+				(value.map(|value| value.root(gc)), start.elapsed())
+			}
+			Err(err) => (Err(err.into()), Duration::ZERO),
+		};
+
+		let stdout = self.output.stop_capture().unwrap_or_default();
+		if let Some(mut previous) = previous_capture {
+			previous.extend_from_slice(&stdout);
+			self.output.start_capture();
+			let _ = self.output.write_all(&previous); // writing to a capture can't fail.
+		}
+
+		Evaluation {
+			value,
+			stdout,
+			warnings,
+			parse_time,
+			run_time,
+			#[cfg(feature = "stats")]
+			stats,
+		}
+	}
+
+	/// Sets where `PROMPT` reads lines from.
+	///
+	/// This doesn't affect any [replacements](Prompt#replacements) that may have been set.
+	pub fn set_stdin(&mut self, stdin: impl Stdin + 'gc) {
+		self.prompt.set_stdin(stdin);
+	}
+
+	/// Sets where `OUTPUT` and `DUMP` write to.
+	///
+	/// Anything that's been buffered is flushed to the old stdout first; errors doing so are ignored.
+	pub fn set_stdout(&mut self, stdout: impl Stdout + 'gc) {
+		let _ = self.output.set_stdout(stdout);
+	}
+
+	/// Sets where `DUMP` writes to, instead of stdout.
+	///
+	/// By default, this is stdout (or stderr, if [`QualityOfLife::dump_to_stderr`] is enabled).
+	/// Unlike stdout, the stream is flushed after every write.
+	///
+	/// [`QualityOfLife::dump_to_stderr`]: crate::options::QualityOfLife::dump_to_stderr
+	pub fn set_dump_stream(&mut self, stream: impl Stdout + 'gc) {
+		let mut dump = Output::new(stream);
+		dump.set_flush_policy(FlushPolicy::EveryOutput);
+		self.dump = Some(dump);
+	}
+
+	/// Makes `DUMP` write to stdout again.
+	pub fn clear_dump_stream(&mut self) {
+		self.dump = None;
+	}
+
+	/// Builder-style version of [`Environment::set_stdin`].
+	pub fn with_stdin(mut self, stdin: impl Stdin + 'gc) -> Self {
+		self.set_stdin(stdin);
+		self
+	}
+
+	/// Builder-style way to set a function that refills stdin when it reaches end of file; see
+	/// [`Prompt::set_eof_hook`].
+	pub fn with_eof_hook(mut self, hook: impl FnMut() -> Option<String> + 'gc) -> Self {
+		self.prompt.set_eof_hook(hook);
+		self
+	}
+
+	/// Builder-style version of [`Environment::set_stdout`].
+	pub fn with_stdout(mut self, stdout: impl Stdout + 'gc) -> Self {
+		self.set_stdout(stdout);
+		self
+	}
+
+	/// Builder-style way to queue up lines for `PROMPT` to return; see [`Prompt::add_lines`].
+	#[cfg(feature = "extensions")]
+	pub fn with_prompt_lines(mut self, lines: &str) -> Self {
+		self.prompt.add_lines(lines);
+		self
+	}
+
+	/// Builder-style way to make `PROMPT` act like stdin is closed; see [`Prompt::close`].
+	#[cfg(feature = "extensions")]
+	pub fn with_closed_prompt(mut self) -> Self {
+		self.prompt.close();
+		self
 	}
 
 	pub fn opts(&self) -> &Options {
@@ -26,77 +281,313 @@ impl<'gc> Environment<'gc> {
 		&self.gc
 	}
 
-	pub fn prompt(&mut self) -> crate::Result<Option<GcRoot<'gc, KnString<'gc>>>> {
-		let mut line = String::new();
-		let amnt = std::io::stdin()
-			.read_line(&mut line)
-			.map_err(|err| crate::Error::IoError { func: "PROMPT", err })?;
+	/// Gets the [`Prompt`] that `PROMPT` reads from.
+	pub fn prompt(&mut self) -> &mut Prompt<'gc> {
+		&mut self.prompt
+	}
 
-		if amnt == 0 {
-			return Ok(None);
+	/// Reads a line for `PROMPT`, returning `None` at end of file.
+	///
+	/// This doesn't handle [computed replacements](Prompt::set_block), as they need a
+	/// [`Vm`](crate::vm::Vm) to run.
+	pub fn read_line(&mut self) -> crate::Result<Option<GcRoot<'gc, KnString<'gc>>>> {
+		// Make sure any prompts that were `OUTPUT`ted are visible before we block on reading.
+		if self.output.flush_policy() != FlushPolicy::Never {
+			let _ = self.output.flush();
 		}
 
-		if line.chars().last().map_or(false, |c| c == '\n') {
-			line.pop();
+		match self.prompt.read_line() {
+			Ok(Some(line)) => Ok(Some(KnString::new(line, self.opts(), self.gc())?)),
+			Ok(None) => Ok(None),
+			Err(err) => Err(crate::Error::IoError { func: "PROMPT", err }),
 		}
+	}
 
-		if cfg!(feature = "knight_2_0_1") {
-			while line.chars().last().map_or(false, |c| c == '\r') {
-				line.pop();
-			}
-		} else {
-			if line.chars().last().map_or(false, |c| c == '\r') {
-				line.pop();
-			}
+	/// Gets the [`Output`] that `OUTPUT` and `DUMP` write to.
+	pub fn output(&mut self) -> &mut Output<'gc> {
+		&mut self.output
+	}
+
+	/// Gets a [`CancelHandle`], which can be used to stop `self`'s programs from other threads.
+	///
+	/// After [`cancel`](CancelHandle::cancel) is called, the [`Vm`](crate::vm::Vm) stops at its
+	/// next jump or block call, returning [`Error::Interrupted`](crate::Error::Interrupted).
+	///
+	/// # Examples
+	/// ```
+	/// # use knightrs_bytecode::*;
+	/// // SAFETY: nothing allocated by the `gc` escapes it.
+	/// unsafe {
+	///     Gc::default().run(|gc| {
+	///         let mut env = Environment::new(Options::default(), gc);
+	///         let handle = env.cancel_handle();
+	///
+	///         std::thread::spawn(move || {
+	///             std::thread::sleep(std::time::Duration::from_millis(10));
+	///             handle.cancel();
+	///         });
+	///
+	///         let evaluation = env.evaluate("WHILE TRUE NULL");
+	///         assert!(matches!(evaluation.value, Err(Error::Interrupted)));
+	///     })
+	/// }
+	/// ```
+	pub fn cancel_handle(&self) -> CancelHandle {
+		self.cancel.clone()
+	}
+
+	/// Returns [`Error::Interrupted`](crate::Error::Interrupted) if `self`'s [`CancelHandle`] has
+	/// been cancelled.
+	#[inline]
+	pub fn check_cancelled(&self) -> crate::Result<()> {
+		if self.cancel.take() {
+			return Err(crate::Error::Interrupted);
 		}
 
-		Ok(Some(KnString::new(line, self.opts(), self.gc())?))
+		Ok(())
+	}
+
+	/// Gets the [`Output`] that `DUMP` writes to; see [`Environment::set_dump_stream`].
+	pub fn dump_output(&mut self) -> &mut Output<'gc> {
+		self.dump.as_mut().unwrap_or(&mut self.output)
 	}
 
-	pub fn output(&mut self) -> impl io::Write {
-		// TODO: eventually allow for capturing output within Knight programs
-		std::io::stdout()
+	/// Converts `err`, which occurred when `func` wrote to stdout, into an [`Error::IoError`].
+	///
+	/// If [`QualityOfLife::ignore_broken_pipe`] is enabled and `err` is a broken pipe, the program
+	/// instead [quits](Environment::quit) with status `0`, and whatever that returns is used.
+	///
+	/// [`Error::IoError`]: crate::Error::IoError
+	/// [`QualityOfLife::ignore_broken_pipe`]: crate::options::QualityOfLife::ignore_broken_pipe
+	pub fn output_error(&mut self, func: &'static str, err: std::io::Error) -> crate::Error {
+		#[cfg(feature = "qol")]
+		if self.opts.qol.ignore_broken_pipe && err.kind() == std::io::ErrorKind::BrokenPipe {
+			return match self.quit(Integer::ZERO) {
+				Err(err) => err,
+				Ok(never) => match never {},
+			};
+		}
+
+		crate::Error::IoError { func, err }
 	}
 
+	/// Quits the program with the given `status`.
+	///
+	/// If [`Compliance::check_quit_status_codes`] is enabled, statuses outside of `0..=127` are
+	/// rejected; statuses which don't fit in an `i32` are always rejected. If
+	/// [`Embedded::dont_exit_when_quitting`] is enabled, [`Error::Exit`] is returned instead of
+	/// exiting the process.
+	///
+	/// [`Compliance::check_quit_status_codes`]: crate::options::Compliance::check_quit_status_codes
+	/// [`Embedded::dont_exit_when_quitting`]: crate::options::Embedded::dont_exit_when_quitting
+	/// [`Error::Exit`]: crate::Error::Exit
 	#[cold] // Don't inline the big function, as it always exits the program.
 	pub fn quit(&mut self, status: Integer) -> crate::Result<std::convert::Infallible> {
-		let status = i32::try_from(status.inner()).expect("todo: out of bounds for i32");
+		use crate::value::integer::IntegerError;
 
 		#[cfg(feature = "compliance")]
-		if self.opts.compliance.check_quit_status_codes && !(0..=127).contains(&status) {
-			// TODO: Mauybe have a custom error for this?
-			return Err(
-				crate::value::integer::IntegerError::DomainError("QUIT: not in bounds").into(),
-			);
+		if self.opts.compliance.check_quit_status_codes && !(0..=127).contains(&status.inner()) {
+			return Err(IntegerError::DomainError("QUIT: status not within 0..=127").into());
 		}
 
+		let status = i32::try_from(status.inner())
+			.map_err(|_| IntegerError::DomainError("QUIT: status doesn't fit in an i32"))?;
+
 		#[cfg(feature = "embedded")]
 		if self.opts.embedded.dont_exit_when_quitting {
 			return Err(crate::Error::Exit(status));
 		}
 
+		// `process::exit` doesn't run destructors, so make sure everything's been written.
+		let _ = self.output().flush();
 		std::process::exit(status);
 	}
 
-	#[cfg(feature = "extensions")]
+	/// Reseeds the random number generator, so that subsequent calls to [`Environment::random`] are
+	/// deterministic.
+	///
+	/// This is what `XSRAND` and `= RANDOM ...` (when those extensions are enabled) use.
 	pub fn seed_random(&mut self, seed: Integer) {
 		self.rng = StdRng::seed_from_u64(seed.inner() as u64)
 	}
 
+	/// Gets a random [`Integer`].
+	///
+	/// See [`Integer::random`] for the range it's within.
 	pub fn random(&mut self) -> crate::Result<Integer> {
-		let min = match () {
-			#[cfg(feature = "extensions")]
-			_ if self.opts.extensions.breaking.random_can_be_negative => Integer::min(&self.opts).inner(),
-			_ => 0,
-		};
+		Ok(Integer::random(&mut self.rng, &self.opts))
+	}
+
+	/// Gets the random number generator used by [`Environment::random`].
+	pub fn rng(&mut self) -> &mut StdRng {
+		&mut self.rng
+	}
+}
+
+#[cfg(feature = "extensions")]
+fn default_system(command: &str) -> crate::Result<String> {
+	use std::process::{Command, Stdio};
+
+	let output = Command::new("/bin/sh")
+		.arg("-c")
+		.arg(command)
+		.stdin(Stdio::inherit())
+		.output()
+		.map_err(|err| crate::Error::IoError { func: "$", err })?;
+
+	Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(feature = "extensions")]
+fn default_read_file(filename: &str) -> crate::Result<String> {
+	std::fs::read_to_string(filename).map_err(|err| crate::Error::IoError { func: "XIMPORT", err })
+}
+
+/// System-command functions.
+#[cfg(feature = "extensions")]
+impl<'gc> Environment<'gc> {
+	/// Sets the function that `$` uses to run commands. By default, `/bin/sh -c` is used.
+	pub fn set_system(&mut self, system: impl FnMut(&str) -> crate::Result<String> + 'gc) {
+		self.system = Box::new(system);
+	}
 
-		let max = match () {
-			#[cfg(feature = "compliance")]
-			_ if self.opts.compliance.limit_rand_range => 0x7FFF,
-			_ => Integer::max(&self.opts).inner(),
+	/// Builder-style version of [`Environment::set_system`].
+	pub fn with_system(mut self, system: impl FnMut(&str) -> crate::Result<String> + 'gc) -> Self {
+		self.set_system(system);
+		self
+	}
+
+	/// Runs `command` as a shell command for `$`, returning its stdout.
+	///
+	/// If any results have been queued via [`Environment::add_to_system`], the oldest one is
+	/// returned instead, and `command` isn't run.
+	pub fn run_command(&mut self, command: &str) -> crate::Result<GcRoot<'gc, KnString<'gc>>> {
+		let result = match self.system_results.pop_front() {
+			Some(result) => result,
+			None => {
+				// Make sure the command's output appears after anything we've written.
+				if self.output.flush_policy() != FlushPolicy::Never {
+					let _ = self.output.flush();
+				}
+				(self.system)(command)?
+			}
 		};
 
-		// We can do `new_unvalidated` as we clamp the min/max based on compliance.
-		Ok(Integer::new_unvalidated_unchecked(self.rng.gen_range(min..=max)))
+		Ok(KnString::new(result, self.opts(), self.gc())?)
+	}
+
+	/// Adds `output` as the next value to return from `$`, instead of running a command.
+	///
+	/// This is what `= $ ...` uses (when [`BuiltinFns::assign_to_system`] is enabled).
+	///
+	/// [`BuiltinFns::assign_to_system`]: crate::options::BuiltinFns::assign_to_system
+	pub fn add_to_system(&mut self, output: String) {
+		self.system_results.push_back(output);
+	}
+
+	/// Gets the next queued result for `$`, if any.
+	pub fn get_next_system_result(&mut self) -> Option<String> {
+		self.system_results.pop_front()
+	}
+}
+
+/// Native functions.
+impl<'gc> Environment<'gc> {
+	/// Registers `func` so Knight code can call it, returning a [`NativeFn`] that refers to it.
+	///
+	/// The returned [`NativeFn`] can be converted to a [`Value`] and handed to Knight code (eg by
+	/// assigning it to a variable). `CALL`ing it runs `func` with no arguments; `XAPPLY`ing it runs
+	/// `func` with the list's elements.
+	///
+	/// Registered functions are kept until `self` is dropped.
+	pub fn register_native_fn(
+		&mut self,
+		func: impl Fn(&[Value<'gc>], &mut Environment<'gc>) -> crate::Result<ValueRoot<'gc>> + 'gc,
+	) -> NativeFn {
+		self.native_fns.push(Rc::new(func));
+		NativeFn::new(self.native_fns.len() - 1)
+	}
+
+	/// Calls `native` with `args`.
+	///
+	/// The result stays rooted until the returned [`ValueRoot`] is dropped, so it should be kept
+	/// until the value's been stored somewhere the garbage collector knows about.
+	///
+	/// # Errors
+	/// Returns an [`Error::DomainError`](crate::Error::DomainError) if `native` wasn't registered
+	/// with `self`, and otherwise returns whatever the function itself does.
+	pub fn call_native_fn(
+		&mut self,
+		native: NativeFn,
+		args: &[Value<'gc>],
+	) -> crate::Result<ValueRoot<'gc>> {
+		let func = self
+			.native_fns
+			.get(native.index())
+			.cloned()
+			.ok_or(crate::Error::DomainError("unknown native function"))?;
+
+		func(args, self)
+	}
+}
+
+/// Custom syntax.
+#[cfg(feature = "custom-types")]
+impl<'gc> Environment<'gc> {
+	/// Adds `func` to the list of functions that are used to parse custom syntax.
+	///
+	/// Whenever an expression is parsed, these functions are tried in the order they were added,
+	/// before any of the builtin syntax. The first one to return a value has it used as a constant.
+	/// This is intended to be used to create literals for [`Custom`](crate::value::Custom) types.
+	pub fn add_parse_fn(
+		&mut self,
+		func: impl for<'src, 'path> Fn(
+				&mut Parser<'_, 'src, 'path, 'gc>,
+			) -> Result<Option<ValueRoot<'gc>>, ParseError<'path>>
+			+ 'gc,
+	) {
+		self.parse_fns.push(Rc::new(func));
+	}
+
+	/// Gets the list of custom parse functions.
+	pub(crate) fn parse_fns(&self) -> &[Rc<ParseFn<'gc>>] {
+		&self.parse_fns
+	}
+}
+
+/// File-reading functions.
+#[cfg(feature = "extensions")]
+impl<'gc> Environment<'gc> {
+	/// Sets the function that `XIMPORT` uses to read files. By default, they're read from disk.
+	pub fn set_read_file(&mut self, read_file: impl FnMut(&str) -> crate::Result<String> + 'gc) {
+		self.read_file = Box::new(read_file);
+	}
+
+	/// Builder-style version of [`Environment::set_read_file`].
+	pub fn with_read_file(
+		mut self,
+		read_file: impl FnMut(&str) -> crate::Result<String> + 'gc,
+	) -> Self {
+		self.set_read_file(read_file);
+		self
+	}
+
+	/// Reads the contents of `filename`, for `XIMPORT`.
+	pub fn read_file(&mut self, filename: &str) -> crate::Result<String> {
+		(self.read_file)(filename)
+	}
+
+	/// Gets a `'static` copy of `path`, for [`SourceLocation`](crate::parser::SourceLocation)s in
+	/// `XIMPORT`ed files.
+	///
+	/// Each distinct path is only leaked once, no matter how many times it's interned.
+	pub fn intern_path(&mut self, path: &Path) -> &'static Path {
+		if let Some(interned) = self.interned_paths.get(path) {
+			return interned;
+		}
+
+		let interned: &'static Path = Box::leak(path.into());
+		self.interned_paths.insert(interned);
+		interned
 	}
 }
@@ -0,0 +1,58 @@
+//! The result of [`Environment::evaluate`](super::Environment::evaluate).
+
+use crate::gc::ValueRoot;
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
+
+/// Everything that happened when running a program via
+/// [`Environment::evaluate`](super::Environment::evaluate).
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct Evaluation<'gc> {
+	/// The value the program returned, or the error that occurred while parsing or running it.
+	pub value: crate::Result<ValueRoot<'gc>>,
+
+	/// Everything the program wrote to stdout via `OUTPUT` (and `DUMP`, unless it has its own
+	/// [stream](super::Environment::set_dump_stream)).
+	pub stdout: Vec<u8>,
+
+	/// Suspicious things about the program, which didn't stop it from running. This is empty if it
+	/// couldn't be parsed.
+	pub warnings: Vec<Warning>,
+
+	/// How long parsing the program took.
+	pub parse_time: Duration,
+
+	/// How long running the program took. This is zero if it couldn't be parsed.
+	pub run_time: Duration,
+
+	/// Statistics about running the program. These are all zero if it couldn't be parsed.
+	#[cfg(feature = "stats")]
+	pub stats: crate::vm::Stats,
+}
+
+impl Evaluation<'_> {
+	/// Returns [`Evaluation::stdout`] as a string, replacing invalid UTF-8 sequences.
+	pub fn stdout_lossy(&self) -> std::borrow::Cow<'_, str> {
+		String::from_utf8_lossy(&self.stdout)
+	}
+}
+
+/// Something suspicious about a program, which didn't stop it from running.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Warning {
+	/// The program uses the variable, but never assigns to it with `=`.
+	///
+	/// The variable may still have a value, eg if it was assigned by an `EVAL`. See
+	/// [`Program::unassigned_variables`](crate::program::Program::unassigned_variables).
+	NeverAssigned(String),
+}
+
+impl Display for Warning {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		match self {
+			Self::NeverAssigned(name) => write!(f, "variable `{name}` is never assigned"),
+		}
+	}
+}
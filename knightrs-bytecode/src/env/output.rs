@@ -0,0 +1,186 @@
+//! How Knight writes to stdout.
+
+use std::io::{self, Write};
+
+/// A trait used for writing to stdout.
+///
+/// This exists instead of simply using [`Write`] so that the interface matches the `knightrs`
+/// crate's, and so that bounds can be added in the future without breaking callers.
+pub trait Stdout: Write {}
+impl<T: Write> Stdout for T {}
+
+/// When [`Output`] flushes its buffer to the underlying [`Stdout`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FlushPolicy {
+	/// Flush after every write. This is the slowest, but always keeps stdout up-to-date.
+	EveryOutput,
+
+	/// Flush whenever a newline is written (or the buffer fills up).
+	#[default]
+	Line,
+
+	/// Only flush when the buffer fills up, before `PROMPT`, and when the program exits.
+	OnExit,
+
+	/// Only flush when the buffer fills up, and when the program exits. Unlike
+	/// [`FlushPolicy::OnExit`], the buffer isn't flushed before `PROMPT` or `$`, so prompts may not
+	/// be visible until later.
+	Never,
+}
+
+/// What `OUTPUT` ends lines with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Newline {
+	/// `\n`, as the Knight spec requires.
+	#[default]
+	Lf,
+
+	/// `\r\n`, for Windows-style line endings.
+	CrLf,
+}
+
+impl Newline {
+	/// Gets the characters that end a line.
+	#[must_use]
+	pub const fn as_str(self) -> &'static str {
+		match self {
+			Self::Lf => "\n",
+			Self::CrLf => "\r\n",
+		}
+	}
+}
+
+/// The type that's in charge of writing text to stdout.
+///
+/// Everything that's written is buffered, and is only written to the underlying [`Stdout`] based
+/// on the [`FlushPolicy`]. The buffer is always flushed when the `Output` is dropped and when
+/// `QUIT` exits the process, and (unless the policy is [`FlushPolicy::Never`]) when `PROMPT` is
+/// called.
+///
+/// # Capturing
+/// [`Output::start_capture`] can be used to capture everything that's written, instead of sending
+/// it to stdout; [`Output::stop_capture`] returns everything that's been captured.
+pub struct Output<'gc> {
+	stdout: Box<dyn Stdout + 'gc>,
+	buffer: Vec<u8>,
+	capacity: usize,
+	policy: FlushPolicy,
+	capture: Option<Vec<u8>>,
+}
+
+impl Default for Output<'_> {
+	fn default() -> Self {
+		Self::new(io::stdout())
+	}
+}
+
+impl<'gc> Output<'gc> {
+	/// The default amount of bytes that are buffered before flushing.
+	pub const DEFAULT_CAPACITY: usize = 8 * 1024;
+
+	/// Creates a new `Output` which writes to `stdout`, with the default [`FlushPolicy`].
+	pub fn new(stdout: impl Stdout + 'gc) -> Self {
+		Self {
+			stdout: Box::new(stdout),
+			buffer: Vec::new(),
+			capacity: Self::DEFAULT_CAPACITY,
+			policy: FlushPolicy::default(),
+			capture: None,
+		}
+	}
+
+	/// Sets the underlying stdout, flushing anything that's been written to the old one.
+	pub fn set_stdout(&mut self, stdout: impl Stdout + 'gc) -> io::Result<()> {
+		let result = self.flush();
+		self.stdout = Box::new(stdout);
+		result
+	}
+
+	/// Gets the current flush policy.
+	pub fn flush_policy(&self) -> FlushPolicy {
+		self.policy
+	}
+
+	/// Sets when the buffer should be flushed.
+	pub fn set_flush_policy(&mut self, policy: FlushPolicy) {
+		self.policy = policy;
+	}
+
+	/// Gets how many bytes are buffered before a flush is forced.
+	pub fn buffer_capacity(&self) -> usize {
+		self.capacity
+	}
+
+	/// Sets how many bytes are buffered before a flush is forced. A capacity of `0` means every
+	/// write is flushed, regardless of the flush policy.
+	pub fn set_buffer_capacity(&mut self, capacity: usize) {
+		self.capacity = capacity;
+	}
+
+	/// Returns whether output is currently being captured.
+	pub fn is_capturing(&self) -> bool {
+		self.capture.is_some()
+	}
+
+	/// Starts capturing everything that's written, instead of sending it to stdout.
+	///
+	/// Anything that's been buffered before this is called is still sent to stdout. If output is
+	/// already being captured, this does nothing.
+	pub fn start_capture(&mut self) {
+		if self.capture.is_none() {
+			self.capture = Some(Vec::new());
+		}
+	}
+
+	/// Stops capturing output, returning everything that's been captured since
+	/// [`Output::start_capture`] was called. Returns `None` if output wasn't being captured.
+	pub fn stop_capture(&mut self) -> Option<Vec<u8>> {
+		self.capture.take()
+	}
+
+	/// Writes everything that's currently been buffered to stdout, without flushing stdout itself.
+	fn drain_buffer(&mut self) -> io::Result<()> {
+		if !self.buffer.is_empty() {
+			let result = self.stdout.write_all(&self.buffer);
+			self.buffer.clear();
+			result?;
+		}
+
+		Ok(())
+	}
+}
+
+impl Write for Output<'_> {
+	fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+		if let Some(capture) = self.capture.as_mut() {
+			capture.extend_from_slice(bytes);
+			return Ok(bytes.len());
+		}
+
+		self.buffer.extend_from_slice(bytes);
+
+		let should_flush = self.buffer.len() >= self.capacity
+			|| match self.policy {
+				FlushPolicy::EveryOutput => true,
+				FlushPolicy::Line => bytes.contains(&b'\n'),
+				FlushPolicy::OnExit | FlushPolicy::Never => false,
+			};
+
+		if should_flush {
+			self.flush()?;
+		}
+
+		Ok(bytes.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.drain_buffer()?;
+		self.stdout.flush()
+	}
+}
+
+impl Drop for Output<'_> {
+	fn drop(&mut self) {
+		let _ = self.flush(); // explicitly ignore errors, as there's nothing we can do.
+	}
+}
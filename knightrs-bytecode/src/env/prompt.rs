@@ -0,0 +1,279 @@
+//! How Knight reads from stdin.
+
+use std::io::{self, BufRead};
+
+#[cfg(feature = "extensions")]
+use {crate::value::Block, std::collections::VecDeque};
+
+/// A trait used for reading from stdin.
+///
+/// This exists instead of simply using [`BufRead`] so that the interface matches the `knightrs`
+/// crate's, and so that bounds can be added in the future without breaking callers.
+pub trait Stdin: BufRead {}
+impl<T: BufRead> Stdin for T {}
+
+/// The type that's in charge of reading lines from stdin.
+///
+/// # Replacements
+/// Enabling `extensions` allows you to use replacements: the ability to change what `PROMPT` will
+/// return from within Knight itself. Only a single replacement can be in use at a time (i.e.
+/// setting a new one will override the previous one) and there's three versions:
+///
+/// - **closed**: Acts as if stdin is at end of file. Set via [`Prompt::close`].
+/// - **buffered**: Specify the lines that future invocations of `PROMPT` will return. Once the
+///   buffer of lines is empty, acts like **closed**. Set via [`Prompt::add_lines`].
+/// - **computed**: Executes a [`Block`] each time `PROMPT` is called. Set via
+///   [`Prompt::set_block`].
+///
+/// If [`BuiltinFns::assign_to_prompt`](crate::options::BuiltinFns::assign_to_prompt) is enabled,
+/// you can set replacements from within Knight:
+///
+/// ```knight
+/// # Pretends like prompt is at EOF.
+/// ; = PROMPT NULL # You can use `FALSE` as an alias of `NULL` .
+/// ; DUMP PROMPT #=> null
+///
+/// # Specify what future results of `PROMPT` will return.
+/// #
+/// # If the string has multiple lines, each line is returned separately.
+/// ; = PROMPT "hello"
+/// ; = PROMPT "world
+/// !"
+/// ; DUMP PROMPT #=> "hello"
+/// ; DUMP PROMPT #=> "world"
+/// ; DUMP PROMPT #=> "!"
+/// ; DUMP PROMPT #=> null
+///
+/// # Dynamically compute what `PROMPT` returns.
+/// #
+/// # If the `BLOCK` returns `NULL`, it acts like `PROMPT` is at
+/// # eof. Anything else is converted to a string before being returned.
+/// #
+/// ; = lineno 0
+/// ; = PROMPT BLOCK
+///    : IF (> lineno 2)
+///      : NULL
+///    : = lineno + lineno 1
+/// ; DUMP PROMPT #=> "1"
+/// ; DUMP PROMPT #=> "2"
+/// ; DUMP PROMPT #=> "3"
+/// ; DUMP PROMPT #=> null
+///
+/// # Resets all replacements, so `PROMPT` behaves normally.
+/// ; = PROMPT TRUE
+/// : DUMP PROMPT #=> reads from stdin
+/// ```
+pub struct Prompt<'gc> {
+	// `None` means the process's stdin, which is only locked while a line is read from it, so that
+	// other threads (such as ones running other `Environment`s) can still use it.
+	default: Option<Box<dyn Stdin + 'gc>>,
+
+	// Called when `default` reaches end of file; see `Prompt::set_eof_hook`.
+	eof_hook: Option<Box<dyn FnMut() -> Option<String> + 'gc>>,
+
+	#[cfg(feature = "extensions")]
+	replacement: Option<PromptReplacement>,
+}
+
+#[cfg(feature = "extensions")]
+enum PromptReplacement {
+	Closed,
+	Buffered(VecDeque<String>),
+	Computed(Block),
+}
+
+/// What reading a line for `PROMPT` resulted in; see [`Prompt::read`].
+///
+/// `PROMPT` returns `NULL` for both [`Eof`](Self::Eof) and [`Closed`](Self::Closed); they're only
+/// distinguished for embedders.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PromptResult {
+	/// A line was read, with its line ending stripped.
+	Line(String),
+
+	/// Stdin reached end of file, and the [EOF hook](Prompt::set_eof_hook) (if any) didn't provide
+	/// any more input.
+	Eof,
+
+	/// A [replacement](Prompt#replacements) is acting like stdin is at end of file: either
+	/// [`Prompt::close`] was used, or the [buffered lines](Prompt::add_lines) ran out. This is never
+	/// returned without `extensions`.
+	Closed,
+}
+
+impl PromptResult {
+	/// Gets the line that was read, or `None` if there wasn't one.
+	pub fn into_line(self) -> Option<String> {
+		match self {
+			Self::Line(line) => Some(line),
+			Self::Eof | Self::Closed => None,
+		}
+	}
+}
+
+impl Default for Prompt<'_> {
+	/// Creates a new `Prompt` which reads from the process's stdin.
+	fn default() -> Self {
+		Self {
+			default: None,
+			eof_hook: None,
+
+			#[cfg(feature = "extensions")]
+			replacement: None,
+		}
+	}
+}
+
+/// Strips the trailing `\n` (and `\r`s, depending on the knight version) off of `line`.
+fn strip_ending(line: &mut String) {
+	if line.ends_with('\n') {
+		line.pop();
+	}
+
+	if cfg!(feature = "knight_2_0_1") {
+		while line.ends_with('\r') {
+			line.pop();
+		}
+	} else if line.ends_with('\r') {
+		line.pop();
+	}
+}
+
+impl<'gc> Prompt<'gc> {
+	/// Creates a new `Prompt` which reads from `stdin`.
+	pub fn new(stdin: impl Stdin + 'gc) -> Self {
+		Self {
+			default: Some(Box::new(stdin)),
+			eof_hook: None,
+
+			#[cfg(feature = "extensions")]
+			replacement: None,
+		}
+	}
+
+	/// Sets the default stdin.
+	///
+	/// This doesn't affect any replacements that may have been set.
+	pub fn set_stdin(&mut self, stdin: impl Stdin + 'gc) {
+		self.default = Some(Box::new(stdin));
+	}
+
+	/// Sets a function that's called whenever stdin reaches end of file.
+	///
+	/// If `hook` returns `Some`, it's read as the new stdin, and reading continues from it. This
+	/// lets interactive hosts refill the input on demand. If it returns `None`, the read results in
+	/// [`PromptResult::Eof`].
+	pub fn set_eof_hook(&mut self, hook: impl FnMut() -> Option<String> + 'gc) {
+		self.eof_hook = Some(Box::new(hook));
+	}
+
+	/// Removes the function set by [`Prompt::set_eof_hook`].
+	pub fn clear_eof_hook(&mut self) {
+		self.eof_hook = None;
+	}
+
+	/// Reads a line from stdin, with its line ending stripped. Returns `None` at end of file.
+	///
+	/// This is [`Prompt::read`], except that end of file and closed replacements aren't
+	/// distinguished.
+	///
+	/// # Errors
+	/// Any errors that occur when reading from stdin are bubbled upwards.
+	pub fn read_line(&mut self) -> io::Result<Option<String>> {
+		self.read().map(PromptResult::into_line)
+	}
+
+	/// Reads a line from stdin, with its line ending stripped.
+	///
+	/// If a [computed](Prompt::set_block) replacement is set, this returns
+	/// [`PromptResult::Closed`], as running blocks requires a [`Vm`](crate::vm::Vm);
+	/// `Opcode::Prompt` checks [`Prompt::computed_block`] first, and runs the block itself.
+	///
+	/// # Errors
+	/// Any errors that occur when reading from stdin are bubbled upwards.
+	pub fn read(&mut self) -> io::Result<PromptResult> {
+		#[cfg(feature = "extensions")]
+		match self.replacement.as_mut() {
+			Some(PromptReplacement::Closed | PromptReplacement::Computed(_)) => {
+				return Ok(PromptResult::Closed)
+			}
+			Some(PromptReplacement::Buffered(queue)) => {
+				return Ok(queue.pop_front().map_or(PromptResult::Closed, PromptResult::Line))
+			}
+			None => {}
+		}
+
+		let mut line = String::new();
+
+		// If we read an empty line, we're at EOF; see if the hook has any more input for us.
+		while self.read_default_line(&mut line)? == 0 {
+			match self.eof_hook.as_mut().and_then(|hook| hook()) {
+				Some(more) => self.set_stdin(io::Cursor::new(more.into_bytes())),
+				None => return Ok(PromptResult::Eof),
+			}
+		}
+
+		strip_ending(&mut line);
+		Ok(PromptResult::Line(line))
+	}
+
+	fn read_default_line(&mut self, line: &mut String) -> io::Result<usize> {
+		match self.default.as_mut() {
+			Some(stdin) => stdin.read_line(line),
+			// `Stdin::read_line` locks stdin just for this line.
+			None => io::stdin().read_line(line),
+		}
+	}
+}
+
+/// Replacement functions.
+#[cfg(feature = "extensions")]
+impl Prompt<'_> {
+	/// Clears the currently set replacement, if any.
+	pub fn reset_replacement(&mut self) {
+		self.replacement = None;
+	}
+
+	/// Mimics stdin reaching EOF.
+	///
+	/// This clears any previous replacement.
+	pub fn close(&mut self) {
+		self.replacement = Some(PromptReplacement::Closed);
+	}
+
+	/// Calling `PROMPT` will actually run `block` and convert its return value to a string.
+	///
+	/// This clears any previous replacement.
+	pub fn set_block(&mut self, block: Block) {
+		self.replacement = Some(PromptReplacement::Computed(block));
+	}
+
+	/// Gets the block set via [`Prompt::set_block`], if it's the current replacement.
+	pub fn computed_block(&self) -> Option<Block> {
+		match self.replacement {
+			Some(PromptReplacement::Computed(block)) => Some(block),
+			_ => None,
+		}
+	}
+
+	/// Adds each line of `new_lines` to a queue of lines to be returned when `PROMPT` is called.
+	///
+	/// This will clear any previous [`close()`](Self::close) and [`set_block()`](Self::set_block)
+	/// replacements. However, it will _not_ clear previous `add_lines` replacements, and instead
+	/// will simply add `new_lines` to the end.
+	pub fn add_lines(&mut self, new_lines: &str) {
+		if !matches!(self.replacement, Some(PromptReplacement::Buffered(_))) {
+			self.replacement = Some(PromptReplacement::Buffered(VecDeque::new()));
+		}
+
+		let Some(PromptReplacement::Buffered(ref mut lines)) = self.replacement else {
+			unreachable!();
+		};
+
+		lines.extend(new_lines.split('\n').map(|line| {
+			let mut line = line.to_string();
+			strip_ending(&mut line);
+			line
+		}));
+	}
+}
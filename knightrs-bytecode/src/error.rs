@@ -1,4 +1,8 @@
-use crate::parser::VariableName;
+use crate::parser::{SourceLocation, VariableName};
+use std::fmt::{self, Display, Formatter};
+
+#[cfg(feature = "annotate")]
+mod annotate;
 
 // TODO: make this just runtime error and parse error?
 #[derive(Error, Debug)]
@@ -6,8 +10,9 @@ pub enum Error {
 	#[error("{0}")]
 	Todo(String),
 
+	/// A runtime error, along with its stacktrace, and where it happened.
 	#[error("{0}")]
-	Stacktrace(String),
+	Stacktrace(String, ErrorLocation),
 
 	#[error("{0}")]
 	StringError(#[from] crate::strings::StringError),
@@ -15,12 +20,21 @@ pub enum Error {
 	#[error("{0}")]
 	IntegerError(#[from] crate::value::integer::IntegerError),
 
-	#[error("{0}")]
-	ParseError(String),
+	/// A program couldn't be parsed.
+	#[error("{1}: {0}")]
+	ParseError(String, ErrorLocation),
+
+	/// A partial program (ie one that had parse errors) was attempted to be run.
+	#[error("program had errors when parsing, and cannot be run")]
+	ProgramNotRunnable,
 
 	#[error("undefined variable {0} accessed")]
 	UndefinedVariable(VariableName<'static>),
 
+	/// A variable name created at runtime (eg via `VALUE` or assigning to a string) was invalid.
+	#[error("{0}")]
+	InvalidVariable(crate::parser::ParseErrorKind),
+
 	#[error("bad type {type_name} to function {function:?}")]
 	TypeError { type_name: &'static str, function: &'static str },
 
@@ -28,6 +42,11 @@ pub enum Error {
 	#[error("end index {index} is out of bounds for length {len}")]
 	IndexOutOfBounds { len: usize, index: usize },
 
+	/// A block was run by a different [`Program`](crate::program::Program) than the one that
+	/// declared it, such as `EVAL`'d code calling a block from outside of it.
+	#[error("block was declared in a different program, and can't be run by this one")]
+	ForeignBlock,
+
 	#[error("list is too large")]
 	ListIsTooLarge,
 
@@ -36,12 +55,34 @@ pub enum Error {
 	#[cfg(feature = "embedded")]
 	Exit(i32),
 
+	/// The program was stopped via a [`CancelHandle`](crate::env::CancelHandle).
+	#[error("interrupted")]
+	Interrupted,
+
 	#[error("Conversion to {to} not defined for {from}")]
 	ConversionNotDefined { to: &'static str, from: &'static str },
 
 	#[error("I/O error happened during {func}: {err}")]
 	IoError { func: &'static str, err: std::io::Error },
 
+	/// `EVAL`s were nested more than [`max_eval_depth`](crate::options::Extensions::max_eval_depth)
+	/// deep.
+	#[error("EVAL nested too deeply (max depth {max})")]
+	#[cfg(feature = "extensions")]
+	EvalTooDeep { max: usize },
+
+	/// `EVAL` was given source code longer than [`max_eval_length`](
+	/// crate::options::Extensions::max_eval_length).
+	#[error("EVAL source code is too long ({len} > {max} bytes)")]
+	#[cfg(feature = "extensions")]
+	EvalTooLong { len: usize, max: usize },
+
+	/// An `XASSERT`'s condition was false. Where it happened is in the [`Error::Stacktrace`] that
+	/// this is wrapped in.
+	#[error("assertion failed: {message}")]
+	#[cfg(feature = "extensions")]
+	AssertionFailed { message: String },
+
 	/// The types to a function were correct, but their values weren't somehow.
 	#[error("domain error: {0}")]
 	DomainError(&'static str),
@@ -51,6 +92,41 @@ pub type Result<T> = std::result::Result<T, Error>;
 
 impl From<crate::parser::ParseError<'_>> for Error {
 	fn from(err: crate::parser::ParseError<'_>) -> Self {
-		Self::ParseError(err.to_string())
+		Self::ParseError(err.kind.to_string(), err.whence.into())
+	}
+}
+
+/// Where an [`Error`] happened.
+///
+/// This is an owned version of [`SourceLocation`], so that [`Error`]s don't borrow the program's
+/// path.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ErrorLocation {
+	/// The [`ProgramSource`](crate::parser::source_location::ProgramSource) the error happened in,
+	/// as it's displayed.
+	pub source: String,
+
+	/// The (one-based) line number.
+	pub lineno: usize,
+
+	/// The (one-based) column, if it's known.
+	pub column: Option<usize>,
+}
+
+impl From<SourceLocation<'_>> for ErrorLocation {
+	fn from(loc: SourceLocation<'_>) -> Self {
+		Self { source: loc.source().to_string(), lineno: loc.lineno(), column: loc.column() }
+	}
+}
+
+impl Display for ErrorLocation {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		write!(f, "{}:{}", self.source, self.lineno)?;
+
+		if let Some(column) = self.column {
+			write!(f, ":{column}")?;
+		}
+
+		Ok(())
 	}
 }
@@ -0,0 +1,117 @@
+//! Rendering [`Error`]s alongside the source code they came from.
+
+use super::Error;
+use crate::parser::source_location::ProgramSource;
+use std::fmt::Write;
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+// How many spaces tabs in the source code are rendered as.
+const TAB_WIDTH: usize = 4;
+
+impl Error {
+	/// Renders `self` as a diagnostic for printing to the user.
+	///
+	/// If `self` happened within `path`, whose source code is `source`, the offending line is shown
+	/// with a caret under the column where the error occurred (or the whole line underlined, if the
+	/// column isn't known). Runtime errors are followed by their stacktrace.
+	///
+	/// The output is colored, unless the `NO_COLOR` environment variable is set to a non-empty
+	/// value. Use [`Error::render_with_color`] to decide explicitly.
+	#[must_use]
+	pub fn render(&self, source: &str, path: ProgramSource<'_>) -> String {
+		let color = std::env::var_os("NO_COLOR").is_none_or(|no_color| no_color.is_empty());
+		self.render_with_color(source, path, color)
+	}
+
+	/// The same as [`Error::render`], except colors are only used if `color` is true.
+	///
+	/// # Examples
+	/// ```
+	/// # use knightrs_bytecode::{error::ErrorLocation, Error};
+	/// # use knightrs_bytecode::parser::source_location::ProgramSource;
+	/// let location = ErrorLocation { source: "-e".into(), lineno: 2, column: Some(5) };
+	/// let err = Error::ParseError("missing argument 2 for `+`".into(), location);
+	///
+	/// assert_eq!(
+	///     err.render_with_color("; = a 1\n: + a", ProgramSource::ExprFlag, false),
+	///     "error: missing argument 2 for `+`
+	///  --> -e:2:5
+	///   |
+	/// 2 | : + a
+	///   |     ^"
+	/// );
+	/// ```
+	#[must_use]
+	pub fn render_with_color(&self, source: &str, path: ProgramSource<'_>, color: bool) -> String {
+		let paint = |style: &str, text: &str| {
+			if color {
+				format!("{style}{text}{RESET}")
+			} else {
+				text.to_string()
+			}
+		};
+
+		let display;
+		let (message, location, rest) = match self {
+			Self::ParseError(kind, location) => (kind.as_str(), Some(location), ""),
+			Self::Stacktrace(trace, location) => {
+				let (first, rest) = trace.split_once('\n').unwrap_or((trace, ""));
+				(first, Some(location), rest)
+			}
+			_ => {
+				display = self.to_string();
+				(display.as_str(), None, "")
+			}
+		};
+
+		let mut out = String::new();
+		let _ = writeln!(out, "{}: {}", paint(RED, "error"), paint(BOLD, message));
+
+		if let Some(location) = location {
+			let lineno = location.lineno.to_string();
+			let pad = " ".repeat(lineno.len());
+			let _ = writeln!(out, "{pad}{} {location}", paint(BLUE, "-->"));
+
+			let line = (location.source == path.to_string())
+				.then(|| source.lines().nth(location.lineno.saturating_sub(1)))
+				.flatten();
+
+			if let Some(line) = line {
+				let (start, width) = match location.column {
+					Some(column) => (rendered_width(line.chars().take(column - 1)), 1),
+					None => {
+						let trimmed = line.trim_start();
+						let start = rendered_width(line[..line.len() - trimmed.len()].chars());
+						(start, rendered_width(trimmed.trim_end().chars()).max(1))
+					}
+				};
+
+				let bar = paint(BLUE, "|");
+				let _ = writeln!(out, "{pad} {bar}");
+				let _ = writeln!(
+					out,
+					"{} {bar} {}",
+					paint(BLUE, &lineno),
+					line.replace('\t', &" ".repeat(TAB_WIDTH))
+				);
+				let caret = paint(RED, &"^".repeat(width));
+				let _ = writeln!(out, "{pad} {bar} {}{caret}", " ".repeat(start));
+			}
+		}
+
+		for line in rest.lines() {
+			let _ = writeln!(out, "{line}");
+		}
+
+		out.truncate(out.trim_end().len());
+		out
+	}
+}
+
+fn rendered_width(chars: impl Iterator<Item = char>) -> usize {
+	chars.map(|chr| if chr == '\t' { TAB_WIDTH } else { 1 }).sum()
+}
@@ -1,11 +1,11 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
-use std::collections::HashSet;
 use std::fmt::{self, Debug, Formatter};
 use std::mem::MaybeUninit;
 use std::sync::atomic::{AtomicU8, Ordering};
 
-use crate::value::{Value, ValueAlign};
+use crate::strings::KnStr;
+use crate::value::{InternPool, KnString, Value, ValueAlign};
 
 /// Gc is the garbage collector for Knight [`Value`]s.
 ///
@@ -19,9 +19,44 @@ pub struct Gc(RefCell<Inner>);
 struct Inner {
 	value_inners: Vec<*mut ValueInner>,
 	idx: usize,
-	roots: HashSet<*const ValueInner>,
+	// How many times each root has been rooted, so rooting the same value twice is sound.
+	roots: HashMap<*const ValueInner, usize>,
 	paused: bool,
 	mark_fns: HashMap<usize, Box<dyn Fn()>>,
+	interned: InternPool,
+	#[cfg(feature = "stats")]
+	allocations: Allocations,
+}
+
+/// How many values of each type a [`Gc`] has allocated, as returned by [`Gc::allocations`].
+#[cfg(feature = "stats")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Allocations {
+	/// The amount of [`KnString`](crate::value::KnString)s allocated.
+	pub strings: u64,
+
+	/// The amount of [`List`](crate::value::List)s allocated.
+	pub lists: u64,
+
+	/// The amount of custom values allocated.
+	pub customs: u64,
+}
+
+#[cfg(feature = "stats")]
+impl Allocations {
+	/// Returns the allocations which happened after `earlier` was taken.
+	pub fn since(self, earlier: Self) -> Self {
+		Self {
+			strings: self.strings - earlier.strings,
+			lists: self.lists - earlier.lists,
+			customs: self.customs - earlier.customs,
+		}
+	}
+
+	/// The total amount of allocations.
+	pub fn total(self) -> u64 {
+		self.strings + self.lists + self.customs
+	}
 }
 
 pub const ALLOC_VALUE_SIZE: usize = 32;
@@ -79,7 +114,10 @@ pub struct GcOptions {
 
 impl Default for GcOptions {
 	fn default() -> Self {
-		Self { starting_cap: 1000 }
+		// Miri is far too slow to preallocate thousands of `ValueInner`s every time a `Gc` is made.
+		let starting_cap = if cfg!(feature = "miri-compat") { 16 } else { 1000 };
+
+		Self { starting_cap }
 	}
 }
 
@@ -97,10 +135,13 @@ impl Gc {
 				value_inners: (0..opts.starting_cap)
 					.map(|_| Box::into_raw(Box::new(EMPTY_INNER)))
 					.collect(),
-				roots: HashSet::new(),
+				roots: HashMap::new(),
 				idx: 0,
 				paused: false,
 				mark_fns: HashMap::new(),
+				interned: InternPool::default(),
+				#[cfg(feature = "stats")]
+				allocations: Allocations::default(),
 			}
 			.into(),
 		)
@@ -177,6 +218,12 @@ impl Gc {
 		self.next_open_inner_().expect("we just extended")
 	}
 
+	/// Returns how many values of each type have been allocated so far.
+	#[cfg(feature = "stats")]
+	pub fn allocations(&self) -> Allocations {
+		self.0.borrow().allocations
+	}
+
 	pub fn pause(&self) {
 		let mut inner = self.0.borrow_mut();
 		assert!(!inner.paused);
@@ -242,6 +289,18 @@ impl Gc {
 			}
 		}
 
+		#[cfg(feature = "stats")]
+		{
+			let allocations = &mut self.0.borrow_mut().allocations;
+			if flags & FLAG_IS_STRING != 0 {
+				allocations.strings += 1;
+			} else if flags & FLAG_IS_LIST != 0 {
+				allocations.lists += 1;
+			} else {
+				allocations.customs += 1;
+			}
+		}
+
 		let inner = self.next_open_inner();
 
 		unsafe {
@@ -261,6 +320,29 @@ impl Gc {
 	// 	}
 	// }
 
+	fn root_inner(&self, inner: *const ValueInner) {
+		*self.0.borrow_mut().roots.entry(inner).or_insert(0) += 1;
+	}
+
+	fn unroot_inner(&self, inner: *const ValueInner) {
+		let mut gc_inner = self.0.borrow_mut();
+
+		let Some(count) = gc_inner.roots.get_mut(&inner) else {
+			unreachable!("unroot of a non-rooted inner? inner={inner:?}, gc={:?}", &gc_inner.roots);
+		};
+
+		*count -= 1;
+		if *count == 0 {
+			gc_inner.roots.remove(&inner);
+		}
+	}
+
+	/// Gets the string with the contents `source` from `self`'s pool of short string literals,
+	/// adding it if it's not there; see [`InternPool::intern`].
+	pub(crate) fn intern(&self, source: &KnStr) -> Option<KnString<'_>> {
+		self.0.borrow_mut().interned.intern(source)
+	}
+
 	// pub only for testing
 	pub unsafe fn mark_and_sweep(&self) {
 		for mark_fn in self.0.borrow().mark_fns.values() {
@@ -268,7 +350,7 @@ impl Gc {
 		}
 
 		// Mark all elements accessible from the root
-		for &root in &self.0.borrow().roots {
+		for &root in self.0.borrow().roots.keys() {
 			unsafe {
 				ValueInner::mark(root);
 			}
@@ -327,6 +409,19 @@ impl ValueInner {
 		unsafe { &raw const (*this).flags }
 	}
 
+	/// In debug mode, ensures that `this` hasn't been collected; used to catch [`Value`]s which were
+	/// used after a collection without being rooted.
+	///
+	/// (This can't catch everything, as the slot may have been reused by another allocation since.)
+	#[inline]
+	pub(crate) unsafe fn debug_assert_live(this: *const Self) {
+		debug_assert_ne!(
+			unsafe { &*Self::flags(this) }.load(Ordering::SeqCst),
+			0,
+			"value used after being garbage collected (it should've been rooted via `ValueRoot`)"
+		);
+	}
+
 	pub(crate) unsafe fn as_knstring<'gc>(this: *const Self) -> Option<crate::value::KnString<'gc>> {
 		if unsafe { &*Self::flags(this) }.load(Ordering::SeqCst) & FLAG_IS_STRING != 0 {
 			Some(unsafe { crate::value::KnString::from_raw(this) })
@@ -343,6 +438,18 @@ impl ValueInner {
 		}
 	}
 
+	#[cfg(feature = "custom-types")]
+	pub(crate) unsafe fn as_custom<'gc>(this: *const Self) -> Option<crate::value::Custom<'gc>> {
+		let flags = unsafe { &*Self::flags(this) }.load(Ordering::SeqCst);
+
+		// `FLAG_IS_CUSTOM` is also used by strings and lists for their own purposes.
+		if flags & (FLAG_IS_STRING | FLAG_IS_LIST) == 0 && flags & FLAG_IS_CUSTOM != 0 {
+			Some(unsafe { crate::value::Custom::from_raw(this) })
+		} else {
+			None
+		}
+	}
+
 	pub(crate) unsafe fn mark(this: *const Self) {
 		let flags = unsafe { &*Self::flags(this) }.fetch_or(FLAG_GC_MARKED, Ordering::SeqCst);
 
@@ -362,6 +469,13 @@ impl ValueInner {
 				list.mark();
 			}
 		}
+
+		#[cfg(feature = "custom-types")]
+		if let Some(custom) = unsafe { Self::as_custom(this) } {
+			unsafe {
+				custom.mark();
+			}
+		}
 	}
 
 	pub(crate) unsafe fn deallocate(this: *const Self, check: bool) {
@@ -375,8 +489,16 @@ impl ValueInner {
 			unsafe {
 				list.deallocate();
 			}
-		} else if check {
-			unreachable!("non-list non-string encountered?");
+		} else {
+			#[cfg(feature = "custom-types")]
+			let is_custom =
+				unsafe { Self::as_custom(this) }.map(|custom| unsafe { custom.deallocate() }).is_some();
+			#[cfg(not(feature = "custom-types"))]
+			let is_custom = false;
+
+			if check && !is_custom {
+				unreachable!("non-list non-string non-custom encountered?");
+			}
 		}
 
 		// Mark it as `0` to indicate it's unused.
@@ -401,7 +523,7 @@ impl<'gc, T: AsValueInner> GcRoot<'gc, T> {
 	// safety: that from_value_inner seems like it could be unsafe potentially lol
 	pub fn new(t: &T, gc: &'gc Gc) -> Self {
 		let inner = t.as_value_inner();
-		gc.0.borrow_mut().roots.insert(inner);
+		gc.root_inner(inner);
 
 		Self(unsafe { T::from_value_inner(inner) }, Some(gc))
 	}
@@ -426,6 +548,26 @@ impl<'gc, T: AsValueInner> GcRoot<'gc, T> {
 		result
 	}
 
+	/// Calls `func` with the rooted value, and then unroots it.
+	///
+	/// This is the safe counterpart to [`GcRoot::with_inner`]: `self` stays rooted while `func` is
+	/// running, so it can allocate freely.
+	pub fn map<R>(self, func: impl FnOnce(&T) -> R) -> R {
+		func(&self.0)
+	}
+
+	/// Gets the rooted value as a [`Value`].
+	///
+	/// Note that the returned value is only guaranteed to be live for as long as `self` is; use
+	/// [`Value::root`] if it needs to outlive `self`.
+	pub fn as_value(&self) -> Value<'gc>
+	where
+		T: Into<Value<'gc>>,
+	{
+		// SAFETY: `self` keeps the inner alive, and the returned value doesn't outlive `'gc`.
+		unsafe { T::from_value_inner(self.0.as_value_inner()) }.into()
+	}
+
 	// Marks the value as a permanent gc root, and returns it.
 	pub fn make_permanent(self) -> T {
 		let inner = unsafe { std::ptr::read(&self.0) };
@@ -435,12 +577,7 @@ impl<'gc, T: AsValueInner> GcRoot<'gc, T> {
 
 	fn unroot_inner(&mut self) {
 		if let Some(gc) = self.1 {
-			let mut gc_inner = gc.0.borrow_mut();
-			let inner = self.0.as_value_inner();
-
-			if !gc_inner.roots.remove(&inner) {
-				unreachable!("unroot of a non-rooted inner? inner={inner:?}, gc={:?}", &gc_inner.roots);
-			}
+			gc.unroot_inner(self.0.as_value_inner());
 		}
 	}
 
@@ -454,6 +591,102 @@ impl<'gc, T: AsValueInner> GcRoot<'gc, T> {
 	// }
 }
 
+/// A set of roots that are all released at once, at the end of a [`Gc::scope`].
+pub struct RootScope<'gc> {
+	roots: Vec<ValueRoot<'gc>>,
+	gc: &'gc Gc,
+}
+
+impl<'gc> RootScope<'gc> {
+	/// Gets the [`Gc`] the scope is for.
+	pub fn gc(&self) -> &'gc Gc {
+		self.gc
+	}
+
+	/// Roots `value` until the end of the scope, and returns it.
+	pub fn root(&mut self, value: Value<'gc>) -> Value<'gc> {
+		self.roots.push(value.root(self.gc));
+		value
+	}
+
+	/// Keeps `root`'s value alive until the end of the scope, and returns it.
+	pub fn keep<T>(&mut self, root: GcRoot<'gc, T>) -> Value<'gc>
+	where
+		T: AsValueInner + Into<Value<'gc>>,
+	{
+		self.root(root.as_value())
+	}
+}
+
+impl Gc {
+	/// Runs `func` with a [`RootScope`], which keeps everything rooted within it alive until `func`
+	/// returns.
+	///
+	/// This is useful when building up multiple values which refer to one another, as it avoids
+	/// having to juggle individual [`GcRoot`]s and [`ValueRoot`]s.
+	pub fn scope<'gc, R>(&'gc self, func: impl FnOnce(&mut RootScope<'gc>) -> R) -> R {
+		func(&mut RootScope { roots: Vec::new(), gc: self })
+	}
+}
+
+/// A [`Value`] which won't be garbage collected until the `ValueRoot` is dropped.
+///
+/// [`Value`]s are `Copy`, and the [`Gc`] has no way of knowing about copies which live on the Rust
+/// stack: A value is only kept alive while it's reachable from a root (or from a mark fn, such as
+/// the [`Vm`](crate::vm::Vm)'s). So, values which need to be kept around across anything that can
+/// allocate (and thus collect) must be rooted first.
+pub struct ValueRoot<'gc> {
+	value: Value<'gc>,
+	gc: &'gc Gc,
+}
+
+impl<'gc> ValueRoot<'gc> {
+	/// Roots `value` in `gc`. Values which aren't allocated (eg integers) don't need to be rooted,
+	/// but they're still accepted for uniformity.
+	pub fn new(value: Value<'gc>, gc: &'gc Gc) -> Self {
+		if let Some(inner) = value.as_alloc() {
+			gc.root_inner(inner);
+		}
+
+		Self { value, gc }
+	}
+
+	/// Gets the rooted value.
+	///
+	/// Note that the returned value is only guaranteed to be live for as long as `self` is.
+	pub fn get(&self) -> Value<'gc> {
+		self.value
+	}
+}
+
+impl Clone for ValueRoot<'_> {
+	fn clone(&self) -> Self {
+		Self::new(self.value, self.gc)
+	}
+}
+
+impl Debug for ValueRoot<'_> {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		Debug::fmt(&self.value, f)
+	}
+}
+
+impl Drop for ValueRoot<'_> {
+	fn drop(&mut self) {
+		if let Some(inner) = self.value.as_alloc() {
+			self.gc.unroot_inner(inner);
+		}
+	}
+}
+
+impl<'gc> std::ops::Deref for ValueRoot<'gc> {
+	type Target = Value<'gc>;
+
+	fn deref(&self) -> &Self::Target {
+		&self.value
+	}
+}
+
 impl<T: AsValueInner> Drop for GcRoot<'_, T> {
 	fn drop(&mut self) {
 		self.unroot_inner();
@@ -1,3 +1,13 @@
+//! A bytecode-based interpreter for [Knight](https://github.com/knight-lang/knight-lang).
+//!
+//! # Panics
+//! No Knight program should be able to panic (or otherwise abort) the process running it: every
+//! runtime failure, such as an overflow, an out-of-bounds index, or a bad `QUIT` status, is
+//! returned as an [`Error`]. The one exception is extensions which aren't implemented yet, which
+//! panic via `todo!`. When the `deny-panics` feature is enabled, they're returned as errors as
+//! well. (Programs that nest deeply enough can still overflow the stack, however.)
+//!
+//! `tests/no_panics.rs` runs a battery of failing programs to check this.
 #![cfg_attr(debug_assertions, allow(unused))] // todo
 #![cfg_attr(debug_assertions, allow(deprecated))] // allow our own deprecated stuff while debugging
 #![warn(unsafe_op_in_unsafe_fn)]
@@ -26,6 +36,24 @@ macro_rules! bug {
 	}
 }
 
+// Like `todo!`, except with the `deny-panics` feature it returns an error from the enclosing
+// function instead. Runtime errors are `Error::Todo`s, and `parse(parser)` makes an
+// `UnsupportedSyntax`.
+macro_rules! todo_or_error {
+	(parse($parser:expr), $what:literal) => {{
+		#[cfg(feature = "deny-panics")]
+		return Err($parser.error(crate::parser::ParseErrorKind::UnsupportedSyntax($what)));
+		#[cfg(not(feature = "deny-panics"))]
+		todo!($what)
+	}};
+	($fmt:literal $(, $arg:expr)* $(,)?) => {{
+		#[cfg(feature = "deny-panics")]
+		return Err(crate::Error::Todo(format!($fmt $(, $arg)*)).into());
+		#[cfg(not(feature = "deny-panics"))]
+		todo!($fmt $(, $arg)*)
+	}};
+}
+
 #[allow(unused)] // TODO
 macro_rules! cfg_expr {
 	(feature = $feature:literal, $ift:expr, $iff:expr) => {{
@@ -40,6 +68,7 @@ macro_rules! cfg_expr {
 	}};
 }
 
+pub mod capabilities;
 mod container;
 pub mod env;
 pub mod error;
@@ -49,10 +78,13 @@ pub mod options;
 pub mod parser;
 pub mod program;
 pub mod strings;
+#[cfg(feature = "embedded")]
+pub mod testing;
 
 pub mod value;
 
 pub mod vm;
+pub use capabilities::capabilities;
 pub use env::Environment;
 pub use error::{Error, Result};
 pub use gc::Gc;
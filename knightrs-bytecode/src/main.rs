@@ -1,7 +1,7 @@
 #![allow(unused)]
 
 use std::default;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use knightrs_bytecode::env::Environment;
 use knightrs_bytecode::gc::Gc;
@@ -19,12 +19,12 @@ fn run(
 	source: ProgramSource<'_>,
 	program: &str,
 	argv: impl Iterator<Item = String>,
-) -> Result<(), String> {
+) -> knightrs_bytecode::Result<()> {
 	let gc = env.gc();
-	let mut parser = Parser::new(env, source, &program).map_err(|s| s.to_string())?;
+	let mut parser = Parser::new(env, source, &program)?;
 
 	gc.pause();
-	let program = parser.parse_program().map_err(|err| err.to_string())?;
+	let program = parser.parse_program()?;
 
 	// dbg!(&program);
 
@@ -32,7 +32,7 @@ fn run(
 	// gc.add_mark_fn(|| vm.mark());
 	gc.unpause();
 
-	vm.run_entire_program(argv).map_err(|e| e.to_string()).and(Ok(()))
+	vm.run_entire_program(argv).and(Ok(()))
 }
 
 fn main1() {
@@ -78,6 +78,16 @@ fn main() {
 	unsafe {
 		let gc = Gc::default();
 		gc.run(|gc| {
+			let mut args = std::env::args().skip(1);
+			let (program, path) = match args.next().as_deref() {
+				Some("-f") => {
+					let path = PathBuf::from(args.next().expect("missing expr for -f"));
+					(std::fs::read_to_string(&path).expect("cannot open file"), Some(path))
+				}
+				Some("-e") => (args.next().expect("missing expr for -e"), None),
+				_ => panic!("invalid option: -e or -f only"),
+			};
+
 			let mut env = Environment::new(
 				{
 					let mut opts = Options::default();
@@ -97,8 +107,23 @@ fn main() {
 						opts.extensions.argv = true;
 						opts.extensions.functions.eval = true;
 						opts.extensions.functions.value = true;
+						opts.extensions.functions.srand = true;
+						opts.extensions.functions.system = true;
+						opts.extensions.functions.base = true;
+						opts.extensions.functions.bitwise = true;
+						opts.extensions.functions.cmp = true;
+						opts.extensions.functions.import = true;
+						opts.extensions.functions.apply = true;
+						opts.extensions.functions.type_of = true;
+						opts.extensions.functions.convert = true;
+						opts.extensions.functions.random = true;
+						opts.extensions.functions.split = true;
+						opts.extensions.functions.assert = true;
 						opts.extensions.builtin_fns.assign_to_strings = true;
 						opts.extensions.builtin_fns.assign_to_random = true;
+						opts.extensions.builtin_fns.assign_to_prompt = true;
+						opts.extensions.builtin_fns.assign_to_system = true;
+						opts.extensions.builtin_fns.length_of_anything = true;
 						opts.extensions.syntax.control_flow = true;
 					}
 					#[cfg(feature = "compliance")]
@@ -110,32 +135,42 @@ fn main() {
 						opts.compliance.variable_name_length = true;
 						opts.compliance.variable_count = true;
 						opts.compliance.forbid_trailing_tokens = true;
+						opts.compliance.forbid_unbalanced_parens = true;
+						opts.compliance.check_parens = true;
 						opts.compliance.limit_rand_range = true;
 						opts.compliance.check_quit_status_codes = true;
 						opts.compliance.strict_conversions = true;
-						opts.compliance.disable_all_extensions = true;
 						opts.compliance.no_block_conversions = true;
 					}
 
+					if let Ok(flags) = std::env::var("KNIGHT_OPTIONS") {
+						if let Err(err) = opts.apply_str_flags(&[&flags]) {
+							eprintln!("error: {err}");
+							std::process::exit(1);
+						}
+					}
+
+					// Scripts can declare the compliance checks and extensions they need.
+					if let Err(err) = opts.apply_pragmas(&program, &["compliance", "extensions"]) {
+						eprintln!("error: {err}");
+						std::process::exit(1);
+					}
+
 					opts
 				},
 				&gc,
 			);
 
-			let mut args = std::env::args().skip(1);
-			let (program, source) = match args.next().as_deref() {
-				Some("-f") => (
-					std::fs::read_to_string(args.next().expect("missing expr for -f"))
-						.expect("cannot open file"),
-					ProgramSource::File(Path::new("<file>")),
-				),
-				Some("-e") => (args.next().expect("missing expr for -e"), ProgramSource::ExprFlag),
-				_ => panic!("invalid option: -e or -f only"),
-			};
+			let source = path.as_deref().map_or(ProgramSource::ExprFlag, ProgramSource::File);
 
 			match run(&mut env, source, &program, args) {
 				Ok(()) => {}
 				Err(err) => {
+					// `process::exit` doesn't run destructors, so make sure `OUTPUT`s are written.
+					let _ = std::io::Write::flush(env.output());
+					#[cfg(feature = "annotate")]
+					eprintln!("{}", err.render(&program, source));
+					#[cfg(not(feature = "annotate"))]
 					eprintln!("error: {err}");
 					std::process::exit(1)
 				}
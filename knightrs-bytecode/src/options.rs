@@ -1,6 +1,8 @@
+use crate::env::{FlushPolicy, Newline};
 use crate::strings::Encoding;
+use knight_strings::IntegerOverflow;
 
-#[derive(Default, Clone)]
+#[derive(Clone)]
 pub struct Options {
 	pub encoding: Encoding,
 
@@ -16,17 +18,506 @@ pub struct Options {
 	#[cfg(feature = "embedded")]
 	pub embedded: Embedded,
 
+	pub io: Io,
+
 	#[cfg(feature = "check-variables")]
 	pub check_variables: bool,
 
+	/// Treat `(` and `)` as tokens, and ensure they always wrap exactly one expression.
+	///
+	/// Without this, parens are treated as whitespace. See also [`Compliance::check_parens`].
 	#[cfg(feature = "check-parens")]
-	pub check_parens: bool, // TODO: also make this strict compliance
+	pub check_parens: bool,
+
+	/// The longest [`KnString`](crate::value::KnString) or [`List`](crate::value::List) that `*` is
+	/// allowed to create by repetition.
+	///
+	/// This is always checked, regardless of [`Compliance::check_container_length`], so programs
+	/// like `* (+,1,1) 99999999999` error out instead of attempting absurd allocations. It defaults
+	/// to [`i32::MAX`], which is the longest container the Knight spec requires.
+	pub max_repetition_length: usize,
+
+	/// What happens when a string containing an out-of-bounds integer is converted to an integer.
+	///
+	/// This is ignored (and overflowing is always an error) when
+	/// [`Compliance::check_overflow`] is enabled.
+	pub string_to_integer_overflow: IntegerOverflow,
+}
+
+impl Default for Options {
+	fn default() -> Self {
+		Self {
+			// Only the strict Knight encoding is checked by default when compliance is compiled in.
+			encoding: cfg_expr!(feature = "compliance", Encoding::Knight, Encoding::Utf8),
+			#[cfg(feature = "compliance")]
+			compliance: Default::default(),
+			#[cfg(feature = "extensions")]
+			extensions: Default::default(),
+			#[cfg(feature = "qol")]
+			qol: Default::default(),
+			#[cfg(feature = "embedded")]
+			embedded: Default::default(),
+			io: Default::default(),
+			#[cfg(feature = "check-variables")]
+			check_variables: false,
+			#[cfg(feature = "check-parens")]
+			check_parens: false,
+			max_repetition_length: knight_strings::MAX_LEN,
+			string_to_integer_overflow: IntegerOverflow::Wrap,
+		}
+	}
+}
+
+impl Options {
+	/// Whether extensions have been turned off via [`Compliance::disable_all_extensions`].
+	#[cfg(feature = "extensions")]
+	pub fn extensions_disabled(&self) -> bool {
+		cfg_expr!(feature = "compliance", self.compliance.disable_all_extensions, false)
+	}
+
+	/// Gets the extensions that are enabled.
+	///
+	/// This should be used instead of accessing [`Options::extensions`] directly, as it returns the
+	/// default (ie all-disabled) extensions when [`Options::extensions_disabled`] is true.
+	#[cfg(feature = "extensions")]
+	pub fn extensions(&self) -> &Extensions {
+		static DISABLED: std::sync::OnceLock<Extensions> = std::sync::OnceLock::new();
+
+		if self.extensions_disabled() {
+			DISABLED.get_or_init(Extensions::default)
+		} else {
+			&self.extensions
+		}
+	}
+}
+
+/// Declares every boolean option that [`Options::from_str_flags`] knows about.
+macro_rules! boolean_flags {
+	($($(#[$meta:meta])* $($field:ident).+),* $(,)?) => {
+		/// The names of every boolean option, as field paths (eg `compliance.i32_integer`).
+		const BOOLEAN_FLAGS: &[&str] = &[$($(#[$meta])* stringify!($($field).+)),*];
+
+		impl Options {
+			fn boolean_flag_mut(&mut self, name: &str) -> Option<&mut bool> {
+				$(
+					$(#[$meta])*
+					if name == stringify!($($field).+) {
+						return Some(&mut self.$($field).+);
+					}
+				)*
+
+				None
+			}
+
+			fn boolean_flag(&self, name: &str) -> Option<bool> {
+				$(
+					$(#[$meta])*
+					if name == stringify!($($field).+) {
+						return Some(self.$($field).+);
+					}
+				)*
+
+				None
+			}
+		}
+	};
+}
+
+boolean_flags! {
+	#[cfg(feature = "check-variables")] check_variables,
+	#[cfg(feature = "check-parens")] check_parens,
+
+	#[cfg(feature = "qol")] qol.stacktrace,
+	#[cfg(feature = "qol")] qol.stacktrace_locals,
+	#[cfg(feature = "qol")] qol.dump_to_stderr,
+	#[cfg(feature = "qol")] qol.ignore_broken_pipe,
+	#[cfg(feature = "qol")] qol.cache_small_integers,
+
+	#[cfg(feature = "embedded")] embedded.dont_exit_when_quitting,
+
+	#[cfg(feature = "compliance")] compliance.check_container_length,
+	#[cfg(feature = "compliance")] compliance.i32_integer,
+	#[cfg(feature = "compliance")] compliance.check_overflow,
+	#[cfg(feature = "compliance")] compliance.check_integer_function_bounds,
+	#[cfg(feature = "compliance")] compliance.variable_name_length,
+	#[cfg(feature = "compliance")] compliance.variable_count,
+	#[cfg(feature = "compliance")] compliance.forbid_trailing_tokens,
+	#[cfg(feature = "compliance")] compliance.forbid_unbalanced_parens,
+	#[cfg(feature = "compliance")] compliance.check_parens,
+	#[cfg(feature = "compliance")] compliance.strict_blocks,
+	#[cfg(feature = "compliance")] compliance.no_block_conversions,
+	#[cfg(feature = "compliance")] compliance.limit_rand_range,
+	#[cfg(feature = "compliance")] compliance.check_quit_status_codes,
+	#[cfg(feature = "compliance")] compliance.strict_conversions,
+	#[cfg(feature = "compliance")] compliance.disable_all_extensions,
+
+	#[cfg(feature = "extensions")] extensions.negative_indexing,
+	#[cfg(feature = "extensions")] extensions.argv,
+	#[cfg(feature = "extensions")] extensions.case_insensitive_extensions,
+	#[cfg(feature = "extensions")] extensions.functions.eval,
+	#[cfg(feature = "extensions")] extensions.functions.value,
+	#[cfg(feature = "extensions")] extensions.functions.srand,
+	#[cfg(feature = "extensions")] extensions.functions.system,
+	#[cfg(feature = "extensions")] extensions.functions.base,
+	#[cfg(feature = "extensions")] extensions.functions.bitwise,
+	#[cfg(feature = "extensions")] extensions.functions.cmp,
+	#[cfg(feature = "extensions")] extensions.functions.import,
+	#[cfg(feature = "extensions")] extensions.functions.apply,
+	#[cfg(feature = "extensions")] extensions.functions.type_of,
+	#[cfg(feature = "extensions")] extensions.functions.convert,
+	#[cfg(feature = "extensions")] extensions.functions.random,
+	#[cfg(feature = "extensions")] extensions.functions.split,
+	#[cfg(feature = "extensions")] extensions.functions.assert,
+	#[cfg(feature = "extensions")] extensions.breaking.random_can_be_negative,
+	#[cfg(feature = "extensions")] extensions.breaking.euclidean_remainder,
+	#[cfg(feature = "extensions")] extensions.syntax.control_flow,
+	#[cfg(feature = "extensions")] extensions.builtin_fns.assign_to_strings,
+	#[cfg(feature = "extensions")] extensions.builtin_fns.assign_to_random,
+	#[cfg(feature = "extensions")] extensions.builtin_fns.assign_to_prompt,
+	#[cfg(feature = "extensions")] extensions.builtin_fns.assign_to_system,
+	#[cfg(feature = "extensions")] extensions.builtin_fns.length_of_anything,
+}
+
+/// Options which take a value rather than being on or off.
+#[rustfmt::skip]
+const VALUE_FLAGS: &[&str] = &[
+	"encoding",
+	"max_repetition_length",
+	"string_to_integer_overflow",
+	"io.newline",
+	"io.flush",
+	#[cfg(feature = "compliance")] "compliance.max_container_length",
+	#[cfg(feature = "extensions")] "extensions.max_eval_depth",
+	#[cfg(feature = "extensions")] "extensions.max_eval_length",
+];
+
+/// Problems that can occur when parsing options via [`Options::from_str_flags`].
+#[derive(Error, Debug)]
+pub enum OptionsError {
+	/// No option has the given name.
+	#[error("unknown option {name:?}; valid options are: {}", valid_flag_names())]
+	UnknownOption { name: String },
+
+	/// The value given to an option isn't valid for it.
+	#[error("invalid value {value:?} for option {name:?}: expected {expected}")]
+	InvalidValue { name: String, value: String, expected: &'static str },
+
+	/// A pragma (see [`Options::apply_pragmas`]) named an option that isn't allowed to be set by
+	/// pragmas.
+	#[error("option {name:?} can't be set by a pragma")]
+	PragmaNotAllowed { name: String },
+
+	/// A pragma (see [`Options::apply_pragmas`]) wasn't well-formed.
+	#[error("invalid pragma {pragma:?}: missing closing `)`")]
+	InvalidPragma { pragma: String },
+}
+
+fn valid_flag_names() -> String {
+	Options::flag_names().collect::<Vec<_>>().join(", ")
+}
+
+impl Options {
+	/// Creates [`Options`] by applying `flags` to the [default](Options::default) options.
+	///
+	/// See [`Options::apply_str_flags`] for the syntax of `flags`.
+	///
+	/// # Examples
+	/// ```
+	/// # use knightrs_bytecode::Options;
+	/// let opts = Options::from_str_flags(&["max-repetition-length=100", "encoding=ascii"]).unwrap();
+	/// assert_eq!(opts.max_repetition_length, 100);
+	///
+	/// assert!(Options::from_str_flags(&["not-an-option"]).is_err());
+	/// ```
+	pub fn from_str_flags(flags: &[&str]) -> Result<Self, OptionsError> {
+		let mut opts = Self::default();
+		opts.apply_str_flags(flags)?;
+		Ok(opts)
+	}
+
+	/// Sets the options named by `flags`.
+	///
+	/// Each element of `flags` is a comma-separated list of options, named by their path with `-`s
+	/// or `_`s (eg `compliance.i32-integer,extensions.negative-indexing`). Boolean options are enabled
+	/// when just named, or can be given explicitly with `=true` or `=false`. Other options require a
+	/// value: `encoding=utf8|knight|ascii`, `max-repetition-length=N`,
+	/// `string-to-integer-overflow=wrap|saturate|error`, `io.newline=lf|crlf`,
+	/// `io.flush=every-output|line|on-exit|never`, (with compliance)
+	/// `compliance.max-container-length=N|none`, and (with extensions)
+	/// `extensions.max-eval-depth=N|none` and `extensions.max-eval-length=N|none`.
+	///
+	/// Every option before an invalid one is still applied.
+	pub fn apply_str_flags(&mut self, flags: &[&str]) -> Result<(), OptionsError> {
+		for flag in flags.iter().flat_map(|flags| flags.split(',')) {
+			let flag = flag.trim();
+			if flag.is_empty() {
+				continue;
+			}
+
+			let (name, value) = match flag.split_once('=') {
+				Some((name, value)) => (name.trim(), Some(value.trim())),
+				None => (flag, None),
+			};
+
+			self.apply_str_flag(name, value)?;
+		}
+
+		Ok(())
+	}
+
+	/// Sets the options declared by pragmas at the start of `source`, so that programs can declare
+	/// what they need to run.
+	///
+	/// Pragmas are lines at the very start of `source` beginning with `#!`, such as
+	/// `#! compliance(i32_integer) extensions(negative-indexing, max-eval-depth=10)`. Each option
+	/// is either named on its own (eg `check-parens`), or within a group, which is the same as
+	/// prefixing it with the group's name and a `.`. Options can be given values in the same way
+	/// as [`Options::apply_str_flags`]. Lines which start with `#!/` (or `#! /`) are shebangs, and
+	/// are ignored. As pragmas are also comments, programs with them can still be run normally.
+	///
+	/// Only options within `allowed` can be set. Each element of it is either an option name, or a
+	/// group, which allows every option within it (eg `extensions` allows
+	/// `extensions.functions.eval`).
+	///
+	/// Every option before an invalid one is still applied.
+	///
+	/// # Examples
+	/// ```
+	/// # use knightrs_bytecode::{Options, options::OptionsError};
+	/// let mut opts = Options::default();
+	/// let source = "#!/usr/bin/env knight\n#! extensions(negative_indexing)\nOUTPUT 1";
+	/// opts.apply_pragmas(source, &["extensions"]).unwrap();
+	/// assert!(opts.extensions.negative_indexing);
+	///
+	/// let err = opts.apply_pragmas("#! check_variables\nOUTPUT 1", &["extensions"]).unwrap_err();
+	/// assert!(matches!(err, OptionsError::PragmaNotAllowed { .. }));
+	/// ```
+	pub fn apply_pragmas(&mut self, source: &str, allowed: &[&str]) -> Result<(), OptionsError> {
+		let pragmas = source
+			.lines()
+			.map_while(|line| line.strip_prefix("#!"))
+			.map(str::trim)
+			.filter(|line| !line.starts_with('/'));
+
+		for line in pragmas {
+			let mut rest = line;
+
+			while !rest.is_empty() {
+				let end = rest.find(|c: char| c == '(' || c.is_whitespace()).unwrap_or(rest.len());
+				let (name, after) = rest.split_at(end);
+
+				if let Some(after) = after.strip_prefix('(') {
+					let (names, after) = after
+						.split_once(')')
+						.ok_or_else(|| OptionsError::InvalidPragma { pragma: line.to_string() })?;
+
+					for option in names.split(|c: char| c == ',' || c.is_whitespace()) {
+						if !option.is_empty() {
+							self.apply_pragma(&format!("{name}.{option}"), allowed)?;
+						}
+					}
+
+					rest = after.trim_start();
+				} else {
+					self.apply_pragma(name, allowed)?;
+					rest = after.trim_start();
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	fn apply_pragma(&mut self, option: &str, allowed: &[&str]) -> Result<(), OptionsError> {
+		let (name, value) = match option.split_once('=') {
+			Some((name, value)) => (name, Some(value)),
+			None => (option, None),
+		};
+
+		let normalized = name.replace('-', "_");
+		let is_allowed = allowed.iter().any(|allowed| {
+			let allowed = allowed.replace('-', "_");
+			normalized
+				.strip_prefix(&allowed)
+				.is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+		});
+
+		if !is_allowed {
+			return Err(OptionsError::PragmaNotAllowed { name: name.to_string() });
+		}
+
+		self.apply_str_flag(name, value)
+	}
+
+	fn apply_str_flag(&mut self, name: &str, value: Option<&str>) -> Result<(), OptionsError> {
+		let normalized = name.replace('-', "_");
+		let length = || value.and_then(|value| value.parse().ok());
+		let invalid = |expected| OptionsError::InvalidValue {
+			name: name.to_string(),
+			value: value.unwrap_or_default().to_string(),
+			expected,
+		};
+
+		if let Some(flag) = self.boolean_flag_mut(&normalized) {
+			*flag = match value {
+				None | Some("true") => true,
+				Some("false") => false,
+				Some(_) => return Err(invalid("`true` or `false`")),
+			};
+			return Ok(());
+		}
+
+		match normalized.as_str() {
+			"encoding" => {
+				self.encoding = match value {
+					Some("utf8" | "utf-8") => Encoding::Utf8,
+					Some("knight") => Encoding::Knight,
+					Some("ascii") => Encoding::Ascii,
+					_ => return Err(invalid("`utf8`, `knight`, or `ascii`")),
+				}
+			}
+			"max_repetition_length" => {
+				self.max_repetition_length = length().ok_or_else(|| invalid("a length"))?;
+			}
+			"string_to_integer_overflow" => {
+				self.string_to_integer_overflow = match value {
+					Some("wrap") => IntegerOverflow::Wrap,
+					Some("saturate") => IntegerOverflow::Saturate,
+					Some("error") => IntegerOverflow::Error,
+					_ => return Err(invalid("`wrap`, `saturate`, or `error`")),
+				}
+			}
+			"io.newline" => {
+				self.io.newline = match value {
+					Some("lf") => Newline::Lf,
+					Some("crlf") => Newline::CrLf,
+					_ => return Err(invalid("`lf` or `crlf`")),
+				}
+			}
+			"io.flush" => {
+				self.io.flush = match value {
+					Some("every-output") => FlushPolicy::EveryOutput,
+					Some("line") => FlushPolicy::Line,
+					Some("on-exit") => FlushPolicy::OnExit,
+					Some("never") => FlushPolicy::Never,
+					_ => return Err(invalid("`always`, `line`, `full`, or `never`")),
+				}
+			}
+			#[cfg(feature = "compliance")]
+			"compliance.max_container_length" => {
+				self.compliance.max_container_length = match value {
+					Some("none") => None,
+					_ => Some(length().ok_or_else(|| invalid("a length or `none`"))?),
+				}
+			}
+			#[cfg(feature = "extensions")]
+			"extensions.max_eval_depth" => {
+				self.extensions.max_eval_depth = match value {
+					Some("none") => None,
+					_ => Some(length().ok_or_else(|| invalid("a depth or `none`"))?),
+				}
+			}
+			#[cfg(feature = "extensions")]
+			"extensions.max_eval_length" => {
+				self.extensions.max_eval_length = match value {
+					Some("none") => None,
+					_ => Some(length().ok_or_else(|| invalid("a length or `none`"))?),
+				}
+			}
+			_ => return Err(OptionsError::UnknownOption { name: name.to_string() }),
+		}
+
+		Ok(())
+	}
+
+	/// Returns every option that's set in `self`, in the format [`Options::apply_str_flags`] accepts.
+	///
+	/// Boolean options are only included if they're enabled. Options which take values are always
+	/// included.
+	pub fn active_flags(&self) -> Vec<String> {
+		let mut active = BOOLEAN_FLAGS
+			.iter()
+			.filter(|name| self.boolean_flag(name) == Some(true))
+			.map(|name| name.replace('_', "-"))
+			.collect::<Vec<_>>();
+
+		let encoding = match self.encoding {
+			Encoding::Utf8 => "utf8",
+			Encoding::Knight => "knight",
+			Encoding::Ascii => "ascii",
+		};
+		active.push(format!("encoding={encoding}"));
+		active.push(format!("max-repetition-length={}", self.max_repetition_length));
+
+		let overflow = match self.string_to_integer_overflow {
+			IntegerOverflow::Wrap => "wrap",
+			IntegerOverflow::Saturate => "saturate",
+			IntegerOverflow::Error => "error",
+		};
+		active.push(format!("string-to-integer-overflow={overflow}"));
+
+		let newline = match self.io.newline {
+			Newline::Lf => "lf",
+			Newline::CrLf => "crlf",
+		};
+		active.push(format!("io.newline={newline}"));
+
+		let flush = match self.io.flush {
+			FlushPolicy::EveryOutput => "every-output",
+			FlushPolicy::Line => "line",
+			FlushPolicy::OnExit => "on-exit",
+			FlushPolicy::Never => "never",
+		};
+		active.push(format!("io.flush={flush}"));
+
+		#[cfg(feature = "compliance")]
+		match self.compliance.max_container_length {
+			Some(len) => active.push(format!("compliance.max-container-length={len}")),
+			None => active.push("compliance.max-container-length=none".to_string()),
+		}
+
+		#[cfg(feature = "extensions")]
+		for (name, max) in [
+			("extensions.max-eval-depth", self.extensions.max_eval_depth),
+			("extensions.max-eval-length", self.extensions.max_eval_length),
+		] {
+			match max {
+				Some(max) => active.push(format!("{name}={max}")),
+				None => active.push(format!("{name}=none")),
+			}
+		}
+
+		active
+	}
+
+	/// Returns the names of every option that [`Options::apply_str_flags`] accepts.
+	pub fn flag_names() -> impl Iterator<Item = String> {
+		BOOLEAN_FLAGS.iter().chain(VALUE_FLAGS).map(|name| name.replace('_', "-"))
+	}
 }
 
 #[derive(Default, Clone)]
 #[cfg(feature = "qol")]
 pub struct QualityOfLife {
 	pub stacktrace: bool,
+
+	/// Include the values of the variables assigned within each block in stacktraces, as of when
+	/// the error occurred. As Knight only has global variables, a variable that's assigned in more
+	/// than one block will show the same (most recent) value in each of them.
+	pub stacktrace_locals: bool,
+
+	/// Write `DUMP`'s output to stderr instead of stdout, so debugging doesn't interfere with a
+	/// program's output. See also [`Environment::set_dump_stream`](crate::Environment::set_dump_stream).
+	pub dump_to_stderr: bool,
+
+	/// When `OUTPUT` or `DUMP` fails because stdout was closed (ie `EPIPE`, such as when piping
+	/// into `head`), quit the program successfully instead of raising an error, like most Unix
+	/// tools do.
+	pub ignore_broken_pipe: bool,
+
+	/// Reuse the same strings when converting integers in `-99..=999` to strings, instead of
+	/// allocating new ones each time. This speeds up programs which `OUTPUT` lots of small numbers.
+	pub cache_small_integers: bool,
 }
 
 #[derive(Default, Clone)]
@@ -35,13 +526,26 @@ pub struct Embedded {
 	pub dont_exit_when_quitting: bool,
 }
 
+/// Options for how `OUTPUT` writes to stdout.
+#[derive(Default, Clone)]
+pub struct Io {
+	/// What `OUTPUT` ends lines with. The Knight spec requires [`Newline::Lf`].
+	pub newline: Newline,
+
+	/// When `OUTPUT`'s writes are flushed to stdout; see [`FlushPolicy`]. This is only the initial
+	/// policy, and can be changed later via [`Output::set_flush_policy`](
+	/// crate::env::Output::set_flush_policy).
+	pub flush: FlushPolicy,
+}
+
 /// Options for additional compliance checking.
 ///
 /// If `feature = "compliance"` is not specified, all of these are disabled.
 #[derive(Default, Clone)]
 #[cfg(feature = "compliance")]
 pub struct Compliance {
-	/// Ensure that [`KnString`] and [`List`]s have lengths no longer than [`i32::MAX`].
+	/// Ensure that [`KnString`] and [`List`]s have lengths no longer than [`i32::MAX`] (or
+	/// [`max_container_length`](Compliance::max_container_length), if it's set).
 	///
 	/// This usually doesn't happen during normal execution (as allocations that long are very rare),
 	/// but can happen if `* str large_number` is used.
@@ -50,6 +554,11 @@ pub struct Compliance {
 	/// beyond that can cause panics.
 	pub check_container_length: bool, // make sure containers are within `i32::MAX`
 
+	/// A custom maximum length for [`check_container_length`](Compliance::check_container_length).
+	///
+	/// When `None`, [`knight_strings::MAX_LEN`] (ie [`i32::MAX`]) is used, as per the Knight spec.
+	pub max_container_length: Option<usize>,
+
 	/// Ensures that all [`Integer`] are strictly within [`i32`]'s bounds, as per the knight specs.
 	///
 	/// This ensure that _every_ operation that could create an [`Integer`] (including integer
@@ -89,6 +598,20 @@ pub struct Compliance {
 	/// Without this, trailing tokens are allowed, and are simply ignored.
 	pub forbid_trailing_tokens: bool,
 
+	/// Ensure that all `(`s and `)`s within the program are balanced.
+	///
+	/// When parens aren't [checked](Compliance::check_parens), they're treated as whitespace and so
+	/// aren't validated at all. This option keeps treating them as whitespace, but ensures that every
+	/// `(` has a matching `)` by the end of the program. (If parens are checked, this does nothing, as
+	/// they're already always balanced.)
+	pub forbid_unbalanced_parens: bool,
+
+	/// Ensure that `(` and `)` always wrap exactly one expression.
+	///
+	/// This is identical to [`Options::check_parens`], but lives here so that enabling every
+	/// compliance option also checks parens.
+	pub check_parens: bool,
+
 	/// Verify that blocks are _exclusively_ used in functions that support them.
 	///
 	/// Without this, a handful of functions (such as [`Value::kn_equals`] and [`Value::kn_dump`])
@@ -118,12 +641,28 @@ pub struct Compliance {
 
 	/// Disables all `feature = "extensions"`, regardless of their setting.
 	///
-	/// Currently not implemented.
+	/// Without this, extensions are enabled based on [`Options::extensions`]. With it, extension
+	/// functions are rejected when parsing, and extension behaviours are disabled at runtime.
 	pub disable_all_extensions: bool,
 }
 
+#[cfg(feature = "compliance")]
+impl Compliance {
+	/// Returns whether a [`KnString`] or [`List`] of length `len` is allowed.
+	///
+	/// This is always true unless [`check_container_length`](Compliance::check_container_length)
+	/// is enabled.
+	#[inline]
+	pub const fn is_valid_container_length(&self, len: usize) -> bool {
+		!self.check_container_length
+			|| knight_strings::is_valid_container_length(len, self.max_container_length)
+	}
+}
+
 cfg_if! {
 if #[cfg(feature = "extensions")] {
+	use std::collections::HashMap;
+
 	#[derive(Default, Clone)]
 	pub struct Extensions {
 		pub builtin_fns: BuiltinFns,
@@ -133,6 +672,28 @@ if #[cfg(feature = "extensions")] {
 		pub functions: Functions,
 		pub negative_indexing: bool,
 		pub argv: bool,
+
+		/// Match `X` function names case-insensitively, so `Xsrand` is the same as `XSRAND`.
+		///
+		/// Normally, only uppercase letters and `_` are part of an `X` function's name.
+		pub case_insensitive_extensions: bool,
+
+		/// Alternative names for `X` functions, mapped to the names they refer to (both without the
+		/// leading `X`). For example, mapping `"SEED"` to `"SRAND"` makes `XSEED` the same as `XSRAND`.
+		///
+		/// This lets renamed extensions keep their old names working. Aliases are only resolved once,
+		/// so an alias can't refer to another alias.
+		pub extension_aliases: HashMap<String, String>,
+
+		/// The most `EVAL`s that can be nested inside each other.
+		///
+		/// When `None`, `EVAL` can nest until the interpreter runs out of stack.
+		pub max_eval_depth: Option<usize>,
+
+		/// The longest source code, in bytes, that `EVAL` will run.
+		///
+		/// When `None`, source code of any length is allowed.
+		pub max_eval_length: Option<usize>,
 	}
 
 	#[derive(Default, Clone)]
@@ -149,12 +710,65 @@ if #[cfg(feature = "extensions")] {
 
 		/// Enables the `VALUE` extension
 		pub value: bool,
+
+		/// Enables the `XSRAND` extension, which seeds [`Env::random`] and returns the seed.
+		pub srand: bool,
+
+		/// Enables the `$` extension, which runs a shell command and returns its stdout.
+		pub system: bool,
+
+		/// Enables the `XBASE` and `XFROMBASE` extensions, which convert integers to and from
+		/// strings in bases `2..=36`.
+		pub base: bool,
+
+		/// Enables the `XBAND`, `XBOR`, `XBXOR`, `XSHL`, and `XSHR` extensions, which do bitwise
+		/// operations on integers.
+		pub bitwise: bool,
+
+		/// Enables the `XCMP` extension, which returns `-1`, `0`, or `1` depending on how its first
+		/// argument compares to its second.
+		pub cmp: bool,
+
+		/// Enables the `XIMPORT` extension, which compiles another file in place, with all of its
+		/// variables prefixed.
+		pub import: bool,
+
+		/// Enables the `XAPPLY` extension, which calls a block after binding the elements of a list
+		/// to argument variables. See [`Vm::call_block`](crate::vm::Vm::call_block) for details.
+		pub apply: bool,
+
+		/// Enables the `XTYPEOF` extension, which returns the name of its argument's type.
+		pub type_of: bool,
+
+		/// Enables the `XBOOL`, `XINT`, `XSTR`, and `XLIST` extensions, which explicitly convert their
+		/// argument to a boolean, integer, string, or list, respectively.
+		pub convert: bool,
+
+		/// Enables the `XSHUFFLE` and `XCHOICE` extensions, which return a list's elements in a
+		/// random order, and a random element of a list, respectively.
+		pub random: bool,
+
+		/// Enables the `XSPLITLINES` and `XSPLITWS` extensions, which split a string into a list of
+		/// its lines, and of its whitespace-separated words, respectively.
+		pub split: bool,
+
+		/// Enables the `XASSERT` extension, which raises an [`Error::AssertionFailed`](
+		/// crate::Error::AssertionFailed) with its second argument as the message if its first
+		/// argument is falsey, and returns `NULL` otherwise.
+		pub assert: bool,
 	}
 
 	#[derive(Default, Clone)]
 	pub struct BreakingChanges {
 		pub negate_reverses_collections: bool, // not working, potential future idea.
 		pub random_can_be_negative: bool,
+
+		/// Have `%` use the Euclidean remainder (which is never negative), instead of truncating
+		/// like Rust's `%` does. For example, `% ~7 3` is `2` instead of `-1`.
+		///
+		/// This only matters for negative operands, which [`Compliance::check_integer_function_bounds`]
+		/// rejects regardless of this option.
+		pub euclidean_remainder: bool,
 	}
 
 	#[derive(Default, Clone)]
@@ -174,5 +788,12 @@ if #[cfg(feature = "extensions")] {
 
 		pub assign_to_strings: bool,
 		pub assign_to_random: bool,
+		pub assign_to_prompt: bool,
+		pub assign_to_system: bool,
+
+		/// Lets `LENGTH` be called on anything, without converting to a list first: integers return
+		/// their digit count, `TRUE` returns `1`, `FALSE` and `NULL` return `0`, and blocks are a
+		/// type error.
+		pub length_of_anything: bool,
 	}
 }}
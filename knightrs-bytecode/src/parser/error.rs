@@ -47,6 +47,11 @@ pub enum ParseErrorKind {
 	#[error("{0}")]
 	StringError(#[from] StringError),
 
+	/// The source code couldn't be read; see [`SourceText::read_validated`](
+	/// crate::parser::SourceText::read_validated).
+	#[error("unable to read source: {0}")]
+	ReadFailed(std::io::Error),
+
 	#[error("missing argument {1} for function {0:?}")]
 	MissingArgument(char, usize),
 
@@ -78,9 +83,60 @@ pub enum ParseErrorKind {
 	#[error("unmatched `)` found")]
 	UnmatchedClosingParen,
 
+	#[cfg(feature = "check-parens")]
+	#[error("`()` doesn't wrap an expression")]
+	EmptyParens,
+
+	#[cfg(feature = "check-parens")]
+	#[error("parens must wrap exactly one expression")]
+	MultipleExpressionsInParens,
+
+	/// `BREAK` or `CONTINUE` was used outside of a `WHILE`.
+	#[cfg(feature = "extensions")]
+	#[error("{0} used outside of a loop")]
+	OutsideOfLoop(&'static str),
+
+	/// Syntax for an extension that isn't implemented yet was used. Without the `deny-panics`
+	/// feature, this panics instead.
+	#[cfg(feature = "deny-panics")]
+	#[error("unsupported syntax: {0}")]
+	UnsupportedSyntax(&'static str),
+
 	#[cfg(feature = "extensions")]
 	#[error("unknown extenision function: {0}")]
 	UnknownExtensionFunction(String),
+
+	/// `XIMPORT` was given something other than string literals.
+	#[cfg(feature = "extensions")]
+	#[error("XIMPORT's arguments must be string literals")]
+	ImportArgumentNotLiteral,
+
+	/// `XIMPORT`'s prefix wasn't a valid start of a variable name.
+	#[cfg(feature = "extensions")]
+	#[error("invalid XIMPORT prefix: {0:?}")]
+	InvalidImportPrefix(String),
+
+	/// The file given to `XIMPORT` couldn't be read.
+	#[cfg(feature = "extensions")]
+	#[error("unable to import {0:?}: {1}")]
+	ImportFailed(String, String),
+
+	/// An extension was used, but [`Compliance::disable_all_extensions`] is set.
+	///
+	/// [`Compliance::disable_all_extensions`]: crate::options::Compliance::disable_all_extensions
+	#[cfg(all(feature = "extensions", feature = "compliance"))]
+	#[error("extensions disabled by compliance settings")]
+	ExtensionsDisabled,
+
+	/// Every [`ProgramId`](crate::program::ProgramId) is in use by another program, so no more
+	/// programs can be compiled until one is dropped.
+	#[error("too many programs exist at once")]
+	TooManyPrograms,
+
+	/// A `BLOCK` started too far into the program for a [`Block`](crate::value::Block) to refer to.
+	#[error("program is too large (blocks must start within the first {} instructions)",
+		crate::value::Block::MAX_START + 1)]
+	ProgramTooLarge,
 }
 
 impl ParseErrorKind {
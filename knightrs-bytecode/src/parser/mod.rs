@@ -1,11 +1,13 @@
 mod error;
 mod parser;
 pub mod source_location;
+mod source_text;
 mod variable_name;
 
 pub use error::*;
 pub use parser::*;
 pub use source_location::SourceLocation;
+pub use source_text::{SourceText, Validator};
 pub use variable_name::VariableName;
 
 pub trait Parseable<'src, 'path, 'gc> {
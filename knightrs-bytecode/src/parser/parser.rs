@@ -9,8 +9,10 @@ use crate::parser::{
 	source_location::ProgramSource, ParseError, ParseErrorKind, Parseable, SourceLocation,
 };
 use crate::program::{Compilable, Compiler, DeferredJump, JumpIndex, Program};
+use crate::strings::syntax;
 use crate::Gc;
 use crate::{Environment, Options};
+use std::borrow::Cow;
 use std::path::Path;
 
 pub struct Parser<'env, 'src, 'path, 'gc> {
@@ -19,9 +21,20 @@ pub struct Parser<'env, 'src, 'path, 'gc> {
 	source: &'src str, // can't use `KnStr` b/c it has a length limit.
 	compiler: Compiler<'src, 'path, 'gc>,
 	lineno: usize,
+	column: usize,
 
 	// Start is loop begin, vec is those to jump to loop end
 	loops: Vec<(JumpIndex, Vec<DeferredJump>)>,
+
+	// What variable names are prefixed with (including the trailing `_`) when in an `XIMPORT`.
+	#[cfg(feature = "extensions")]
+	variable_prefix: Option<String>,
+
+	// When parens are whitespace, these are used to check `forbid_unbalanced_parens`.
+	#[cfg(feature = "compliance")]
+	open_parens: Vec<SourceLocation<'path>>,
+	#[cfg(feature = "compliance")]
+	unmatched_closing_paren: Option<SourceLocation<'path>>,
 }
 
 #[cfg(feature = "compliance")]
@@ -30,34 +43,89 @@ fn validate_source<'e, 'path>(
 	filename: ProgramSource<'path>,
 	opts: &Options,
 ) -> Result<(), ParseError<'path>> {
-	let Err(err) = opts.encoding.validate(source) else {
+	validate_source_from(source, filename, 1, opts.encoding)
+}
+
+// Same as `validate_source`, except `source` starts on line `first_lineno`. This is used when
+// validating source code a chunk at a time.
+#[cfg(feature = "compliance")]
+pub(super) fn validate_source_from<'path>(
+	source: &str,
+	filename: ProgramSource<'path>,
+	first_lineno: usize,
+	encoding: crate::strings::Encoding,
+) -> Result<(), ParseError<'path>> {
+	let Err(err) = encoding.validate(source) else {
 		return Ok(());
 	};
 
 	// figure out the line number; we can do btyes cause the encoding only fails in ascii and knight
-	// 1 + because line numbering starts at 1
-	let lineno = 1 + source.as_bytes().iter().take(err.position).filter(|&&c| c == b'\n').count();
+	let lineno =
+		first_lineno + source.as_bytes().iter().take(err.position).filter(|&&c| c == b'\n').count();
 
 	let whence = SourceLocation::new(filename, lineno);
-	Err(ParseErrorKind::InvalidCharInEncoding(opts.encoding, err.character).error(whence))
+	Err(ParseErrorKind::InvalidCharInEncoding(encoding, err.character).error(whence))
 }
 
 impl<'env, 'src, 'path, 'gc> Parser<'env, 'src, 'path, 'gc> {
+	/// Creates a new parser for `source`.
+	///
+	/// `filename` is where the source came from, and is used in error messages and stacktraces. It
+	/// can be a [`ProgramSource`], or a path (or `Option` of one) for source code from a file.
 	pub fn new(
 		env: &'env mut Environment<'gc>,
-		filename: ProgramSource<'path>,
+		filename: impl Into<ProgramSource<'path>>,
 		source: &'src str,
 	) -> Result<Self, ParseError<'path>> {
+		let filename = filename.into();
+
 		#[cfg(feature = "compliance")]
 		validate_source(source, filename, env.opts())?;
 
+		Self::new_prevalidated(env, filename, source)
+	}
+
+	/// Creates a new parser for `source`, whose program starts out with `variables` (in that order).
+	///
+	/// This is used by `EVAL`, so that the evaluated program's variables have the same offsets as
+	/// the ones in the program running it.
+	pub fn with_variables(
+		env: &'env mut Environment<'gc>,
+		filename: impl Into<ProgramSource<'path>>,
+		source: &'src str,
+		variables: impl IntoIterator<Item = VariableName<'src>>,
+	) -> Result<Self, ParseError<'path>> {
+		let mut parser = Self::new(env, filename, source)?;
+		parser.compiler.declare_variables(variables);
+		Ok(parser)
+	}
+
+	// Same as `new`, except `source` must already have been validated against `env`'s encoding.
+	pub(super) fn new_prevalidated(
+		env: &'env mut Environment<'gc>,
+		filename: ProgramSource<'path>,
+		source: &'src str,
+	) -> Result<Self, ParseError<'path>> {
+		let whence = || SourceLocation::new(filename, 1);
+		let compiler = Compiler::new(whence(), env.gc())
+			.ok_or_else(|| ParseErrorKind::TooManyPrograms.error(whence()))?;
+
 		Ok(Self {
-			compiler: Compiler::new(SourceLocation::new(filename, 1), env.gc()),
+			compiler,
 			env,
 			filename,
 			source,
 			lineno: 1,
+			column: 1,
 			loops: Vec::new(),
+
+			#[cfg(feature = "extensions")]
+			variable_prefix: None,
+
+			#[cfg(feature = "compliance")]
+			open_parens: Vec::new(),
+			#[cfg(feature = "compliance")]
+			unmatched_closing_paren: None,
 		})
 	}
 
@@ -73,6 +141,22 @@ impl<'env, 'src, 'path, 'gc> Parser<'env, 'src, 'path, 'gc> {
 		self.env.gc()
 	}
 
+	/// The prefix that variable names are given, if an `XIMPORT`ed file is being parsed.
+	#[cfg(feature = "extensions")]
+	pub fn variable_prefix(&self) -> Option<&str> {
+		self.variable_prefix.as_deref()
+	}
+
+	/// Whether parens are tokens which must wrap exactly one expression.
+	///
+	/// This is enabled by either [`Options::check_parens`] or [`Compliance::check_parens`](
+	/// crate::options::Compliance::check_parens).
+	#[cfg(feature = "check-parens")]
+	pub fn check_parens(&self) -> bool {
+		self.opts().check_parens
+			|| cfg_expr!(feature = "compliance", self.opts().compliance.check_parens, false)
+	}
+
 	pub fn peek(&self) -> Option<char> {
 		self.source.chars().next()
 	}
@@ -91,8 +175,13 @@ impl<'env, 'src, 'path, 'gc> Parser<'env, 'src, 'path, 'gc> {
 
 		if head == '\n' {
 			self.lineno += 1;
+			self.column = 1;
+			// This is just the start of the line, not of any specific expression, so the column is
+			// left out.
 			#[cfg(feature = "qol")]
-			self.compiler.record_source_location(self.location());
+			self.compiler.record_source_location(SourceLocation::new(self.filename, self.lineno));
+		} else {
+			self.column += 1;
 		}
 
 		self.source = chars.as_str();
@@ -127,13 +216,17 @@ impl<'env, 'src, 'path, 'gc> Parser<'env, 'src, 'path, 'gc> {
 		let start = self.source;
 
 		#[cfg(feature = "check-parens")]
-		let check_parens = self.opts().check_parens;
+		let check_parens = self.check_parens();
+
+		// Parens are still whitespace, but we keep track of them to make sure they're balanced.
+		#[cfg(feature = "compliance")]
+		let track_parens = !check_parens && self.opts().compliance.forbid_unbalanced_parens;
 
-		// TODO: when not in stacktrace mode, consider (, ), and : as whitespace
+		// TODO: when not in stacktrace mode, consider ( and ) as whitespace
 		loop {
 			// strip all leading whitespace, if any.
 			self.take_while(|c| {
-				if c.is_whitespace() {
+				if syntax::is_whitespace(c) {
 					return true;
 				}
 
@@ -143,16 +236,39 @@ impl<'env, 'src, 'path, 'gc> Parser<'env, 'src, 'path, 'gc> {
 					return false;
 				}
 
-				matches!(c, '(' | ')' | ':')
+				#[cfg(feature = "compliance")]
+				if track_parens {
+					return false;
+				}
+
+				syntax::is_paren(c)
 			});
 
+			#[cfg(feature = "compliance")]
+			if track_parens {
+				if self.advance_if('(').is_some() {
+					self.open_parens.push(self.location());
+					continue;
+				}
+
+				if self.advance_if(')').is_some() {
+					if self.open_parens.pop().is_none() && self.unmatched_closing_paren.is_none() {
+						self.unmatched_closing_paren = Some(self.location());
+					}
+					continue;
+				}
+			}
+
 			// If we're not at the start of a comment, break out
-			if self.advance_if('#').is_none() {
+			if self.advance_if(syntax::COMMENT_START).is_none() {
 				break;
 			}
 
-			// Eat a comment.
-			self.take_while(|chr| chr != '\n');
+			// Eat a comment. Ones starting with `#:` are pragmas, which set the program's metadata.
+			let comment = self.take_while(|chr| chr != '\n').unwrap_or_default();
+			if let Some(pragma) = comment.strip_prefix(':') {
+				self.record_pragma(pragma);
+			}
 		}
 
 		if start.len() == self.source.len() {
@@ -162,14 +278,45 @@ impl<'env, 'src, 'path, 'gc> Parser<'env, 'src, 'path, 'gc> {
 		Some(start.get(..start.len() - self.source.len()).unwrap())
 	}
 
+	// Pragmas are `key value` pairs, with the key being everything up to the first whitespace.
+	fn record_pragma(&mut self, pragma: &str) {
+		let pragma = pragma.trim();
+		let (key, value) = pragma.split_once(syntax::is_whitespace).unwrap_or((pragma, ""));
+
+		if !key.is_empty() {
+			self.compiler.metadata_mut().insert(key.to_string(), value.trim().to_string());
+		}
+	}
+
 	// ick,
 	pub fn location(&self) -> SourceLocation<'path> {
-		SourceLocation::new(self.filename.clone(), self.lineno)
+		SourceLocation::new(self.filename.clone(), self.lineno).with_column(self.column)
 	}
 
 	/// Removes the remainder of a keyword function.
 	pub fn strip_keyword_function(&mut self) -> Option<&'src str> {
-		self.take_while(|c| c.is_uppercase() || c == '_')
+		self.take_while(syntax::is_keyword_char)
+	}
+
+	/// Finishes stripping the name of an `X` function, given the part after the `X` which
+	/// [`strip_keyword_function`](Self::strip_keyword_function) already removed.
+	///
+	/// This is where [`case_insensitive_extensions`](crate::options::Extensions::case_insensitive_extensions)
+	/// and [`extension_aliases`](crate::options::Extensions::extension_aliases) are handled, so the
+	/// returned name is always the canonical one.
+	#[cfg(feature = "extensions")]
+	pub fn strip_extension_name(&mut self, name: &'src str) -> Cow<'src, str> {
+		let mut name = Cow::Borrowed(name);
+
+		if self.opts().extensions().case_insensitive_extensions {
+			let rest = self.take_while(|c| c.is_alphabetic() || c == '_').unwrap_or_default();
+			name = Cow::Owned((name.into_owned() + rest).to_uppercase());
+		}
+
+		match self.opts().extensions().extension_aliases.get(&*name) {
+			Some(aliased) => Cow::Owned(aliased.clone()),
+			None => name,
+		}
 	}
 
 	/// Creates an error at the current source code position.
@@ -180,27 +327,212 @@ impl<'env, 'src, 'path, 'gc> Parser<'env, 'src, 'path, 'gc> {
 
 	/// Parses a whole program, returning a [`Value`] corresponding to its ast.
 	///
-	/// This will return an [`ErrorKind::TrailingTokens`] if [`forbid_trailing_tokens`](
-	/// crate::env::flags::Compliance::forbid_trailing_tokens) is set.
+	/// This will return an [`ParseErrorKind::TrailingTokens`] if [`forbid_trailing_tokens`](
+	/// crate::options::Compliance::forbid_trailing_tokens) is set, and anything other than whitespace
+	/// and comments follow the first expression. Likewise, if [`forbid_unbalanced_parens`](
+	/// crate::options::Compliance::forbid_unbalanced_parens) is set, unbalanced parens are reported.
 	pub fn parse_program(mut self) -> Result<Program<'src, 'path, 'gc>, ParseError<'path>> {
 		self.parse_expression()?;
 
-		// If we forbid any trailing tokens, then see if we could have parsed anything else.
 		#[cfg(feature = "compliance")]
-		if self.env.opts().compliance.forbid_trailing_tokens
-			&& !matches!(self.parse_expression().map_err(|e| e.kind), Err(ParseErrorKind::EmptySource))
-		{
-			return Err(self.error(ParseErrorKind::TrailingTokens));
-		}
+		self.check_end_of_program()?;
 
 		// SAFETY: this program ensures that things are built properly
 		Ok(unsafe { self.compiler.build() })
 	}
 
+	/// Checks the remainder of the source after the program's expression has been parsed.
+	///
+	/// Trailing tokens are detected without parsing them, so the error is always reported where the
+	/// trailing token starts (and not wherever parsing it happened to fail).
+	#[cfg(feature = "compliance")]
+	fn check_end_of_program(&mut self) -> Result<(), ParseError<'path>> {
+		let forbid_trailing_tokens = self.opts().compliance.forbid_trailing_tokens;
+		let forbid_unbalanced_parens = self.opts().compliance.forbid_unbalanced_parens;
+
+		if !forbid_trailing_tokens && !forbid_unbalanced_parens {
+			return Ok(());
+		}
+
+		self.strip_whitespace_and_comments();
+
+		if forbid_unbalanced_parens {
+			if let Some(whence) = self.unmatched_closing_paren {
+				return Err(ParseErrorKind::UnmatchedClosingParen.error(whence));
+			}
+
+			if let Some(&whence) = self.open_parens.first() {
+				return Err(ParseErrorKind::MissingClosingParen.error(whence));
+			}
+		}
+
+		// When parens are checked, a stray `)` is better described as unmatched than as trailing.
+		match self.peek() {
+			Some(')') if self.check_parens() => Err(self.error(ParseErrorKind::UnmatchedClosingParen)),
+			Some(_) if forbid_trailing_tokens => Err(self.error(ParseErrorKind::TrailingTokens)),
+			_ => Ok(()),
+		}
+	}
+
+	/// Parses a whole program, but continues onwards after errors instead of stopping at the first.
+	///
+	/// Whenever an error is encountered, it's recorded, the code for the offending expression is
+	/// discarded, and the parser skips ahead to the next whitespace before trying again. This is
+	/// intended for tooling (such as editors), which want to report every error in one go.
+	///
+	/// If there were no errors, the returned [`Program`] is the same as what [`parse_program`](
+	/// Self::parse_program) would have returned. Otherwise, it's a best-effort partial program,
+	/// which isn't [runnable](Program::is_runnable).
+	pub fn parse_program_recovering(
+		mut self,
+	) -> (Program<'src, 'path, 'gc>, Vec<ParseError<'path>>) {
+		let forbid_trailing_tokens =
+			cfg_expr!(feature = "compliance", self.opts().compliance.forbid_trailing_tokens, false);
+		let mut errors = Vec::new();
+		let mut parsed_an_expression = false;
+
+		loop {
+			self.strip_whitespace_and_comments();
+			let start = self.location();
+			let checkpoint = self.compiler.jump_index();
+			let remaining = self.source.len();
+
+			match self.parse_expression() {
+				Ok(()) if !parsed_an_expression => parsed_an_expression = true,
+				Ok(()) => {
+					// Only report trailing tokens once, otherwise every expression would get one.
+					#[cfg(feature = "compliance")]
+					if forbid_trailing_tokens
+						&& !errors.iter().any(|e: &ParseError<'_>| {
+							matches!(e.kind, ParseErrorKind::TrailingTokens)
+						}) {
+						errors.push(ParseErrorKind::TrailingTokens.error(start));
+					}
+				}
+				Err(err) if matches!(err.kind, ParseErrorKind::EmptySource) => {
+					if !parsed_an_expression && errors.is_empty() {
+						errors.push(err);
+					}
+					break;
+				}
+				Err(err) => {
+					errors.push(err);
+					self.compiler.rollback(checkpoint);
+					self.loops.clear();
+					self.synchronize(remaining);
+					continue;
+				}
+			}
+
+			// Just like `parse_program`, trailing tokens are ignored unless they're forbidden.
+			if errors.is_empty() && !forbid_trailing_tokens {
+				break;
+			}
+		}
+
+		#[cfg(feature = "compliance")]
+		if self.opts().compliance.forbid_unbalanced_parens {
+			self.strip_whitespace_and_comments();
+
+			if let Some(whence) = self.unmatched_closing_paren {
+				errors.push(ParseErrorKind::UnmatchedClosingParen.error(whence));
+			}
+
+			if let Some(&whence) = self.open_parens.first() {
+				errors.push(ParseErrorKind::MissingClosingParen.error(whence));
+			}
+		}
+
+		if errors.is_empty() {
+			// SAFETY: No errors occurred, so exactly one expression was parsed.
+			(unsafe { self.compiler.build() }, errors)
+		} else {
+			(self.compiler.build_partial(), errors)
+		}
+	}
+
+	/// Skips to the next point where parsing can resume after an error, ie the next whitespace.
+	///
+	/// `remaining` is how much source was left before the erroring expression was parsed; if nothing
+	/// was consumed since then, one character is skipped to ensure we don't get stuck.
+	fn synchronize(&mut self, remaining: usize) {
+		if self.source.len() == remaining {
+			self.advance();
+		}
+
+		self.take_while(|c| !c.is_whitespace());
+	}
+
+	/// Parses the file `path`, whose contents are `source`, as a single expression in place. Every
+	/// variable in it is named with `prefix` and an `_` in front (after any prefix that's already in
+	/// effect). This is how `XIMPORT` works.
+	///
+	/// `source` is kept by the compiled program, and freed when it's dropped. `path` is needed for
+	/// the program's source locations, so it's [interned](Environment::intern_path), and each one is
+	/// only leaked once. (Programs are expected to import a handful of files at most.)
+	#[cfg(feature = "extensions")]
+	pub fn parse_module(
+		&mut self,
+		path: &Path,
+		source: String,
+		prefix: &str,
+	) -> Result<(), ParseError<'path>> {
+		// SAFETY: Every variable name in `source` is prefixed, so only owned copies of them are
+		// kept, and everything else borrowed from `source` is done with once it's been parsed.
+		let source = unsafe { self.compiler.keep_source(source) };
+		let filename = ProgramSource::File(self.env.intern_path(path));
+
+		#[cfg(feature = "compliance")]
+		validate_source(source, filename, self.opts())?;
+
+		let prefix = format!("{}{prefix}_", self.variable_prefix().unwrap_or_default());
+
+		let outer_source = std::mem::replace(&mut self.source, source);
+		let outer_filename = std::mem::replace(&mut self.filename, filename);
+		let outer_lineno = std::mem::replace(&mut self.lineno, 1);
+		let outer_column = std::mem::replace(&mut self.column, 1);
+		let outer_prefix = self.variable_prefix.replace(prefix);
+
+		#[cfg(feature = "stacktrace")]
+		self.compiler.record_source_location(SourceLocation::new(filename, 1));
+
+		let result = self.parse_expression();
+
+		self.source = outer_source;
+		self.filename = outer_filename;
+		self.lineno = outer_lineno;
+		self.column = outer_column;
+		self.variable_prefix = outer_prefix;
+
+		#[cfg(feature = "stacktrace")]
+		self.compiler.record_source_location(self.location());
+
+		result
+	}
+
+	/// Tries each of the environment's [custom parse functions](Environment::add_parse_fn).
+	#[cfg(feature = "custom-types")]
+	fn parse_custom(&mut self) -> Result<Option<crate::gc::ValueRoot<'gc>>, ParseError<'path>> {
+		// They're cloned so that they can be given `self`.
+		for parse_fn in self.env.parse_fns().to_vec() {
+			if let Some(value) = parse_fn(self)? {
+				return Ok(Some(value));
+			}
+		}
+
+		Ok(None)
+	}
+
 	/// Parses a single expression and returns it.
 	pub fn parse_expression(&mut self) -> Result<(), ParseError<'path>> {
 		self.strip_whitespace_and_comments();
 
+		#[cfg(feature = "custom-types")]
+		if let Some(value) = self.parse_custom()? {
+			self.compiler.push_constant(value.get());
+			return Ok(());
+		}
+
 		if let Some(x) = crate::value::Integer::parse(self)? {
 			return x.compile(&mut self.compiler, &self.env.opts());
 		}
@@ -221,7 +553,7 @@ impl<'env, 'src, 'path, 'gc> Parser<'env, 'src, 'path, 'gc> {
 		}
 
 		#[cfg(feature = "check-parens")]
-		if self.env.opts().check_parens && parens::parse_parens(self)? {
+		if self.check_parens() && parens::parse_parens(self)? {
 			return Ok(());
 		}
 
@@ -1,9 +1,11 @@
 use crate::parser::{ParseError, ParseErrorKind, Parseable, Parser, VariableName};
 use crate::program::JumpWhen;
 #[cfg(feature = "extensions")]
-use crate::vm::opcode::DynamicAssignment;
+use crate::vm::opcode::{BitwiseOp, DynamicAssignment, UnaryOp};
 use crate::vm::Opcode;
 use crate::Options;
+#[cfg(feature = "extensions")]
+use {crate::gc::GcRoot, crate::value::KnString, std::path::Path};
 
 use super::SourceLocation;
 
@@ -18,7 +20,6 @@ fn simple_opcode_for(func: char, opts: &Options) -> Option<Opcode> {
 		// arity 1
 		'C' => Some(Opcode::Call),
 		'Q' => Some(Opcode::Quit),
-		'O' => Some(Opcode::Output),
 		'L' => Some(Opcode::Length),
 		'!' => Some(Opcode::Not),
 		'~' => Some(Opcode::Negate),
@@ -46,14 +47,29 @@ fn simple_opcode_for(func: char, opts: &Options) -> Option<Opcode> {
 
 		// Extensions
 		#[cfg(feature = "extensions")]
-		'E' if opts.extensions.functions.eval => Some(Opcode::Eval),
+		'E' if opts.extensions().functions.eval => Some(Opcode::Eval),
+		#[cfg(feature = "extensions")]
+		'V' if opts.extensions().functions.value => Some(Opcode::Value),
 		#[cfg(feature = "extensions")]
-		'V' if opts.extensions.functions.value => Some(Opcode::Value),
+		'$' if opts.extensions().functions.system => Some(Opcode::System),
 
 		_ => None,
 	}
 }
 
+// `X` functions which don't need anything special, like `simple_opcode_for`.
+#[cfg(feature = "extensions")]
+fn simple_extension_for(name: &str, opts: &Options) -> Option<Opcode> {
+	match name {
+		"BASE" if opts.extensions().functions.base => Some(Opcode::Base),
+		"FROMBASE" if opts.extensions().functions.base => Some(Opcode::FromBase),
+		"CMP" if opts.extensions().functions.cmp => Some(Opcode::Cmp),
+		"APPLY" if opts.extensions().functions.apply => Some(Opcode::Apply),
+		"ASSERT" if opts.extensions().functions.assert => Some(Opcode::Assert),
+		_ => None,
+	}
+}
+
 fn parse_argument<'path>(
 	parser: &mut Parser<'_, '_, 'path, '_>,
 	start: &SourceLocation<'path>,
@@ -64,14 +80,40 @@ fn parse_argument<'path>(
 		Err(err) if matches!(err.kind, ParseErrorKind::EmptySource) => {
 			return Err(ParseErrorKind::MissingArgument(fn_name, arg).error(*start));
 		}
+
+		// A `)` where an argument should be means the function's parens closed too early.
+		#[cfg(feature = "check-parens")]
+		Err(err) if matches!(err.kind, ParseErrorKind::UnmatchedClosingParen) => {
+			Err(ParseErrorKind::MissingArgument(fn_name, arg).error(*start))
+		}
 		other => other,
 	}
 }
 
+// Parses an argument to `XIMPORT`, which must be a string literal as it's needed at compile time.
+#[cfg(feature = "extensions")]
+fn parse_import_argument<'path, 'gc>(
+	parser: &mut Parser<'_, '_, 'path, 'gc>,
+	start: &SourceLocation<'path>,
+	fn_name: char,
+	arg: usize,
+) -> Result<GcRoot<'gc, KnString<'gc>>, ParseError<'path>> {
+	parser.strip_whitespace_and_comments();
+
+	if parser.peek().is_none() {
+		return Err(ParseErrorKind::MissingArgument(fn_name, arg).error(*start));
+	}
+
+	KnString::parse(parser)?.ok_or_else(|| ParseErrorKind::ImportArgumentNotLiteral.error(*start))
+}
+
+// If `discard` is set, the assigned value isn't needed afterwards. Returns whether the value was
+// already popped because of it.
 fn parse_assignment<'path>(
 	start: SourceLocation<'path>,
 	parser: &mut Parser<'_, '_, 'path, '_>,
-) -> Result<(), ParseError<'path>> {
+	discard: bool,
+) -> Result<bool, ParseError<'path>> {
 	parser.strip_whitespace_and_comments();
 
 	// TODO: handle `()` around variable name.
@@ -86,11 +128,19 @@ fn parse_assignment<'path>(
 			if parser.peek().map_or(false, |c| c == 'B') {
 				parser.strip_keyword_function();
 				parse_block(start, parser, Some(name.clone()))?;
+			} else if discard && parser.peek() == Some('S') {
+				return parse_set_assignment(parser, name, location);
 			} else {
 				parse_argument(parser, &start, '=', 2)?;
 			}
 			// ew, cloning is not a good answer.
 			let opts = (*parser.opts()).clone();
+			if discard {
+				unsafe { parser.compiler().set_variable_pop(name, &opts) }
+					.map_err(|err| err.error(location))?;
+				return Ok(true);
+			}
+
 			unsafe { parser.compiler().set_variable(name, &opts) }
 				.map_err(|err| err.error(location))?;
 		}
@@ -100,7 +150,7 @@ fn parse_assignment<'path>(
 				parser.strip_whitespace_and_comments();
 				match parser.peek() {
 					Some('R') => {
-						if parser.opts().extensions.builtin_fns.assign_to_random {
+						if parser.opts().extensions().builtin_fns.assign_to_random {
 							parser.strip_keyword_function();
 							parse_argument(parser, &start, '=', 2)?;
 							unsafe {
@@ -109,18 +159,39 @@ fn parse_assignment<'path>(
 									DynamicAssignment::Random as _,
 								);
 							}
-							return Ok(());
+							return Ok(false);
 						}
 						// no else so we fallthru to the end
 					}
-					Some('O') | Some('P') | Some('$') => todo!("assign to builtins"),
-					_ if parser.opts().extensions.builtin_fns.assign_to_strings => {
+					Some('P') if parser.opts().extensions().builtin_fns.assign_to_prompt => {
+						parser.strip_keyword_function();
+						parse_argument(parser, &start, '=', 2)?;
+						unsafe {
+							parser.compiler.opcode_with_offset(
+								Opcode::AssignDynamic,
+								DynamicAssignment::Prompt as _,
+							);
+						}
+						return Ok(false);
+					}
+					Some('$') if parser.opts().extensions().builtin_fns.assign_to_system => {
+						parser.advance();
+						parse_argument(parser, &start, '=', 2)?;
+						unsafe {
+							parser.compiler.opcode_with_offset(
+								Opcode::AssignDynamic,
+								DynamicAssignment::System as _,
+							);
+						}
+						return Ok(false);
+					}
+					_ if parser.opts().extensions().builtin_fns.assign_to_strings => {
 						parse_argument(parser, &start, '=', 1)?;
 						parse_argument(parser, &start, '=', 2)?;
 						unsafe {
 							parser.compiler().opcode_without_offset(Opcode::SetDynamicVar);
 						}
-						return Ok(());
+						return Ok(false);
 					}
 					_ => {}
 				}
@@ -130,7 +201,41 @@ fn parse_assignment<'path>(
 		}
 	}
 
-	Ok(())
+	Ok(false)
+}
+
+// Parses the `SET` in `; = name SET ...`. If `SET`'s first argument is just `name`, the variable's
+// value is modified in place when possible, instead of being copied. Always pops the value.
+fn parse_set_assignment<'src, 'path>(
+	parser: &mut Parser<'_, 'src, 'path, '_>,
+	name: VariableName<'src>,
+	location: SourceLocation<'path>,
+) -> Result<bool, ParseError<'path>> {
+	let start = parser.location();
+	parser.advance();
+	parser.strip_keyword_function();
+
+	let source = parser.compiler().jump_index();
+	parse_argument(parser, &start, 'S', 1)?;
+	let in_place = parser.compiler().get_variable_to_set(source, &name);
+
+	for arg in 2..=4 {
+		parse_argument(parser, &start, 'S', arg)?;
+	}
+
+	// ew, cloning is not a good answer.
+	let opts = (*parser.opts()).clone();
+	if in_place {
+		unsafe { parser.compiler().set_variable_in_place(name, &opts) }
+	} else {
+		unsafe {
+			parser.compiler().opcode_without_offset(Opcode::Set);
+			parser.compiler().set_variable_pop(name, &opts)
+		}
+	}
+	.map_err(|err| err.error(location))?;
+
+	Ok(true)
 }
 
 fn parse_block<'src, 'path>(
@@ -147,11 +252,15 @@ fn parse_block<'src, 'path>(
 		parser.compiler().opcode_without_offset(Opcode::Return);
 		jump_after.jump_to_current(parser.compiler());
 	}
+	#[cfg(feature = "stacktrace")]
+	let end = parser.compiler().jump_index();
 
-	parser.compiler().push_constant(crate::value::Block::new(jump_index).into());
+	let block = crate::value::Block::try_new(jump_index, parser.compiler().program_id())
+		.ok_or_else(|| ParseErrorKind::ProgramTooLarge.error(start))?;
+	parser.compiler().push_constant(block.into());
 
-	#[cfg(feature = "qol")]
-	parser.compiler().record_block(start, jump_index, name);
+	#[cfg(feature = "stacktrace")]
+	parser.compiler().record_block(start, jump_index, end, name);
 	Ok(())
 }
 
@@ -160,6 +269,7 @@ impl Function {
 		// this should be reowrked ot allow for registering arbitrary functions, as it doesn't
 		// support `X`s
 
+		let start = parser.location();
 		let (fn_name, full_name) = if let Some(fn_name) = parser.advance_if(char::is_uppercase) {
 			(fn_name, parser.strip_keyword_function().unwrap_or_default())
 		} else if let Some(chr) = parser.advance() {
@@ -168,7 +278,15 @@ impl Function {
 			return Ok(false);
 		};
 
-		let start = parser.location();
+		#[cfg(feature = "extensions")]
+		let extension_name;
+		#[cfg(feature = "extensions")]
+		let full_name = if fn_name == 'X' {
+			extension_name = parser.strip_extension_name(full_name);
+			&*extension_name
+		} else {
+			full_name
+		};
 
 		// Handle opcodes without anything special
 		if let Some(simple_opcode) = simple_opcode_for(fn_name, parser.opts()) {
@@ -178,11 +296,28 @@ impl Function {
 				parse_argument(parser, &start, fn_name, arg + 1)?;
 			}
 
+			// Stacktraces show where each `CALL` (and `EVAL`) is, so record its exact location, and
+			// then where the code after it is.
+			#[cfg(feature = "stacktrace")]
+			let is_callsite = simple_opcode == Opcode::Call
+				|| cfg_expr!(feature = "extensions", simple_opcode == Opcode::Eval, false);
+
+			#[cfg(feature = "stacktrace")]
+			if is_callsite {
+				parser.compiler.record_source_location(start);
+			}
+
 			unsafe {
 				// todo: rename to simple opcode?
 				parser.compiler.opcode_without_offset(simple_opcode);
 			}
 
+			#[cfg(feature = "stacktrace")]
+			if is_callsite {
+				let location = parser.location();
+				parser.compiler.record_source_location(location);
+			}
+
 			return Ok(true);
 		}
 
@@ -198,22 +333,39 @@ impl Function {
 
 		// Non-simple ones
 		match fn_name {
-			';' => {
+			'O' => {
 				parse_argument(parser, &start, fn_name, 1)?;
 				unsafe {
-					parser.compiler.opcode_without_offset(Opcode::Pop);
+					parser.compiler().output();
 				}
-				parse_argument(parser, &start, fn_name, 1)?;
+				Ok(true)
+			}
+			';' => {
+				// Assignments don't need to keep around a value that's immediately discarded.
+				parser.strip_whitespace_and_comments();
+				let popped = if parser.advance_if('=').is_some() {
+					let assign_start = parser.location();
+					parse_assignment(assign_start, parser, true)?
+				} else {
+					parse_argument(parser, &start, fn_name, 1)?;
+					false
+				};
+
+				if !popped {
+					unsafe {
+						parser.compiler.opcode_without_offset(Opcode::Pop);
+					}
+				}
+				parse_argument(parser, &start, fn_name, 2)?;
 				Ok(true)
 			}
 
-			// technically not needed, as it wont ever get here. same with the if
-			#[cfg(feature = "check-parens")]
-			':' if parser.opts().check_parens => {
+			// `:` is a no-op, so it just compiles its argument.
+			':' => {
 				parse_argument(parser, &start, fn_name, 1)?;
-				return Ok(true);
+				Ok(true)
 			}
-			'=' => parse_assignment(start, parser).and(Ok(true)),
+			'=' => parse_assignment(start, parser, false).and(Ok(true)),
 			'B' => parse_block(start, parser, None).and(Ok(true)),
 			'&' | '|' => {
 				parse_argument(parser, &start, fn_name, 1)?;
@@ -273,32 +425,136 @@ impl Function {
 				Ok(true)
 			}
 			// TODO: extensions lol
+			#[cfg(all(feature = "extensions", feature = "compliance"))]
+			'X' | 'E' | 'V' | '$' if parser.opts().extensions_disabled() => {
+				Err(ParseErrorKind::ExtensionsDisabled.error(start))
+			}
+			#[cfg(feature = "extensions")]
+			'X' if simple_extension_for(full_name, parser.opts()).is_some() => {
+				let opcode = simple_extension_for(full_name, parser.opts()).unwrap();
+
+				for arg in 0..opcode.arity() {
+					parse_argument(parser, &start, fn_name, arg + 1)?;
+				}
+
+				unsafe {
+					parser.compiler.opcode_without_offset(opcode);
+				}
+
+				Ok(true)
+			}
 			#[cfg(feature = "extensions")]
 			'X' => match full_name {
-				"BREAK" if parser.opts().extensions.syntax.control_flow => {
+				"BAND" | "BOR" | "BXOR" | "SHL" | "SHR"
+					if parser.opts().extensions().functions.bitwise =>
+				{
+					parse_argument(parser, &start, fn_name, 1)?;
+					parse_argument(parser, &start, fn_name, 2)?;
+
+					let op = match full_name {
+						"BAND" => BitwiseOp::And,
+						"BOR" => BitwiseOp::Or,
+						"BXOR" => BitwiseOp::Xor,
+						"SHL" => BitwiseOp::ShiftLeft,
+						_ => BitwiseOp::ShiftRight,
+					};
+					unsafe {
+						parser.compiler.opcode_with_offset(Opcode::Bitwise, op as _);
+					}
+					Ok(true)
+				}
+				"TYPEOF" if parser.opts().extensions().functions.type_of => {
+					parse_argument(parser, &start, fn_name, 1)?;
+					unsafe {
+						parser.compiler.opcode_with_offset(Opcode::Unary, UnaryOp::TypeOf as _);
+					}
+					Ok(true)
+				}
+				"BOOL" | "INT" | "STR" | "LIST" if parser.opts().extensions().functions.convert => {
+					parse_argument(parser, &start, fn_name, 1)?;
+
+					let op = match full_name {
+						"BOOL" => UnaryOp::ToBoolean,
+						"INT" => UnaryOp::ToInteger,
+						"STR" => UnaryOp::ToString,
+						_ => UnaryOp::ToList,
+					};
+					unsafe {
+						parser.compiler.opcode_with_offset(Opcode::Unary, op as _);
+					}
+					Ok(true)
+				}
+				"SHUFFLE" | "CHOICE" if parser.opts().extensions().functions.random => {
+					parse_argument(parser, &start, fn_name, 1)?;
+
+					let op = if full_name == "SHUFFLE" { UnaryOp::Shuffle } else { UnaryOp::Choice };
+					unsafe {
+						parser.compiler.opcode_with_offset(Opcode::Unary, op as _);
+					}
+					Ok(true)
+				}
+				"SPLITLINES" | "SPLITWS" if parser.opts().extensions().functions.split => {
+					parse_argument(parser, &start, fn_name, 1)?;
+
+					let op = if full_name == "SPLITLINES" {
+						UnaryOp::SplitLines
+					} else {
+						UnaryOp::SplitWhitespace
+					};
+					unsafe {
+						parser.compiler.opcode_with_offset(Opcode::Unary, op as _);
+					}
+					Ok(true)
+				}
+				"BREAK" if parser.opts().extensions().syntax.control_flow => {
+					if parser.loops.is_empty() {
+						return Err(parser.error(ParseErrorKind::OutsideOfLoop("BREAK")));
+					}
+
 					let deferred = parser.compiler().defer_jump(JumpWhen::Always);
-					parser
-						.loops
-						.last_mut()
-						.expect("<todo: exception when `break` when nothing to break, or in a funciton?>")
-						.1
-						.push(deferred);
+					parser.loops.last_mut().unwrap().1.push(deferred);
 					Ok(true)
 				}
-				"CONTINUE" if parser.opts().extensions.syntax.control_flow => {
-					let starting = parser
-						.loops
-						.last()
-						.expect("<todo: exception when `break` when nothing to break, or in a funciton?>")
-						.0;
+				"CONTINUE" if parser.opts().extensions().syntax.control_flow => {
+					let Some(&(starting, _)) = parser.loops.last() else {
+						return Err(parser.error(ParseErrorKind::OutsideOfLoop("CONTINUE")));
+					};
 					unsafe {
 						parser.compiler().jump_to(JumpWhen::Always, starting);
 					}
 					Ok(true)
 				}
+				"IMPORT" if parser.opts().extensions().functions.import => {
+					// Modules are compiled in place, so which one is imported must be known now.
+					let filename = parse_import_argument(parser, &start, fn_name, 1)?;
+					let prefix = parse_import_argument(parser, &start, fn_name, 2)?;
+
+					let is_prefix_char =
+						|c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_';
+					if prefix.is_empty() || !prefix.chars().all(is_prefix_char) {
+						return Err(ParseErrorKind::InvalidImportPrefix(prefix.to_string()).error(start));
+					}
+
+					let contents = parser.env.read_file(filename.as_str()).map_err(|err| {
+						ParseErrorKind::ImportFailed(filename.to_string(), err.to_string()).error(start)
+					})?;
+
+					parser.parse_module(Path::new(filename.as_str()), contents, prefix.as_str())?;
+					Ok(true)
+				}
+				"SRAND" if parser.opts().extensions().functions.srand => {
+					// `XSRAND seed` is the same as `= RANDOM seed`.
+					parse_argument(parser, &start, fn_name, 1)?;
+					unsafe {
+						parser
+							.compiler()
+							.opcode_with_offset(Opcode::AssignDynamic, DynamicAssignment::Random as _);
+					}
+					Ok(true)
+				}
 				_ => Err(ParseErrorKind::UnknownExtensionFunction(full_name.to_string()).error(start)),
 			},
-			_ => todo!("invalid fn: {fn_name:?}"),
+			_ => Err(ParseErrorKind::UnknownTokenStart(fn_name).error(start)),
 		}
 	}
 }
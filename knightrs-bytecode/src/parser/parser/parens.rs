@@ -1,5 +1,9 @@
 use crate::parser::{ParseError, ParseErrorKind, Parser};
 
+/// Parses a parenthesized expression, returning whether one was found.
+///
+/// The parens must wrap exactly one expression; if they don't, an error is returned with the
+/// location of the offending paren (or, for extra expressions, the first extra token).
 pub fn parse_parens<'path>(
 	parser: &mut Parser<'_, '_, 'path, '_>,
 ) -> Result<bool, ParseError<'path>> {
@@ -14,13 +18,26 @@ pub fn parse_parens<'path>(
 	}
 
 	let start = parser.location();
-	parser.parse_expression()?;
 
-	//
 	parser.strip_whitespace_and_comments();
-	if parser.advance_if(')').is_none() {
-		return Err(ParseErrorKind::MissingClosingParen.error(start));
+	if parser.advance_if(')').is_some() {
+		return Err(ParseErrorKind::EmptyParens.error(start));
+	}
+
+	match parser.parse_expression() {
+		Err(err) if matches!(err.kind, ParseErrorKind::EmptySource) => {
+			return Err(ParseErrorKind::MissingClosingParen.error(start));
+		}
+		other => other?,
 	}
 
-	return Ok(true);
+	parser.strip_whitespace_and_comments();
+	match parser.peek() {
+		Some(')') => {
+			parser.advance();
+			Ok(true)
+		}
+		Some(_) => Err(parser.error(ParseErrorKind::MultipleExpressionsInParens)),
+		None => Err(ParseErrorKind::MissingClosingParen.error(start)),
+	}
 }
@@ -10,6 +10,7 @@ use std::path::Path;
 pub struct SourceLocation<'path> {
 	source: ProgramSource<'path>,
 	lineno: usize,
+	column: usize, // zero if unknown
 }
 
 /// Whence a program originates.
@@ -35,7 +36,14 @@ impl<'path> SourceLocation<'path> {
 	pub const fn new(source: ProgramSource<'path>, lineno: usize) -> Self {
 		debug_assert!(lineno != 0);
 
-		Self { source, lineno }
+		Self { source, lineno, column: 0 }
+	}
+
+	/// Returns `self`, except at the (one-based) `column` of its line.
+	pub const fn with_column(self, column: usize) -> Self {
+		debug_assert!(column != 0);
+
+		Self { column, ..self }
 	}
 
 	/// The filename of this source location.
@@ -47,11 +55,26 @@ impl<'path> SourceLocation<'path> {
 	pub const fn lineno(&self) -> usize {
 		self.lineno
 	}
+
+	/// The column (in characters, starting at one) for this source location, if it's known.
+	pub const fn column(&self) -> Option<usize> {
+		if self.column == 0 {
+			None
+		} else {
+			Some(self.column)
+		}
+	}
 }
 
 impl Display for SourceLocation<'_> {
 	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-		write!(f, "{}:{}", self.source, self.lineno)
+		write!(f, "{}:{}", self.source, self.lineno)?;
+
+		if let Some(column) = self.column() {
+			write!(f, ":{column}")?;
+		}
+
+		Ok(())
 	}
 }
 
@@ -67,3 +90,16 @@ impl Display for ProgramSource<'_> {
 		}
 	}
 }
+
+impl<'path> From<&'path Path> for ProgramSource<'path> {
+	fn from(path: &'path Path) -> Self {
+		Self::File(path)
+	}
+}
+
+impl<'path> From<Option<&'path Path>> for ProgramSource<'path> {
+	/// Programs without a path are [`ProgramSource::Other`]`("<unknown>")`.
+	fn from(path: Option<&'path Path>) -> Self {
+		path.map_or(Self::Other("<unknown>"), Self::File)
+	}
+}
@@ -0,0 +1,268 @@
+use crate::parser::source_location::ProgramSource;
+use crate::parser::{ParseError, ParseErrorKind, Parser, SourceLocation};
+use crate::{Environment, Options};
+use std::io::{self, Read};
+
+#[cfg(feature = "compliance")]
+use crate::strings::Encoding;
+
+/// Validates Knight source code a chunk at a time, as it's read.
+///
+/// Each chunk is checked to be UTF-8 (a character split across chunks is held onto until the rest
+/// of it arrives) and, when `compliance` is enabled, to be in the [encoding](Options::encoding).
+/// Only the current chunk is kept, so arbitrarily large sources can be validated without having
+/// them in memory; see [`SourceText::validate`].
+///
+/// # Examples
+/// ```
+/// # use knightrs_bytecode::parser::{source_location::ProgramSource, Validator};
+/// # use knightrs_bytecode::{strings::Encoding, Options};
+/// let mut opts = Options::default();
+/// opts.encoding = Encoding::Utf8;
+/// let mut validator = Validator::new(ProgramSource::ExprFlag, &opts);
+///
+/// // `é` is split across the chunks, so it's returned with the second one.
+/// assert_eq!(validator.push(b"OUTPUT \"\xC3").unwrap(), "OUTPUT \"");
+/// assert_eq!(validator.push(b"\xA9\"\n").unwrap(), "\u{e9}\"\n");
+/// assert_eq!(validator.lineno(), 2);
+/// validator.finish().unwrap();
+///
+/// let mut validator = Validator::new(ProgramSource::ExprFlag, &opts);
+/// validator.push(b"# cut off: \xC3").unwrap();
+/// assert!(validator.finish().is_err());
+/// ```
+#[derive(Debug)]
+pub struct Validator<'path> {
+	filename: ProgramSource<'path>,
+	lineno: usize,
+
+	// The last chunk, and then any bytes of a character that was cut off at its end.
+	buffer: Vec<u8>,
+	// How much of `buffer` was returned from the last `push`.
+	returned: usize,
+
+	#[cfg(feature = "compliance")]
+	encoding: Encoding,
+}
+
+impl<'path> Validator<'path> {
+	/// Creates a new [`Validator`] for source code from `filename`, which is checked against `opts`.
+	#[must_use]
+	pub fn new(
+		filename: ProgramSource<'path>,
+		#[cfg_attr(not(feature = "compliance"), allow(unused_variables))] opts: &Options,
+	) -> Self {
+		Self {
+			filename,
+			lineno: 1,
+			buffer: Vec::new(),
+			returned: 0,
+
+			#[cfg(feature = "compliance")]
+			encoding: opts.encoding,
+		}
+	}
+
+	/// The line that the next chunk starts on.
+	#[must_use]
+	pub const fn lineno(&self) -> usize {
+		self.lineno
+	}
+
+	/// Validates `chunk`, which comes right after the previous one, and returns the source code in
+	/// it.
+	///
+	/// If `chunk` ends partway through a UTF-8 character, that character is left out, and returned
+	/// with the next chunk instead.
+	///
+	/// # Errors
+	/// If the source isn't valid UTF-8, [`ParseErrorKind::ReadFailed`] is returned with an
+	/// [`io::ErrorKind::InvalidData`] error. If [`opts.encoding`](Options::encoding) rejects a
+	/// character (and `compliance` is enabled), [`ParseErrorKind::InvalidCharInEncoding`] is
+	/// returned. In both cases, the error's location is the line where the problem occurred.
+	pub fn push(&mut self, chunk: &[u8]) -> Result<&str, ParseError<'path>> {
+		self.buffer.drain(..self.returned);
+		self.buffer.extend_from_slice(chunk);
+
+		let valid_len = match std::str::from_utf8(&self.buffer) {
+			Ok(_) => self.buffer.len(),
+			// The last character was cut off; the rest of it should be in the next chunk.
+			Err(err) if err.error_len().is_none() => err.valid_up_to(),
+			Err(err) => return Err(self.invalid_utf8(err)),
+		};
+
+		// SAFETY: `from_utf8` verified the first `valid_len` bytes are valid UTF-8.
+		let source = unsafe { std::str::from_utf8_unchecked(&self.buffer[..valid_len]) };
+
+		#[cfg(feature = "compliance")]
+		super::parser::validate_source_from(source, self.filename, self.lineno, self.encoding)?;
+
+		self.lineno += source.bytes().filter(|&c| c == b'\n').count();
+		self.returned = valid_len;
+		Ok(source)
+	}
+
+	/// Finishes validating the source code.
+	///
+	/// # Errors
+	/// If the source ended partway through a UTF-8 character, [`ParseErrorKind::ReadFailed`] is
+	/// returned with an [`io::ErrorKind::InvalidData`] error.
+	pub fn finish(mut self) -> Result<(), ParseError<'path>> {
+		self.buffer.drain(..self.returned);
+
+		match std::str::from_utf8(&self.buffer) {
+			Ok(_) => Ok(()),
+			Err(err) => Err(self.invalid_utf8(err)),
+		}
+	}
+
+	// `err` is from `self.buffer`, which starts on `self.lineno`.
+	fn invalid_utf8(&self, err: std::str::Utf8Error) -> ParseError<'path> {
+		let valid = &self.buffer[..err.valid_up_to()];
+		let lineno = self.lineno + valid.iter().filter(|&&c| c == b'\n').count();
+		let err = io::Error::new(io::ErrorKind::InvalidData, err);
+		ParseErrorKind::ReadFailed(err).error(SourceLocation::new(self.filename, lineno))
+	}
+}
+
+/// Knight source code which was read from a [`Read`] and validated, for use with a [`Parser`].
+///
+/// The entire source is kept in memory, as the compiled [`Program`](crate::program::Program)
+/// borrows variable names from it (so the [`SourceText`] has to outlive it). It's validated by a
+/// [`Validator`] as it's read, a chunk at a time, so encoding errors are reported (with the correct
+/// line number) without reading the rest of the file, and [`parser`](Self::parser) doesn't have to
+/// check them again. To validate a source without keeping it in memory, use
+/// [`SourceText::validate`].
+#[derive(Debug)]
+pub struct SourceText<'path> {
+	filename: ProgramSource<'path>,
+	text: String,
+
+	// The encoding `text` was validated with, so parsers with the same encoding can skip it.
+	#[cfg(feature = "compliance")]
+	encoding: Encoding,
+}
+
+impl<'path> SourceText<'path> {
+	/// How many bytes are read from the underlying reader, and validated, at once.
+	pub const CHUNK_SIZE: usize = 64 * 1024;
+
+	/// Reads all of `reader` into memory as the source code for `filename`, validating it as it's
+	/// read.
+	///
+	/// # Examples
+	/// ```
+	/// # use knightrs_bytecode::parser::{source_location::ProgramSource, SourceText};
+	/// # use knightrs_bytecode::Options;
+	/// let opts = Options::default();
+	/// let source = SourceText::read_validated(&b"OUTPUT 1"[..], ProgramSource::ExprFlag, &opts);
+	/// assert_eq!(source.unwrap().as_str(), "OUTPUT 1");
+	///
+	/// let invalid = &b"# a\n# b\n\xFF"[..];
+	/// let invalid = SourceText::read_validated(invalid, ProgramSource::ExprFlag, &opts);
+	/// let err = invalid.unwrap_err().to_string();
+	/// assert!(err.starts_with("-e:3:"), "{err}");
+	/// ```
+	///
+	/// # Errors
+	/// If `reader` fails, [`ParseErrorKind::ReadFailed`] is returned. Otherwise, the errors are the
+	/// same as [`Validator::push`] and [`Validator::finish`].
+	pub fn read_validated<R: Read>(
+		reader: R,
+		filename: ProgramSource<'path>,
+		opts: &Options,
+	) -> Result<Self, ParseError<'path>> {
+		let mut text = String::new();
+		Self::read_chunks(reader, filename, opts, |chunk| text.push_str(chunk))?;
+
+		Ok(Self {
+			filename,
+			text,
+
+			#[cfg(feature = "compliance")]
+			encoding: opts.encoding,
+		})
+	}
+
+	/// Reads all of `reader` as the source code for `filename`, and validates it, without keeping it
+	/// in memory.
+	///
+	/// # Examples
+	/// ```
+	/// # use knightrs_bytecode::parser::{source_location::ProgramSource, SourceText};
+	/// # use knightrs_bytecode::Options;
+	/// use std::io::Read;
+	///
+	/// let opts = Options::default();
+	/// let huge = std::io::repeat(b' ').take(1 << 20).chain(&b"\xFF"[..]);
+	/// assert!(SourceText::validate(huge, ProgramSource::ExprFlag, &opts).is_err());
+	/// ```
+	///
+	/// # Errors
+	/// The same as [`SourceText::read_validated`].
+	pub fn validate<R: Read>(
+		reader: R,
+		filename: ProgramSource<'path>,
+		opts: &Options,
+	) -> Result<(), ParseError<'path>> {
+		Self::read_chunks(reader, filename, opts, |_| {})
+	}
+
+	// Reads `reader` a chunk at a time, validating each one and then handing it to `each`.
+	fn read_chunks<R: Read>(
+		mut reader: R,
+		filename: ProgramSource<'path>,
+		opts: &Options,
+		mut each: impl FnMut(&str),
+	) -> Result<(), ParseError<'path>> {
+		let mut validator = Validator::new(filename, opts);
+		let mut chunk = vec![0; Self::CHUNK_SIZE];
+
+		loop {
+			let amount = match reader.read(&mut chunk) {
+				Ok(0) => break,
+				Ok(amount) => amount,
+				Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+				Err(err) => {
+					let whence = SourceLocation::new(filename, validator.lineno());
+					return Err(ParseErrorKind::ReadFailed(err).error(whence));
+				}
+			};
+
+			each(validator.push(&chunk[..amount])?);
+		}
+
+		validator.finish()
+	}
+
+	/// Where the source code came from.
+	#[must_use]
+	pub const fn filename(&self) -> ProgramSource<'path> {
+		self.filename
+	}
+
+	/// Gets the source code itself.
+	#[must_use]
+	pub fn as_str(&self) -> &str {
+		&self.text
+	}
+
+	/// Creates a [`Parser`] for this source code.
+	///
+	/// This is the same as [`Parser::new`], except the source isn't validated again if `env` uses
+	/// the same encoding it was read with.
+	///
+	/// # Errors
+	/// The same as [`Parser::new`].
+	pub fn parser<'env, 'gc>(
+		&self,
+		env: &'env mut Environment<'gc>,
+	) -> Result<Parser<'env, '_, 'path, 'gc>, ParseError<'path>> {
+		#[cfg(feature = "compliance")]
+		if env.opts().encoding != self.encoding {
+			return Parser::new(env, self.filename, &self.text);
+		}
+
+		Parser::new_prevalidated(env, self.filename, &self.text)
+	}
+}
@@ -2,7 +2,7 @@ use super::{ParseErrorKind, Parseable, SourceLocation};
 use crate::options::Options;
 use crate::parser::{ParseError, Parser};
 use crate::program::{Compilable, Compiler};
-use crate::strings::KnStr;
+use crate::strings::{syntax, KnStr};
 use std::fmt::{self, Display, Formatter};
 
 /// The name of a variable within Knight.
@@ -52,16 +52,24 @@ impl<'src, 'path> Parseable<'src, 'path, '_> for VariableName<'src> {
 	fn parse(
 		parser: &mut Parser<'_, 'src, 'path, '_>,
 	) -> Result<Option<Self::Output>, ParseError<'path>> {
-		if !parser.peek().map_or(false, |c| c.is_lowercase() || c == '_') {
+		if !parser.peek().map_or(false, syntax::is_variable_start) {
 			return Ok(None);
 		}
 
 		let start = parser.location();
 
 		let name = parser
-			.take_while(|c| c.is_lowercase() || c.is_digit(10) || c == '_')
+			.take_while(syntax::is_variable_char)
 			.expect("at least one element should exist, as we checked for lower || '_' earlier");
 
+		#[cfg(feature = "extensions")]
+		if let Some(prefix) = parser.variable_prefix() {
+			let prefixed = format!("{prefix}{name}");
+			return VariableName::new(KnStr::new_unvalidated(&prefixed), parser.opts())
+				.map_err(|err| parser.error(err))
+				.map(|name| Some((name.become_owned(), start)));
+		}
+
 		Self::new(KnStr::new_unvalidated(name), parser.opts())
 			.map_err(|err| parser.error(err))
 			.map(|name| Some((name, start)))
@@ -1,10 +1,11 @@
 mod compiler;
 
+use crate::parser::source_location::ProgramSource;
 use crate::parser::{SourceLocation, VariableName};
-use crate::value::Value;
+use crate::value::{Block, Value};
 use crate::vm::Opcode;
 pub use compiler::{Compilable, Compiler};
-use indexmap::IndexSet;
+use indexmap::{IndexMap, IndexSet};
 use std::fmt::{self, Debug, Formatter};
 
 // todo: u32 vs u64? i did u64 bx `0x00ff_ffff` isn't a lot of offsets.
@@ -25,6 +26,9 @@ pub struct Program<'src, 'path, 'gc> {
 	// The list of variable names.
 	variables: IndexSet<VariableName<'src>>,
 
+	// Arbitrary metadata about the program, such as its name or version.
+	metadata: IndexMap<String, String>,
+
 	// Only enabled when stacktrace printing is enabled, this is a map from the bytecode offset (ie
 	// the index into `code`) to a source location. Only the first bytecode from each line is added
 	// (to improve efficiency), so when looking up in `source_lines`, if a value doesn't exist you
@@ -36,17 +40,181 @@ pub struct Program<'src, 'path, 'gc> {
 	// correspond to the first instruction of a [`Block`]) to the (optional) name of the block, and
 	// the location where the block was declared.
 	#[cfg(feature = "stacktrace")]
-	#[cfg_attr(debug_assertions, allow(unused))] // TODO: do we need it?
-	// (IMPL NOTE: Technically, do we need the source location? it's not currently used in msgs.)
 	block_locations:
 		std::collections::HashMap<JumpIndex, (Option<VariableName<'src>>, SourceLocation<'path>)>,
 
+	// Only enabled when stacktrace printing is enabled, this maps the first instruction of each
+	// [`Block`] to the index right after its `Return`.
+	#[cfg(feature = "stacktrace")]
+	block_ends: std::collections::HashMap<JumpIndex, JumpIndex>,
+
+	// Whether the program was fully parsed without errors; partial programs can't be run.
+	runnable: bool,
+
+	// How many constants the compiler reused instead of adding again.
+	#[cfg(feature = "stats")]
+	reused_constants: usize,
+
+	// Which program this is, so blocks from other programs can't be run by it.
+	id: OwnedProgramId,
+
+	// One bit for each instruction, which is set if a block starts there.
+	block_starts: Box<[u64]>,
+
+	// The source code of `XIMPORT`ed files, which the parser borrowed from while compiling them.
+	// They're kept here so they're freed along with the program.
+	#[cfg(feature = "extensions")]
+	imported_sources: Box<[Box<str>]>,
+
 	// Needed for `'src` when qol and stacktrace aren't enabled.
 	_ignored: (&'src (), &'path ()),
 }
 
+/// Statistics about how a [`Program`]'s constants were pooled, as returned by
+/// [`Program::constant_pool_stats`].
+///
+/// These are only collected when `feature = "stats"` is enabled.
+#[cfg(feature = "stats")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ConstantPoolStats {
+	/// How many distinct constants the program has.
+	pub unique: usize,
+
+	/// How many times a literal was compiled that was equal to an existing constant, and so reused
+	/// it instead of adding a new one.
+	pub reused: usize,
+}
+
+/// Problems that [`Program::validate`] can find with a [`Program`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum InvalidProgram {
+	/// The program has no instructions.
+	#[error("program has no instructions")]
+	Empty,
+
+	/// The last instruction isn't [`Opcode::Return`], so the [`Vm`](crate::vm::Vm) could run past
+	/// the end of the program.
+	#[error("program doesn't end with a Return")]
+	MissingReturn,
+
+	/// The instruction at `index` doesn't have a valid opcode.
+	#[error("invalid opcode {byte:#04x} at instruction {index}")]
+	InvalidOpcode { index: usize, byte: u8 },
+
+	/// The instruction at `index` has an offset that's out of range for its opcode.
+	#[error("offset {offset} is out of range for {opcode} at instruction {index}")]
+	InvalidOffset { index: usize, opcode: Opcode, offset: usize },
+
+	/// The constant at `index` is a block that starts past the end of the program.
+	#[error("constant {index} is a block which starts out of bounds")]
+	InvalidBlock { index: usize },
+
+	/// The variable at `index` was given more than once.
+	#[error("variable {index} is a duplicate")]
+	DuplicateVariable { index: usize },
+
+	/// Extensions are enabled, and the program doesn't have a variable for
+	/// [`ARGV_VARIABLE_INDEX`](Compiler::ARGV_VARIABLE_INDEX).
+	#[cfg(feature = "extensions")]
+	#[error("program doesn't have an `_argv` variable")]
+	MissingArgv,
+
+	/// Every [`ProgramId`] is in use by another program.
+	#[error("too many programs exist at once")]
+	TooManyPrograms,
+}
+
+/// Identifies a [`Program`], so that [`Block`](crate::value::Block)s from one program can't be run
+/// by another (such as blocks passed into `EVAL`'d code).
+///
+/// Ids are only [`ProgramId::BITS`] bits wide, as they're stored alongside a block's [`JumpIndex`].
+/// An id becomes free again once its program is dropped, but ids are handed out in order (wrapping
+/// around at the end), so it's only reused after every other id has been. Only if that many
+/// programs are alive at once does creating another fail (with
+/// [`ParseErrorKind::TooManyPrograms`](crate::parser::ParseErrorKind::TooManyPrograms) or
+/// [`InvalidProgram::TooManyPrograms`]).
+///
+/// Blocks can outlive their programs, so a block could have the same id as a newer program. That's
+/// still safe, as a program only runs blocks which start where one of its own blocks does (see
+/// [`Program::declares`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProgramId(u32);
+
+// The ids of every program (or compiler) which is still alive, and where to start looking for the
+// next free id.
+struct ProgramIds {
+	live: std::collections::BTreeSet<u32>,
+	next: u32,
+}
+
+static PROGRAM_IDS: std::sync::Mutex<ProgramIds> =
+	std::sync::Mutex::new(ProgramIds { live: std::collections::BTreeSet::new(), next: 0 });
+
+impl ProgramId {
+	/// How many bits wide program ids are.
+	pub const BITS: u32 = 29;
+
+	const COUNT: u32 = 1 << Self::BITS;
+
+	/// Gets the id as a number.
+	pub const fn get(self) -> u32 {
+		self.0
+	}
+
+	pub(crate) const fn new_unchecked(id: u32) -> Self {
+		debug_assert!(id < Self::COUNT);
+		Self(id)
+	}
+}
+
+// A `ProgramId` that's in use until this is dropped. Compilers and programs hold onto one each.
+pub(crate) struct OwnedProgramId(ProgramId);
+
+impl OwnedProgramId {
+	// Returns `None` if every id is in use.
+	pub(crate) fn new() -> Option<Self> {
+		let mut ids = PROGRAM_IDS.lock().unwrap_or_else(|err| err.into_inner());
+		if ids.live.len() == ProgramId::COUNT as usize {
+			return None;
+		}
+
+		let mut id = ids.next;
+		while ids.live.contains(&id) {
+			id = (id + 1) % ProgramId::COUNT;
+		}
+
+		ids.live.insert(id);
+		ids.next = (id + 1) % ProgramId::COUNT;
+		Some(Self(ProgramId(id)))
+	}
+
+	pub(crate) fn get(&self) -> ProgramId {
+		self.0
+	}
+}
+
+impl Drop for OwnedProgramId {
+	fn drop(&mut self) {
+		let mut ids = PROGRAM_IDS.lock().unwrap_or_else(|err| err.into_inner());
+		ids.live.remove(&self.0 .0);
+	}
+}
+
+// The `Program::block_starts` for a program with `len` instructions and `constants`: the program
+// itself starts at `0`, and every other block is a constant.
+fn block_starts(len: usize, constants: &[Value<'_>]) -> Box<[u64]> {
+	let mut starts = vec![0; len.div_ceil(64)].into_boxed_slice();
+	let blocks = constants.iter().filter_map(|constant| constant.as_block());
+
+	for start in std::iter::once(0).chain(blocks.map(|block| block.inner().0)).filter(|&s| s < len) {
+		starts[start / 64] |= 1 << (start % 64);
+	}
+
+	starts
+}
+
 /// A type that represents a place programs can jump to.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct JumpIndex(pub(super) usize);
 
 /// Represents a jump that's been deferred---it'll be reified once we know the target destination.
@@ -71,20 +239,17 @@ pub enum JumpWhen {
 impl Debug for Program<'_, '_, '_> {
 	/// Write the debug output for `Program`.
 	///
-	/// This also decodes the bytecode contained within the [`Program`], to make it easy understand
-	/// what's happening.
+	/// Normally, this is just a summary of the program (how many instructions, constants, and
+	/// variables it has, and where it starts), which is suitable for logs. The alternate form
+	/// (`{:#?}`) instead writes out the constants and variables, and decodes the bytecode contained
+	/// within the [`Program`], to make it easy understand what's happening. Either way, the
+	/// program's [metadata](Program::metadata) is included if it has any.
 	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-		struct Bytecode<'a>(&'a [InstructionAndOffset]);
-		impl Debug for Bytecode<'_> {
+		struct Bytecode<'a, 'src, 'path, 'gc>(&'a Program<'src, 'path, 'gc>);
+		impl Debug for Bytecode<'_, '_, '_, '_> {
 			fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-				if !f.alternate() {
-					return f.write_str("[...]");
-				}
-
 				let mut bytecode = f.debug_list();
-				for (idx, &number) in self.0.into_iter().enumerate() {
-					let opcode = unsafe { Opcode::from_byte_unchecked(number as u8) };
-					let offset = (number >> 0o10) as usize;
+				for (idx, (opcode, offset)) in self.0.instructions().enumerate() {
 					if opcode.takes_offset() {
 						bytecode.entry(&format!("{}: {:?} (offset={})", idx, opcode, offset));
 					} else {
@@ -95,10 +260,27 @@ impl Debug for Program<'_, '_, '_> {
 			}
 		}
 
+		let alternate = f.alternate();
 		let mut prog = f.debug_struct("Program");
-		prog.field("constants", &self.constants);
-		prog.field("bytecode", &Bytecode(&self.code));
-		prog.field("variables", &self.variables);
+
+		if alternate {
+			prog.field("constants", &self.constants);
+			prog.field("bytecode", &Bytecode(self));
+			prog.field("variables", &self.variables);
+		} else {
+			prog.field("instructions", &self.code.len());
+			prog.field("constants", &self.constants.len());
+			prog.field("variables", &self.variables.len());
+		}
+
+		prog.field("runnable", &self.runnable);
+
+		if !self.metadata.is_empty() {
+			prog.field("metadata", &self.metadata);
+		}
+
+		#[cfg(feature = "stacktrace")]
+		prog.field("entry", &format_args!("{}", self.source_location_at(0)));
 
 		prog.finish()
 	}
@@ -124,6 +306,153 @@ impl<'src, 'path, 'gc> Program<'src, 'path, 'gc> {
 		(opcode, location)
 	}
 
+	/// Creates a new runnable [`Program`] from its raw parts, such as one that was loaded from a file.
+	///
+	/// Each element of `code` is an instruction, where the bottom-most byte is the [`Opcode`] and the
+	/// rest is its offset. The [`Vm`](crate::vm::Vm) doesn't check offsets when running programs,
+	/// so unlike programs built by a [`Compiler`], these are [validated](Self::validate) first.
+	///
+	/// # Safety
+	/// The stack's depth isn't validated, so the caller must ensure every instruction always has
+	/// enough values on the stack for its arguments, and that each block (and the program itself)
+	/// leaves exactly one value on the stack when it returns.
+	///
+	/// # Errors
+	/// Returns an error if `variables` has duplicates, or if the program is invalid.
+	///
+	/// # Examples
+	/// ```
+	/// use knightrs_bytecode::parser::{source_location::ProgramSource, VariableName};
+	/// use knightrs_bytecode::program::{InvalidProgram, Program};
+	/// use knightrs_bytecode::strings::KnStr;
+	/// use knightrs_bytecode::vm::Opcode;
+	///
+	/// let argv = VariableName::new_unvalidated(KnStr::new_unvalidated("_argv"));
+	/// let load = |code: &[i64]| {
+	///     let (source, variables) = (ProgramSource::Other("raw"), vec![argv.clone()]);
+	///     // SAFETY: Every program below either pushes one value and returns, or is invalid.
+	///     unsafe { Program::from_raw_parts(code.into(), Box::new([]), variables, source) }
+	/// };
+	///
+	/// let get_var = |index: i64| Opcode::GetVar as i64 | index << 8;
+	/// assert!(load(&[get_var(0), Opcode::Return as i64]).is_ok());
+	///
+	/// assert_eq!(
+	///     load(&[get_var(1), Opcode::Return as i64]).unwrap_err(),
+	///     InvalidProgram::InvalidOffset { index: 0, opcode: Opcode::GetVar, offset: 1 },
+	/// );
+	/// assert_eq!(load(&[get_var(0)]).unwrap_err(), InvalidProgram::MissingReturn);
+	/// assert_eq!(
+	///     load(&[0xff, Opcode::Return as i64]).unwrap_err(),
+	///     InvalidProgram::InvalidOpcode { index: 0, byte: 0xff },
+	/// );
+	/// ```
+	pub unsafe fn from_raw_parts(
+		code: Box<[i64]>,
+		mut constants: Box<[Value<'gc>]>,
+		variables: Vec<VariableName<'src>>,
+		source: ProgramSource<'path>,
+	) -> Result<Self, InvalidProgram> {
+		let mut unique = IndexSet::with_capacity(variables.len());
+		for (index, variable) in variables.into_iter().enumerate() {
+			if !unique.insert(variable) {
+				return Err(InvalidProgram::DuplicateVariable { index });
+			}
+		}
+
+		// Every block in the constants is from this program, which didn't have an id until now.
+		let id = OwnedProgramId::new().ok_or(InvalidProgram::TooManyPrograms)?;
+		for constant in constants.iter_mut() {
+			if let Some(block) = constant.as_block() {
+				*constant = Block::new(block.inner(), id.get()).into();
+			}
+		}
+
+		let _ = source;
+		let program = Self {
+			block_starts: block_starts(code.len(), &constants),
+			code,
+			constants,
+			variables: unique,
+			metadata: IndexMap::new(),
+
+			#[cfg(feature = "stacktrace")]
+			source_lines: [(0, SourceLocation::new(source, 1))].into_iter().collect(),
+
+			#[cfg(feature = "stacktrace")]
+			block_locations: Default::default(),
+
+			#[cfg(feature = "stacktrace")]
+			block_ends: Default::default(),
+
+			runnable: true,
+			#[cfg(feature = "stats")]
+			reused_constants: 0,
+			id,
+			#[cfg(feature = "extensions")]
+			imported_sources: Box::default(),
+			_ignored: (&(), &()),
+		};
+
+		program.validate()?;
+		Ok(program)
+	}
+
+	/// Checks that every instruction in `self` can be run by the [`Vm`](crate::vm::Vm).
+	///
+	/// This ensures every opcode is valid, every offset (constant and variable indices, jump targets,
+	/// and the operations of opcodes like [`Opcode::Unary`]) is in range, every block constant starts
+	/// within the program, and that the program ends with a [`Opcode::Return`].
+	///
+	/// Programs built by a [`Compiler`] are always valid, and don't need to be checked.
+	pub fn validate(&self) -> Result<(), InvalidProgram> {
+		let last = self.code.last().ok_or(InvalidProgram::Empty)?;
+		if *last as u8 != Opcode::Return as u8 {
+			return Err(InvalidProgram::MissingReturn);
+		}
+
+		#[cfg(feature = "extensions")]
+		if self.variables.get_index(Compiler::ARGV_VARIABLE_INDEX).is_none() {
+			return Err(InvalidProgram::MissingArgv);
+		}
+
+		for (index, &number) in self.code.iter().enumerate() {
+			let byte = number as u8;
+			let opcode =
+				Opcode::from_byte(byte).ok_or(InvalidProgram::InvalidOpcode { index, byte })?;
+			let offset = (number >> 0o10) as usize;
+
+			let max = match opcode {
+				Opcode::PushConstant | Opcode::OutputConst => self.constants.len(),
+				Opcode::Jump | Opcode::JumpIfTrue | Opcode::JumpIfFalse => self.code.len(),
+				Opcode::GetVar
+				| Opcode::GetVarToSet
+				| Opcode::SetVar
+				| Opcode::SetVarPop
+				| Opcode::SetInPlace => self.variables.len(),
+				#[cfg(feature = "extensions")]
+				Opcode::AssignDynamic => crate::vm::opcode::DynamicAssignment::System as usize + 1,
+				#[cfg(feature = "extensions")]
+				Opcode::Unary => crate::vm::opcode::UnaryOp::SplitWhitespace as usize + 1,
+				#[cfg(feature = "extensions")]
+				Opcode::Bitwise => crate::vm::opcode::BitwiseOp::ShiftRight as usize + 1,
+				_ => continue,
+			};
+
+			if max <= offset {
+				return Err(InvalidProgram::InvalidOffset { index, opcode, offset });
+			}
+		}
+
+		for (index, constant) in self.constants.iter().enumerate() {
+			if constant.as_block().is_some_and(|block| self.code.len() <= block.inner().0) {
+				return Err(InvalidProgram::InvalidBlock { index });
+			}
+		}
+
+		Ok(())
+	}
+
 	pub unsafe fn mark(&self) {
 		use crate::gc::GarbageCollected;
 
@@ -143,23 +472,273 @@ impl<'src, 'path, 'gc> Program<'src, 'path, 'gc> {
 		unsafe { *self.constants.get_unchecked(offset) }
 	}
 
+	/// Gets the id of this program, which all of the [`Block`]s it declares have.
+	#[inline]
+	pub fn id(&self) -> ProgramId {
+		self.id.get()
+	}
+
+	/// Whether `block` was declared by this program (or is the whole program).
+	///
+	/// Blocks from other programs which happen to have the same [id](Self::id) (which can happen
+	/// once the program that declared them is dropped) are only accepted if they start where one of
+	/// this program's blocks does, so running them is still safe.
+	#[inline]
+	pub fn declares(&self, block: Block) -> bool {
+		let start = block.inner().0;
+		block.program() == self.id()
+			&& self.block_starts.get(start / 64).is_some_and(|bits| bits & 1 << (start % 64) != 0)
+	}
+
+	/// Whether the program can be run.
+	///
+	/// Programs returned from [`Parser::parse_program_recovering`](
+	/// crate::parser::Parser::parse_program_recovering) which had errors are only partial programs,
+	/// and can't be run.
+	#[inline]
+	pub fn is_runnable(&self) -> bool {
+		self.runnable
+	}
+
+	/// The number of instructions in this program.
+	#[inline]
+	pub fn num_instructions(&self) -> usize {
+		self.code.len()
+	}
+
+	/// Gets every instruction in this program, as its opcode and offset, in order.
+	///
+	/// This is the safe counterpart to [`Program::opcode_at`], for tools such as disassemblers. The
+	/// offset is only meaningful for opcodes which [take one](Opcode::takes_offset), and is `0` for
+	/// the rest.
+	///
+	/// # Examples
+	/// ```
+	/// # use knightrs_bytecode::{parser::{source_location::ProgramSource, Parser}, vm::Opcode, *};
+	/// // SAFETY: nothing allocated by the `gc` escapes it.
+	/// unsafe {
+	///     Gc::default().run(|gc| {
+	///         let mut env = Environment::new(Options::default(), gc);
+	///         let source = "; = a 3 : + a 4";
+	///         let parser = Parser::new(&mut env, ProgramSource::ExprFlag, source).unwrap();
+	///         let program = parser.parse_program().unwrap();
+	///
+	///         let adds = program.instructions().filter(|&(opcode, _)| opcode == Opcode::Add);
+	///         assert_eq!(adds.count(), 1);
+	///         assert_eq!(program.instructions().len(), program.num_instructions());
+	///
+	///         let names = program.variable_names();
+	///         let index = names.iter().position(|name| name.to_string() == "a").unwrap();
+	///         assert!(program.instructions().any(|instr| instr == (Opcode::GetVar, index)));
+	///         assert_eq!(program.constants().len(), 2);
+	///     })
+	/// }
+	/// ```
+	pub fn instructions(&self) -> impl ExactSizeIterator<Item = (Opcode, usize)> + '_ {
+		// SAFETY: every index is in bounds.
+		(0..self.code.len()).map(|index| unsafe { self.opcode_at(index) })
+	}
+
+	/// Gets every constant in this program, which [`Opcode::PushConstant`]'s offset indexes into.
+	#[inline]
+	pub fn constants(&self) -> &[Value<'gc>] {
+		&self.constants
+	}
+
+	/// Gets statistics about how this program's constants were pooled.
+	///
+	/// Programs made with [`Program::from_raw_parts`] never have any reused constants.
+	///
+	/// # Examples
+	/// ```
+	/// # use knightrs_bytecode::{parser::{source_location::ProgramSource, Parser}, *};
+	/// // SAFETY: nothing allocated by the `gc` escapes it.
+	/// unsafe {
+	///     Gc::default().run(|gc| {
+	///         let mut env = Environment::new(Options::default(), gc);
+	///         let source = r#"+ "hi" + "hi" + 12 12"#;
+	///         let parser = Parser::new(&mut env, ProgramSource::ExprFlag, source).unwrap();
+	///         let stats = parser.parse_program().unwrap().constant_pool_stats();
+	///
+	///         assert_eq!(stats.unique, 2);
+	///         assert_eq!(stats.reused, 2);
+	///     })
+	/// }
+	/// ```
+	#[cfg(feature = "stats")]
+	pub fn constant_pool_stats(&self) -> ConstantPoolStats {
+		ConstantPoolStats { unique: self.constants.len(), reused: self.reused_constants }
+	}
+
+	/// Gets the program's metadata, such as its name, version, or a hash of its source code.
+	///
+	/// Metadata is set by embedders (via [`Compiler::metadata_mut`] or [`Program::metadata_mut`]),
+	/// or by pragma comments in the source code: a comment starting with `#:` is a `key value`
+	/// pair, where the key is its first word and the value is the rest of the line. It's shown in
+	/// stacktraces and when the program's [debugged](Debug).
+	///
+	/// # Examples
+	/// ```
+	/// # use knightrs_bytecode::{parser::{source_location::ProgramSource, Parser}, *};
+	/// // SAFETY: nothing allocated by the `gc` escapes it.
+	/// unsafe {
+	///     Gc::default().run(|gc| {
+	///         let mut env = Environment::new(Options::default(), gc);
+	///         let source = "#: name greeter\n#: version 1.2 beta\nOUTPUT 'hi'";
+	///         let parser = Parser::new(&mut env, ProgramSource::ExprFlag, source).unwrap();
+	///         let program = parser.parse_program().unwrap();
+	///
+	///         assert_eq!(program.metadata()["name"], "greeter");
+	///         assert_eq!(program.metadata()["version"], "1.2 beta");
+	///     })
+	/// }
+	/// ```
+	pub fn metadata(&self) -> &IndexMap<String, String> {
+		&self.metadata
+	}
+
+	/// Gets a mutable reference to the program's [metadata](Program::metadata).
+	pub fn metadata_mut(&mut self) -> &mut IndexMap<String, String> {
+		&mut self.metadata
+	}
+
 	/// The number of variables that're defined in this program.
 	#[inline]
 	pub fn num_variables(&self) -> usize {
 		self.variables.len()
 	}
 
+	/// Gets the names of every variable in this program, which [`Opcode::GetVar`] and friends'
+	/// offsets index into.
+	#[inline]
+	pub fn variable_names(&self) -> &IndexSet<VariableName<'src>> {
+		&self.variables
+	}
+
+	/// Gets the names of the variables that this program uses but never assigns to, in the order
+	/// they first appear.
+	///
+	/// Only assignments within `self` are considered, so variables that are only assigned by
+	/// `EVAL`'d code are included.
+	pub fn unassigned_variables(&self) -> impl Iterator<Item = &VariableName<'src>> + '_ {
+		let mut assigned = vec![false; self.variables.len()];
+
+		for &number in self.code.iter() {
+			let opcode = number as u8;
+			if opcode == Opcode::SetVar as u8
+				|| opcode == Opcode::SetVarPop as u8
+				|| opcode == Opcode::SetInPlace as u8
+			{
+				assigned[(number >> 0o10) as usize] = true;
+			}
+		}
+
+		// `ARGV` is assigned by the `Vm` when the program is run.
+		#[cfg(feature = "extensions")]
+		if let Some(argv) = assigned.get_mut(Compiler::ARGV_VARIABLE_INDEX) {
+			*argv = true;
+		}
+
+		self.variables.iter().zip(assigned).filter(|(_, assigned)| !assigned).map(|(name, _)| name)
+	}
+
+	/// Converts `self` into a [`Program`] which doesn't borrow from its source code, by copying its
+	/// variables' names.
+	pub fn become_owned(self) -> Program<'static, 'path, 'gc> {
+		Program {
+			code: self.code,
+			constants: self.constants,
+			variables: self.variables.into_iter().map(VariableName::become_owned).collect(),
+			metadata: self.metadata,
+
+			#[cfg(feature = "stacktrace")]
+			source_lines: self.source_lines,
+
+			#[cfg(feature = "stacktrace")]
+			block_locations: self
+				.block_locations
+				.into_iter()
+				.map(|(start, (name, location))| {
+					(start, (name.map(VariableName::become_owned), location))
+				})
+				.collect(),
+
+			#[cfg(feature = "stacktrace")]
+			block_ends: self.block_ends,
+
+			runnable: self.runnable,
+			#[cfg(feature = "stats")]
+			reused_constants: self.reused_constants,
+			id: self.id,
+			block_starts: self.block_starts,
+			#[cfg(feature = "extensions")]
+			imported_sources: self.imported_sources,
+			_ignored: (&(), self._ignored.1),
+		}
+	}
+
 	/// Gets the variable at `idx`.
 	pub fn variable_name(&self, var_idx: usize) -> &VariableName<'src> {
 		&self.variables[var_idx]
 	}
 
-	/// Gets the variable at `idx`.
-	#[cfg(feature = "extensions")]
+	/// Gets the index of the variable named `name`, if this program uses it.
 	pub fn variable_index(&self, name: &VariableName<'_>) -> Option<usize> {
 		self.variables.get_index_of(name)
 	}
 
+	/// Gets the name (if it had one) and source location of the block declared at `block`.
+	///
+	/// Returns `None` if `block` wasn't declared by this program.
+	#[cfg(feature = "stacktrace")]
+	pub fn block_location(
+		&self,
+		block: crate::value::Block,
+	) -> Option<(Option<&VariableName<'src>>, SourceLocation<'path>)> {
+		if !self.declares(block) {
+			return None;
+		}
+
+		self.block_locations.get(&block.inner()).map(|(name, loc)| (name.as_ref(), *loc))
+	}
+
+	/// Gets the indices of the variables assigned directly within the innermost block containing
+	/// the program offset `offset`, in the order they're first assigned.
+	///
+	/// Assignments within blocks nested inside that block aren't included. Returns `None` if
+	/// `offset` isn't within any block (or if this program wasn't compiled with block information).
+	#[cfg(feature = "stacktrace")]
+	pub fn variables_assigned_around(&self, offset: usize) -> Option<IndexSet<usize>> {
+		let (start, end) = self
+			.block_ends
+			.iter()
+			.map(|(start, end)| (start.0, end.0))
+			.filter(|&(start, end)| start <= offset && offset < end)
+			.max_by_key(|&(start, _)| start)?;
+
+		let mut assigned = IndexSet::new();
+		let mut index = start;
+		while index < end {
+			let nested_end = self.block_ends.get(&JumpIndex(index)).filter(|_| index != start);
+			if let Some(nested_end) = nested_end {
+				index = nested_end.0;
+				continue;
+			}
+
+			let number = self.code[index];
+			let opcode = number as u8;
+			if opcode == Opcode::SetVar as u8
+				|| opcode == Opcode::SetVarPop as u8
+				|| opcode == Opcode::SetInPlace as u8
+			{
+				assigned.insert((number >> 0o10) as usize);
+			}
+			index += 1;
+		}
+
+		Some(assigned)
+	}
+
 	/// Gets the source location at the program offset `offset`.
 	///
 	/// If `offset` doesn't directly map to a known source location, [`source_location_at`] works
@@ -1,4 +1,6 @@
-use super::{DeferredJump, InstructionAndOffset, JumpIndex, JumpWhen, Program};
+use super::{
+	DeferredJump, InstructionAndOffset, JumpIndex, JumpWhen, OwnedProgramId, Program, ProgramId,
+};
 use crate::gc::Gc;
 use crate::options::Options;
 use crate::parser::{ParseError, ParseErrorKind, SourceLocation, VariableName};
@@ -6,7 +8,7 @@ use crate::strings::KnStr;
 use crate::value::Value;
 use crate::vm::Opcode;
 
-use indexmap::IndexSet;
+use indexmap::{IndexMap, IndexSet};
 use std::collections::HashMap;
 
 // safety: cannot do invalid things with the builder.
@@ -28,8 +30,13 @@ pub struct Compiler<'src, 'path, 'gc> {
 	#[allow(unused)] // it'll be actually used later
 	gc: &'gc Gc,
 
-	// All the constants that've been declared so far. Used with [`Opcode::PushConstant`].
-	constants: Vec<Value<'gc>>,
+	// All the constants that've been declared so far. Used with [`Opcode::PushConstant`]. Equal
+	// constants are only stored once, so programs with lots of repeated literals stay small.
+	constants: IndexSet<Value<'gc>>,
+
+	// How many times `push_constant` reused a constant that was already in `constants`.
+	#[cfg(feature = "stats")]
+	reused_constants: usize,
 
 	// The list of all variables encountered so far. (They're stored in an ordered set, as their
 	// index is the "offset" that all `Opcodes` that interact with variables (eg [`Opcode::GetVar`])
@@ -48,9 +55,28 @@ pub struct Compiler<'src, 'path, 'gc> {
 	#[cfg(feature = "stacktrace")]
 	block_locations: HashMap<JumpIndex, (Option<VariableName<'src>>, SourceLocation<'path>)>,
 
+	// Only enabled when stacktrace printing is enabled, this maps the first instruction of each
+	// [`Block`] to the index right after its `Return`.
+	#[cfg(feature = "stacktrace")]
+	block_ends: HashMap<JumpIndex, JumpIndex>,
+
 	// TODO: not public
 	pub loops: Vec<(JumpIndex, Vec<DeferredJump>)>,
 
+	// The id the built program will have; it's needed up front for the blocks it declares.
+	id: OwnedProgramId,
+
+	// Metadata for the built program; see `Program::metadata`.
+	metadata: IndexMap<String, String>,
+
+	// The most recent index that a jump was pointed at. Code right before it can't be fused with
+	// what comes after it (eg by `Compiler::output`), as the jump would skip part of the fused code.
+	latest_jump_target: usize,
+
+	// The source code of `XIMPORT`ed files; see `Compiler::keep_source`.
+	#[cfg(feature = "extensions")]
+	imported_sources: Vec<Box<str>>,
+
 	// Needed for when `stacktrace` is disabled
 	_ignored: &'path (),
 }
@@ -64,10 +90,15 @@ impl<'src, 'path, 'gc> Compiler<'src, 'path, 'gc> {
 	#[cfg(feature = "extensions")]
 	pub const ARGV_VARIABLE_INDEX: usize = 0;
 
-	pub fn new(start: SourceLocation<'path>, gc: &'gc Gc) -> Self {
-		Self {
+	/// Creates a new [`Compiler`] for a program starting at `start`.
+	///
+	/// Returns `None` if every [`ProgramId`] is in use.
+	pub fn new(start: SourceLocation<'path>, gc: &'gc Gc) -> Option<Self> {
+		Some(Self {
 			code: vec![],
-			constants: vec![],
+			constants: IndexSet::new(),
+			#[cfg(feature = "stats")]
+			reused_constants: 0,
 			gc,
 			variables: {
 				let mut variables = IndexSet::new();
@@ -93,9 +124,17 @@ impl<'src, 'path, 'gc> Compiler<'src, 'path, 'gc> {
 				bl.insert(JumpIndex(0), (None, start));
 				bl
 			},
+
+			#[cfg(feature = "stacktrace")]
+			block_ends: HashMap::new(),
 			_ignored: &(),
 			loops: vec![],
-		}
+			id: OwnedProgramId::new()?,
+			metadata: IndexMap::new(),
+			latest_jump_target: 0,
+			#[cfg(feature = "extensions")]
+			imported_sources: Vec::new(),
+		})
 	}
 	/// Finished building the [`Program`], and returns it
 	///
@@ -104,9 +143,22 @@ impl<'src, 'path, 'gc> Compiler<'src, 'path, 'gc> {
 	/// value on top of its stack whenever it returns, which is the return value of the program.
 	///
 	/// Additionally, the caller must enure that all deferred jumps have been `jump_to`'d
-	pub unsafe fn build(mut self) -> Program<'src, 'path, 'gc> {
-		// SAFETY: The caller guarantees that we'll always have exactly one opcode on the top when
-		// the program is finished executing, so we know
+	pub unsafe fn build(self) -> Program<'src, 'path, 'gc> {
+		self.finish(true)
+	}
+
+	/// Finishes building a [`Program`] that had errors while it was being parsed.
+	///
+	/// The returned program isn't [runnable](Program::is_runnable), and is only useful for inspecting
+	/// what was able to be parsed. As such, unlike [`Compiler::build`], this function is safe.
+	pub fn build_partial(self) -> Program<'src, 'path, 'gc> {
+		self.finish(false)
+	}
+
+	fn finish(mut self, runnable: bool) -> Program<'src, 'path, 'gc> {
+		// SAFETY: When `runnable` is true, the caller guarantees that we'll always have exactly one
+		// opcode on the top when the program is finished executing. When it's false, the program will
+		// never be executed.
 		unsafe {
 			self.opcode_without_offset(Opcode::Return);
 		}
@@ -116,10 +168,17 @@ impl<'src, 'path, 'gc> Compiler<'src, 'path, 'gc> {
 			debug_assert_ne!(opcode, 0, "deferred jump which was never un-deferred encountered.")
 		}
 
-		Program {
+		let constants = self.constants.into_iter().collect::<Box<[_]>>();
+		let program = Program {
+			runnable,
+			id: self.id,
+			block_starts: super::block_starts(self.code.len(), &constants),
 			code: self.code.into_boxed_slice(),
-			constants: self.constants.into_boxed_slice(),
+			constants,
+			#[cfg(feature = "stats")]
+			reused_constants: self.reused_constants,
 			variables: self.variables,
+			metadata: self.metadata,
 
 			#[cfg(feature = "stacktrace")]
 			source_lines: self.source_lines,
@@ -127,8 +186,52 @@ impl<'src, 'path, 'gc> Compiler<'src, 'path, 'gc> {
 			#[cfg(feature = "stacktrace")]
 			block_locations: self.block_locations,
 
+			#[cfg(feature = "stacktrace")]
+			block_ends: self.block_ends,
+
+			#[cfg(feature = "extensions")]
+			imported_sources: self.imported_sources.into_boxed_slice(),
+
 			_ignored: (&(), &()),
-		}
+		};
+
+		debug_assert!(!runnable || program.validate().is_ok(), "compiler built an invalid program");
+		program
+	}
+
+	/// Keeps `source` alive until the [`Program`] that's being built is dropped, so it can be parsed
+	/// as part of the program (eg by `XIMPORT`).
+	///
+	/// # Safety
+	/// Nothing borrowed from the returned string may be kept past the program being dropped. (The
+	/// parser only ever keeps owned copies of variable names from imported files, as they're
+	/// always prefixed.)
+	#[cfg(feature = "extensions")]
+	pub(crate) unsafe fn keep_source(&mut self, source: String) -> &'src str {
+		let source = source.into_boxed_str();
+
+		// SAFETY: The `str` is on the heap, so moving `source` into `self` (and later into the
+		// program) doesn't move it, and it isn't freed until the program is dropped. The caller
+		// guarantees that nothing borrowed from it is kept past that.
+		let kept = unsafe { &*(&*source as *const str) };
+		self.imported_sources.push(source);
+		kept
+	}
+
+	/// Gets the id of the [`Program`] that's being built.
+	pub fn program_id(&self) -> ProgramId {
+		self.id.get()
+	}
+
+	/// Adds `variables` to the end of the program's variables (skipping any it already has) without
+	/// using them, so they'll have the same offsets as they do in another program.
+	pub fn declare_variables(&mut self, variables: impl IntoIterator<Item = VariableName<'src>>) {
+		self.variables.extend(variables);
+	}
+
+	/// Gets the metadata the built [`Program`] will have; see [`Program::metadata`].
+	pub fn metadata_mut(&mut self) -> &mut IndexMap<String, String> {
+		&mut self.metadata
 	}
 
 	/// Gets the current index for the program, for use later on with jumps.
@@ -136,6 +239,22 @@ impl<'src, 'path, 'gc> Compiler<'src, 'path, 'gc> {
 		JumpIndex(self.code.len())
 	}
 
+	/// Discards all the code that's been written since `index`.
+	///
+	/// This is used when recovering from parse errors, so the partially-compiled expression that
+	/// caused the error doesn't leave any dangling deferred jumps around. Constants and variables
+	/// aren't removed, as code before `index` might be using them.
+	pub fn rollback(&mut self, index: JumpIndex) {
+		self.code.truncate(index.0);
+
+		#[cfg(feature = "stacktrace")]
+		{
+			self.source_lines.retain(|&offset, _| offset == 0 || offset < index.0);
+			self.block_locations.retain(|&whence, _| whence.0 == 0 || whence.0 < index.0);
+			self.block_ends.retain(|_, &mut end| end.0 <= index.0);
+		}
+	}
+
 	/// Indicates that a new line of code, located at `loc`, is about to begin. Used for stacktraces.
 	#[cfg(feature = "stacktrace")]
 	pub fn record_source_location(&mut self, loc: SourceLocation<'path>) {
@@ -143,15 +262,17 @@ impl<'src, 'path, 'gc> Compiler<'src, 'path, 'gc> {
 	}
 
 	/// Indicates that at the offset `whence`, a block named `name` with the source location `loc`
-	/// exists. Used for stacktraces.
+	/// exists, and that its code stops right before `end`. Used for stacktraces.
 	#[cfg(feature = "stacktrace")]
 	pub fn record_block(
 		&mut self,
 		loc: SourceLocation<'path>,
 		whence: JumpIndex,
+		end: JumpIndex,
 		name: Option<VariableName<'src>>,
 	) {
 		self.block_locations.insert(whence, (name, loc));
+		self.block_ends.insert(whence, end);
 	}
 
 	/// Writes a jump to `index`, which will only be run if `when` is valid.
@@ -192,15 +313,38 @@ impl<'src, 'path, 'gc> Compiler<'src, 'path, 'gc> {
 		self.code.push(code_from_opcode_and_offset(opcode, 0)) // any offset'll do, it's ignored
 	}
 
-	pub fn push_constant(&mut self, value: Value<'gc>) {
-		let index = match self.constants.iter().enumerate().find(|(_, v)| value == **v) {
-			Some((index, _)) => index,
-			None => {
-				let i = self.constants.len();
-				self.constants.push(value);
-				i
+	/// Writes an [`Opcode::Output`].
+	///
+	/// If the value being output is a string constant that was just pushed, the push is replaced by
+	/// an [`Opcode::OutputConst`] instead, which writes the constant without converting it first.
+	///
+	/// # Safety
+	/// The caller must ensure that the value to be output has just been written.
+	pub unsafe fn output(&mut self) {
+		if let Some(&last) = self.code.last() {
+			let offset = (last >> 0o10) as usize;
+
+			if last as u8 == Opcode::PushConstant as u8
+				&& self.latest_jump_target != self.code.len()
+				&& self.constants[offset].as_knstring().is_some()
+			{
+				*self.code.last_mut().unwrap() = code_from_opcode_and_offset(Opcode::OutputConst, offset);
+				return;
 			}
-		};
+		}
+
+		unsafe {
+			self.opcode_without_offset(Opcode::Output);
+		}
+	}
+
+	pub fn push_constant(&mut self, value: Value<'gc>) {
+		let (index, _inserted) = self.constants.insert_full(value);
+
+		#[cfg(feature = "stats")]
+		if !_inserted {
+			self.reused_constants += 1;
+		}
 
 		// SAFETY: we know that `index` is a valid constant cause we just checked
 		unsafe {
@@ -259,8 +403,7 @@ impl<'src, 'path, 'gc> Compiler<'src, 'path, 'gc> {
 		Ok(())
 	}
 
-	// SAFETY: when called, a value has to be on the stack
-	#[deprecated(note = "not actually used yet, could be an optimization")]
+	// SAFETY: when called, a value has to be on the stack. Unlike `set_variable`, it's popped.
 	pub unsafe fn set_variable_pop(
 		&mut self,
 		name: VariableName<'src>,
@@ -274,6 +417,67 @@ impl<'src, 'path, 'gc> Compiler<'src, 'path, 'gc> {
 
 		Ok(())
 	}
+
+	/// If the only code written since `start` is a [`Opcode::GetVar`] of `name`, replaces it with
+	/// an [`Opcode::GetVarToSet`], and returns `true`.
+	///
+	/// This is used for the first argument of the `SET` in `= name SET name ...`, so that `name` can
+	/// be modified in place if nothing else refers to its contents.
+	///
+	/// # Examples
+	/// ```
+	/// # use knightrs_bytecode::{parser::{source_location::ProgramSource, Parser}, vm::Opcode, *};
+	/// let src = r#"; = s * "ab" 3 : ; = t s : ; = s SET s 0 1 "X" : ; = s SET s 1 1 "Y" : + s t"#;
+	///
+	/// // SAFETY: nothing allocated by the `gc` escapes it.
+	/// unsafe {
+	///     Gc::default().run(|gc| {
+	///         let mut env = Environment::new(Options::default(), gc);
+	///         let parser = Parser::new(&mut env, ProgramSource::ExprFlag, src).unwrap();
+	///         let program = parser.parse_program().unwrap();
+	///
+	///         let opcodes = program.instructions().map(|(opcode, _)| opcode).collect::<Vec<_>>();
+	///         assert_eq!(opcodes.iter().filter(|&&op| op == Opcode::GetVarToSet).count(), 2);
+	///         assert_eq!(opcodes.iter().filter(|&&op| op == Opcode::SetInPlace).count(), 2);
+	///         assert!(!opcodes.contains(&Opcode::Set));
+	///     })
+	/// }
+	///
+	/// // `t` still refers to the original string, so it mustn't be modified.
+	/// let outcome = testing::run(&format!("OUTPUT {src}"), "", Options::default());
+	/// assert_eq!(outcome.stdout, "XYababababab\n");
+	/// ```
+	pub fn get_variable_to_set(&mut self, start: JumpIndex, name: &VariableName<'src>) -> bool {
+		let Some(index) = self.variables.get_index_of(name) else {
+			return false;
+		};
+
+		if self.code.get(start.0..) != Some(&[code_from_opcode_and_offset(Opcode::GetVar, index)]) {
+			return false;
+		}
+
+		self.code[start.0] = code_from_opcode_and_offset(Opcode::GetVarToSet, index);
+		true
+	}
+
+	/// Writes a [`Opcode::SetInPlace`] for `name`, which pops the `SET`'s arguments.
+	///
+	/// # Safety
+	/// The `SET`'s arguments have to be on the stack, with the first one having been pushed by
+	/// [`Compiler::get_variable_to_set`] for the same `name`.
+	pub unsafe fn set_variable_in_place(
+		&mut self,
+		name: VariableName<'src>,
+		opts: &Options,
+	) -> Result<(), ParseErrorKind> {
+		let index = self.variable_index(name, opts)?;
+
+		unsafe {
+			self.opcode_with_offset(Opcode::SetInPlace, index);
+		}
+
+		Ok(())
+	}
 }
 
 impl DeferredJump {
@@ -298,5 +502,6 @@ impl DeferredJump {
 		};
 
 		compiler.code[self.0] = code_from_opcode_and_offset(opcode, index.0);
+		compiler.latest_jump_target = index.0;
 	}
 }
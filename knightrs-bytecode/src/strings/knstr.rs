@@ -5,6 +5,10 @@ use std::fmt::{self, Debug, Display, Formatter};
 ///
 /// This is actually just a wrapper around rust's [`str`], except it's only able to be created if
 /// compliance is checked (or `new_unvalidated` is used).
+///
+/// This is the supported way for embedders to validate and inspect Knight strings: it only needs
+/// [`Options`], not an [`Environment`](crate::Environment), and all of its indices are byte
+/// offsets unless the method says otherwise (eg [`KnStr::get_chars`]).
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(transparent)]
 pub struct KnStr(str);
@@ -38,7 +42,7 @@ pub enum StringError {
 	/// This is only ever returned if [`check_container_length`](
 	/// crate::env::flags::Compliance::check_container_length) is enabled.
 	#[cfg(feature = "compliance")]
-	#[error("string is too large ({0} < {len})", len = KnStr::COMPLIANCE_MAX_LEN)]
+	#[error("string is too large (length {0})")]
 	#[cfg_attr(docsrs, doc(cfg(feature = "compliance")))]
 	LengthTooLong(usize),
 
@@ -53,25 +57,23 @@ pub enum StringError {
 }
 
 impl KnStr {
-	/// The maximum length a string can be when compliance checking is enabled.
-	pub const COMPLIANCE_MAX_LEN: usize = i32::MAX as usize;
+	/// The default maximum length a string can be when compliance checking is enabled.
+	pub const COMPLIANCE_MAX_LEN: usize = knight_strings::MAX_LEN;
 
 	/// Creates a new [`KnStr`] without doing any forms of validation.
 	///
 	/// # Compliance
 	/// The `source` that's passed in should be a valid Knight string under all compliance features.
 	/// More specifically, that means that its length must never be more than [`COMPLIANCE_MAX_LEN`],
-	/// and that [`Encoding::Knight::validate`] should pass for it.
+	/// and that it should be valid in the [`Encoding`](super::Encoding) the options allow.
 	///
 	/// [`COMPLIANCE_MAX_LEN`]: Self::COMPLIANCE_MAX_LEN
-	/// [`Encoding::Knight::validate`]: super::Encoding::Knight::validate
 	#[inline]
-	pub const fn new_unvalidated(source: &str) -> &Self {
-		#[cfg(feature = "compliance")] // Only enable debug checks in compliance mode
-		{
-			debug_assert!(source.len() <= Self::COMPLIANCE_MAX_LEN);
-			debug_assert!(super::Encoding::Knight.validate(source).is_ok());
-		}
+	pub fn new_unvalidated(source: &str) -> &Self {
+		// Only enable debug checks in compliance mode. The encoding isn't checked, as that depends on
+		// the options, which aren't known here.
+		#[cfg(feature = "compliance")]
+		debug_assert!(knight_strings::is_valid_length(source.len()));
 
 		// SAFETY: `KnStr`s are `#[repr(transparent)]` around `str`s
 		unsafe { &*(source as *const str as *const Self) }
@@ -83,25 +85,35 @@ impl KnStr {
 	/// If the `compliance` option is disabled, this function never fails.
 	///
 	/// If `opts.compliance.check_container_length` is enabled, and `source.len()` is greater than
-	/// [`COMPLIANCE_MAX_LEN`](Self::COMPLIANCE_MAX_LEN), an [`StringError::LengthTooLong`] is
-	/// returned.
+	/// the [maximum container length](crate::options::Compliance::is_valid_container_length), an
+	/// [`StringError::LengthTooLong`] is returned.
 	///
 	/// The `opts.encoding` also validates the source.
 	#[cfg_attr(not(feature = "compliance"), inline)] // inline when we don't have compliance checks.
 	pub fn new<'a>(source: &'a str, opts: &Options) -> Result<&'a Self, StringError> {
-		// TODO: Combine with new_validate_length ?
+		Self::validate(source, opts)?;
 
+		// SAFETY: `KnStr`s are `#[repr(transparent)]` around `str`s
+		Ok(unsafe { &*(source as *const str as *const Self) })
+	}
+
+	/// Checks whether `source` is a valid Knight string under `opts`, without creating a [`KnStr`].
+	///
+	/// # Errors
+	/// The same as [`KnStr::new`].
+	#[cfg_attr(not(feature = "compliance"), inline)]
+	pub fn validate(source: &str, opts: &Options) -> Result<(), StringError> {
 		#[cfg(feature = "compliance")]
 		{
-			if opts.compliance.check_container_length && Self::COMPLIANCE_MAX_LEN < source.len() {
+			if !opts.compliance.is_valid_container_length(source.len()) {
 				return Err(StringError::LengthTooLong(source.len()));
 			}
 
 			opts.encoding.validate(source)?;
 		}
 
-		// SAFETY: `KnStr`s are `#[repr(transparent)]` around `str`s
-		Ok(unsafe { &*(source as *const str as *const Self) })
+		let _ = (source, opts);
+		Ok(())
 	}
 
 	/// Returns the underlying `str`.
@@ -133,6 +145,88 @@ impl KnStr {
 	pub fn chars(&self) -> std::str::Chars<'_> {
 		self.0.chars()
 	}
+
+	/// Returns the length of this string, in chars.
+	pub fn char_len(&self) -> usize {
+		self.0.chars().count()
+	}
+
+	/// Gets the first character of `self`, if it exists.
+	pub fn head(&self) -> Option<char> {
+		self.0.chars().next()
+	}
+
+	/// Gets everything _but_ the first character of `self`, or `None` if `self` is empty.
+	pub fn tail(&self) -> Option<&Self> {
+		let head = self.head()?;
+		self.get(head.len_utf8()..)
+	}
+
+	/// Converts the char index `index` into a byte offset, or `None` if it's out of bounds.
+	///
+	/// `index` may be equal to [`KnStr::char_len`], in which case [`KnStr::len`] is returned.
+	pub fn byte_offset(&self, index: usize) -> Option<usize> {
+		self.0.char_indices().map(|(offset, _)| offset).chain(Some(self.len())).nth(index)
+	}
+
+	/// Returns the subslice of `self` from the char indices `range`, or `None` if it's out of
+	/// bounds.
+	pub fn get_chars(&self, range: std::ops::Range<usize>) -> Option<&Self> {
+		if range.end < range.start {
+			return None;
+		}
+
+		let start = self.byte_offset(range.start)?;
+		let end = start + self.get(start..)?.byte_offset(range.end - range.start)?;
+		self.get(start..end)
+	}
+
+	/// Returns the byte offset of the first occurrence of `needle` within `self`.
+	pub fn find(&self, needle: &Self) -> Option<usize> {
+		self.0.find(&needle.0)
+	}
+
+	/// Returns whether `needle` is contained within `self`.
+	pub fn contains(&self, needle: &Self) -> bool {
+		self.0.contains(&needle.0)
+	}
+
+	/// Splits `self` by `sep`, like the string extension to `/`.
+	///
+	/// If `sep` is empty, `self` is split into its individual characters.
+	pub fn split<'a>(&'a self, sep: &'a Self) -> Split<'a> {
+		if sep.is_empty() {
+			Split(SplitInner::Chars(self.0.char_indices(), &self.0))
+		} else {
+			Split(SplitInner::Str(self.0.split(&sep.0)))
+		}
+	}
+}
+
+/// The iterator returned from [`KnStr::split`].
+#[derive(Debug, Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct Split<'a>(SplitInner<'a>);
+
+#[derive(Debug, Clone)]
+enum SplitInner<'a> {
+	Chars(std::str::CharIndices<'a>, &'a str),
+	Str(std::str::Split<'a, &'a str>),
+}
+
+impl<'a> Iterator for Split<'a> {
+	type Item = &'a KnStr;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		// COMPLIANCE: Substrings of valid `KnStr`s are also valid.
+		match &mut self.0 {
+			SplitInner::Chars(chars, source) => {
+				let (start, chr) = chars.next()?;
+				Some(KnStr::new_unvalidated(&source[start..start + chr.len_utf8()]))
+			}
+			SplitInner::Str(split) => split.next().map(KnStr::new_unvalidated),
+		}
+	}
 }
 
 // impl ToOwned for KnStr {
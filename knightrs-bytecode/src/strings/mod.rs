@@ -1,9 +1,6 @@
-mod character;
-mod encoding;
 mod knstr;
 mod knstrref;
 
-pub use character::Character;
-pub use encoding::{Encoding, EncodingError};
-pub use knstr::{KnStr, StringError};
+pub use knight_strings::{syntax, Character, Encoding, EncodingError};
+pub use knstr::{KnStr, Split, StringError};
 pub use knstrref::KnStrRef;
@@ -0,0 +1,65 @@
+//! Helpers for running Knight programs with captured IO.
+//!
+//! This mirrors `knightrs::testing`, so that the same programs can be run under both engines and
+//! their results compared.
+//!
+//! For example, a comment right at the end of a program is ignored:
+//! ```
+//! let outcome = knightrs_bytecode::testing::run("OUTPUT 1 # a comment", "", Default::default());
+//! assert_eq!(outcome.stdout, "1\n");
+//! assert_eq!(outcome.status, 0);
+//! ```
+//!
+//! Programs which both engines are checked against live in `knightrs-conformance`.
+
+use crate::parser::source_location::ProgramSource;
+use crate::parser::Parser;
+use crate::vm::Vm;
+use crate::{Error, Gc, Options};
+
+/// The observable result of running a program via [`run`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Outcome {
+	/// Everything that was written by `OUTPUT` and `DUMP`.
+	pub stdout: String,
+
+	/// The exit status: `0` if the program finished normally, the status passed to `QUIT`, or `1` if
+	/// an error occurred.
+	pub status: i32,
+
+	/// The error's message, if the program didn't finish normally (or via `QUIT`).
+	pub error: Option<String>,
+}
+
+/// Runs `source` with `opts`, using `stdin` as the input to `PROMPT`.
+///
+/// Nothing is read from the real stdin or written to the real stdout. [`Embedded::dont_exit_when_quitting`]
+/// is always enabled, so `QUIT` doesn't exit the process.
+///
+/// [`Embedded::dont_exit_when_quitting`]: crate::options::Embedded::dont_exit_when_quitting
+pub fn run(source: &str, stdin: &str, mut opts: Options) -> Outcome {
+	opts.embedded.dont_exit_when_quitting = true;
+
+	let mut stdout = Vec::new();
+
+	// SAFETY: Only the `Outcome`, which doesn't contain any values, escapes the `gc`.
+	let result = unsafe {
+		Gc::default().run(|gc| {
+			let mut env = crate::Environment::new(opts, gc);
+			env.set_stdin(stdin.as_bytes());
+			env.set_stdout(&mut stdout);
+
+			let parser = Parser::new(&mut env, ProgramSource::ExprFlag, source)?;
+			let program = parser.parse_program()?;
+			Vm::new(&program, &mut env).run_entire_program_without_argv().and(Ok(()))
+		})
+	};
+
+	let (status, error) = match result {
+		Ok(()) => (0, None),
+		Err(Error::Exit(status)) => (status, None),
+		Err(err) => (1, Some(err.to_string())),
+	};
+
+	Outcome { stdout: String::from_utf8_lossy(&stdout).into_owned(), status, error }
+}
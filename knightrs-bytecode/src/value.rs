@@ -1,23 +1,32 @@
 use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
 use std::mem::MaybeUninit;
 
-use crate::gc::{GarbageCollected, GcRoot, ValueInner};
+use crate::gc::{GarbageCollected, GcRoot, ValueInner, ValueRoot};
+use crate::program::{JumpIndex, ProgramId};
 use crate::strings::KnStr;
-use crate::{program::JumpIndex, vm::Vm, Environment, Error};
+use crate::{vm::Vm, Environment, Error};
 
 mod block;
 mod boolean;
+#[cfg(feature = "custom-types")]
+mod custom;
 pub mod integer;
 mod knstring;
 mod list;
+mod native_fn;
 mod null;
 
-pub use block::Block;
+pub use block::{Block, BlockInfo};
 pub use boolean::{Boolean, ToBoolean};
+#[cfg(feature = "custom-types")]
+pub use custom::{Custom, CustomType};
 pub use integer::{Integer, IntegerError, ToInteger};
+pub(crate) use knstring::InternPool;
 pub use knstring::{KnString, ToKnString};
-pub use list::{List, ToList};
+pub use list::{List, ListBuilder, ToList};
+pub use native_fn::NativeFn;
 pub use null::Null;
 use std::fmt::{self, Debug, Formatter};
 
@@ -35,11 +44,18 @@ XXXX ... XXXX 000 -- "allocated", nonzero `X`
 XXXX ... XXXX XX1 -- Integer
 0000 ... 0000 010 -- False
 0000 ... 0001 010 -- True
+XXXX ... X00 010 -- NativeFn, nonzero `X` (the index into the environment's table, plus one)
 XXXX ... XXXX 100 -- Block
 XXXX ... XXXX 110 -- Float32
 */
+/// A Knight value.
+///
+/// # Garbage collection
+/// `Value`s are `Copy`, and so the [`Gc`](crate::Gc) can't track them. Allocated values (strings and
+/// lists) are only kept alive while they're reachable from a root, so any `Value` that's held onto
+/// across an allocation must be rooted via [`Value::root`].
 #[repr(transparent)]
-#[derive(Clone, Copy)] // TODO: HOW DOES THIS PLAY WITH THE GC?
+#[derive(Clone, Copy)]
 pub struct Value<'gc>(Inner, PhantomData<&'gc ()>);
 
 #[repr(C)]
@@ -62,12 +78,19 @@ const REPR_FALSE: ValueRepr = 0b0000_010;
 const REPR_TRUE: ValueRepr = 0b0001_010;
 
 const TAG_BLOCK: ValueRepr = 0b100;
+const TAG_NATIVE_FN: ValueRepr = 0b010;
+const NATIVE_FN_SHIFT: ValueRepr = 5;
 const TAG_MASK: ValueRepr = 0b111;
 const TAG_SHIFT: ValueRepr = 3;
 const TAG_INT: ValueRepr = 1;
 const TAG_MASK_INT: ValueRepr = 1;
 const TAG_INT_SHIFT: ValueRepr = 1;
 
+// Blocks store their `JumpIndex` in the bottom bits, and their `ProgramId` above that.
+const BLOCK_INDEX_BITS: ValueRepr = 32;
+sa::const_assert_eq!(BLOCK_INDEX_BITS + ProgramId::BITS as ValueRepr + TAG_SHIFT, 64);
+sa::const_assert_eq!(Block::MAX_START as ValueRepr, (1 << BLOCK_INDEX_BITS) - 1);
+
 impl Debug for Value<'_> {
 	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
 		if self.is_null() {
@@ -82,7 +105,14 @@ impl Debug for Value<'_> {
 			Debug::fmt(&string, f)
 		} else if let Some(block) = self.as_block() {
 			Debug::fmt(&block, f)
+		} else if let Some(native) = self.as_native_fn() {
+			Debug::fmt(&native, f)
 		} else {
+			#[cfg(feature = "custom-types")]
+			if let Some(custom) = self.as_custom() {
+				return Debug::fmt(&custom, f);
+			}
+
 			unreachable!()
 		}
 	}
@@ -128,15 +158,28 @@ impl From<Boolean> for Value<'_> {
 impl From<Block> for Value<'_> {
 	#[inline]
 	fn from(block: Block) -> Self {
-		let repr = block.inner().0 as u64;
+		let index = block.inner().0 as ValueRepr;
 
-		// TODO: make this assertion a guarantee within `JumpIndex`.
-		debug_assert_eq!((repr << TAG_SHIFT) >> TAG_SHIFT, repr, "repr has top TAG_SHIFT bits set");
+		// `Block::try_new` refuses to create blocks whose index is too large, and the compiler
+		// rejects programs where that happens.
+		debug_assert!(index < 1 << BLOCK_INDEX_BITS, "jump index is too large for a block");
 
+		let repr = (block.program().get() as ValueRepr) << BLOCK_INDEX_BITS | index;
 		unsafe { Self::from_val((repr << TAG_SHIFT) | TAG_BLOCK) }
 	}
 }
 
+impl From<NativeFn> for Value<'_> {
+	#[inline]
+	fn from(native: NativeFn) -> Self {
+		// Offset by one so that the first native function isn't mistaken for `FALSE`.
+		let repr = (native.index() as ValueRepr + 1) << NATIVE_FN_SHIFT;
+		debug_assert_eq!(repr >> NATIVE_FN_SHIFT, native.index() as ValueRepr + 1);
+
+		unsafe { Self::from_val(repr | TAG_NATIVE_FN) }
+	}
+}
+
 impl From<List<'_>> for Value<'_> {
 	#[inline]
 	fn from(list: List) -> Self {
@@ -144,6 +187,14 @@ impl From<List<'_>> for Value<'_> {
 	}
 }
 
+#[cfg(feature = "custom-types")]
+impl From<Custom<'_>> for Value<'_> {
+	#[inline]
+	fn from(custom: Custom) -> Self {
+		unsafe { Self::from_alloc(custom.into_raw()) }
+	}
+}
+
 impl From<KnString<'_>> for Value<'_> {
 	#[inline]
 	fn from(string: KnString) -> Self {
@@ -167,7 +218,14 @@ impl NamedType for Value<'_> {
 			x.type_name()
 		} else if let Some(x) = self.as_block() {
 			x.type_name()
+		} else if let Some(x) = self.as_native_fn() {
+			x.type_name()
 		} else {
+			#[cfg(feature = "custom-types")]
+			if let Some(x) = self.as_custom() {
+				return x.type_name();
+			}
+
 			bug!("typename for another type: {:x}", self.repr())
 		}
 	}
@@ -201,7 +259,7 @@ impl<'gc> Value<'gc> {
 	/// `ptr` must be point to a valid, properly aligned valid [`ValueInner`] that's valid for `'gc`.
 	#[inline]
 	unsafe fn from_alloc(ptr: *const ValueInner) -> Self {
-		debug_assert_eq!((ptr as ValueRepr) & TAG_MASK, 0, "repr has tag bits set");
+		debug_assert_eq!((ptr.addr() as ValueRepr) & TAG_MASK, 0, "repr has tag bits set");
 		Self(Inner { ptr }, PhantomData)
 	}
 
@@ -216,6 +274,21 @@ impl<'gc> Value<'gc> {
 		self.is_alloc_or_null() && !self.is_null()
 	}
 
+	/// Returns the allocated pointer, if `self` is allocated.
+	pub(crate) fn as_alloc(self) -> Option<*const ValueInner> {
+		if self.is_alloc() {
+			// SAFETY: `is_alloc` ensures `ptr` is the active field.
+			Some(unsafe { self.0.ptr })
+		} else {
+			None
+		}
+	}
+
+	/// Roots `self`, so that it won't be collected until the returned [`ValueRoot`] is dropped.
+	pub fn root(self, gc: &'gc crate::Gc) -> ValueRoot<'gc> {
+		ValueRoot::new(self, gc)
+	}
+
 	/// Returns whether [`self`] is NULL.
 	#[inline]
 	pub const fn is_null(self) -> bool {
@@ -250,7 +323,20 @@ impl<'gc> Value<'gc> {
 	#[inline]
 	pub fn as_block(self) -> Option<Block> {
 		if self.repr() & TAG_MASK == TAG_BLOCK {
-			Some(Block::new(JumpIndex(self.repr() as usize >> TAG_SHIFT)))
+			let repr = self.repr() >> TAG_SHIFT;
+			let index = JumpIndex((repr & ((1 << BLOCK_INDEX_BITS) - 1)) as usize);
+			let program = ProgramId::new_unchecked((repr >> BLOCK_INDEX_BITS) as u32);
+			Some(Block::new(index, program))
+		} else {
+			None
+		}
+	}
+
+	/// Returns the underlying [`NativeFn`], if `self` is actually a native function.
+	#[inline]
+	pub fn as_native_fn(self) -> Option<NativeFn> {
+		if self.repr() & TAG_MASK == TAG_NATIVE_FN && self.repr() > REPR_TRUE {
+			Some(NativeFn::new(((self.repr() >> NATIVE_FN_SHIFT) - 1) as usize))
 		} else {
 			None
 		}
@@ -260,7 +346,10 @@ impl<'gc> Value<'gc> {
 	#[inline]
 	pub fn as_list(self) -> Option<List<'gc>> {
 		if self.is_alloc() {
-			unsafe { ValueInner::as_list(self.0.ptr) }
+			unsafe {
+				ValueInner::debug_assert_live(self.0.ptr);
+				ValueInner::as_list(self.0.ptr)
+			}
 		} else {
 			None
 		}
@@ -270,7 +359,24 @@ impl<'gc> Value<'gc> {
 	#[inline]
 	pub fn as_knstring(self) -> Option<KnString<'gc>> {
 		if self.is_alloc() {
-			unsafe { ValueInner::as_knstring(self.0.ptr) }
+			unsafe {
+				ValueInner::debug_assert_live(self.0.ptr);
+				ValueInner::as_knstring(self.0.ptr)
+			}
+		} else {
+			None
+		}
+	}
+
+	/// Returns the underlying [`Custom`], if `self` is actually a custom type.
+	#[cfg(feature = "custom-types")]
+	#[inline]
+	pub fn as_custom(self) -> Option<Custom<'gc>> {
+		if self.is_alloc() {
+			unsafe {
+				ValueInner::debug_assert_live(self.0.ptr);
+				ValueInner::as_custom(self.0.ptr)
+			}
 		} else {
 			None
 		}
@@ -293,85 +399,145 @@ unsafe impl GarbageCollected for Value<'_> {
 	}
 }
 
-/// Knight functions
-impl<'gc> Value<'gc> {
-	#[inline] // CHECKME: is this optimization worth it?
-	pub fn kn_dump(self, env: &mut Environment<'gc>) -> crate::Result<()> {
-		use std::io::{self, Write};
-
-		fn dump(value: Value<'_>, mut out: impl Write) -> io::Result<()> {
-			if value.is_null() {
-				return write!(out, "null");
+// Writes the debug representation of `value` to `out`, for `DUMP`.
+fn dump_to_buffer(
+	value: Value<'_>,
+	out: &mut Vec<u8>,
+	#[cfg_attr(not(feature = "compliance"), allow(unused_variables))] opts: &crate::Options,
+) -> crate::Result<()> {
+	use std::io::Write;
+
+	if value.is_null() {
+		write!(out, "null")
+	} else if let Some(boolean) = value.as_boolean() {
+		write!(out, "{boolean}")
+	} else if let Some(integer) = value.as_integer() {
+		write!(out, "{integer}")
+	} else if let Some(string) = value.as_knstring() {
+		write!(out, "{}", knight_strings::dump(string.as_str()))
+	} else if let Some(list) = value.as_list() {
+		out.push(b'[');
+
+		for (idx, element) in list.iter().enumerate() {
+			if idx != 0 {
+				out.extend_from_slice(b", ");
 			}
 
-			if let Some(boolean) = value.as_boolean() {
-				return write!(out, "{boolean}");
-			}
-
-			if let Some(integer) = value.as_integer() {
-				return write!(out, "{integer}");
-			}
-
-			if let Some(string) = value.as_knstring() {
-				return write!(out, "{string:?}");
-			}
-
-			if let Some(list) = value.as_list() {
-				write!(out, "[")?;
-
-				for (idx, arg) in list.into_iter().enumerate() {
-					if idx != 0 {
-						write!(out, ", ")?;
-					}
+			dump_to_buffer(element, out, opts)?;
+		}
 
-					dump(arg, out.by_ref())?;
-				}
+		out.push(b']');
+		Ok(())
+	} else if let Some(block) = value.as_block() {
+		#[cfg(feature = "compliance")]
+		if opts.compliance.strict_blocks {
+			return Err(Error::TypeError { type_name: value.type_name(), function: "DUMP" });
+		}
 
-				write!(out, "]")?;
-			}
+		write!(out, "{block:?}")
+	} else if let Some(native) = value.as_native_fn() {
+		#[cfg(feature = "compliance")]
+		if opts.compliance.strict_blocks {
+			return Err(Error::TypeError { type_name: value.type_name(), function: "DUMP" });
+		}
 
-			// #[cfg(feature = "compliance")]
-			// if env.opts().compliance.strict_blocks && self.as_block().is_some() {
-			// 	return Err(Error::TypeError { type_name: self.type_name(), function: "DUMP" });
+		write!(out, "{native:?}")
+	} else {
+		#[cfg(feature = "custom-types")]
+		if let Some(custom) = value.as_custom() {
+			return custom.data().dump(out).map_err(|err| Error::IoError { func: "DUMP", err });
+		}
 
-			// 	return write!(env.output(), "{:?}", self.as_block().unwrap())
-			// 		.map_err(|err| Error::IoError { func: "OUTPUT", err });
-			// }
+		return Err(Error::TypeError { type_name: value.type_name(), function: "DUMP" });
+	}
+	.map_err(|err| Error::IoError { func: "DUMP", err })
+}
 
-			Ok(())
-		}
+/// Knight functions
+impl<'gc> Value<'gc> {
+	/// Writes the debug representation of `self` to `env`'s [`dump_output`](Environment::dump_output).
+	///
+	/// Blocks (either `self` or ones nested within lists) are written as `Block(...)`, unless
+	/// [`strict_blocks`](crate::options::Compliance::strict_blocks) is enabled, in which case a
+	/// [`Error::TypeError`] is returned. Nothing is written if an error occurs.
+	///
+	/// # Examples
+	/// ```
+	/// # use knightrs_bytecode::{testing::run, Options};
+	/// let dump = |source: &str, opts: &Options| run(source, "", opts.clone());
+	///
+	/// let nested = ["DUMP BLOCK 1", "DUMP ,BLOCK 1", "DUMP + ,1 ,,BLOCK 1", "DUMP ,,,BLOCK 1"];
+	///
+	/// let mut opts = Options::default();
+	/// for source in nested {
+	///     let outcome = dump(source, &opts);
+	///     assert_eq!(outcome.error, None, "{source}");
+	///     assert_eq!(outcome.stdout.matches("Block(").count(), 1, "{source}");
+	/// }
+	///
+	/// // The representation is stable, and each block is written after what precedes it.
+	/// let outcome = dump("; = b BLOCK 1 : DUMP + ,b + ,2 ,,b", &opts);
+	/// let block = dump("; = b BLOCK 1 : DUMP b", &opts).stdout;
+	/// assert_eq!(outcome.stdout, format!("[{block}, 2, [{block}]]"));
+	///
+	/// // With `strict_blocks`, dumping a block anywhere in a list fails, without writing anything.
+	/// #[cfg(feature = "compliance")]
+	/// {
+	///     opts.compliance.strict_blocks = true;
+	///     for source in nested {
+	///         let outcome = dump(&format!(r#"; OUTPUT "before" : {source}"#), &opts);
+	///         assert_eq!(outcome.stdout, "before\n", "{source}");
+	///         assert!(outcome.error.is_some_and(|err| err.contains("DUMP")), "{source}");
+	///     }
+	///
+	///     assert_eq!(dump("DUMP + ,1 ,,2", &opts).stdout, "[1, [2]]");
+	/// }
+	/// ```
+	pub fn kn_dump(self, env: &mut Environment<'gc>) -> crate::Result<()> {
+		use std::io::Write;
 
-		// return Ok(write!(env.output(), "{self:?}").unwrap());
+		// Dump into a buffer first, so nothing's written if a block is nested somewhere in a list.
+		let mut buf = Vec::new();
+		dump_to_buffer(self, &mut buf, env.opts())?;
+		env.dump_output().write_all(&buf).map_err(|err| env.output_error("DUMP", err))
+	}
 
-		if self.is_null() {
-			write!(env.output(), "null")
-		} else if let Some(b) = self.as_boolean() {
-			write!(env.output(), "{b}")
-		} else if let Some(i) = self.as_integer() {
-			write!(env.output(), "{i}")
-		} else if let Some(s) = self.as_knstring() {
-			write!(env.output(), "{:?}", s.as_str())
-		} else if let Some(l) = self.as_list() {
-			write!(env.output(), "[").map_err(|err| Error::IoError { func: "OUTPUT", err })?;
-			for (idx, arg) in l.iter().enumerate() {
-				if idx != 0 {
-					write!(env.output(), ", ").map_err(|err| Error::IoError { func: "OUTPUT", err })?;
-				}
-				arg.kn_dump(env)?;
-			}
-			write!(env.output(), "]")
-		} else {
-			#[cfg(feature = "compliance")]
-			if env.opts().compliance.strict_blocks && self.as_block().is_some() {
-				return write!(env.output(), "{:?}", self.as_block().unwrap())
-					.map_err(|err| Error::IoError { func: "OUTPUT", err });
-			}
+	/// Writes the debug representation of `self` to `out`, as [`Value::kn_dump`] does.
+	///
+	/// # Errors
+	/// The same as [`Value::kn_dump`]. Errors from `out` are returned as [`Error::IoError`]s.
+	pub fn kn_dump_into<W: std::io::Write + ?Sized>(
+		self,
+		out: &mut W,
+		opts: &crate::Options,
+	) -> crate::Result<()> {
+		let mut buf = Vec::new();
+		dump_to_buffer(self, &mut buf, opts)?;
+		out.write_all(&buf).map_err(|err| Error::IoError { func: "DUMP", err })
+	}
 
-			return Err(Error::TypeError { type_name: self.type_name(), function: "DUMP" });
-		}
-		.map_err(|err| Error::IoError { func: "OUTPUT", err })
+	/// Returns the debug representation of `self`, as [`Value::kn_dump`] would write it.
+	///
+	/// This is useful for comparing values against what the Knight spec expects `DUMP` to print,
+	/// without having to capture stdout.
+	///
+	/// # Errors
+	/// The same as [`Value::kn_dump`].
+	pub fn dump_to_string(&self, env: &Environment<'gc>) -> crate::Result<String> {
+		let mut buf = Vec::new();
+		dump_to_buffer(*self, &mut buf, env.opts())?;
+
+		// Custom types may write invalid UTF-8, so this can't be unchecked.
+		String::from_utf8(buf).map_err(|err| Error::IoError {
+			func: "DUMP",
+			err: std::io::Error::new(std::io::ErrorKind::InvalidData, err),
+		})
 	}
 
+	/// Compares `self` with `rhs`, as per `<`, `>`, and `XCMP`.
+	///
+	/// `rhs` is converted to `self`'s type first (except for strings and lists, which are compared
+	/// directly against the same type). `function` is used for errors.
 	#[inline] // CHECKME: is this optimization worth it?
 	pub fn kn_compare(
 		&self,
@@ -384,6 +550,11 @@ impl<'gc> Value<'gc> {
 		}
 
 		if let Some(string) = self.as_knstring() {
+			// Strings don't need to be converted (and rooted) when compared against strings.
+			if let Some(rhs) = rhs.as_knstring() {
+				return Ok(string.as_str().cmp(rhs.as_str()));
+			}
+
 			return Ok(string.cmp(&rhs.to_knstring(env)?));
 		}
 
@@ -392,9 +563,19 @@ impl<'gc> Value<'gc> {
 		}
 
 		if let Some(list) = self.as_list() {
+			// Likewise, lists (and nested lists) compared against lists aren't converted.
+			if let Some(rhs) = rhs.as_list() {
+				return list.try_cmp(&rhs, function, env);
+			}
+
 			return list.try_cmp(&*rhs.to_list(env)?, function, env);
 		}
 
+		#[cfg(feature = "custom-types")]
+		if let Some(custom) = self.as_custom() {
+			return custom.data().compare(*rhs, env);
+		}
+
 		Err(Error::TypeError { type_name: self.type_name(), function })
 	}
 
@@ -404,7 +585,7 @@ impl<'gc> Value<'gc> {
 		#[cfg(feature = "compliance")]
 		if env.opts().compliance.strict_blocks {
 			fn forbid_block_arguments(value: &Value, function: &'static str) -> crate::Result<()> {
-				if value.as_block().is_some() {
+				if value.as_block().is_some() || value.as_native_fn().is_some() {
 					return Err(Error::TypeError { type_name: value.type_name(), function });
 				}
 
@@ -430,7 +611,15 @@ impl<'gc> Value<'gc> {
 	pub fn kn_call(&self, vm: &mut Vm<'_, '_, '_, '_, 'gc>) -> crate::Result<Self> {
 		if let Some(block) = self.as_block() {
 			vm.run(block)
+		} else if let Some(native) = self.as_native_fn() {
+			// `CALL` pushes the result onto the stack before anything else is allocated.
+			Ok(vm.env().call_native_fn(native, &[])?.get())
 		} else {
+			#[cfg(feature = "custom-types")]
+			if let Some(custom) = self.as_custom() {
+				return Ok(custom.data().call(vm.env())?.get());
+			}
+
 			Err(Error::TypeError { type_name: self.type_name(), function: "CALL" })
 		}
 	}
@@ -439,32 +628,49 @@ impl<'gc> Value<'gc> {
 	#[inline] // CHECKME: is this optimization worth it?
 	pub fn kn_length(&self, env: &mut Environment<'gc>) -> crate::Result<Integer> {
 		if let Some(string) = self.as_knstring() {
-			// Rust guarantees that `str::len` won't be larger than `isize::MAX`. Since we're always
-			// using `i64`, if `usize == u32` or `usize == u64`, we can always cast the `isize` to
-			// the `i64` without failure.
-			//
-			// With compliance enabled, it's possible that we are only checking for compliance on
-			// integer bounds, and not on string lengths, so we do have to check in compliance mode.
-			#[cfg(feature = "compliance")]
-			if env.opts().compliance.i32_integer && !env.opts().compliance.check_container_length {
-				return Ok(Integer::new_error(string.len() as i64, env.opts())?.into());
-			}
-
-			return Ok(Integer::new_unvalidated(string.len() as i64).into());
+			// If `compliance.check_container_length` is enabled, this never fails. However, it's
+			// possible we're only checking integer bounds, and not container lengths.
+			return Ok(Integer::from_usize(string.len(), env.opts())?);
 		}
 
 		if let Some(list) = self.as_list() {
-			// (same guarantees as `ValueEnum::String`)
-			#[cfg(feature = "compliance")]
-			if env.opts().compliance.i32_integer && !env.opts().compliance.check_container_length {
-				return Ok(Integer::new_error(list.len() as i64, env.opts())?.into());
+			return Ok(Integer::from_usize(list.len(), env.opts())?);
+		}
+
+		#[cfg(feature = "custom-types")]
+		if let Some(custom) = self.as_custom() {
+			return custom.data().length(env);
+		}
+
+		#[cfg(feature = "extensions")]
+		let length_of_anything = env.opts().extensions().builtin_fns.length_of_anything;
+		#[cfg(not(feature = "extensions"))]
+		let length_of_anything = false;
+		let strict_conversions =
+			cfg_expr!(feature = "compliance", env.opts().compliance.strict_conversions, false);
+
+		// Integers, booleans, and null have lengths that are known without converting them to
+		// lists, so don't bother allocating one. (When conversions are strict, `to_list` decides
+		// which of them are allowed, so it's used instead.)
+		if length_of_anything || !strict_conversions {
+			if let Some(integer) = self.as_integer() {
+				return Ok(Integer::from_usize(integer.number_of_digits(), env.opts())?);
 			}
 
-			return Ok(Integer::new_unvalidated(list.len() as i64).into());
+			if let Some(boolean) = self.as_boolean() {
+				return Ok(if boolean { Integer::ONE } else { Integer::ZERO });
+			}
+
+			if self.is_null() {
+				return Ok(Integer::ZERO);
+			}
+		}
+
+		if length_of_anything {
+			return Err(Error::TypeError { type_name: self.type_name(), function: "LENGTH" });
 		}
 
-		// TODO: optimizations of other things
-		Ok(Integer::new_error(self.to_list(env)?.len() as i64, env.opts())?)
+		Ok(Integer::from_usize(self.to_list(env)?.len(), env.opts())?)
 	}
 
 	#[inline] // CHECKME: is this optimization worth it?
@@ -484,8 +690,8 @@ impl<'gc> Value<'gc> {
 		env: &mut Environment<'gc>,
 	) -> crate::Result<()> {
 		#[cfg(feature = "extensions")]
-		if env.opts().extensions.breaking.negate_reverses_collections {
-			todo!();
+		if env.opts().extensions().breaking.negate_reverses_collections {
+			todo_or_error!("negating collections");
 		}
 
 		target.write(self.to_integer(env)?.negate(env.opts())?.into());
@@ -507,30 +713,29 @@ impl<'gc> Value<'gc> {
 
 		if let Some(string) = self.as_knstring() {
 			let foo = string.concat(&rhs.to_knstring(env)?, env.opts(), env.gc())?;
-			unsafe {
-				foo.with_inner(|inner| target.write(inner.into()));
-			}
+			target.write(foo.as_value());
 			return Ok(());
 		}
 
 		if let Some(list) = self.as_list() {
 			let foo = list.concat(&*rhs.to_list(env)?, env.opts(), env.gc())?;
-			unsafe {
-				foo.with_inner(|inner| target.write(inner.into()));
-			}
+			target.write(foo.as_value());
 			return Ok(());
 		}
 
 		#[cfg(feature = "extensions")]
-		if env.opts().extensions.builtin_fns.boolean {
+		if env.opts().extensions().builtin_fns.boolean {
 			if let Some(b) = self.as_boolean() {
 				target.write((b | rhs.to_boolean(env)?).into());
 				return Ok(());
 			}
 		}
 
-		// 	#[cfg(feature = "custom-types")]
-		// 	ValueEnum::Custom(custom) => custom.add(rhs, env),
+		#[cfg(feature = "custom-types")]
+		if let Some(custom) = self.as_custom() {
+			target.write(custom.data().add(*rhs, env)?.get());
+			return Ok(());
+		}
 
 		Err(Error::TypeError { type_name: self.type_name(), function: "+" })
 	}
@@ -547,16 +752,22 @@ impl<'gc> Value<'gc> {
 			return Ok(());
 		}
 
+		#[cfg(feature = "custom-types")]
+		if let Some(custom) = self.as_custom() {
+			target.write(custom.data().subtract(*rhs, env)?.get());
+			return Ok(());
+		}
+
 		#[cfg(feature = "extensions")]
 		{
-			if env.opts().extensions.builtin_fns.string {
+			if env.opts().extensions().builtin_fns.string {
 				// return Ok(string.remove_substr(&rhs.to_kstring(env)?).into());
-				todo!()
+				todo_or_error!("subtracting strings")
 			}
 
-			if env.opts().extensions.builtin_fns.list {
+			if env.opts().extensions().builtin_fns.list {
 				// return list.difference(&rhs.to_list(env)?).map(Self::from);
-				todo!()
+				todo_or_error!("subtracting lists")
 			}
 		}
 
@@ -579,32 +790,30 @@ impl<'gc> Value<'gc> {
 			let amount = usize::try_from(rhs.to_integer(env)?.inner())
 				.or(Err(IntegerError::DomainError("repetition count is negative")))?;
 
-			if amount.checked_mul(string.len()).map_or(true, |c| isize::MAX as usize <= c) {
-				return Err(IntegerError::DomainError("repetition is too large").into());
-			}
-
 			let repeated = string.repeat(amount, env.opts(), env.gc())?;
-			unsafe {
-				repeated.with_inner(|inner| target.write(inner.into()));
-			}
+			target.write(repeated.as_value());
 			return Ok(());
 		}
 
 		if let Some(list) = self.as_list() {
 			// Multiplying by a block is invalid, so we can do this as an extension.
 			#[cfg(feature = "extensions")]
-			if env.opts().extensions.builtin_fns.list && rhs.as_block().is_some() {
+			if env.opts().extensions().builtin_fns.list && rhs.as_block().is_some() {
 				// return list.map(rhs, env).map(Self::from);
-				todo!()
+				todo_or_error!("mapping lists")
 			}
 
 			let amount = usize::try_from(rhs.to_integer(env)?.inner())
 				.or(Err(IntegerError::DomainError("repetition count is negative")))?;
 
 			let repeated = list.repeat(amount, env.opts(), env.gc())?;
-			unsafe {
-				repeated.with_inner(|inner| target.write(inner.into()));
-			}
+			target.write(repeated.as_value());
+			return Ok(());
+		}
+
+		#[cfg(feature = "custom-types")]
+		if let Some(custom) = self.as_custom() {
+			target.write(custom.data().multiply(*rhs, env)?.get());
 			return Ok(());
 		}
 
@@ -623,21 +832,27 @@ impl<'gc> Value<'gc> {
 			return Ok(());
 		}
 
+		#[cfg(feature = "custom-types")]
+		if let Some(custom) = self.as_custom() {
+			target.write(custom.data().divide(*rhs, env)?.get());
+			return Ok(());
+		}
+
 		#[cfg(feature = "extensions")]
 		{
-			if env.opts().extensions.builtin_fns.string {
+			if env.opts().extensions().builtin_fns.string {
 				if let Some(string) = self.as_knstring() {
 					let _ = string;
 					// Ok(string.split(&rhs.to_kstring(env)?, env).into())
-					todo!()
+					todo_or_error!("splitting strings")
 				}
 			}
 
-			if env.opts().extensions.builtin_fns.list {
+			if env.opts().extensions().builtin_fns.list {
 				if let Some(list) = self.as_list() {
 					let _ = list;
 					// Ok(list.reduce(rhs, env)?.unwrap_or_default())
-					todo!()
+					todo_or_error!("reducing lists")
 				}
 			}
 		}
@@ -657,15 +872,21 @@ impl<'gc> Value<'gc> {
 			return Ok(());
 		}
 
+		#[cfg(feature = "custom-types")]
+		if let Some(custom) = self.as_custom() {
+			target.write(custom.data().remainder(*rhs, env)?.get());
+			return Ok(());
+		}
+
 		#[cfg(feature = "extensions")]
 		{
 			// TODO: `printf`-style formatting
 
-			if env.opts().extensions.builtin_fns.list {
+			if env.opts().extensions().builtin_fns.list {
 				if let Some(list) = self.as_list() {
 					let _ = list;
 					// list.filter(rhs, env).map(Self::from)
-					todo!()
+					todo_or_error!("filtering lists")
 				}
 			}
 		}
@@ -687,9 +908,13 @@ impl<'gc> Value<'gc> {
 
 		if let Some(list) = self.as_list() {
 			let joined = list.join(&rhs.to_knstring(env)?, env)?;
-			unsafe {
-				joined.with_inner(|inner| target.write(inner.into()));
-			}
+			target.write(joined.as_value());
+			return Ok(());
+		}
+
+		#[cfg(feature = "custom-types")]
+		if let Some(custom) = self.as_custom() {
+			target.write(custom.data().power(*rhs, env)?.get());
 			return Ok(());
 		}
 
@@ -704,9 +929,7 @@ impl<'gc> Value<'gc> {
 	) -> crate::Result<()> {
 		if let Some(string) = self.as_knstring() {
 			let head = string.head(env.gc())?;
-			unsafe {
-				head.with_inner(|inner| target.write(inner.into()));
-			}
+			target.write(head.as_value());
 			return Ok(());
 		}
 
@@ -715,13 +938,19 @@ impl<'gc> Value<'gc> {
 			return Ok(());
 		}
 
+		#[cfg(feature = "custom-types")]
+		if let Some(custom) = self.as_custom() {
+			target.write(custom.data().head(env)?.get());
+			return Ok(());
+		}
+
 		#[cfg(feature = "extensions")]
 		{
-			if env.opts().extensions.builtin_fns.integer {
+			if env.opts().extensions().builtin_fns.integer {
 				if let Some(integer) = self.as_integer() {
 					let _ = integer;
 					// Ok(integer.head().into()),
-					todo!()
+					todo_or_error!("the head of an integer")
 				}
 			}
 		}
@@ -737,27 +966,29 @@ impl<'gc> Value<'gc> {
 	) -> crate::Result<()> {
 		if let Some(string) = self.as_knstring() {
 			let head = string.tail(env.gc())?;
-			unsafe {
-				head.with_inner(|inner| target.write(inner.into()));
-			}
+			target.write(head.as_value());
 			return Ok(());
 		}
 
 		if let Some(list) = self.as_list() {
 			let head = list.tail(env.gc())?;
-			unsafe {
-				head.with_inner(|inner| target.write(inner.into()));
-			}
+			target.write(head.as_value());
+			return Ok(());
+		}
+
+		#[cfg(feature = "custom-types")]
+		if let Some(custom) = self.as_custom() {
+			target.write(custom.data().tail(env)?.get());
 			return Ok(());
 		}
 
 		#[cfg(feature = "extensions")]
 		{
-			if env.opts().extensions.builtin_fns.integer {
+			if env.opts().extensions().builtin_fns.integer {
 				if let Some(integer) = self.as_integer() {
 					let _ = integer;
 					// Ok(integer.tail().into()),
-					todo!()
+					todo_or_error!("the tail of an integer")
 				}
 			}
 		}
@@ -779,9 +1010,7 @@ impl<'gc> Value<'gc> {
 				&env.gc(),
 			);
 
-			unsafe {
-				gcstring.with_inner(|inner| target.write(inner.into()));
-			}
+			target.write(gcstring.as_value());
 			return Ok(());
 		}
 
@@ -790,6 +1019,12 @@ impl<'gc> Value<'gc> {
 			return Ok(());
 		}
 
+		#[cfg(feature = "custom-types")]
+		if let Some(custom) = self.as_custom() {
+			target.write(custom.data().ascii(env)?.get());
+			return Ok(());
+		}
+
 		Err(Error::TypeError { type_name: self.type_name(), function: "ASCII" })
 	}
 
@@ -801,22 +1036,24 @@ impl<'gc> Value<'gc> {
 		target: &mut MaybeUninit<Self>,
 		env: &mut Environment<'gc>,
 	) -> crate::Result<()> {
+		#[cfg(feature = "custom-types")]
+		if let Some(custom) = self.as_custom() {
+			target.write(custom.data().get(*start, *len, env)?.get());
+			return Ok(());
+		}
+
 		let start = fix_len(self, start.to_integer(env)?, "GET", env)?;
 		let len = usize::try_from(len.to_integer(env)?.inner())
 			.or(Err(Error::DomainError("negative length")))?;
 
 		if let Some(list) = self.as_list() {
-			let sublist = list.try_get(start..start + len, env.gc())?;
-			unsafe {
-				sublist.with_inner(|inner| target.write(inner.into()));
-			}
+			let sublist = list.try_get(start, len, env.gc())?;
+			target.write(sublist.as_value());
 			return Ok(());
 		}
 		if let Some(string) = self.as_knstring() {
-			let substring = string.try_get(start..start + len, env.gc())?;
-			unsafe {
-				substring.with_inner(|inner| target.write(inner.into()));
-			}
+			let substring = string.try_get(start, len, env.gc())?;
+			target.write(substring.as_value());
 			return Ok(());
 		}
 
@@ -833,10 +1070,9 @@ impl<'gc> Value<'gc> {
 		env: &mut Environment<'gc>,
 	) -> crate::Result<()> {
 		#[cfg(feature = "custom-types")]
-		{
-			// if let ValueEnum::Custom(custom) = self {
-			// 	return custom.set(start, len, replacement, env);
-			// }
+		if let Some(custom) = self.as_custom() {
+			target.write(custom.data().set(*start, *len, *repl, env)?.get());
+			return Ok(());
 		}
 
 		let start = fix_len(self, start.to_integer(env)?, "SET", env)?;
@@ -845,23 +1081,93 @@ impl<'gc> Value<'gc> {
 
 		if let Some(list) = self.as_list() {
 			let set = list.try_set(start, len, &*repl.to_list(env)?, env.opts(), env.gc())?;
-			unsafe {
-				set.with_inner(|inner| target.write(inner.into()));
-			}
+			target.write(set.as_value());
 			return Ok(());
 		}
 
 		if let Some(string) = self.as_knstring() {
 			let set = string.try_set(start, len, &*repl.to_knstring(env)?, env.opts(), env.gc())?;
-			unsafe {
-				set.with_inner(|inner| target.write(inner.into()));
-			}
+			target.write(set.as_value());
 			return Ok(());
 		}
 
 		Err(Error::TypeError { type_name: self.type_name(), function: "SET" })
 	}
 
+	/// The same as [`Value::kn_set`], except if `is_unique` is set, and `repl` is the same length as
+	/// what it replaces, `self` is modified in place instead of being copied.
+	///
+	/// Returns whether the result is a string or list that nothing else refers to yet, ie whether
+	/// it's safe to pass `is_unique` the next time it's `SET`.
+	///
+	/// # Safety
+	/// The same as [`Value::kn_set`]. Additionally, if `is_unique` is set, nothing else may refer to
+	/// `self`'s contents.
+	///
+	/// # Examples
+	/// ```
+	/// # use knightrs_bytecode::{value::{Integer, KnString, Value}, *};
+	/// # use std::mem::MaybeUninit;
+	/// // SAFETY: nothing allocated by the `gc` escapes it, and nothing else refers to `string`.
+	/// unsafe {
+	///     Gc::default().run(|gc| {
+	///         let mut env = Environment::new(Options::default(), gc);
+	///         let string = KnString::new_unvalidated("hello, world".to_string(), gc);
+	///         let address = string.as_knstr().as_str().as_ptr();
+	///         let start = Value::from(Integer::new_unvalidated(0));
+	///         let len = Value::from(Integer::new_unvalidated(1));
+	///         let repl = KnString::new_unvalidated("J".to_string(), gc);
+	///
+	///         let mut target = MaybeUninit::uninit();
+	///         let is_unique = string
+	///             .as_value()
+	///             .kn_set_in_place(&start, &len, &repl.as_value(), true, &mut target, &mut env)
+	///             .unwrap();
+	///         let result = target.assume_init().as_knstring().unwrap();
+	///
+	///         assert!(is_unique);
+	///         assert_eq!(result.as_knstr().as_str(), "Jello, world");
+	///         assert_eq!(result.as_knstr().as_str().as_ptr(), address);
+	///     })
+	/// }
+	/// ```
+	pub unsafe fn kn_set_in_place(
+		&self,
+		start: &Self,
+		len: &Self,
+		repl: &Self,
+		is_unique: bool,
+		target: &mut MaybeUninit<Self>,
+		env: &mut Environment<'gc>,
+	) -> crate::Result<bool> {
+		if !is_unique || (self.as_list().is_none() && self.as_knstring().is_none()) {
+			unsafe { self.kn_set(start, len, repl, target, env)? };
+			return Ok(self.as_list().is_some() || self.as_knstring().is_some());
+		}
+
+		let start = fix_len(self, start.to_integer(env)?, "SET", env)?;
+		let len = usize::try_from(len.to_integer(env)?.inner())
+			.or(Err(Error::DomainError("negative length")))?;
+
+		if let Some(list) = self.as_list() {
+			let repl = repl.to_list(env)?;
+			if repl.len() == len && unsafe { list.overwrite(start, &repl) } {
+				target.write(*self);
+			} else {
+				target.write(list.try_set(start, len, &repl, env.opts(), env.gc())?.as_value());
+			}
+		} else if let Some(string) = self.as_knstring() {
+			let repl = repl.to_knstring(env)?;
+			if repl.len() == len && unsafe { string.overwrite(start, &repl) } {
+				target.write(*self);
+			} else {
+				target.write(string.try_set(start, len, &repl, env.opts(), env.gc())?.as_value());
+			}
+		}
+
+		Ok(true)
+	}
+
 	const fn repr(&self) -> u64 {
 		// safety: all permutations are valid `u64`s
 		unsafe { self.0.repr }
@@ -875,7 +1181,7 @@ fn fix_len(
 	env: &mut Environment<'_>,
 ) -> crate::Result<usize> {
 	#[cfg(feature = "extensions")]
-	if env.opts().extensions.negative_indexing && start < Integer::ZERO {
+	if env.opts().extensions().negative_indexing && start < Integer::ZERO {
 		let len = if let Some(string) = container.as_knstring() {
 			string.len()
 		} else if let Some(list) = container.as_list() {
@@ -884,7 +1190,7 @@ fn fix_len(
 			return Err(Error::TypeError { type_name: container.type_name(), function });
 		};
 
-		start = start.add(Integer::new_error(len as _, env.opts())?, env.opts())?;
+		start = start.add(Integer::from_usize(len, env.opts())?, env.opts())?;
 	}
 
 	let _ = (container, env);
@@ -923,17 +1229,26 @@ impl ToInteger for Value<'_> {
 			return string.to_integer(env);
 		}
 
+		#[cfg(feature = "custom-types")]
+		if let Some(custom) = self.as_custom() {
+			return custom.data().to_integer(env);
+		}
+
 		#[cfg(feature = "extensions")]
 		{
 			// TODO: check for `float`s
 		}
 
-		debug_assert!(self.as_block().is_some());
+		debug_assert!(self.as_block().is_some() || self.as_native_fn().is_some());
 
 		if self.as_block().is_some() {
 			return Err(crate::Error::Todo("cannot convert Blocks to integers".into()));
 		}
 
+		if self.as_native_fn().is_some() {
+			return Err(crate::Error::Todo("cannot convert NativeFns to integers".into()));
+		}
+
 		unsafe {
 			bug_unchecked!("invalid type for `to_integer()`?? {:?}", self.repr());
 		}
@@ -966,7 +1281,9 @@ impl ToBoolean for Value<'_> {
 			} else if let Some(i) = self.as_integer() {
 				debug_assert_ne!(i, 0, "the `zero` condition should've already been checked");
 			} else {
-				debug_assert!(self.as_block().is_some() /*|| self.as_float().is_some()*/);
+				debug_assert!(
+					self.as_block().is_some() || self.as_native_fn().is_some() /*|| self.as_float().is_some()*/
+				);
 			}
 
 			#[cfg(feature = "compliance")]
@@ -985,6 +1302,11 @@ impl ToBoolean for Value<'_> {
 			return string.to_boolean(env);
 		}
 
+		#[cfg(feature = "custom-types")]
+		if let Some(custom) = self.as_custom() {
+			return custom.data().to_boolean(env);
+		}
+
 		// SAFETY: we've already covered every single type, so there's no reason this should ever
 		// happen.
 		unsafe {
@@ -995,12 +1317,12 @@ impl ToBoolean for Value<'_> {
 
 impl<'gc> ToKnString<'gc> for Value<'gc> {
 	fn to_knstring(&self, env: &mut Environment<'gc>) -> crate::Result<GcRoot<'gc, KnString<'gc>>> {
-		if self.repr() <= knstring::consts::LITERAL_MAX_LENGTH as _ {
-			#[cfg(feature = "compliance")]
-			if env.opts().compliance.no_block_conversions && self.as_block().is_some() {
-				return Err(crate::Error::Todo("cannot convert Blocks to strings".into()));
-			}
-
+		// Blocks and native functions with small enough indices are also below the maximum, and
+		// they're handled at the end.
+		if self.repr() <= knstring::consts::LITERAL_MAX_LENGTH as _
+			&& self.as_block().is_none()
+			&& self.as_native_fn().is_none()
+		{
 			// NOTE: We need to somehow guarantee that we'll never actually pass in pointers
 			// `0b01_000` or `0b10_000`.
 			debug_assert!(
@@ -1026,6 +1348,11 @@ impl<'gc> ToKnString<'gc> for Value<'gc> {
 			return integer.to_knstring(env);
 		}
 
+		#[cfg(feature = "custom-types")]
+		if let Some(custom) = self.as_custom() {
+			return custom.data().to_knstring(env);
+		}
+
 		#[cfg(feature = "extensions")]
 		{
 			// TODO: check for `float`s
@@ -1035,6 +1362,10 @@ impl<'gc> ToKnString<'gc> for Value<'gc> {
 			return Err(crate::Error::Todo("cannot convert Blocks to strings".into()));
 		}
 
+		if self.as_native_fn().is_some() {
+			return Err(crate::Error::Todo("cannot convert NativeFns to strings".into()));
+		}
+
 		unsafe {
 			bug_unchecked!("invalid type for `to_knstring()`?? {:?}", self.repr());
 		}
@@ -1064,33 +1395,121 @@ impl<'gc> ToList<'gc> for Value<'gc> {
 			return Null.to_list(env);
 		}
 
+		#[cfg(feature = "custom-types")]
+		if let Some(custom) = self.as_custom() {
+			return custom.data().to_list(env);
+		}
+
 		// todo: floats
 		if self.as_block().is_some() {
 			return Err(crate::Error::Todo("cannot convert Blocks to lists".into()));
 		}
 
+		if self.as_native_fn().is_some() {
+			return Err(crate::Error::Todo("cannot convert NativeFns to lists".into()));
+		}
+
 		unsafe {
 			bug_unchecked!("invalid type for `to_list()`?? {:?}", self.repr());
 		}
 	}
 }
 
+/// Equality between values, as used by `?`.
+///
+/// This is total over every representation, and never panics:
+/// - `NULL`, booleans, integers, blocks, and native functions are only equal to the exact same
+///   value. (So blocks are equal iff they start at the same jump index.)
+/// - Strings and lists are equal to other strings and lists (respectively) with equal contents.
+/// - Custom types are equal to themselves, and to whatever their `CustomType::eql` says
+///   they're equal to (when comparing two custom types, both have to agree).
+///
+/// Other than custom types, values of different types are never equal.
+///
+/// # Examples
+/// ```
+/// # use knightrs_bytecode::{testing, Options};
+/// // Every pair of values is compared, both directly and when wrapped in lists.
+/// let program = r#"
+/// ; = vals +++++++++++ ,NULL ,TRUE ,FALSE ,0 ,1 ,"" ,"0" ,@ ,,0 ,,1 ,BLOCK 1 ,BLOCK 1
+/// ; = i 0
+/// ; WHILE < i LENGTH vals
+///   ; = j 0
+///   ; = row ""
+///   ; WHILE < j LENGTH vals
+///     ; = row + row IF ? [GET vals i 1 [GET vals j 1 "1" "0"
+///     ; = row + row IF ? GET vals i 1 GET vals j 1 "1" "0"
+///     : = j + j 1
+///   ; OUTPUT row
+///   : = i + i 1
+/// OUTPUT ++ "" (? "ab" + "a" "b") (? +@"ab" +@+ "a" "b")
+/// "#;
+///
+/// let mut expected = String::new();
+/// for i in 0..12 {
+///     for j in 0..12 {
+///         expected.push_str(if i == j { "11" } else { "00" });
+///     }
+///     expected.push('\n');
+/// }
+/// expected.push_str("truetrue\n");
+///
+/// assert_eq!(testing::run(program, "", Options::default()).stdout, expected);
+/// ```
 impl PartialEq for Value<'_> {
 	fn eq(&self, rhs: &Self) -> bool {
+		// Identical representations are the same value. For everything that's not allocated, this is
+		// the only way to be equal.
 		if self.repr() == rhs.repr() {
 			return true;
 		}
 
+		// Custom types decide for themselves what they're equal to, regardless of which side
+		// they're on.
+		#[cfg(feature = "custom-types")]
+		match (self.as_custom(), rhs.as_custom()) {
+			(Some(lhs), Some(rhs)) => return lhs == rhs,
+			(Some(custom), None) => return custom.data().eql(*rhs),
+			(None, Some(custom)) => return custom.data().eql(*self),
+			(None, None) => {}
+		}
+
 		if !self.is_alloc() || !rhs.is_alloc() {
 			return false;
 		}
 
-		if let Some(knstr) = self.as_knstring() {
-			rhs.as_knstring().map_or(false, |r| knstr == r)
+		if let (Some(lhs), Some(rhs)) = (self.as_knstring(), rhs.as_knstring()) {
+			return lhs == rhs;
+		}
+
+		if let (Some(lhs), Some(rhs)) = (self.as_list(), rhs.as_list()) {
+			return lhs == rhs;
+		}
+
+		// Any other allocations are different types.
+		false
+	}
+}
+
+impl Eq for Value<'_> {}
+
+impl Hash for Value<'_> {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		// This has to agree with `PartialEq`, so custom types are hashed however they want, strings
+		// and lists by their contents, and everything else by its representation.
+		#[cfg(feature = "custom-types")]
+		if let Some(custom) = self.as_custom() {
+			custom.hash(state);
+			return;
+		}
+
+		if let Some(string) = self.as_knstring() {
+			string.as_str().hash(state);
 		} else if let Some(list) = self.as_list() {
-			rhs.as_list().map_or(false, |r| list == r)
+			list.len().hash(state);
+			list.iter().for_each(|element| element.hash(state));
 		} else {
-			unreachable!()
+			self.repr().hash(state);
 		}
 	}
 }
@@ -1,9 +1,23 @@
-use crate::program::JumpIndex;
+use crate::parser::{SourceLocation, VariableName};
+use crate::program::{JumpIndex, ProgramId};
+use std::fmt::{self, Debug, Formatter};
 
 use super::NamedType;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Block(JumpIndex);
+/// A block of code, which starts at a [`JumpIndex`] in the [`Program`](crate::program::Program)
+/// that declared it.
+///
+/// Blocks remember which program they're from, as running them in a different one (such as calling
+/// a block from within `EVAL`'d code) would jump into the wrong code.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Block(JumpIndex, ProgramId);
+
+impl Debug for Block {
+	/// Blocks are written as `Block(<offset>)`, where the offset is where the block's code starts.
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		write!(f, "Block({})", (self.0).0)
+	}
+}
 
 impl NamedType for Block {
 	fn type_name(&self) -> &'static str {
@@ -12,11 +26,59 @@ impl NamedType for Block {
 }
 
 impl Block {
-	pub const fn new(idx: JumpIndex) -> Self {
-		Self(idx)
+	/// The largest [`JumpIndex`] a block can start at, as [`Value`](crate::Value)s only have room
+	/// for 32 bits of it.
+	pub const MAX_START: usize = u32::MAX as usize;
+
+	pub(crate) const fn new(idx: JumpIndex, program: ProgramId) -> Self {
+		debug_assert!(idx.0 <= Self::MAX_START);
+		Self(idx, program)
+	}
+
+	/// Creates a new [`Block`] starting at `idx` in `program`, or returns `None` if `idx` is larger
+	/// than [`MAX_START`](Self::MAX_START).
+	pub const fn try_new(idx: JumpIndex, program: ProgramId) -> Option<Self> {
+		if idx.0 <= Self::MAX_START {
+			Some(Self(idx, program))
+		} else {
+			None
+		}
 	}
 
 	pub fn inner(self) -> JumpIndex {
 		self.0
 	}
+
+	/// Gets the id of the program that declared this block.
+	pub fn program(self) -> ProgramId {
+		self.1
+	}
+}
+
+/// Metadata about a [`Block`], as returned by [`Vm::block_info`](crate::vm::Vm::block_info).
+///
+/// Both the name and location are only tracked when the `stacktrace` feature is enabled.
+#[derive(Clone, PartialEq, Eq)]
+pub struct BlockInfo<'src, 'path> {
+	/// The block this is about.
+	pub block: Block,
+
+	/// The name of the block, if it was declared via `= name BLOCK ...` or assigned to a variable.
+	pub name: Option<VariableName<'src>>,
+
+	/// Where the block was declared.
+	pub location: Option<SourceLocation<'path>>,
+}
+
+impl Debug for BlockInfo<'_, '_> {
+	/// Written as `Block(name @ file:line)`, with `<anon>` for blocks without names. If the location
+	/// isn't known, it's the same as [`Block`]'s `Debug`.
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		match (&self.name, &self.location) {
+			(Some(name), Some(location)) => write!(f, "Block({name} @ {location})"),
+			(None, Some(location)) => write!(f, "Block(<anon> @ {location})"),
+			(Some(name), None) => write!(f, "Block({name})"),
+			(None, None) => Debug::fmt(&self.block, f),
+		}
+	}
 }
@@ -0,0 +1,282 @@
+use crate::gc::{self, AsValueInner, GarbageCollected, Gc, GcRoot, ValueInner, ValueRoot};
+use crate::value::{Boolean, Integer, KnString, List, NamedType, Value};
+use crate::{Environment, Error};
+use std::cmp::Ordering;
+use std::fmt::{self, Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::mem::{size_of, ManuallyDrop, MaybeUninit};
+use std::sync::atomic::AtomicU8;
+
+use super::{ValueAlign, ALLOC_VALUE_SIZE_IN_BYTES};
+
+/// A garbage collected value whose behaviour is defined by a [`CustomType`].
+///
+/// This is how embedders expose their own types to Knight programs. They're created with
+/// [`Custom::new`], and can be handed to programs either via [`NativeFn`](super::NativeFn)s or
+/// via [parse functions](Environment::add_parse_fn).
+#[repr(transparent)]
+pub struct Custom<'gc>(*const Inner<'gc>);
+
+/// Trait for custom types.
+///
+/// Every function has a default implementation (generally returning an [`Error::TypeError`] or an
+/// [`Error::ConversionNotDefined`]), which can be overridden to provide actual implementations.
+///
+/// # Garbage collection
+/// Custom types are garbage collected along with everything else, and are dropped when they're
+/// collected. Any [`Value`]s a custom type holds on to must be reported by
+/// [`CustomType::for_each_value`], otherwise they may be collected out from under it.
+///
+/// The values returned from functions are [`ValueRoot`]s, so they don't get collected before the
+/// caller gets a chance to use them.
+#[allow(unused_variables)] // all the `env`s.
+pub trait CustomType<'gc>: Debug + 'gc {
+	/// Returns the name of this type.
+	fn type_name(&self) -> &'static str {
+		std::any::type_name::<Self>()
+	}
+
+	/// Calls `func` with every [`Value`] `self` references, so the garbage collector knows they're
+	/// still in use.
+	fn for_each_value(&self, func: &mut dyn FnMut(Value<'gc>)) {}
+
+	/// Called when `self` is passed to `CALL`.
+	fn call(&self, env: &mut Environment<'gc>) -> crate::Result<ValueRoot<'gc>> {
+		Err(Error::TypeError { type_name: self.type_name(), function: "CALL" })
+	}
+
+	/// Converts `self` to a [`Boolean`].
+	fn to_boolean(&self, env: &mut Environment<'_>) -> crate::Result<Boolean> {
+		Err(Error::ConversionNotDefined { to: "Boolean", from: self.type_name() })
+	}
+
+	/// Converts `self` to an [`Integer`].
+	fn to_integer(&self, env: &mut Environment<'_>) -> crate::Result<Integer> {
+		Err(Error::ConversionNotDefined { to: "Integer", from: self.type_name() })
+	}
+
+	/// Converts `self` to a [`KnString`].
+	fn to_knstring(&self, env: &mut Environment<'gc>) -> crate::Result<GcRoot<'gc, KnString<'gc>>> {
+		Err(Error::ConversionNotDefined { to: "String", from: self.type_name() })
+	}
+
+	/// Converts `self` to a [`List`].
+	fn to_list(&self, env: &mut Environment<'gc>) -> crate::Result<GcRoot<'gc, List<'gc>>> {
+		Err(Error::ConversionNotDefined { to: "List", from: self.type_name() })
+	}
+
+	/// Writes the debug representation of `self` to `out`, as per `DUMP`.
+	fn dump(&self, out: &mut dyn std::io::Write) -> std::io::Result<()> {
+		write!(out, "{self:?}")
+	}
+
+	/// Gets the length of `self`; the default converts `self` to a list and gets its length.
+	fn length(&self, env: &mut Environment<'gc>) -> crate::Result<Integer> {
+		let list = self.to_list(env)?;
+		Ok(Integer::from_usize(list.len(), env.opts())?)
+	}
+
+	/// Gets the first element of `self`, as per `[`.
+	fn head(&self, env: &mut Environment<'gc>) -> crate::Result<ValueRoot<'gc>> {
+		Err(Error::TypeError { type_name: self.type_name(), function: "[" })
+	}
+
+	/// Gets everything but the first element of `self`, as per `]`.
+	fn tail(&self, env: &mut Environment<'gc>) -> crate::Result<ValueRoot<'gc>> {
+		Err(Error::TypeError { type_name: self.type_name(), function: "]" })
+	}
+
+	/// Called when `self` is passed to `ASCII`.
+	fn ascii(&self, env: &mut Environment<'gc>) -> crate::Result<ValueRoot<'gc>> {
+		Err(Error::TypeError { type_name: self.type_name(), function: "ASCII" })
+	}
+
+	/// Called when `self` is the first argument to `+`.
+	fn add(&self, rhs: Value<'gc>, env: &mut Environment<'gc>) -> crate::Result<ValueRoot<'gc>> {
+		Err(Error::TypeError { type_name: self.type_name(), function: "+" })
+	}
+
+	/// Called when `self` is the first argument to `-`.
+	fn subtract(
+		&self,
+		rhs: Value<'gc>,
+		env: &mut Environment<'gc>,
+	) -> crate::Result<ValueRoot<'gc>> {
+		Err(Error::TypeError { type_name: self.type_name(), function: "-" })
+	}
+
+	/// Called when `self` is the first argument to `*`.
+	fn multiply(
+		&self,
+		rhs: Value<'gc>,
+		env: &mut Environment<'gc>,
+	) -> crate::Result<ValueRoot<'gc>> {
+		Err(Error::TypeError { type_name: self.type_name(), function: "*" })
+	}
+
+	/// Called when `self` is the first argument to `/`.
+	fn divide(&self, rhs: Value<'gc>, env: &mut Environment<'gc>) -> crate::Result<ValueRoot<'gc>> {
+		Err(Error::TypeError { type_name: self.type_name(), function: "/" })
+	}
+
+	/// Called when `self` is the first argument to `%`.
+	fn remainder(
+		&self,
+		rhs: Value<'gc>,
+		env: &mut Environment<'gc>,
+	) -> crate::Result<ValueRoot<'gc>> {
+		Err(Error::TypeError { type_name: self.type_name(), function: "%" })
+	}
+
+	/// Called when `self` is the first argument to `^`.
+	fn power(&self, rhs: Value<'gc>, env: &mut Environment<'gc>) -> crate::Result<ValueRoot<'gc>> {
+		Err(Error::TypeError { type_name: self.type_name(), function: "^" })
+	}
+
+	/// Compares `self` with `rhs`, as per `<`, `>`, and `XCMP`.
+	fn compare(&self, rhs: Value<'gc>, env: &mut Environment<'gc>) -> crate::Result<Ordering> {
+		Err(Error::TypeError { type_name: self.type_name(), function: "<cmp>" })
+	}
+
+	/// Checks to see if `self` is equal to `rhs`, as per `?` (and [`Value`]'s [`PartialEq`]).
+	///
+	/// This is only called when `rhs` isn't the exact same [`Custom`] as `self`, as identical values
+	/// are always equal. It's called regardless of which side of the comparison `self` is on, and if
+	/// `rhs` is also a custom type, both of their `eql`s have to return `true`, so that equality is
+	/// symmetric. The default returns `false`.
+	fn eql(&self, rhs: Value<'gc>) -> bool {
+		false
+	}
+
+	/// Hashes `self`, as per [`Value`]'s [`Hash`].
+	///
+	/// Everything that `self` is [`eql`](Self::eql) to must hash the same as `self` does. The
+	/// default hashes the address of `self`, which is only suitable if `eql` isn't overridden.
+	fn hash(&self, state: &mut dyn Hasher) {
+		state.write_usize(self as *const Self as *const u8 as usize);
+	}
+
+	/// Called when `self` is the first argument to `GET`.
+	fn get(
+		&self,
+		start: Value<'gc>,
+		len: Value<'gc>,
+		env: &mut Environment<'gc>,
+	) -> crate::Result<ValueRoot<'gc>> {
+		Err(Error::TypeError { type_name: self.type_name(), function: "GET" })
+	}
+
+	/// Called when `self` is the first argument to `SET`.
+	fn set(
+		&self,
+		start: Value<'gc>,
+		len: Value<'gc>,
+		replacement: Value<'gc>,
+		env: &mut Environment<'gc>,
+	) -> crate::Result<ValueRoot<'gc>> {
+		Err(Error::TypeError { type_name: self.type_name(), function: "SET" })
+	}
+}
+
+#[repr(C)]
+struct Inner<'gc> {
+	_alignment: ValueAlign,
+	flags: AtomicU8,
+	_align: MaybeUninit<[u8; 7]>, // TODO: don't use a constant
+	data: ManuallyDrop<Box<dyn CustomType<'gc>>>,
+	_padding: MaybeUninit<[u8; PADDING_LEN]>,
+}
+
+// The amount of bytes left over after the flags (and their alignment) and the data.
+const PADDING_LEN: usize = ALLOC_VALUE_SIZE_IN_BYTES - 8 - size_of::<Box<dyn CustomType>>();
+
+sa::assert_eq_align!(crate::gc::ValueInner, Inner);
+sa::assert_eq_size!(crate::gc::ValueInner, Inner);
+sa::assert_eq_size!(Custom, super::Value);
+
+impl Clone for Custom<'_> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+impl Copy for Custom<'_> {}
+
+impl Eq for Custom<'_> {}
+impl PartialEq for Custom<'_> {
+	/// Customs are equal to themselves, and to each other if both their [`CustomType::eql`]s agree.
+	fn eq(&self, rhs: &Self) -> bool {
+		std::ptr::eq(self.0, rhs.0)
+			|| (self.data().eql(Value::from(*rhs)) && rhs.data().eql(Value::from(*self)))
+	}
+}
+
+impl Hash for Custom<'_> {
+	fn hash<H: Hasher>(&self, mut state: &mut H) {
+		self.data().hash(&mut state);
+	}
+}
+
+impl<'gc> Custom<'gc> {
+	/// Creates a new [`Custom`] from the given `data`.
+	pub fn new(data: impl CustomType<'gc>, gc: &'gc Gc) -> GcRoot<'gc, Self> {
+		// SAFETY: `FLAG_IS_CUSTOM` is a valid set of flags.
+		let inner = unsafe { gc.alloc_value_inner(gc::FLAG_IS_CUSTOM) }.cast::<Inner<'gc>>();
+
+		// SAFETY: `alloc_value_inner` guarantees it'll be aligned and non-null
+		unsafe {
+			(&raw mut (*inner).data).write(ManuallyDrop::new(Box::new(data)));
+		}
+
+		GcRoot::new(&Self(inner), gc)
+	}
+
+	pub(super) fn into_raw(self) -> *const ValueInner {
+		self.0.cast()
+	}
+
+	pub(crate) unsafe fn from_raw(raw: *const ValueInner) -> Self {
+		Self(raw.cast())
+	}
+
+	/// Gets the underlying [`CustomType`].
+	pub fn data(&self) -> &(dyn CustomType<'gc> + 'gc) {
+		// SAFETY: `self.0` is always initialized when constructed via `Custom::new`.
+		unsafe { &**(*self.0).data }
+	}
+}
+
+impl Debug for Custom<'_> {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		Debug::fmt(self.data(), f)
+	}
+}
+
+impl NamedType for Custom<'_> {
+	#[inline]
+	fn type_name(&self) -> &'static str {
+		self.data().type_name()
+	}
+}
+
+unsafe impl GarbageCollected for Custom<'_> {
+	unsafe fn mark(&self) {
+		self.data().for_each_value(&mut |value| unsafe { value.mark() });
+	}
+
+	unsafe fn deallocate(self) {
+		// SAFETY: This is only called once, when `self` is being freed.
+		unsafe {
+			ManuallyDrop::drop(&mut (*self.0.cast_mut()).data);
+		}
+	}
+}
+
+unsafe impl<'gc> AsValueInner for Custom<'gc> {
+	fn as_value_inner(&self) -> *const ValueInner {
+		self.0.cast()
+	}
+
+	unsafe fn from_value_inner(inner: *const ValueInner) -> Self {
+		unsafe { Self::from_raw(inner) }
+	}
+}
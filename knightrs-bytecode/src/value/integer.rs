@@ -2,7 +2,7 @@ use crate::gc::GcRoot;
 use crate::parser::{ParseError, ParseErrorKind, Parseable, Parser};
 use crate::program::{Compilable, Compiler};
 use crate::strings::{Character, Encoding};
-use crate::value::{Boolean, KnString, List, NamedType, ToBoolean, ToKnString, ToList};
+use crate::value::{Boolean, KnString, List, NamedType, ToBoolean, ToKnString, ToList, Value};
 use crate::{Environment, Options};
 use std::fmt::{self, Debug, Display, Formatter};
 
@@ -57,6 +57,14 @@ impl PartialOrd<IntegerInner> for Integer {
 	}
 }
 
+impl From<std::cmp::Ordering> for Integer {
+	/// Converts `ordering` to `-1`, `0`, or `1`, as per `XCMP`.
+	#[inline]
+	fn from(ordering: std::cmp::Ordering) -> Self {
+		Self(ordering as IntegerInner)
+	}
+}
+
 /// Problems that can occur when performing operations on integers.
 #[derive(Error, Debug)]
 pub enum IntegerError {
@@ -70,14 +78,20 @@ pub enum IntegerError {
 	#[error("method {0:?} overflowed the bounds")]
 	MethodOverflow(char),
 
-	/// Division/Remainder/Power by zero.
-	#[error("{0}")]
-	DivisionByZero(ZeroDivisionKind),
+	/// Division/Remainder/Power by zero. `operand` is the other argument: the dividend for `/` and
+	/// `%`, and the exponent for `^`.
+	#[error("{kind}: {}", kind.expression(*operand))]
+	DivisionByZero { kind: ZeroDivisionKind, operand: Integer },
 
 	/// The arguments passed to a function are outside of its expected domain.
 	#[error("domain error: {0}")]
 	DomainError(&'static str),
 
+	/// A string being converted to an integer was out of bounds, and overflowing is an error. See
+	/// [`Integer::parse_from_str`].
+	#[error("integer in string is out of bounds")]
+	StringOutOfBounds,
+
 	/// Means `chr` was called on an int and it's not valid for an encoding.
 	#[error("integer {0:?} isn't a valid char for {1:?}")]
 	NotAValidChar(Integer, Encoding),
@@ -94,6 +108,17 @@ pub enum ZeroDivisionKind {
 	Power,
 }
 
+impl ZeroDivisionKind {
+	// The expression that caused the error, eg `5 / 0`.
+	fn expression(self, operand: Integer) -> String {
+		match self {
+			Self::Divide => format!("{operand} / 0"),
+			Self::Remainder => format!("{operand} % 0"),
+			Self::Power => format!("0 ^ {operand}"),
+		}
+	}
+}
+
 impl Display for ZeroDivisionKind {
 	#[inline]
 	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
@@ -112,6 +137,9 @@ impl Integer {
 	/// The value one.
 	pub const ONE: Self = Self(1);
 
+	/// The most digits an integer can have, as returned by [`Integer::number_of_digits`].
+	pub const MAX_DIGITS: usize = IntegerInner::MAX.ilog10() as usize + 1;
+
 	/// Returns the value contained within the integer.
 	pub const fn inner(self) -> IntegerInner {
 		self.0
@@ -165,6 +193,19 @@ impl Integer {
 		Some(Self(int))
 	}
 
+	/// Converts a length (or any other `usize`) to an [`Integer`], with the given options.
+	///
+	/// # Errors
+	/// Returns [`IntegerError::DomainError`] if `len` is larger than [`Integer::max`], which can
+	/// happen with `compliance.i32_integer`, or on platforms where `usize` is larger than `i64`.
+	pub fn from_usize(len: usize, opts: &Options) -> Result<Self, IntegerError> {
+		IntegerInner::try_from(len)
+			.ok()
+			.filter(|&int| int <= Self::max(opts).0)
+			.map(Self)
+			.ok_or(IntegerError::DomainError("length doesn't fit in an integer"))
+	}
+
 	/// Returns the maximum value for [`Integer`]s given `opts`.
 	#[cfg_attr(not(feature = "compliance"), inline)]
 	pub fn max(opts: &Options) -> Self {
@@ -187,6 +228,36 @@ impl Integer {
 		Self(IntegerInner::MIN >> super::TAG_INT_SHIFT)
 	}
 
+	/// Gets a random [`Integer`] from `rng`.
+	///
+	/// Normally, this is within `0..=Integer::max`. If [`Compliance::limit_rand_range`] is enabled,
+	/// it's instead within `0..=0x7FFF`; otherwise, if [`BreakingChanges::random_can_be_negative`]
+	/// is enabled, it's within `Integer::min..=Integer::max`. (`Integer::min` and `Integer::max`
+	/// respect [`Compliance::i32_integer`].)
+	///
+	/// These are the same ranges the `knightrs` crate uses for the equivalent flags, and values are
+	/// drawn the same way, so both produce the same distribution (and, when `i32_integer` or
+	/// `limit_rand_range` is on, the same sequence for the same seed).
+	///
+	/// [`Compliance::limit_rand_range`]: crate::options::Compliance::limit_rand_range
+	/// [`Compliance::i32_integer`]: crate::options::Compliance::i32_integer
+	/// [`BreakingChanges::random_can_be_negative`]: crate::options::BreakingChanges::random_can_be_negative
+	pub fn random<R: rand::Rng + ?Sized>(rng: &mut R, opts: &Options) -> Self {
+		let (min, max) = match () {
+			// `limit_rand_range` takes precedence, as it's required for spec compliance.
+			#[cfg(feature = "compliance")]
+			_ if opts.compliance.limit_rand_range => (0, 0x7FFF),
+
+			#[cfg(feature = "extensions")]
+			_ if opts.extensions().breaking.random_can_be_negative => (Self::min(opts).0, Self::max(opts).0),
+
+			_ => (0, Self::max(opts).0),
+		};
+
+		// The bounds are always valid integers for `opts`, so this doesn't need validating.
+		Self(rng.gen_range(min..=max))
+	}
+
 	/// Negates `self`, wrapping unless `opts.compliance.check_overflow` is on.
 	///
 	/// # Errors
@@ -270,7 +341,10 @@ impl Integer {
 	/// If `opts.compliance.check_overflow` is on, overflows yield [`IntegerError::MethodOverflow`].
 	pub fn divide(self, divisor: Self, opts: &Options) -> Result<Self, IntegerError> {
 		if divisor == 0 {
-			return Err(IntegerError::DivisionByZero(ZeroDivisionKind::Divide));
+			return Err(IntegerError::DivisionByZero {
+				kind: ZeroDivisionKind::Divide,
+				operand: self,
+			});
 		}
 
 		self.binary_op(divisor.0, opts, '/', i64::checked_div, i64::wrapping_div)
@@ -285,9 +359,58 @@ impl Integer {
 	/// when either `self` or `base` are negative.
 	///
 	/// If `opts.compliance.check_overflow` is on, overflows yield [`IntegerError::MethodOverflow`].
+	///
+	/// # Negative operands
+	/// By default, the remainder is truncated (ie it has the same sign as `self`, like Rust's `%`).
+	/// If [`BreakingChanges::euclidean_remainder`] is enabled, the Euclidean remainder is used
+	/// instead, which is never negative. Both agree when neither operand is negative, which is the
+	/// only case the Knight spec defines.
+	///
+	/// # Examples
+	/// ```
+	/// # use knightrs_bytecode::{value::Integer, Options};
+	/// let remainder = |lhs: i64, rhs: i64, opts: &Options| {
+	///     let (lhs, rhs) = (Integer::new_unvalidated(lhs), Integer::new_unvalidated(rhs));
+	///     lhs.remainder(rhs, opts).ok().map(Integer::inner)
+	/// };
+	///
+	/// let mut opts = Options::default();
+	/// for (lhs, rhs, expected) in [(7, 3, 1), (7, -3, 1), (-7, 3, -1), (-7, -3, -1), (-6, 3, 0)] {
+	///     assert_eq!(remainder(lhs, rhs, &opts), Some(expected), "{lhs} % {rhs}");
+	/// }
+	/// assert_eq!(remainder(7, 0, &opts), None);
+	/// assert_eq!(remainder(-7, 0, &opts), None);
+	///
+	/// // Euclidean remainders are never negative.
+	/// #[cfg(feature = "extensions")]
+	/// {
+	///     let mut opts = opts.clone();
+	///     opts.extensions.breaking.euclidean_remainder = true;
+	///     let euclidean = [(7, 3, 1), (7, -3, 1), (-7, 3, 2), (-7, -3, 2), (-6, 3, 0)];
+	///     for (lhs, rhs, expected) in euclidean {
+	///         assert_eq!(remainder(lhs, rhs, &opts), Some(expected), "{lhs} % {rhs}");
+	///     }
+	///     assert_eq!(remainder(-7, 0, &opts), None);
+	/// }
+	///
+	/// // Only nonnegative operands are allowed when checking bounds.
+	/// #[cfg(feature = "compliance")]
+	/// {
+	///     opts.compliance.check_integer_function_bounds = true;
+	///     assert_eq!(remainder(7, 3, &opts), Some(1));
+	///     assert_eq!(remainder(-7, 3, &opts), None);
+	///     assert_eq!(remainder(7, -3, &opts), None);
+	///     assert_eq!(remainder(-7, -3, &opts), None);
+	/// }
+	/// ```
+	///
+	/// [`BreakingChanges::euclidean_remainder`]: crate::options::BreakingChanges::euclidean_remainder
 	pub fn remainder(self, base: Self, opts: &Options) -> Result<Self, IntegerError> {
 		if base == 0 {
-			return Err(IntegerError::DivisionByZero(ZeroDivisionKind::Remainder));
+			return Err(IntegerError::DivisionByZero {
+				kind: ZeroDivisionKind::Remainder,
+				operand: self,
+			});
 		}
 
 		#[cfg(feature = "compliance")]
@@ -301,20 +424,39 @@ impl Integer {
 			}
 		}
 
+		#[cfg(feature = "extensions")]
+		if opts.extensions().breaking.euclidean_remainder {
+			return self.binary_op(
+				base.0,
+				opts,
+				'%',
+				i64::checked_rem_euclid,
+				i64::wrapping_rem_euclid,
+			);
+		}
+
 		self.binary_op(base.0, opts, '%', i64::checked_rem, i64::wrapping_rem)
 	}
 
 	/// Raises `self` to `exponent`, wrapping unless `opts.compliance.check_overflow` is on.
 	///
+	/// Negative exponents are computed as `1 / (self ^ -exponent)`, truncated towards zero, so:
+	/// - `1` to any power is `1`,
+	/// - `-1` to any power is `1` if the exponent is even, and `-1` if it's odd,
+	/// - `0` to a negative power is a division by zero, and
+	/// - everything else to a negative power is `0`.
+	///
+	/// Anything to the zeroth power (including `0`) is `1`.
+	///
 	/// # Errors
 	/// If the exponent is negative and `compliance.check_integer_function_bounds` is enabled, then
-	/// an [`Error::DomainError`] is returned.
+	/// an [`IntegerError::DomainError`] is returned.
 	///
-	/// If the exponent is negative, `compliance.check_integer_function_bounds` isn't enabled, and
-	/// `self` is zero, an [`Error::DivisionByZero`] is returned.
+	/// If the exponent is negative and `self` is zero, an [`IntegerError::DivisionByZero`] is
+	/// returned.
 	///
-	/// If `self` is not zero or one, `compliance.check_integer_function_bounds` is enabled, and the
-	/// exponent is larger than an [`u32`], then an [`Error::DomainError`] is returned.
+	/// If the exponent is larger than a [`u32`] and `self` isn't `-1`, `0`, or `1` (ie the result
+	/// is guaranteed to overflow), an [`IntegerError::DomainError`] is returned.
 	///
 	/// If `opts.compliance.check_overflow` is on, overflows yield [`IntegerError::MethodOverflow`].
 	pub fn power(self, exponent: Self, opts: &Options) -> Result<Self, IntegerError> {
@@ -330,8 +472,11 @@ impl Integer {
 				}
 
 				// Special cases for negative exponents of -1, 0, and 1.
-				-1 => Ok(if exponent.0 % 2 == 0 { self } else { Self::ONE }),
-				0 => Err(IntegerError::DivisionByZero(ZeroDivisionKind::Power)),
+				-1 => Ok(if exponent.0 % 2 == 0 { Self::ONE } else { self }),
+				0 => Err(IntegerError::DivisionByZero {
+					kind: ZeroDivisionKind::Power,
+					operand: exponent,
+				}),
 				1 => Ok(Self::ONE),
 
 				// Otherwise, return 0, as everything else is below zero.
@@ -346,9 +491,10 @@ impl Integer {
 				// If the exponent could fit in a `u32`, then perform the normal operation
 				Ok(exp) => self.binary_op(exp, opts, '^', i64::checked_pow, i64::wrapping_pow),
 
-				// It was too large to fit in a `u32`; special-case `0` and `1` which won't overflow
+				// It was too large to fit in a `u32`; special-case `-1`, `0` and `1` which won't
+				// overflow.
 				Err(_) => match self.inner() {
-					// 0 to massive integers is zero
+					-1 => Ok(if exponent.0 % 2 == 0 { Self::ONE } else { self }),
 					0 | 1 => Ok(self),
 
 					// Anything else means the exponent was far too large.
@@ -374,41 +520,169 @@ impl Integer {
 		u32::try_from(self.0)
 			.ok()
 			.and_then(char::from_u32)
-			.and_then(|chr| Character::new(chr, &opts.encoding))
+			.and_then(|chr| Character::new(chr, opts.encoding))
 			.ok_or(IntegerError::NotAValidChar(self, opts.encoding))
 	}
 
+	/// Bitwise ANDs `self` and `rhs`, as per `XBAND`.
+	#[cfg(feature = "extensions")]
+	pub fn bitwise_and(self, rhs: Self) -> Self {
+		// Bitwise operations of two in-bounds integers are always in bounds.
+		Self::new_unvalidated_unchecked(self.0 & rhs.0)
+	}
+
+	/// Bitwise ORs `self` and `rhs`, as per `XBOR`.
+	#[cfg(feature = "extensions")]
+	pub fn bitwise_or(self, rhs: Self) -> Self {
+		Self::new_unvalidated_unchecked(self.0 | rhs.0)
+	}
+
+	/// Bitwise XORs `self` and `rhs`, as per `XBXOR`.
+	#[cfg(feature = "extensions")]
+	pub fn bitwise_xor(self, rhs: Self) -> Self {
+		Self::new_unvalidated_unchecked(self.0 ^ rhs.0)
+	}
+
+	/// Validates the amount to shift by for [`Integer::shift_left`] and [`Integer::shift_right`].
+	#[cfg(feature = "extensions")]
+	fn shift_amount(
+		amount: Self,
+		#[cfg_attr(not(feature = "compliance"), allow(unused_variables))] opts: &Options,
+	) -> Result<u32, IntegerError> {
+		#[cfg(feature = "compliance")]
+		if opts.compliance.check_integer_function_bounds {
+			let bits = if opts.compliance.i32_integer { i32::BITS } else { IntegerInner::BITS };
+
+			if !(0..bits as IntegerInner).contains(&amount.0) {
+				return Err(IntegerError::DomainError("shift amount out of bounds"));
+			}
+		}
+
+		u32::try_from(amount.0).or(Err(IntegerError::DomainError("negative shift amount")))
+	}
+
+	/// Shifts `self` left by `amount` bits, as per `XSHL`.
+	///
+	/// # Errors
+	/// A [`IntegerError::DomainError`] is returned if `amount` is negative, or, when
+	/// `compliance.check_integer_function_bounds` is enabled, if `amount` is at least the amount of
+	/// bits in an integer.
+	///
+	/// If `opts.compliance.check_overflow` is on, shifting bits out yields
+	/// [`IntegerError::MethodOverflow`].
+	#[cfg(feature = "extensions")]
+	pub fn shift_left(self, amount: Self, opts: &Options) -> Result<Self, IntegerError> {
+		let amount = Self::shift_amount(amount, opts)?;
+
+		self.binary_op(
+			amount,
+			opts,
+			'X',
+			|int, amount| int.checked_shl(amount).filter(|shifted| shifted >> amount == int),
+			IntegerInner::wrapping_shl,
+		)
+	}
+
+	/// Arithmetically shifts `self` right by `amount` bits, as per `XSHR`.
+	///
+	/// # Errors
+	/// The same as [`Integer::shift_left`], except shifting right never overflows.
+	#[cfg(feature = "extensions")]
+	pub fn shift_right(self, amount: Self, opts: &Options) -> Result<Self, IntegerError> {
+		let amount = Self::shift_amount(amount, opts)?;
+
+		self.binary_op(amount, opts, 'X', IntegerInner::checked_shr, IntegerInner::wrapping_shr)
+	}
+
+	/// Gets the radix corresponding to `base`, which must be within `2..=36`.
+	#[cfg(feature = "extensions")]
+	fn radix(base: Self) -> Result<u32, IntegerError> {
+		match base.0 {
+			2..=36 => Ok(base.0 as u32),
+			_ => Err(IntegerError::DomainError("base must be within 2..=36")),
+		}
+	}
+
+	/// Converts `self` to a string of digits in `base`, as per `XBASE`.
+	///
+	/// Digits above `9` are written as lowercase letters, and negative numbers start with `-`.
+	///
+	/// # Errors
+	/// If `base` isn't within `2..=36`, a [`IntegerError::DomainError`] is returned.
+	#[cfg(feature = "extensions")]
+	pub fn to_base(self, base: Self) -> Result<String, IntegerError> {
+		let radix = Self::radix(base)?;
+		let mut magnitude = self.0.unsigned_abs();
+		let mut digits = Vec::new();
+
+		loop {
+			digits.push(char::from_digit((magnitude % radix as u64) as u32, radix).unwrap());
+			magnitude /= radix as u64;
+
+			if magnitude == 0 {
+				break;
+			}
+		}
+
+		if self.0 < 0 {
+			digits.push('-');
+		}
+
+		Ok(digits.into_iter().rev().collect())
+	}
+
+	/// Parses `source` as digits in `base`, as per `XFROMBASE`.
+	///
+	/// Leading and trailing whitespace is ignored, and an optional leading `+` or `-` is allowed.
+	///
+	/// # Errors
+	/// If `base` isn't within `2..=36`, `source` contains invalid digits, or the result doesn't fit
+	/// within an `Integer` (which depends on `compliance.i32_integer`), then a
+	/// [`IntegerError::DomainError`] is returned.
+	#[cfg(feature = "extensions")]
+	pub fn from_base(source: &str, base: Self, opts: &Options) -> Result<Self, IntegerError> {
+		use std::num::IntErrorKind;
+
+		let radix = Self::radix(base)?;
+		let int = IntegerInner::from_str_radix(source.trim(), radix).map_err(|err| match err.kind() {
+			IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => {
+				IntegerError::DomainError("XFROMBASE: result is out of bounds")
+			}
+			_ => IntegerError::DomainError("XFROMBASE: invalid digits for base"),
+		})?;
+
+		Self::new(int, opts).ok_or(IntegerError::DomainError("XFROMBASE: result is out of bounds"))
+	}
+
 	/// Parses out an integer from `source` according to the Knight specifications for string ->
 	/// integer conversions.
 	///
-	/// This could certainly be cleaned up.
+	/// See [`knight_strings::parse_integer`] for the exact rules. Integers which are out of bounds are
+	/// handled according to [`Options::string_to_integer_overflow`], except that they're always an
+	/// error when [`check_overflow`](crate::options::Compliance::check_overflow) is enabled.
+	///
+	/// # Errors
+	/// If the integer is out of bounds and overflowing is an error, an
+	/// [`IntegerError::StringOutOfBounds`] is returned.
 	pub fn parse_from_str(source: &str, opts: &Options) -> crate::Result<Self> {
-		// This could definitely be cleaned up
-		let source = source.trim_start();
-
-		let mut chars = source.chars();
-		let mut start = match chars.next() {
-			None => return Ok(Self::ZERO),
-			Some('+' | '-') => chars.as_str(),
-			_ => source,
-		};
+		#[cfg_attr(not(feature = "compliance"), allow(unused_mut))]
+		let (mut bits, mut overflow) =
+			(IntegerInner::BITS - super::TAG_INT_SHIFT as u32, opts.string_to_integer_overflow);
 
-		if let Some(bad) = start.find(|c: char| !c.is_ascii_digit()) {
-			start = &source[..bad + (start != source) as usize];
-		} else if start != source {
-			start = source;
-		}
+		#[cfg(feature = "compliance")]
+		{
+			if opts.compliance.i32_integer {
+				bits = i32::BITS;
+			}
 
-		match <IntegerInner as std::str::FromStr>::from_str(start) {
-			Ok(value) => Ok(Self::new_error(value, opts)?),
-			Err(err) => match err.kind() {
-				std::num::IntErrorKind::Empty | std::num::IntErrorKind::InvalidDigit => Ok(Self::ZERO),
-				std::num::IntErrorKind::PosOverflow | std::num::IntErrorKind::NegOverflow => {
-					todo!("integer overflow error")
-				}
-				other => todo!("{:?}", other),
-			},
+			if opts.compliance.check_overflow {
+				overflow = knight_strings::IntegerOverflow::Error;
+			}
 		}
+
+		knight_strings::parse_integer(source, bits, overflow)
+			.map(Self)
+			.map_err(|_| IntegerError::StringOutOfBounds.into())
 	}
 }
 
@@ -422,9 +696,10 @@ impl<'path> Parseable<'_, 'path, '_> for Integer {
 			return Ok(None);
 		};
 
-		#[cfg(feature = "extensions")]
+		#[cfg(feature = "floats")]
 		if parser.peek().map_or(false, |c| c == '.') {
-			todo!("float extensions. (really this should be its own `Parseable`");
+			// TODO: really this should be its own `Parseable`
+			todo_or_error!(parse(parser), "float literals");
 		}
 
 		digits
@@ -467,6 +742,13 @@ impl<'gc> ToKnString<'gc> for Integer {
 	/// Returns whether `self` is nonzero.
 	#[inline]
 	fn to_knstring(&self, env: &mut Environment<'gc>) -> crate::Result<GcRoot<'gc, KnString<'gc>>> {
+		#[cfg(feature = "qol")]
+		if env.opts().qol.cache_small_integers {
+			if let Some(string) = crate::value::knstring::consts::small_integer(self.0) {
+				return Ok(GcRoot::new_unchecked(string));
+			}
+		}
+
 		// COMPLIANCE: `Integer#to_string` yields just an optional leading `-` followed by digits,
 		// which is valid in all encodings. Additionally, it's nowhere near the maximum length for a
 		// string.
@@ -485,16 +767,20 @@ impl<'gc> ToList<'gc> for Integer {
 			return Ok(List::boxed((*self).into(), env.gc()));
 		}
 
+		// The digits are written to the end of a buffer on the stack (as they're found in reverse
+		// order), so the list can be created in one go.
+		let mut digits = [Value::NULL; Self::MAX_DIGITS];
+		let mut start = digits.len();
 		let mut integer = self.0;
-		let mut digits = Vec::with_capacity(self.number_of_digits());
 
 		while integer != 0 {
-			digits.insert(0, Self(integer % 10).into());
+			start -= 1;
+			digits[start] = Self(integer % 10).into();
 			integer /= 10;
 		}
 
 		// COMPLIANCE: The maximum amount of digits in an integer is vastly smaller than the maximum
 		// size of `i32::MAX`.
-		Ok(List::new_unvalidated(digits, env.gc()))
+		Ok(List::from_slice_unvalidated(&digits[start..], env.gc()))
 	}
 }
@@ -1,8 +1,10 @@
 use crate::gc::{self, AsValueInner, GarbageCollected, Gc, GcRoot, ValueInner};
+#[cfg(feature = "extensions")]
+use crate::parser::source_location::ProgramSource;
 use crate::parser::{ParseError, ParseErrorKind, Parseable, Parser};
 use crate::program::Compilable;
 use crate::program::Compiler;
-use crate::value::{Boolean, Integer, List, NamedType, ToBoolean, ToInteger, ToList};
+use crate::value::{Boolean, Integer, List, ListBuilder, NamedType, ToBoolean, ToInteger, ToList};
 use crate::{Environment, Options};
 use std::fmt::{self, Debug, Display, Formatter};
 use std::isize;
@@ -20,6 +22,49 @@ use crate::strings::{KnStr, StringError};
 #[repr(transparent)]
 pub struct KnString<'gc>(*const Inner, PhantomData<&'gc ()>);
 
+/// Short string literals which have been interned by a [`Gc`], so that identical literals in every
+/// program it compiles share the same string.
+///
+/// Interned strings are static, so they don't participate in garbage collection. Instead, they're
+/// freed along with the [`Gc`] that owns the pool.
+#[derive(Default)]
+pub(crate) struct InternPool(std::collections::HashMap<Box<str>, Box<Inner>>);
+
+impl InternPool {
+	/// The longest string that'll be interned; longer ones are allocated normally.
+	pub const MAX_LEN: usize = 64;
+
+	/// The most strings a pool will hold; once it's full, literals are allocated normally.
+	pub const MAX_COUNT: usize = 4096;
+
+	/// Gets the interned string with the contents `source`, creating it if it doesn't exist yet.
+	///
+	/// Returns `None` if `source` is longer than [`MAX_LEN`](Self::MAX_LEN), or if it'd have to be
+	/// added and the pool is full. The returned string must not be used after `self` is dropped.
+	pub fn intern<'gc>(&mut self, source: &KnStr) -> Option<KnString<'gc>> {
+		if source.len() > Self::MAX_LEN {
+			return None;
+		}
+
+		if let Some(inner) = self.0.get(source.as_str()) {
+			return Some(KnString(&**inner, PhantomData));
+		}
+
+		if self.0.len() >= Self::MAX_COUNT {
+			return None;
+		}
+
+		let string: Box<str> = source.as_str().into();
+
+		// SAFETY: The `Inner` is only ever used while it's in the pool, and so `string` is alive.
+		// (Moving the `Box<str>` into the pool doesn't move the data it points to.)
+		let inner = Box::new(unsafe { consts::static_inner_unchecked(&string) });
+		let knstring = KnString(&*inner, PhantomData);
+		self.0.insert(string, inner);
+		Some(knstring)
+	}
+}
+
 /// Represents the ability to be converted to a [`KnString`].
 pub trait ToKnString<'gc> {
 	/// Converts `self` to a [`KnString`].
@@ -37,10 +82,16 @@ pub(crate) mod consts {
 	}
 
 	const fn static_inner(string: &'static str) -> Inner {
+		// SAFETY: `string` lives forever.
+		unsafe { static_inner_unchecked(string) }
+	}
+
+	// SAFETY: The returned `Inner` mustn't be used after `string` is freed.
+	pub(super) const unsafe fn static_inner_unchecked(string: &str) -> Inner {
 		Inner {
 			_alignment: ValueAlign,
 			// TODO: make the `FLAG_CUSTOM_2` use a function.
-			flags: AtomicU8::new(gc::FLAG_GC_STATIC | gc::FLAG_IS_LIST | ALLOCATED_FLAG),
+			flags: AtomicU8::new(gc::FLAG_GC_STATIC | gc::FLAG_IS_STRING | ALLOCATED_FLAG),
 			kind: Kind {
 				alloc: Alloc {
 					_padding: MaybeUninit::uninit(),
@@ -51,6 +102,30 @@ pub(crate) mod consts {
 		}
 	}
 
+	/// The integers whose strings are cached by [`small_integer`].
+	pub const SMALL_INTEGERS: std::ops::RangeInclusive<i64> = -99..=999;
+
+	/// Gets the static string for `integer`, if it's within [`SMALL_INTEGERS`].
+	///
+	/// The strings are created the first time this is called, and are never freed.
+	pub(crate) fn small_integer(integer: i64) -> Option<KnString<'static>> {
+		use std::sync::OnceLock;
+
+		static CACHE: OnceLock<Box<[Inner]>> = OnceLock::new();
+
+		if !SMALL_INTEGERS.contains(&integer) {
+			return None;
+		}
+
+		let cache = CACHE.get_or_init(|| {
+			SMALL_INTEGERS
+				.map(|int| static_inner(Box::leak(int.to_string().into_boxed_str())))
+				.collect()
+		});
+
+		Some(KnString(&cache[(integer - SMALL_INTEGERS.start()) as usize], PhantomData))
+	}
+
 	// pub const EMPTY: KnString<'_> = static_str!("0");
 	pub const TRUE: KnString<'_> = static_str!("true");
 	pub const FALSE: KnString<'_> = static_str!("false");
@@ -317,8 +392,8 @@ impl<'gc> KnString<'gc> {
 		opts: &Options,
 		gc: &'gc Gc,
 	) -> crate::Result<GcRoot<'gc, Self>> {
-		if self.len().checked_mul(amount).map_or(true, |f| f > isize::MAX as usize) {
-			return Err(crate::Error::Todo("bounds too large!".to_string()));
+		if self.len().checked_mul(amount).map_or(true, |f| f > opts.max_repetition_length) {
+			return Err(crate::Error::DomainError("repetition is too large"));
 		}
 
 		if amount == 0 || self.is_empty() {
@@ -333,7 +408,42 @@ impl<'gc> KnString<'gc> {
 	pub fn split(&self, _by: &str, _gc: &'gc Gc) -> crate::Result<GcRoot<'gc, List<'gc>>> {
 		// let list =
 		// Ok(List::new_unvalidated(self.as_str().split(by).map(|substr|collect(), gc))
-		todo!()
+		todo_or_error!("splitting strings")
+	}
+
+	/// Returns a list of the lines in `self`, as per `XSPLITLINES`.
+	///
+	/// Lines end with either `\n` or `\r\n`, and a trailing line ending doesn't add an empty line
+	/// at the end.
+	#[cfg(feature = "extensions")]
+	pub fn split_lines(&self, gc: &'gc Gc) -> GcRoot<'gc, List<'gc>> {
+		Self::list_of_substrings(self.as_str().lines(), gc)
+	}
+
+	/// Returns a list of the whitespace-separated parts of `self`, as per `XSPLITWS`.
+	///
+	/// Leading and trailing whitespace is ignored, so there are never any empty strings in the
+	/// list.
+	#[cfg(feature = "extensions")]
+	pub fn split_whitespace(&self, gc: &'gc Gc) -> GcRoot<'gc, List<'gc>> {
+		Self::list_of_substrings(self.as_str().split_whitespace(), gc)
+	}
+
+	// Collects `substrings` (which are all parts of a valid string) into a list.
+	#[cfg(feature = "extensions")]
+	fn list_of_substrings<'a>(
+		substrings: impl Iterator<Item = &'a str>,
+		gc: &'gc Gc,
+	) -> GcRoot<'gc, List<'gc>> {
+		let mut builder = ListBuilder::with_capacity(0, gc);
+
+		for substring in substrings {
+			builder.push_root(Self::new_unvalidated(substring.to_string(), gc));
+		}
+
+		// COMPLIANCE: If the original string is within the container bounds, so is the amount of
+		// substrings in it.
+		builder.finish_unvalidated()
 	}
 
 	pub fn head(&self, gc: &'gc Gc) -> crate::Result<GcRoot<'gc, Self>> {
@@ -361,21 +471,33 @@ impl<'gc> KnString<'gc> {
 		let first =
 			self.as_str().chars().next().ok_or(crate::Error::DomainError("empty string for head"))?;
 
-		Ok(Integer::new_unvalidated(first as _))
+		Ok(Integer::new_unvalidated(u32::from(first).into()))
 	}
 
-	pub fn try_get<I>(&self, index: I, gc: &'gc Gc) -> crate::Result<GcRoot<'gc, Self>>
-	where
-		I: SliceIndex<str, Output = str>,
-	{
-		let rest = self
-			.as_str()
-			.get(index)
-			.ok_or(crate::Error::DomainError("invalid args for get for str"))?;
+	/// Returns the `len` bytes starting at `start`, as per `GET`.
+	///
+	/// # Errors
+	/// If `start + len` overflows, an [`Error::DomainError`](crate::Error::DomainError) is returned.
+	/// If it's past the end of `self` (or either end isn't on a character boundary), an
+	/// [`Error::IndexOutOfBounds`](crate::Error::IndexOutOfBounds) is returned.
+	pub fn try_get(
+		&self,
+		start: usize,
+		len: usize,
+		gc: &'gc Gc,
+	) -> crate::Result<GcRoot<'gc, Self>> {
+		let (_, substring, _) = self.split_for_range(start, len)?;
 
-		Ok(Self::from_knstr(KnStr::new_unvalidated(rest), gc))
+		Ok(Self::from_knstr(substring, gc))
 	}
 
+	/// Returns a copy of `self` with the `len` bytes starting at `start` replaced by `repl`, as per
+	/// `SET`.
+	///
+	/// # Errors
+	/// The same as [`KnString::try_get`]. Additionally, if the resulting string would be too long
+	/// (and [`check_container_length`](crate::options::Compliance::check_container_length) is
+	/// enabled), an [`Error::StringError`](crate::Error::StringError) is returned.
 	pub fn try_set(
 		&self,
 		start: usize,
@@ -384,14 +506,56 @@ impl<'gc> KnString<'gc> {
 		opts: &Options,
 		gc: &'gc Gc,
 	) -> crate::Result<GcRoot<'gc, Self>> {
-		// TODO: optimize this
-		let mut s = String::new();
-		let mut chars = self.as_str().chars();
+		let (prefix, _, suffix) = self.split_for_range(start, len)?;
+
+		let mut string = String::with_capacity(prefix.len() + repl.len() + suffix.len());
+		string.push_str(prefix.as_str());
+		string.push_str(repl.as_str());
+		string.push_str(suffix.as_str());
+		Ok(Self::new(string, opts, gc)?)
+	}
+
+	/// Replaces the bytes starting at `start` with `repl`, without allocating. This is `SET` when
+	/// the replacement is the same length as what it replaces.
+	///
+	/// Returns `false` (and leaves `self` alone) if `self` is static, or if `start..start +
+	/// repl.len()` isn't a valid range of `self`.
+	///
+	/// # Safety
+	/// Nothing else may refer to `self`, as its contents change.
+	pub(crate) unsafe fn overwrite(&self, start: usize, repl: &KnStr) -> bool {
+		let (flags, inner) = self.flags_and_inner();
+		if flags & gc::FLAG_GC_STATIC != 0 || self.split_for_range(start, repl.len()).is_err() {
+			return false;
+		}
+
+		// SAFETY: `self` isn't static, so its bytes were allocated by us and are writable. The range
+		// is on character boundaries (as `split_for_range` succeeded), and is replaced with a valid
+		// string of the same length, so `self` stays valid.
+		unsafe {
+			let bytes = if flags & ALLOCATED_FLAG != 0 {
+				(&raw const (*inner).kind.alloc.ptr).read().cast_mut()
+			} else {
+				(&raw mut (*inner).kind.embedded).cast::<u8>()
+			};
+
+			bytes.add(start).copy_from(repl.as_str().as_ptr(), repl.len());
+		}
 
-		s.push_str(&chars.by_ref().take(start).collect::<String>());
-		s.push_str(repl.as_str());
-		s.push_str(&chars.skip(len).collect::<String>());
-		Ok(Self::new(s, opts, gc)?)
+		true
+	}
+
+	// Splits `self` into the parts before, within, and after `start..start + len`.
+	fn split_for_range(&self, start: usize, len: usize) -> crate::Result<(&KnStr, &KnStr, &KnStr)> {
+		let end =
+			start.checked_add(len).ok_or(crate::Error::DomainError("end position is too large"))?;
+		let out_of_bounds = || crate::Error::IndexOutOfBounds { len: self.len(), index: end };
+
+		Ok((
+			self.get(..start).ok_or_else(out_of_bounds)?,
+			self.get(start..end).ok_or_else(out_of_bounds)?,
+			self.get(end..).ok_or_else(out_of_bounds)?,
+		))
 	}
 }
 
@@ -485,20 +649,14 @@ impl<'gc> ToList<'gc> for KnString<'gc> {
 	/// Returns an empty list for `false`, and a list with just `self` if true.
 	#[inline]
 	fn to_list(&self, env: &mut Environment<'gc>) -> crate::Result<GcRoot<'gc, List<'gc>>> {
-		env.gc().pause();
+		let mut builder = ListBuilder::with_capacity(self.len(), env.gc());
 
-		let chars = self
-			.chars()
-			.map(|c| {
-				let chr_string = Self::new_unvalidated(c.to_string(), env.gc());
-				unsafe { chr_string.assume_used() }.into()
-			})
-			.collect::<Vec<_>>();
+		for chr in self.chars() {
+			builder.push_root(Self::new_unvalidated(chr.to_string(), env.gc()));
+		}
 
 		// COMPLIANCE: If `self` is within the container bounds, so is the length of its chars.
-		let result = List::new_unvalidated(chars, env.gc());
-		env.gc().unpause();
-
+		let result = builder.finish_unvalidated();
 		Ok(result)
 	}
 }
@@ -510,8 +668,8 @@ impl<'path, 'gc> Parseable<'_, 'path, 'gc> for KnString<'gc> {
 		parser: &mut Parser<'_, '_, 'path, 'gc>,
 	) -> Result<Option<Self::Output>, ParseError<'path>> {
 		#[cfg(feature = "extensions")]
-		if parser.opts().extensions.syntax.string_interpolation && parser.advance_if('`').is_some() {
-			todo!();
+		if parser.opts().extensions().syntax.string_interpolation && parser.advance_if('`').is_some() {
+			todo_or_error!(parse(parser), "string interpolation");
 		}
 
 		let Some(quote) = parser.advance_if(|c| c == '\'' || c == '\"') else {
@@ -527,8 +685,21 @@ impl<'path, 'gc> Parseable<'_, 'path, 'gc> for KnString<'gc> {
 			return Err(ParseErrorKind::MissingEndingQuote(quote).error(start));
 		}
 
-		let string = KnString::new(contents.to_string(), parser.opts(), parser.gc())
-			.map_err(|err| ParseErrorKind::from(err).error(start))?;
+		let contents =
+			KnStr::new(contents, parser.opts()).map_err(|err| ParseErrorKind::from(err).error(start))?;
+
+		// Short literals are common, and so they're shared between programs. (Except for `EVAL`, as it
+		// can generate an unbounded amount of distinct literals, which would fill up the pool.)
+		#[cfg(feature = "extensions")]
+		let can_intern = !matches!(start.source(), ProgramSource::Eval);
+		#[cfg(not(feature = "extensions"))]
+		let can_intern = true;
+
+		let string = match can_intern.then(|| parser.gc().intern(contents)).flatten() {
+			Some(interned) => GcRoot::new_unchecked(interned),
+			None => KnString::from_knstr(contents, parser.gc()),
+		};
+
 		Ok(Some(string))
 	}
 }
@@ -1,4 +1,4 @@
-use crate::gc::{self, AsValueInner, GarbageCollected, Gc, GcRoot, ValueInner};
+use crate::gc::{self, AsValueInner, GarbageCollected, Gc, GcRoot, ValueInner, ValueRoot};
 use crate::parser::{ParseError, Parseable, Parser};
 use crate::program::{Compilable, Compiler};
 use crate::strings::KnStr;
@@ -127,8 +127,64 @@ where
 {
 }
 
+/// Incrementally builds a [`List`], keeping every pushed element rooted until it's finished.
+///
+/// This should be used instead of collecting [`Value`]s into a `Vec` whenever creating the elements
+/// can allocate, as nothing would otherwise keep the earlier elements alive.
+pub struct ListBuilder<'gc> {
+	elements: Vec<ValueRoot<'gc>>,
+	gc: &'gc Gc,
+}
+
+impl<'gc> ListBuilder<'gc> {
+	/// Creates a new, empty [`ListBuilder`].
+	pub fn new(gc: &'gc Gc) -> Self {
+		Self::with_capacity(0, gc)
+	}
+
+	/// Creates a new, empty [`ListBuilder`] with space for `capacity` elements.
+	pub fn with_capacity(capacity: usize, gc: &'gc Gc) -> Self {
+		Self { elements: Vec::with_capacity(capacity), gc }
+	}
+
+	/// Gets how many elements have been pushed so far.
+	pub fn len(&self) -> usize {
+		self.elements.len()
+	}
+
+	/// Returns whether nothing's been pushed yet.
+	pub fn is_empty(&self) -> bool {
+		self.elements.is_empty()
+	}
+
+	/// Adds `value` to the end of the list.
+	pub fn push(&mut self, value: Value<'gc>) {
+		self.elements.push(value.root(self.gc));
+	}
+
+	/// Adds an already-rooted value to the end of the list.
+	pub fn push_root<T>(&mut self, root: GcRoot<'gc, T>)
+	where
+		T: AsValueInner + Into<Value<'gc>>,
+	{
+		// SAFETY: `root` is kept alive until after its value is rooted by `push`.
+		self.push(unsafe { root.assume_used() }.into());
+	}
+
+	/// Finishes building the list, returning an error if it's too large.
+	pub fn finish(self, opts: &Options) -> crate::Result<GcRoot<'gc, List<'gc>>> {
+		// The elements are still rooted by `self` while the list is allocated.
+		List::new(self.elements.iter().map(|root| root.get()).collect::<Vec<_>>(), opts, self.gc)
+	}
+
+	/// Finishes building the list without checking its length.
+	pub fn finish_unvalidated(self) -> GcRoot<'gc, List<'gc>> {
+		List::new_unvalidated(self.elements.iter().map(|root| root.get()).collect::<Vec<_>>(), self.gc)
+	}
+}
+
 impl<'gc> List<'gc> {
-	/// The maximum length a list can be when compliance checking is enabled.
+	/// The default maximum length a list can be when compliance checking is enabled.
 	pub const COMPLIANCE_MAX_LEN: usize = i32::MAX as usize;
 
 	pub fn into_raw(self) -> *const ValueInner {
@@ -149,7 +205,7 @@ impl<'gc> List<'gc> {
 		gc: &'gc Gc,
 	) -> crate::Result<GcRoot<'gc, Self>> {
 		#[cfg(feature = "compliance")]
-		if opts.compliance.check_container_length && Self::COMPLIANCE_MAX_LEN < source.len() {
+		if !opts.compliance.is_valid_container_length(source.len()) {
 			return Err(Error::ListIsTooLarge);
 		}
 
@@ -181,7 +237,7 @@ impl<'gc> List<'gc> {
 	{
 		let source = source.into_iter();
 		#[cfg(feature = "compliance")]
-		if opts.compliance.check_container_length && Self::COMPLIANCE_MAX_LEN < source.len() {
+		if !opts.compliance.is_valid_container_length(source.len()) {
 			return Err(Error::ListIsTooLarge);
 		}
 
@@ -309,9 +365,7 @@ impl<'gc> List<'gc> {
 
 			let ele_str = element.to_knstring(env)?;
 
-			unsafe {
-				ele_str.with_inner(|inner| s.push_str(inner.as_str()));
-			}
+			s.push_str(ele_str.as_str());
 		}
 		Ok(KnString::new(s, env.opts(), env.gc())?)
 		// // Ok(GcRoot::new_unchecked(Self(self.0, PhantomData)))
@@ -332,14 +386,14 @@ impl<'gc> List<'gc> {
 		// Ok(result)
 	}
 
-	pub fn concat(&self, other: &Self, opts: &Options, gc: &'gc Gc) -> crate::Result<GcRoot<Self>> {
+	pub fn concat(&self, other: &Self, opts: &Options, gc: &'gc Gc) -> crate::Result<GcRoot<'gc, Self>> {
 		// todo: use a "concat" variant
 		Self::new(self.into_iter().chain(other.into_iter()).collect::<Vec<_>>(), opts, gc)
 	}
 
-	pub fn repeat(&self, amount: usize, opts: &Options, gc: &'gc Gc) -> crate::Result<GcRoot<Self>> {
-		if self.len().checked_mul(amount).map_or(true, |f| f > isize::MAX as usize) {
-			return Err(crate::Error::Todo("bounds too large!".to_string()));
+	pub fn repeat(&self, amount: usize, opts: &Options, gc: &'gc Gc) -> crate::Result<GcRoot<'gc, Self>> {
+		if self.len().checked_mul(amount).map_or(true, |f| f > opts.max_repetition_length) {
+			return Err(crate::Error::DomainError("repetition is too large"));
 		}
 
 		if amount == 0 || self.is_empty() {
@@ -354,6 +408,26 @@ impl<'gc> List<'gc> {
 		Ok(Self::new(self.__as_slice().repeat(amount), opts, gc)?)
 	}
 
+	/// Returns a copy of `self` with its elements in a random order, via a Fisher–Yates shuffle.
+	#[cfg(feature = "extensions")]
+	pub fn shuffled<R: rand::Rng + ?Sized>(&self, rng: &mut R, gc: &'gc Gc) -> GcRoot<'gc, Self> {
+		use rand::seq::SliceRandom;
+
+		let mut elements = self.__as_slice().to_vec();
+		elements.shuffle(rng);
+		Self::from_slice_unvalidated(&elements, gc)
+	}
+
+	/// Returns a uniformly random element of `self`.
+	#[cfg(feature = "extensions")]
+	pub fn choose<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> crate::Result<Value<'gc>> {
+		if self.is_empty() {
+			return Err(crate::Error::DomainError("XCHOICE: empty list given"));
+		}
+
+		Ok(self.__as_slice()[rng.gen_range(0..self.len())])
+	}
+
 	pub fn head(&self, _gc: &'gc Gc) -> crate::Result<Value<'gc>> {
 		self.into_iter().next().ok_or(crate::Error::DomainError("empty list for head"))
 	}
@@ -368,17 +442,33 @@ impl<'gc> List<'gc> {
 		Ok(Self::from_slice_unvalidated(rest, gc))
 	}
 
-	pub fn try_get<I>(&self, index: I, gc: &'gc Gc) -> crate::Result<GcRoot<'gc, Self>>
-	where
-		I: SliceIndex<[Value<'gc>], Output = [Value<'gc>]>,
-	{
-		let rest = self
+	/// Returns the `len` elements starting at `start`, as per `GET`.
+	///
+	/// # Errors
+	/// If `start + len` overflows, an [`Error::DomainError`] is returned. If it's past the end of
+	/// `self`, an [`Error::IndexOutOfBounds`] is returned.
+	pub fn try_get(
+		&self,
+		start: usize,
+		len: usize,
+		gc: &'gc Gc,
+	) -> crate::Result<GcRoot<'gc, Self>> {
+		let end = start.checked_add(len).ok_or(Error::DomainError("end position is too large"))?;
+		let sublist = self
 			.__as_slice()
-			.get(index)
-			.ok_or(crate::Error::DomainError("invalid args for get for list"))?;
-		Ok(Self::from_slice_unvalidated(rest, gc))
+			.get(start..end)
+			.ok_or(Error::IndexOutOfBounds { len: self.len(), index: end })?;
+
+		Ok(Self::from_slice_unvalidated(sublist, gc))
 	}
 
+	/// Returns a copy of `self` with the `len` elements starting at `start` replaced by `repl`, as
+	/// per `SET`.
+	///
+	/// # Errors
+	/// The same as [`List::try_get`]. Additionally, if the resulting list would be too long (and
+	/// [`check_container_length`](crate::options::Compliance::check_container_length) is enabled),
+	/// an [`Error::ListIsTooLarge`] is returned.
 	pub fn try_set(
 		&self,
 		start: usize,
@@ -387,12 +477,51 @@ impl<'gc> List<'gc> {
 		opts: &Options,
 		gc: &'gc Gc,
 	) -> crate::Result<GcRoot<'gc, Self>> {
-		// TODO: optimize this
-		let mut v = Vec::new();
-		v.extend(&mut self.into_iter().take(start));
-		v.extend(repl);
-		v.extend(&mut self.into_iter().skip(start + len));
-		Self::new(v, opts, gc)
+		let end = start.checked_add(len).ok_or(Error::DomainError("end position is too large"))?;
+		let slice = self.__as_slice();
+		if end > slice.len() {
+			return Err(Error::IndexOutOfBounds { len: slice.len(), index: end });
+		}
+
+		let mut elements = Vec::with_capacity(slice.len() - len + repl.len());
+		elements.extend_from_slice(&slice[..start]);
+		elements.extend(repl);
+		elements.extend_from_slice(&slice[end..]);
+		Self::new(elements, opts, gc)
+	}
+
+	/// Replaces the elements starting at `start` with `repl`'s, without allocating. This is `SET`
+	/// when the replacement is the same length as what it replaces.
+	///
+	/// Returns `false` (and leaves `self` alone) if `self` is static, or if `start..start +
+	/// repl.len()` is out of bounds.
+	///
+	/// # Safety
+	/// Nothing else may refer to `self`, as its contents change.
+	pub(crate) unsafe fn overwrite(&self, start: usize, repl: &Self) -> bool {
+		let (flags, inner) = self.flags_and_inner();
+		if flags & gc::FLAG_GC_STATIC != 0
+			|| start.checked_add(repl.len()).is_none_or(|end| self.len() < end)
+		{
+			return false;
+		}
+
+		// SAFETY: `self` isn't static, so its elements were allocated by us and are writable, and
+		// `start..start + repl.len()` is in bounds. If `repl` is `self`, then `start` is zero, so
+		// each element is just overwritten with itself.
+		unsafe {
+			let elements = if flags & ALLOCATED_FLAG != 0 {
+				(&raw const (*inner).kind.alloc.ptr).read().cast_mut()
+			} else {
+				(&raw mut (*inner).kind.embedded).cast::<Value<'gc>>()
+			};
+
+			for (index, element) in repl.iter().enumerate() {
+				elements.add(start + index).write(element);
+			}
+		}
+
+		true
 	}
 
 	pub fn try_cmp(
@@ -507,10 +636,10 @@ impl ToBoolean for List<'_> {
 }
 
 impl ToInteger for List<'_> {
-	/// Returns `1` for true and `0` for false.
+	/// Returns `self`'s length.
 	#[inline]
-	fn to_integer(&self, _: &mut Environment<'_>) -> crate::Result<Integer> {
-		Ok(Integer::new_unvalidated(self.len() as _))
+	fn to_integer(&self, env: &mut Environment<'_>) -> crate::Result<Integer> {
+		Ok(Integer::from_usize(self.len(), env.opts())?)
 	}
 }
 
@@ -0,0 +1,43 @@
+use std::fmt::{self, Debug, Formatter};
+
+use super::NamedType;
+
+/// A handle to a function implemented in Rust, which Knight code can `CALL`.
+///
+/// `NativeFn`s are created by [`Environment::register_native_fn`], and are only valid for the
+/// [`Environment`] that created them; calling one with another environment will either call the
+/// wrong function or return an error.
+///
+/// Like [`Block`](super::Block)s, `NativeFn`s aren't allocated, and so the garbage collector
+/// doesn't need to know about them. However, any [`Value`](super::Value)s the function itself
+/// holds on to must be rooted (eg with [`ValueRoot`](crate::gc::ValueRoot)s).
+///
+/// [`Environment`]: crate::Environment
+/// [`Environment::register_native_fn`]: crate::Environment::register_native_fn
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NativeFn(usize);
+
+impl Debug for NativeFn {
+	/// Native functions are written as `NativeFn(<index>)`, where the index is the order it was
+	/// registered in.
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		write!(f, "NativeFn({})", self.0)
+	}
+}
+
+impl NamedType for NativeFn {
+	fn type_name(&self) -> &'static str {
+		"NativeFn"
+	}
+}
+
+impl NativeFn {
+	pub(crate) const fn new(index: usize) -> Self {
+		Self(index)
+	}
+
+	/// Gets the index of `self` within its environment's native function table.
+	pub const fn index(self) -> usize {
+		self.0
+	}
+}
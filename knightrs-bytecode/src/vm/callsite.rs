@@ -5,11 +5,23 @@ use std::fmt::{self, Display, Formatter};
 pub struct Callsite<'src, 'path> {
 	src: SourceLocation<'path>,
 	fn_name: Option<VariableName<'src>>,
+	locals: Vec<(VariableName<'src>, String)>,
 }
 
 impl<'src, 'path> Callsite<'src, 'path> {
 	pub fn new(fn_name: Option<VariableName<'src>>, src: SourceLocation<'path>) -> Self {
-		Self { src, fn_name }
+		Self { src, fn_name, locals: Vec::new() }
+	}
+
+	/// Where the call happened.
+	pub fn source_location(&self) -> SourceLocation<'path> {
+		self.src
+	}
+
+	/// Attaches the (already `DUMP`ed) values of variables in scope at the callsite.
+	pub fn with_locals(mut self, locals: Vec<(VariableName<'src>, String)>) -> Self {
+		self.locals = locals;
+		self
 	}
 }
 
@@ -21,6 +33,15 @@ impl Display for Callsite<'_, '_> {
 			write!(f, " (function {})", fn_name)?;
 		}
 
+		for (idx, (name, value)) in self.locals.iter().enumerate() {
+			let prefix = if idx == 0 { " [" } else { ", " };
+			write!(f, "{prefix}{name} = {value}")?;
+		}
+
+		if !self.locals.is_empty() {
+			write!(f, "]")?;
+		}
+
 		Ok(())
 	}
 }
@@ -4,9 +4,17 @@ use std::fmt::{self, Display, Formatter};
 pub struct RuntimeError<'src, 'path> {
 	pub(super) err: crate::Error,
 
+	// Where the instruction that caused the error came from.
+	#[cfg(feature = "stacktrace")]
+	pub(super) location: super::Callsite<'src, 'path>,
+
 	#[cfg(feature = "stacktrace")]
 	pub(super) stacktrace: super::Stacktrace<'src, 'path>,
 
+	// The metadata of the program the error happened in.
+	#[cfg(feature = "stacktrace")]
+	pub(super) metadata: Vec<(String, String)>,
+
 	// used for when `stacktrace` isn't enabled
 	pub(super) _ignored: (&'src (), &'path ()),
 }
@@ -16,7 +24,22 @@ impl Display for RuntimeError<'_, '_> {
 		write!(f, "runtime error: {}", self.err)?;
 
 		#[cfg(feature = "stacktrace")]
-		write!(f, "{}", self.stacktrace)?;
+		{
+			write!(f, "\n\tat {}{}", self.location, self.stacktrace)?;
+
+			for (idx, (key, value)) in self.metadata.iter().enumerate() {
+				let prefix = if idx == 0 { "\n\tin program [" } else { ", " };
+				write!(f, "{prefix}{key}")?;
+
+				if !value.is_empty() {
+					write!(f, " = {value}")?;
+				}
+			}
+
+			if !self.metadata.is_empty() {
+				write!(f, "]")?;
+			}
+		}
 
 		Ok(())
 	}
@@ -5,12 +5,16 @@ mod vm;
 
 #[cfg(feature = "stacktrace")]
 mod stacktrace;
+#[cfg(feature = "stats")]
+mod stats;
 #[cfg(feature = "stacktrace")]
 pub use stacktrace::Stacktrace;
+#[cfg(feature = "stats")]
+pub use stats::Stats;
 
 pub use callsite::Callsite;
 pub use error::RuntimeError;
-pub use opcode::Opcode;
+pub use opcode::{Opcode, OpcodeInfo};
 pub use vm::*;
 
 #[cfg(feature = "compliance")]
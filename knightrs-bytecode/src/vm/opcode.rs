@@ -1,3 +1,5 @@
+use std::fmt;
+
 /// Opcodes represent different instructions that the [`Vm`](crate::vm::Vm) understands.
 // Implementation note: They're intentionally constructed in a special way, so as to make accessing
 // information like their arity super easy. More precisely, they're structured like:
@@ -21,6 +23,8 @@ pub enum Opcode {
 	SetVarPop    = opcode(6, 1, true), // same as setvar but it pips
 	#[cfg(feature = "extensions")]
 	AssignDynamic = opcode(7, 0, true), // offset is the type to use
+	OutputConst  = opcode(8, 0, true), // `OUTPUT` of a string constant; offset is the constant
+	GetVarToSet  = opcode(9, 0, true), // same as getvar, but it's only ever used by `SetInPlace`
 
 	// Arity 0
 	Prompt = opcode(1, 0, false),
@@ -50,6 +54,10 @@ pub enum Opcode {
 	Eval   = opcode(12, 1, false),
 	#[cfg(feature = "extensions")]
 	Value  = opcode(13, 1, false),
+	#[cfg(feature = "extensions")]
+	System = opcode(14, 1, false),
+	#[cfg(feature = "extensions")]
+	Unary  = opcode(15, 1, true), // offset is the `UnaryOp`
 
 	// Arity 2
 	Add           = opcode(0, 2, false),
@@ -63,12 +71,25 @@ pub enum Opcode {
 	Eql           = opcode(8, 2, false),
 	#[cfg(feature = "extensions")]
 	SetDynamicVar = opcode(9, 2, false),
+	#[cfg(feature = "extensions")]
+	Base          = opcode(10, 2, false),
+	#[cfg(feature = "extensions")]
+	FromBase      = opcode(11, 2, false),
+	#[cfg(feature = "extensions")]
+	Bitwise       = opcode(12, 2, true), // offset is the `BitwiseOp`
+	#[cfg(feature = "extensions")]
+	Cmp           = opcode(13, 2, false),
+	#[cfg(feature = "extensions")]
+	Apply         = opcode(14, 2, false),
+	#[cfg(feature = "extensions")]
+	Assert        = opcode(15, 2, false),
 
 	// Arity 3
 	Get = opcode(0, 3, false),
 
 	// Arity 4
 	Set = opcode(0, 4, false),
+	SetInPlace = opcode(1, 4, true), // `; = var SET var ...`; offset is the variable
 }
 
 #[cfg(feature = "extensions")]
@@ -81,10 +102,59 @@ pub enum DynamicAssignment {
 	System,
 }
 
+#[cfg(feature = "extensions")]
+#[repr(u8)]
+pub enum BitwiseOp {
+	And,
+	Or,
+	Xor,
+	ShiftLeft,
+	ShiftRight,
+}
+
+/// Extension functions which take one argument, and share the [`Opcode::Unary`] opcode.
+#[cfg(feature = "extensions")]
+#[repr(u8)]
+pub enum UnaryOp {
+	TypeOf,
+	ToBoolean,
+	ToInteger,
+	ToString,
+	ToList,
+	Shuffle,
+	Choice,
+	SplitLines,
+	SplitWhitespace,
+}
+
+/// A description of an [`Opcode`], for use by tooling such as disassemblers and verifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpcodeInfo {
+	/// The opcode being described.
+	pub opcode: Opcode,
+
+	/// The opcode's name, which is also what its [`Display`](fmt::Display) impl writes.
+	pub name: &'static str,
+
+	/// How many arguments the [`Vm`](crate::vm::Vm) pops off the stack before executing the opcode.
+	/// See [`Opcode::arity`].
+	pub arity: usize,
+
+	/// The net change in the stack's length after the opcode's executed.
+	///
+	/// For example, `Add` pops two arguments and pushes its result, so it's `-1`. `Return` is
+	/// `-1`, as its value is given to the caller; opcodes which never return (e.g. `Quit`) are
+	/// treated as if they pushed their result.
+	pub stack_effect: isize,
+
+	/// Whether the opcode takes an offset. See [`Opcode::takes_offset`].
+	pub takes_offset: bool,
+}
+
 // If it goes higher than this, we need to rework the structure of the opcode.
 const fn opcode(id: u8, arity: u8, takes_offset: bool) -> u8 {
 	assert!(arity as usize <= 0b111, "7 is max arity that can be taken");
-	assert!(id <= 0b11111, "too many IDs of a given arity will clobber stuff");
+	assert!(id <= 0b1111, "too many IDs of a given arity will clobber stuff");
 
 	(arity << 5) | (id << 1) | (takes_offset as u8)
 }
@@ -93,6 +163,123 @@ impl Opcode {
 	// TODO: check for things
 	pub const MAX_ARITY: usize = 4;
 
+	/// Every opcode that's enabled, in declaration order.
+	#[rustfmt::skip]
+	pub const ALL: &'static [Self] = &[
+		Self::PushConstant, Self::Jump, Self::JumpIfTrue, Self::JumpIfFalse, Self::GetVar,
+		Self::SetVar, Self::SetVarPop,
+		#[cfg(feature = "extensions")] Self::AssignDynamic,
+		Self::OutputConst, Self::GetVarToSet,
+
+		Self::Prompt, Self::Random, Self::Dup, Self::Dump, Self::Return,
+
+		Self::Call, Self::Quit, Self::Output, Self::Length, Self::Not, Self::Negate, Self::Ascii,
+		Self::Box, Self::Head, Self::Tail, Self::Pop,
+		#[cfg(feature = "extensions")] Self::Eval,
+		#[cfg(feature = "extensions")] Self::Value,
+		#[cfg(feature = "extensions")] Self::System,
+		#[cfg(feature = "extensions")] Self::Unary,
+
+		Self::Add, Self::Sub, Self::Mul, Self::Div, Self::Mod, Self::Pow, Self::Lth, Self::Gth,
+		Self::Eql,
+		#[cfg(feature = "extensions")] Self::SetDynamicVar,
+		#[cfg(feature = "extensions")] Self::Base,
+		#[cfg(feature = "extensions")] Self::FromBase,
+		#[cfg(feature = "extensions")] Self::Bitwise,
+		#[cfg(feature = "extensions")] Self::Cmp,
+		#[cfg(feature = "extensions")] Self::Apply,
+		#[cfg(feature = "extensions")] Self::Assert,
+
+		Self::Get,
+
+		Self::Set, Self::SetInPlace,
+	];
+
+	/// Gets the [`OpcodeInfo`] describing `self`.
+	pub const fn info(self) -> OpcodeInfo {
+		let (name, stack_effect) = match self {
+			Self::PushConstant => ("PushConstant", 1),
+			Self::Jump => ("Jump", 0),
+			Self::JumpIfTrue => ("JumpIfTrue", -1),
+			Self::JumpIfFalse => ("JumpIfFalse", -1),
+			Self::GetVar => ("GetVar", 1),
+			Self::SetVar => ("SetVar", 0),
+			Self::SetVarPop => ("SetVarPop", -1),
+			#[cfg(feature = "extensions")]
+			Self::AssignDynamic => ("AssignDynamic", 0),
+			Self::OutputConst => ("OutputConst", 1),
+			Self::GetVarToSet => ("GetVarToSet", 1),
+
+			Self::Prompt => ("Prompt", 1),
+			Self::Random => ("Random", 1),
+			Self::Dup => ("Dup", 1),
+			Self::Dump => ("Dump", 0),
+			Self::Return => ("Return", -1),
+
+			Self::Call => ("Call", 0),
+			Self::Quit => ("Quit", 0),
+			Self::Output => ("Output", 0),
+			Self::Length => ("Length", 0),
+			Self::Not => ("Not", 0),
+			Self::Negate => ("Negate", 0),
+			Self::Ascii => ("Ascii", 0),
+			Self::Box => ("Box", 0),
+			Self::Head => ("Head", 0),
+			Self::Tail => ("Tail", 0),
+			Self::Pop => ("Pop", -1),
+			#[cfg(feature = "extensions")]
+			Self::Eval => ("Eval", 0),
+			#[cfg(feature = "extensions")]
+			Self::Value => ("Value", 0),
+			#[cfg(feature = "extensions")]
+			Self::System => ("System", 0),
+			#[cfg(feature = "extensions")]
+			Self::Unary => ("Unary", 0),
+
+			Self::Add => ("Add", -1),
+			Self::Sub => ("Sub", -1),
+			Self::Mul => ("Mul", -1),
+			Self::Div => ("Div", -1),
+			Self::Mod => ("Mod", -1),
+			Self::Pow => ("Pow", -1),
+			Self::Lth => ("Lth", -1),
+			Self::Gth => ("Gth", -1),
+			Self::Eql => ("Eql", -1),
+			#[cfg(feature = "extensions")]
+			Self::SetDynamicVar => ("SetDynamicVar", -1),
+			#[cfg(feature = "extensions")]
+			Self::Base => ("Base", -1),
+			#[cfg(feature = "extensions")]
+			Self::FromBase => ("FromBase", -1),
+			#[cfg(feature = "extensions")]
+			Self::Bitwise => ("Bitwise", -1),
+			#[cfg(feature = "extensions")]
+			Self::Cmp => ("Cmp", -1),
+			#[cfg(feature = "extensions")]
+			Self::Apply => ("Apply", -1),
+			#[cfg(feature = "extensions")]
+			Self::Assert => ("Assert", -1),
+
+			Self::Get => ("Get", -2),
+
+			Self::Set => ("Set", -3),
+			Self::SetInPlace => ("SetInPlace", -4),
+		};
+
+		OpcodeInfo {
+			opcode: self,
+			name,
+			arity: self.arity(),
+			stack_effect,
+			takes_offset: self.takes_offset(),
+		}
+	}
+
+	/// Gets the name of the opcode; the same as `self.info().name`.
+	pub const fn name(self) -> &'static str {
+		self.info().name
+	}
+
 	/// The amount of arguments the opcode expects the stack to have.
 	#[inline]
 	pub const fn arity(self) -> usize {
@@ -105,6 +292,11 @@ impl Opcode {
 		(self as u8) & 1 != 0
 	}
 
+	/// Returns the [`Opcode`] represented by `byte`, or `None` if it isn't a valid [`Opcode`].
+	pub fn from_byte(byte: u8) -> Option<Self> {
+		Self::ALL.iter().copied().find(|&opcode| opcode as u8 == byte)
+	}
+
 	/// Returns the [`Opcode`] from the byte, without checking to see if it's a valid [`Opcode`].
 	///
 	/// # Safety
@@ -120,6 +312,8 @@ impl Opcode {
 				|| byte == Self::GetVar as u8
 				|| byte == Self::SetVar as u8
 				|| byte == Self::SetVarPop as u8
+				|| byte == Self::OutputConst as u8
+				|| byte == Self::GetVarToSet as u8
 
 			// Arity 0
 				|| byte == Self::Prompt as u8
@@ -143,7 +337,15 @@ impl Opcode {
 				|| { #[cfg(feature = "extensions")] {
 					   byte == Self::Eval as u8
 					|| byte == Self::Value as u8
+					|| byte == Self::System as u8
+					|| byte == Self::Unary as u8
 					|| byte == Self::SetDynamicVar as u8
+					|| byte == Self::Base as u8
+					|| byte == Self::FromBase as u8
+					|| byte == Self::Bitwise as u8
+					|| byte == Self::Cmp as u8
+					|| byte == Self::Apply as u8
+					|| byte == Self::Assert as u8
 					|| byte == Self::AssignDynamic as u8
 				}
 				#[cfg(not(feature = "extensions"))] { false } }
@@ -164,6 +366,7 @@ impl Opcode {
 
 			// Arity 4
 				|| byte == Self::Set as u8
+				|| byte == Self::SetInPlace as u8
 		);
 
 		// SAFETY: `Opcode` is `#[repr(u8)]`, and the caller ensures that `byte` is actually a valid
@@ -171,3 +374,10 @@ impl Opcode {
 		return unsafe { std::mem::transmute::<u8, Opcode>(byte) };
 	}
 }
+
+impl fmt::Display for Opcode {
+	/// Writes the opcode's [name](Opcode::name).
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(self.name())
+	}
+}
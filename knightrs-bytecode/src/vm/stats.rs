@@ -0,0 +1,62 @@
+use super::Opcode;
+use crate::gc::Allocations;
+
+/// Statistics about a [`Vm`](super::Vm)'s execution, as returned by [`Vm::stats`](super::Vm::stats).
+///
+/// These are only collected when `feature = "stats"` is enabled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stats {
+	// Indexed by the opcode's byte.
+	instructions: [u64; 256],
+
+	/// The values allocated while the `Vm` was running, by type.
+	pub allocations: Allocations,
+
+	/// The most values that were ever on the value stack at once.
+	pub peak_stack_depth: usize,
+
+	/// How many distinct variables were assigned.
+	pub variables_used: usize,
+
+	/// How many `XASSERT`s passed.
+	pub assertions: u64,
+}
+
+impl Default for Stats {
+	fn default() -> Self {
+		Self {
+			instructions: [0; 256],
+			allocations: Allocations::default(),
+			peak_stack_depth: 0,
+			variables_used: 0,
+			assertions: 0,
+		}
+	}
+}
+
+impl Stats {
+	/// Returns how many times `opcode` was executed.
+	pub fn instructions_for(&self, opcode: Opcode) -> u64 {
+		self.instructions[opcode as u8 as usize]
+	}
+
+	/// Returns every opcode that was executed at least once, along with how many times it was.
+	pub fn instructions(&self) -> impl Iterator<Item = (Opcode, u64)> + '_ {
+		Opcode::ALL
+			.iter()
+			.map(|&opcode| (opcode, self.instructions_for(opcode)))
+			.filter(|&(_, count)| count != 0)
+	}
+
+	/// Returns the total amount of instructions executed.
+	pub fn total_instructions(&self) -> u64 {
+		self.instructions.iter().sum()
+	}
+
+	// Records that `opcode` is about to be executed with a stack of `stack_depth` values.
+	#[inline]
+	pub(super) fn record(&mut self, opcode: Opcode, stack_depth: usize) {
+		self.instructions[opcode as u8 as usize] += 1;
+		self.peak_stack_depth = self.peak_stack_depth.max(stack_depth);
+	}
+}
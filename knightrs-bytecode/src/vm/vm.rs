@@ -1,48 +1,203 @@
 use crate::gc::GarbageCollected;
+#[cfg(feature = "extensions")]
+use indexmap::{IndexMap, IndexSet};
 use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
 
 use super::{Opcode, RuntimeError};
 use crate::parser::VariableName;
-use crate::program::{JumpIndex, Program};
-use crate::value::{Block, KnString, List, ToBoolean, ToInteger, ToKnString, Value};
+use crate::program::{JumpIndex, Program, ProgramId};
+use crate::strings::KnStr;
+use crate::value::{
+	Block, KnString, List, ListBuilder, NamedType, ToBoolean, ToInteger, ToKnString, ToList, Value,
+};
 use crate::{Environment, Error};
 
+// What a variable is stored as. Unassigned variables are `None` with `check-variables`, and `NULL`
+// without it.
+#[cfg(feature = "check-variables")]
+type VariableSlot<'gc> = Option<Value<'gc>>;
+#[cfg(not(feature = "check-variables"))]
+type VariableSlot<'gc> = Value<'gc>;
+
+// Whether a variable's string or list can be modified in place by `SetInPlace`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ownership {
+	// Something else might refer to the variable's value.
+	Shared,
+
+	// Nothing else refers to the variable's value.
+	Unique,
+
+	// The value was unique, and has been pushed by `GetVarToSet` for its `SetInPlace`. Anything
+	// else that reads the variable in the meantime (including another `GetVarToSet`, if the
+	// replacement sets the same variable) makes it `Shared` again, as the value is still on the
+	// stack.
+	HandedOut,
+}
+
+#[cfg(feature = "check-variables")]
+const UNASSIGNED: VariableSlot<'static> = None;
+#[cfg(not(feature = "check-variables"))]
+const UNASSIGNED: VariableSlot<'static> = Value::NULL;
+
 pub struct Vm<'prog, 'src, 'path, 'env, 'gc> {
+	// The program `self` was created to run.
+	root: &'prog Program<'src, 'path, 'gc>,
+
+	// The program whose code is currently running. This is `root`, unless a block declared by
+	// `EVAL`'d code is running.
 	program: &'prog Program<'src, 'path, 'gc>,
 	env: &'env mut Environment<'gc>,
 	current_index: usize,
 	stack: Vec<Value<'gc>>,
+	variables: Box<[VariableSlot<'gc>]>,
 
-	#[cfg(feature = "check-variables")]
-	variables: Box<[Option<Value<'gc>>]>,
-
-	#[cfg(not(feature = "check-variables"))]
-	variables: Box<[Value<'gc>]>,
+	// Which variables hold a string or list that nothing else refers to, and so can be modified in
+	// place by `SetInPlace`. Doing anything else with a variable (eg `GetVar`) makes it `Shared`.
+	ownership: Box<[Ownership]>,
 
+	// The program and index of each call that's currently running.
 	#[cfg(feature = "stacktrace")]
-	callstack: Vec<usize>,
+	callstack: Vec<(&'prog Program<'src, 'path, 'gc>, usize)>,
 
 	#[cfg(feature = "stacktrace")]
-	known_blocks: HashMap<usize, VariableName<'src>>,
+	known_blocks: HashMap<Block, VariableName<'src>>,
+
+	// The programs compiled by `EVAL` which declare blocks. They're kept until `self` is dropped, as
+	// their blocks can end up anywhere, and `program` and `callstack` can refer to them.
+	#[cfg(feature = "extensions")]
+	evaluated: HashMap<ProgramId, Box<Program<'src, 'path, 'gc>>>,
+
+	// The variables that `EVAL`'d code introduced, which come after `root`'s in `variables`.
+	#[cfg(feature = "extensions")]
+	evaluated_variables: IndexSet<VariableName<'static>>,
 
+	// Insertion-ordered, so that `dynamic_variables` is deterministic.
 	#[cfg(feature = "extensions")]
-	dynamic_variables: HashMap<VariableName<'static>, Value<'gc>>,
+	dynamic_variables: IndexMap<VariableName<'static>, Value<'gc>>,
+
+	// How many `EVAL`s deep `self` is, for `Extensions::max_eval_depth`.
+	#[cfg(feature = "extensions")]
+	eval_depth: usize,
+
+	#[cfg(feature = "stats")]
+	stats: super::Stats,
+
+	// Which variables have been assigned, for `Stats::variables_used`.
+	#[cfg(feature = "stats")]
+	assigned_variables: Box<[bool]>,
+
+	// What the gc had allocated when `self` was created, so only our allocations are counted.
+	#[cfg(feature = "stats")]
+	starting_allocations: crate::gc::Allocations,
+}
+
+impl Debug for Vm<'_, '_, '_, '_, '_> {
+	/// Writes a summary of where the [`Vm`] is, suitable for logs.
+	///
+	/// This includes the instruction pointer, how deep the stack is, and (with `stacktrace`) the
+	/// current source location and the callstack. Values on the stack and in variables aren't
+	/// included, as they can be arbitrarily large, and may contain user data.
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		let mut vm = f.debug_struct("Vm");
+		vm.field("ip", &self.current_index);
+
+		if self.current_index < self.program.num_instructions() {
+			// SAFETY: we just checked `current_index` is in bounds.
+			let (opcode, _) = unsafe { self.program.opcode_at(self.current_index) };
+			vm.field("next", &format_args!("{opcode}"));
+		}
+
+		vm.field("stack_depth", &self.stack.len());
+		vm.field("variables", &self.variables.len());
+
+		#[cfg(feature = "stacktrace")]
+		{
+			struct Callstack<'a, 'src, 'path>(&'a [super::Callsite<'src, 'path>]);
+			impl Debug for Callstack<'_, '_, '_> {
+				fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+					let mut callstack = f.debug_list();
+					for site in self.0 {
+						callstack.entry(&format_args!("{site}"));
+					}
+					callstack.finish()
+				}
+			}
+
+			let location = self.program.source_location_at(self.current_index.saturating_sub(1));
+			vm.field("location", &format_args!("{location}"));
+			vm.field("callstack", &Callstack(&self.stacktrace_frames()));
+		}
+
+		#[cfg(feature = "extensions")]
+		vm.field("eval_depth", &self.eval_depth);
+
+		vm.finish_non_exhaustive()
+	}
+}
+
+/// Which of its parent's variables a [child `Vm`](Vm::child) can see.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VariableVisibility {
+	/// The child starts out with every variable unassigned.
+	#[default]
+	Isolated,
+
+	/// The child starts out with the parent's values for any variables they have in common. The
+	/// child's assignments are never visible to the parent.
+	ReadThrough,
 }
 
 impl<'prog, 'src, 'path, 'env, 'gc> Vm<'prog, 'src, 'path, 'env, 'gc> {
+	/// Creates a new [`Vm`] which runs `program` in `env`.
+	///
+	/// Instructions aren't checked as they're run. This is sound because every [`Program`] was either
+	/// built by a [`Compiler`](crate::program::Compiler), or [validated](Program::validate) when it
+	/// was loaded via [`Program::from_raw_parts`] (whose caller guarantees the rest).
 	pub fn new(program: &'prog Program<'src, 'path, 'gc>, env: &'env mut Environment<'gc>) -> Self {
+		Self::with_buffers(program, env, Vec::new(), Box::default())
+	}
+
+	/// Creates a new [`Vm`] which runs `program`, reusing the environment and allocations of
+	/// `previous`.
+	///
+	/// This is the same as [`Vm::new`], except `previous`'s stack and variables are reused (and
+	/// only reallocated if `program` has a different amount of variables), so a single `Vm`'s
+	/// allocations can be pooled across lots of programs. To run the same program again, use
+	/// [`Vm::reset`].
+	pub fn with_capacity_from(
+		program: &'prog Program<'src, 'path, 'gc>,
+		previous: Vm<'_, '_, '_, 'env, 'gc>,
+	) -> Self {
+		Self::with_buffers(program, previous.env, previous.stack, previous.variables)
+	}
+
+	// Creates a new `Vm`, using `stack` and `variables` as its stack and variables.
+	fn with_buffers(
+		program: &'prog Program<'src, 'path, 'gc>,
+		env: &'env mut Environment<'gc>,
+		mut stack: Vec<Value<'gc>>,
+		variables: Box<[VariableSlot<'gc>]>,
+	) -> Self {
+		#[cfg(feature = "stats")]
+		let starting_allocations = env.gc().allocations();
+
+		stack.clear();
+
+		let mut variables = variables.into_vec();
+		variables.clear();
+		variables.resize(program.num_variables(), UNASSIGNED);
+
 		Self {
+			root: program,
 			program,
 			env,
 			current_index: 0,
-			stack: Vec::new(),
-
-			#[cfg(feature = "check-variables")]
-			variables: vec![None; program.num_variables()].into(),
-
-			#[cfg(not(feature = "check-variables"))]
-			variables: vec![Value::NULL; program.num_variables()].into(),
+			stack,
+			variables: variables.into_boxed_slice(),
+			ownership: vec![Ownership::Shared; program.num_variables()].into(),
 
 			#[cfg(feature = "stacktrace")]
 			callstack: Vec::new(),
@@ -51,13 +206,248 @@ impl<'prog, 'src, 'path, 'env, 'gc> Vm<'prog, 'src, 'path, 'env, 'gc> {
 			known_blocks: HashMap::default(),
 
 			#[cfg(feature = "extensions")]
-			dynamic_variables: HashMap::default(),
+			evaluated: HashMap::default(),
+
+			#[cfg(feature = "extensions")]
+			evaluated_variables: IndexSet::default(),
+
+			#[cfg(feature = "extensions")]
+			dynamic_variables: IndexMap::default(),
+
+			#[cfg(feature = "extensions")]
+			eval_depth: 0,
+
+			#[cfg(feature = "stats")]
+			stats: super::Stats::default(),
+
+			#[cfg(feature = "stats")]
+			assigned_variables: vec![false; program.num_variables()].into(),
+
+			#[cfg(feature = "stats")]
+			starting_allocations,
 		}
 	}
 
+	/// Resets `self` so it can run its program again from scratch, without reallocating.
+	///
+	/// Every variable becomes unassigned, the stack is cleared, and (with `stats`) the statistics
+	/// start over. `self`'s environment is left as-is, as is its `EVAL` depth.
+	///
+	/// # Examples
+	/// ```
+	/// # use knightrs_bytecode::parser::{source_location::ProgramSource, Parser};
+	/// # use knightrs_bytecode::{vm::Vm, Environment, Gc, Options};
+	/// // SAFETY: nothing allocated by the `gc` escapes it.
+	/// unsafe {
+	///     Gc::default().run(|gc| {
+	///         let mut env = Environment::new(Options::default(), gc);
+	///         let source = "; = n 0 ; WHILE < n 10 = n + n 3 : n";
+	///         let program = Parser::new(&mut env, ProgramSource::ExprFlag, source)
+	///             .and_then(|parser| parser.parse_program())
+	///             .unwrap();
+	///
+	///         let mut vm = Vm::new(&program, &mut env);
+	///         for _ in 0..3 {
+	///             let result = vm.run_entire_program_without_argv().unwrap();
+	///             assert_eq!(result.as_integer().map(|n| n.inner()), Some(12));
+	///             vm.reset();
+	///         }
+	///     })
+	/// }
+	/// ```
+	pub fn reset(&mut self) {
+		self.program = self.root;
+		self.current_index = 0;
+		self.stack.clear();
+		self.variables.fill(UNASSIGNED);
+		self.ownership.fill(Ownership::Shared);
+
+		#[cfg(feature = "stacktrace")]
+		{
+			self.callstack.clear();
+			self.known_blocks.clear();
+		}
+
+		#[cfg(feature = "extensions")]
+		self.dynamic_variables.clear();
+
+		#[cfg(feature = "stats")]
+		{
+			self.stats = super::Stats::default();
+			self.assigned_variables.fill(false);
+			self.starting_allocations = self.env.gc().allocations();
+		}
+	}
+
+	/// Returns statistics about everything `self` has executed so far, including any `EVAL`s.
+	#[cfg(feature = "stats")]
+	pub fn stats(&self) -> super::Stats {
+		let mut stats = self.stats.clone();
+		stats.allocations = self.env.gc().allocations().since(self.starting_allocations);
+		stats
+	}
+
+	/// Creates a new [`Vm`] for `program` which shares `self`'s environment, but has its own
+	/// variables.
+	///
+	/// Since the child borrows `self` for its entire lifetime, `self`'s variables can't change while
+	/// it's running, so [`VariableVisibility::ReadThrough`] only needs to copy them over once.
+	pub fn child<'cprog, 'csrc, 'cpath>(
+		&mut self,
+		program: &'cprog Program<'csrc, 'cpath, 'gc>,
+		visibility: VariableVisibility,
+	) -> Vm<'cprog, 'csrc, 'cpath, '_, 'gc> {
+		let visible = (visibility == VariableVisibility::ReadThrough).then(|| {
+			let names = program.variable_names().iter();
+			names.map(|name| self.variable_index(name)).collect::<Vec<_>>()
+		});
+		let mut child = Vm::new(program, self.env);
+
+		if let Some(visible) = visible {
+			// The child can store our variables' values anywhere, such as in its return value.
+			self.ownership.fill(Ownership::Shared);
+
+			for (slot, parent_index) in child.variables.iter_mut().zip(visible) {
+				if let Some(parent_index) = parent_index {
+					*slot = self.variables[parent_index];
+				}
+			}
+
+			#[cfg(feature = "extensions")]
+			child.dynamic_variables.clone_from(&self.dynamic_variables);
+		}
+
+		#[cfg(feature = "extensions")]
+		{
+			child.eval_depth = self.eval_depth;
+		}
+
+		child
+	}
+
+	/// Runs `source` as a Knight program, as per `EVAL`.
+	///
+	/// `source` is compiled against `self`'s variables, and then run by `self`, so it can use and
+	/// assign any of them (including ones `self`'s program doesn't mention, which are then
+	/// accessible via `VALUE`), and stacktraces include both `self`'s calls and the `EVAL` itself.
+	///
+	/// Blocks that `source` declares can be used after the `EVAL` finishes, as the program it's
+	/// compiled to is kept until `self` is dropped (unless it doesn't declare any blocks).
+	///
+	/// # Errors
+	/// If `source` is longer than [`max_eval_length`](crate::options::Extensions::max_eval_length),
+	/// or `self` is already [`max_eval_depth`](crate::options::Extensions::max_eval_depth) `EVAL`s
+	/// deep, an error is returned without `source` being parsed.
+	///
+	/// # Examples
+	/// ```
+	/// # use knightrs_bytecode::{testing, Options};
+	/// let mut opts = Options::default();
+	/// opts.apply_str_flags(&["extensions.functions.eval"]).unwrap();
+	/// let run = |source: &str| testing::run(source, "", opts.clone());
+	///
+	/// assert_eq!(run(r#"; EVAL "= a 3" : OUTPUT a"#).stdout, "3\n");
+	/// assert_eq!(run(r#"; = f BLOCK 1 : OUTPUT EVAL "CALL f""#).stdout, "1\n");
+	/// assert_eq!(run(r#"; = g EVAL "BLOCK 7" : OUTPUT CALL g"#).stdout, "7\n");
+	/// assert_eq!(run(r#"; EVAL "= l ,BLOCK + 1 2" : OUTPUT CALL [l"#).stdout, "3\n");
+	/// ```
+	#[cfg(feature = "extensions")]
+	pub fn eval(&mut self, source: &str) -> crate::Result<Value<'gc>> {
+		use crate::parser::{source_location::ProgramSource, Parser};
+
+		let limits = self.env.opts().extensions();
+		if let Some(max) = limits.max_eval_length.filter(|&max| max < source.len()) {
+			return Err(crate::Error::EvalTooLong { len: source.len(), max });
+		}
+
+		if let Some(max) = limits.max_eval_depth.filter(|&max| max <= self.eval_depth) {
+			return Err(crate::Error::EvalTooDeep { max });
+		}
+
+		let variables = self.root.variable_names().iter().chain(&self.evaluated_variables);
+		let variables = variables.map(|name| name.clone().become_owned()).collect::<Vec<_>>();
+		let program = Parser::with_variables(self.env, ProgramSource::Eval, source, variables)?
+			.parse_program()?
+			.become_owned();
+
+		// Make room for the variables `source` introduced, moving any that were dynamic over.
+		let mut variables = std::mem::take(&mut self.variables).into_vec();
+		for name in program.variable_names().iter().skip(variables.len()) {
+			let value = self.dynamic_variables.shift_remove(name);
+
+			#[cfg(feature = "check-variables")]
+			variables.push(value);
+			#[cfg(not(feature = "check-variables"))]
+			variables.push(value.unwrap_or(UNASSIGNED));
+
+			self.evaluated_variables.insert(name.clone());
+		}
+
+		self.variables = variables.into_boxed_slice();
+		self.ownership = resized(&self.ownership, self.variables.len(), Ownership::Shared);
+		#[cfg(feature = "stats")]
+		{
+			self.assigned_variables = resized(&self.assigned_variables, self.variables.len(), false);
+		}
+
+		// The program has to stay around if any of its blocks could escape.
+		let id = program.id();
+		let declares_blocks =
+			program.constants().iter().any(|constant| constant.as_block().is_some());
+		self.evaluated.insert(id, Box::new(program));
+
+		self.eval_depth += 1;
+		let result = self.run(Block::new(JumpIndex(0), id));
+		self.eval_depth -= 1;
+
+		if !declares_blocks {
+			self.evaluated.remove(&id);
+		}
+
+		result
+	}
+
+	// Gets the program which declared `block`, if it's `self`'s program or one `EVAL` compiled.
+	fn program_declaring(&self, block: Block) -> Option<&'prog Program<'src, 'path, 'gc>> {
+		if self.program.declares(block) {
+			return Some(self.program);
+		}
+
+		if self.root.declares(block) {
+			return Some(self.root);
+		}
+
+		#[cfg(feature = "extensions")]
+		if let Some(program) = self.evaluated.get(&block.program()).filter(|p| p.declares(block)) {
+			// SAFETY: Programs in `evaluated` are boxed, and are only removed once they're no longer
+			// running (ie aren't `self.program` or in the callstack), and none of their blocks exist.
+			// So, this reference is valid for as long as `self` uses it, and `'prog` never escapes.
+			return Some(unsafe { &*(&**program as *const Program<'src, 'path, 'gc>) });
+		}
+
+		None
+	}
+
+	// Gets the offset of the variable named `name`, including ones introduced by `EVAL`.
+	fn variable_index(&self, name: &VariableName<'_>) -> Option<usize> {
+		#[cfg(feature = "extensions")]
+		if let Some(index) = self.evaluated_variables.get_index_of(name) {
+			return Some(self.root.num_variables() + index);
+		}
+
+		self.root.variable_index(name)
+	}
+
 	pub unsafe fn mark(&self) {
 		unsafe {
-			self.program.mark();
+			self.root.mark();
+		}
+
+		#[cfg(feature = "extensions")]
+		for program in self.evaluated.values() {
+			unsafe {
+				program.mark();
+			}
 		}
 
 		for value in self.stack.iter() {
@@ -93,54 +483,84 @@ impl<'prog, 'src, 'path, 'env, 'gc> Vm<'prog, 'src, 'path, 'env, 'gc> {
 		argv: impl IntoIterator<Item = String>,
 	) -> crate::Result<Value<'gc>> {
 		#[cfg(feature = "extensions")]
-		if self.env.opts().extensions.argv {
+		if self.env.opts().extensions().argv {
 			let mut first = true;
-			self.env.gc().pause();
-			let argv = argv
-				.into_iter()
-				.skip_while(|ele| {
-					if first {
-						first = false;
-						ele == "--"
-					} else {
-						false
-					}
-				})
-				.map(|str| {
-					KnString::new(str, self.env.opts(), self.env.gc())
-						.map(|string| unsafe { string.assume_used() }.into())
-				})
-				.collect::<Result<Vec<_>, _>>()?;
+			let mut builder = ListBuilder::new(self.env.gc());
+
+			for arg in argv.into_iter().skip_while(|ele| {
+				if first {
+					first = false;
+					ele == "--"
+				} else {
+					false
+				}
+			}) {
+				builder.push_root(KnString::new(arg, self.env.opts(), self.env.gc())?);
+			}
 
-			let argv = List::new(argv, self.env.opts(), self.env.gc())
-				.map(|l| unsafe { l.assume_used() }.into())?;
+			let argv = builder.finish(self.env.opts())?;
 
 			// SAFETY: if extensions are enabled, argv is always added, regardless of whether or not it
 			// was specified, so this is valid. Also, TODO: make sure `VALUE`, when implemented, fails
 			// for undefined variables on `argv` if argv isn't set
 			debug_assert_ne!(self.variables.len(), 0);
 			unsafe {
-				self.set_variable(crate::program::Compiler::ARGV_VARIABLE_INDEX, argv);
+				self.set_variable(
+					crate::program::Compiler::ARGV_VARIABLE_INDEX,
+					argv.assume_used().into(),
+				);
 			}
-			self.env.gc().unpause();
 		}
 
 		self.run_entire_program_without_argv()
 	}
 
 	pub fn run_entire_program_without_argv(&mut self) -> crate::Result<Value<'gc>> {
-		self.run(Block::new(JumpIndex(0)))
+		self.run(Block::new(JumpIndex(0), self.root.id()))
 	}
 
+	/// Runs `block`, returning its result.
+	///
+	/// # Errors
+	/// Returns [`Error::ProgramNotRunnable`] if this vm's program isn't [runnable](
+	/// Program::is_runnable), [`Error::ForeignBlock`] if `block` wasn't declared by it, or whatever
+	/// error running `block` results in.
+	///
+	/// # Examples
+	/// Partial programs can't be run at all, not even the blocks within them:
+	/// ```
+	/// # use knightrs_bytecode::{parser::{source_location::ProgramSource, Parser}, vm::Vm};
+	/// # use knightrs_bytecode::{Environment, Error, Gc, Options};
+	/// # unsafe { Gc::default().run(|gc| {
+	/// let mut env = Environment::new(Options::default(), gc);
+	/// let source = "XNOTAFUNCTION BLOCK 1";
+	/// let parser = Parser::new(&mut env, ProgramSource::ExprFlag, source).unwrap();
+	/// let (program, errors) = parser.parse_program_recovering();
+	/// assert_eq!(errors.len(), 1);
+	///
+	/// let block = program.constants().iter().find_map(|constant| constant.as_block()).unwrap();
+	/// let mut vm = Vm::new(&program, &mut env);
+	/// assert!(matches!(vm.run(block), Err(Error::ProgramNotRunnable)));
+	/// assert!(matches!(vm.run_entire_program_without_argv(), Err(Error::ProgramNotRunnable)));
+	/// # }) }
+	/// ```
 	pub fn run(&mut self, block: Block) -> crate::Result<Value<'gc>> {
+		if !self.root.is_runnable() {
+			return Err(Error::ProgramNotRunnable);
+		}
+
+		let Some(program) = self.program_declaring(block) else {
+			return Err(Error::ForeignBlock);
+		};
+
 		// Save previous index
 		let index = self.current_index;
+		let caller = std::mem::replace(&mut self.program, program);
 
 		#[cfg(feature = "stacktrace")]
-		self.callstack.push(self.current_index);
+		self.callstack.push((caller, self.current_index));
 
-		// Used for debugging later
-		#[cfg(debug_assertions)]
+		// Used to discard whatever was left on the stack if an error occurs
 		let stack_len = self.stack.len();
 
 		// Actually call the function
@@ -151,47 +571,202 @@ impl<'prog, 'src, 'path, 'env, 'gc> Vm<'prog, 'src, 'path, 'env, 'gc> {
 		#[cfg(feature = "stacktrace")]
 		let result = match result {
 			Ok(ok) => Ok(ok),
-			Err(todo @ crate::Error::Stacktrace(_)) => Err(todo),
-			Err(err) => Err(crate::Error::Stacktrace(self.error(err).to_string())),
+			Err(todo @ crate::Error::Stacktrace(..)) => Err(todo),
+			// Exiting isn't really an error, so embedders need to be able to see it.
+			#[cfg(feature = "embedded")]
+			Err(exit @ crate::Error::Exit(_)) => Err(exit),
+			// Likewise, embedders need to know a program was interrupted.
+			Err(crate::Error::Interrupted) => Err(crate::Error::Interrupted),
+			Err(err) => {
+				let err = self.error(err);
+				let location = err.location.source_location().into();
+				Err(crate::Error::Stacktrace(err.to_string(), location))
+			}
 		};
 
 		#[cfg(feature = "stacktrace")]
 		{
-			let result = self.callstack.pop();
+			let result = self.callstack.pop().map(|(_, index)| index);
 			debug_assert_eq!(result, Some(index));
 		}
 
-		#[cfg(debug_assertions)]
-		debug_assert_eq!(stack_len, self.stack.len(), "{:?}", result);
+		if result.is_ok() {
+			debug_assert_eq!(stack_len, self.stack.len(), "{:?}", result);
+		} else {
+			// Errors can happen midway through a function, so its arguments might still be around.
+			self.stack.truncate(stack_len);
+		}
 
 		self.current_index = index;
+		self.program = caller;
 
 		result
 	}
 
+	/// Gets every dynamic variable (ie ones only created at runtime, such as via `VALUE` or
+	/// `XAPPLY`), along with its value.
+	///
+	/// They're returned in the order they were first assigned, so the output is reproducible.
+	#[cfg(feature = "extensions")]
+	pub fn dynamic_variables(&self) -> impl Iterator<Item = (&VariableName<'static>, Value<'gc>)> {
+		self.dynamic_variables.iter().map(|(name, &value)| (name, value))
+	}
+
+	/// Gets the environment `self` is running in.
+	pub fn env(&mut self) -> &mut Environment<'gc> {
+		self.env
+	}
+
+	/// Runs `block` with `args` as its arguments, as per `XAPPLY`.
+	///
+	/// The list of all the arguments is assigned to `_0`, and each argument is assigned to `_1`,
+	/// `_2`, and so on. Like all Knight variables, these are global: nested calls overwrite them,
+	/// and they keep their values after `block` returns.
+	///
+	/// The caller is responsible for making sure `args` aren't garbage collected during the call
+	/// (eg by holding [`ValueRoot`](crate::gc::ValueRoot)s to them).
+	///
+	/// # Errors
+	/// Returns an error if `args` is too long to be a [`List`], or if `block` itself fails.
+	pub fn call_block(&mut self, block: Block, args: &[Value<'gc>]) -> crate::Result<Value<'gc>> {
+		let list = List::from_slice(args, self.env.opts(), self.env.gc())?;
+
+		// `list` stays rooted for the duration of the call.
+		self.apply(block, list.as_value(), &list)
+	}
+
+	/// Binds `list` (whose value is `args`) to the argument variables, and then runs `block`.
+	fn apply(
+		&mut self,
+		block: Block,
+		list: Value<'gc>,
+		args: &List<'gc>,
+	) -> crate::Result<Value<'gc>> {
+		self.bind_argument(0, list);
+		for (position, arg) in args.into_iter().enumerate() {
+			self.bind_argument(position + 1, arg);
+		}
+
+		self.run(block)
+	}
+
+	/// Assigns `value` to the `position`th argument variable (ie `_<position>`).
+	fn bind_argument(&mut self, position: usize, value: Value<'gc>) {
+		let name = format!("_{position}");
+		let varname = VariableName::new_unvalidated(KnStr::new_unvalidated(&name));
+
+		if let Some(index) = self.variable_index(&varname) {
+			// SAFETY: `variable_index` always returns valid indices.
+			unsafe { self.set_variable(index, value) };
+			return;
+		}
+
+		// It's not used in the program, but could still be looked up via `VALUE`.
+		#[cfg(feature = "extensions")]
+		self.dynamic_variables.insert(varname.become_owned(), value);
+	}
+
 	pub fn error(&mut self, err: crate::Error) -> RuntimeError {
 		RuntimeError {
 			err,
+			// `current_index` is already past the instruction that failed.
+			#[cfg(feature = "stacktrace")]
+			location: {
+				let idx = self.current_index.saturating_sub(1);
+				super::Callsite::new(None, self.program.source_location_at(idx))
+					.with_locals(self.locals_around(self.program, idx))
+			},
 			#[cfg(feature = "stacktrace")]
 			stacktrace: self.stacktrace(),
+			#[cfg(feature = "stacktrace")]
+			metadata: self.program.metadata().iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
 			_ignored: (&(), &()),
 		}
 	}
 
 	#[cfg(feature = "stacktrace")]
 	pub fn stacktrace(&self) -> super::Stacktrace {
+		super::Stacktrace::new(self.stacktrace_frames())
+	}
+
+	#[cfg(feature = "stacktrace")]
+	fn stacktrace_frames(&self) -> Vec<super::Callsite<'src, 'path>> {
 		use super::Callsite;
 
-		super::Stacktrace::new(self.callstack.iter().map(|&idx| {
-			let loc = self.program.source_location_at(idx);
-			Callsite::new(self.block_name_at(idx), loc)
-		}))
+		self
+			.callstack
+			.iter()
+			.map(|&(program, idx)| {
+				// Like in `error`, `idx` is already past the instruction that made the call.
+				let idx = idx.saturating_sub(1);
+				let loc = program.source_location_at(idx);
+				Callsite::new(self.block_name_at(program, idx + 1), loc)
+					.with_locals(self.locals_around(program, idx))
+			})
+			.collect()
+	}
+
+	// The names and `DUMP`ed values of the variables assigned in the block around `idx`, if
+	// `qol.stacktrace_locals` is enabled. Long values are truncated, so a huge list doesn't bury
+	// the rest of the stacktrace.
+	#[cfg(feature = "stacktrace")]
+	fn locals_around(
+		&self,
+		program: &Program<'src, 'path, 'gc>,
+		idx: usize,
+	) -> Vec<(VariableName<'src>, String)> {
+		const MAX_VALUE_LEN: usize = 64;
+
+		#[cfg(feature = "qol")]
+		if self.env.opts().qol.stacktrace_locals {
+			let Some(assigned) = program.variables_assigned_around(idx) else {
+				return Vec::new();
+			};
+
+			return assigned
+				.into_iter()
+				.filter_map(|offset| {
+					let value = self.variables[offset]?;
+					let mut dumped =
+						value.dump_to_string(self.env).unwrap_or_else(|err| format!("<{err}>"));
+					if let Some((end, _)) = dumped.char_indices().nth(MAX_VALUE_LEN) {
+						dumped.truncate(end);
+						dumped.push_str("...");
+					}
+					Some((program.variable_name(offset).clone(), dumped))
+				})
+				.collect();
+		}
+
+		let _ = (program, idx);
+		Vec::new()
+	}
+
+	/// Gets the name and declaration location of `block`.
+	///
+	/// The name is the one given when the block was declared (via `= name BLOCK ...`), or, failing
+	/// that, the most recent variable the block was assigned to. Without the `stacktrace` feature,
+	/// neither are tracked.
+	pub fn block_info(&self, block: Block) -> crate::value::BlockInfo<'src, 'path> {
+		#[cfg(feature = "stacktrace")]
+		if let Some((name, location)) =
+			self.program_declaring(block).and_then(|p| p.block_location(block))
+		{
+			let name = name.or_else(|| self.known_blocks.get(&block)).cloned();
+			return crate::value::BlockInfo { block, name, location: Some(location) };
+		}
+
+		crate::value::BlockInfo { block, name: None, location: None }
 	}
 
 	#[cfg(feature = "stacktrace")]
-	fn block_name_at(&self, mut idx: usize) -> Option<VariableName> {
+	fn block_name_at(
+		&self,
+		program: &Program<'src, 'path, 'gc>,
+		mut idx: usize,
+	) -> Option<VariableName<'src>> {
 		while idx != 0 {
-			if let Some(name) = self.known_blocks.get(&idx) {
+			if let Some(name) = self.known_blocks.get(&Block::new(JumpIndex(idx), program.id())) {
 				return Some(name.clone());
 			}
 
@@ -213,6 +788,9 @@ impl<'prog, 'src, 'path, 'env, 'gc> Vm<'prog, 'src, 'path, 'env, 'gc> {
 			// println!("{opcode:?}");
 			self.current_index += 1;
 
+			#[cfg(feature = "stats")]
+			self.stats.record(opcode, self.stack.len());
+
 			// Pop the arguments off the stack. The remaining arguments are in `spare_capacity_mut`.
 			// This does mean that we cannot modify `self.stack` until we've interacted with all the
 			// individual arguments.
@@ -273,7 +851,12 @@ impl<'prog, 'src, 'path, 'env, 'gc> Vm<'prog, 'src, 'path, 'env, 'gc> {
 				Opcode::PushConstant => self.stack.push(unsafe { self.program.constant_at(offset) }),
 
 				// SAFETY: program is well-defined, so jumps are always correct
-				Opcode::Jump => unsafe { self.jump_to(offset) },
+				Opcode::Jump => {
+					// Every loop jumps backwards, so checking here (and in `Call`) is enough to
+					// make sure cancelled programs stop.
+					self.env.check_cancelled()?;
+					unsafe { self.jump_to(offset) }
+				}
 				Opcode::JumpIfTrue => {
 					if unsafe { arg![0] }.to_boolean(self.env)? {
 						// SAFETY: program is well-defined, so jumps are always correct
@@ -293,6 +876,19 @@ impl<'prog, 'src, 'path, 'env, 'gc> Vm<'prog, 'src, 'path, 'env, 'gc> {
 					self.stack.push(value);
 				}
 
+				// Unlike `GetVar`, the value isn't considered to be stored anywhere else, as it's always
+				// consumed by the `SetInPlace` of the same variable. Until then, it's on the stack, so
+				// it's only `HandedOut` if nothing else has it.
+				Opcode::GetVarToSet => {
+					let value = unsafe { self.read_variable(offset) }?;
+					let ownership = &mut self.ownership[offset];
+					*ownership = match *ownership {
+						Ownership::Unique => Ownership::HandedOut,
+						_ => Ownership::Shared,
+					};
+					self.stack.push(value);
+				}
+
 				Opcode::SetVar => {
 					// SAFETY: construction of `Program`s guarantee that `SetVar` always has at least one
 					// value on the stack (the value to assign)
@@ -309,25 +905,26 @@ impl<'prog, 'src, 'path, 'env, 'gc> Vm<'prog, 'src, 'path, 'env, 'gc> {
 				Opcode::SetDynamicVar => {
 					let value = unsafe { arg![1] }; // read in case `.to_kstring` in the next line modifies args
 					let name = unsafe { arg![0] }.to_knstring(self.env)?;
-					let varname = VariableName::new(&name, self.env.opts())
-						.map_err(|err| crate::Error::Todo(err.to_string()))?;
+					let varname =
+						VariableName::new(&name, self.env.opts()).map_err(crate::Error::InvalidVariable)?;
 
 					// If it already exists, then just use that
-					if let Some(index) = self.program.variable_index(&varname) {
+					if let Some(index) = self.variable_index(&varname) {
 						unsafe {
 							self.set_variable(index, value.clone());
 						}
 					} else {
-						// check for compliance, even with the extension
+						// check for compliance, even with the extension. Reassigning an existing dynamic
+						// variable doesn't create a new one, so it's always allowed.
 						#[cfg(feature = "compliance")]
 						if self.env.opts().compliance.variable_count
-							&& self.dynamic_variables.len() + self.program.num_variables()
-								> super::MAX_VARIABLE_COUNT
+							&& !self.dynamic_variables.contains_key(&varname)
+							&& self.dynamic_variables.len() + self.variables.len()
+								>= super::MAX_VARIABLE_COUNT
 						{
-							return Err(crate::Error::Todo(format!(
-								"too many variables encountered (only {} allowed)",
-								super::MAX_VARIABLE_COUNT
-							)));
+							return Err(crate::Error::InvalidVariable(
+								crate::parser::ParseErrorKind::TooManyVariables,
+							));
 						}
 
 						self.dynamic_variables.insert(varname.become_owned(), value.clone());
@@ -337,12 +934,52 @@ impl<'prog, 'src, 'path, 'env, 'gc> Vm<'prog, 'src, 'path, 'env, 'gc> {
 					self.stack.push(value);
 				}
 
-				Opcode::SetVarPop => todo!(), //self.variables[offset] = unsafe{arg![0]}.clone(),
+				Opcode::SetVarPop => {
+					// SAFETY: Same as `SetVar`, except the value to assign was popped as the argument.
+					let value = unsafe { arg![0] };
+					unsafe {
+						self.set_variable(offset, value);
+					}
+				}
+
+				// `; = var SET var start length replacement`, where `var` was pushed by `GetVarToSet`.
+				// If nothing's used the variable since the last `SetInPlace` (which would've made it
+				// `Shared`), nothing else refers to its value, so it can be modified.
+				Opcode::SetInPlace => unsafe {
+					let (first, rest) = args.split_at_mut_unchecked(1);
+					let value = first.get_unchecked(0).assume_init_read();
+					let start = rest.get_unchecked(0).assume_init_read();
+					let length = rest.get_unchecked(1).assume_init_read();
+					let repl = rest.get_unchecked(2).assume_init_read();
+					let target = first.get_unchecked_mut(0);
+
+					let is_unique = self.ownership[offset] == Ownership::HandedOut;
+					let is_unique =
+						value.kn_set_in_place(&start, &length, &repl, is_unique, target, self.env)?;
+					let result = target.assume_init_read();
+					self.set_variable(offset, result);
+					if is_unique {
+						self.ownership[offset] = Ownership::Unique;
+					}
+				},
 
 				// Arity 0
 				Opcode::Prompt => {
-					if let Some(prompted) = self.env.prompt()? {
-						unsafe { prompted.with_inner(|inner| self.stack.push(inner.into())) }
+					// Computed replacements are run by this vm, so they can see (and modify) variables.
+					// `NULL` means EOF, and everything else is converted to a string.
+					#[cfg(feature = "extensions")]
+					if let Some(block) = self.env.prompt().computed_block() {
+						let line = self.run(block)?;
+						if line.is_null() {
+							self.stack.push(Value::NULL);
+						} else {
+							self.stack.push(line.to_knstring(self.env)?.as_value());
+						}
+						continue;
+					}
+
+					if let Some(prompted) = self.env.read_line()? {
+						self.stack.push(prompted.as_value());
 					} else {
 						self.stack.push(Value::NULL);
 					}
@@ -375,13 +1012,17 @@ impl<'prog, 'src, 'path, 'env, 'gc> Vm<'prog, 'src, 'path, 'env, 'gc> {
 
 				Opcode::Call => {
 					let arg = unsafe { arg![0] };
+					self.env.check_cancelled()?;
 
+					// Blocks from other programs are rejected by `kn_call`.
 					#[cfg(not(feature = "stacktrace"))]
 					if let Some(block) = arg.as_block() {
-						likely_stable::likely(true);
-						jumpstack.push(self.current_index);
-						unsafe { self.jump_to(block.inner().0) };
-						continue;
+						if self.program.declares(block) {
+							likely_stable::likely(true);
+							jumpstack.push(self.current_index);
+							unsafe { self.jump_to(block.inner().0) };
+							continue;
+						}
 					}
 
 					let value = arg.kn_call(self)?;
@@ -394,19 +1035,8 @@ impl<'prog, 'src, 'path, 'env, 'gc> Vm<'prog, 'src, 'path, 'env, 'gc> {
 				}
 
 				Opcode::Output => {
-					use std::io::Write;
 					let kstring = unsafe { arg![0] }.to_knstring(self.env)?;
-					let strref = kstring.as_str();
-
-					let mut output = self.env.output();
-
-					if let Some(stripped) = strref.strip_suffix('\\') {
-						write!(output, "{stripped}")
-					} else {
-						writeln!(output, "{strref}")
-					}
-					.map_err(|err| Error::IoError { func: "OUTPUT", err })?;
-					let _ = output.flush(); // explicitly ignore errors with flushing
+					Self::write_output(self.env, kstring.as_str())?;
 
 					// SAFETY: `Output` is guaranteed to be given an argument. We've also already
 					// read from it.
@@ -414,6 +1044,17 @@ impl<'prog, 'src, 'path, 'env, 'gc> Vm<'prog, 'src, 'path, 'env, 'gc> {
 						push_no_resize!(Value::NULL);
 					}
 				}
+				Opcode::OutputConst => {
+					let constant = unsafe { self.program.constant_at(offset) };
+
+					// The compiler only emits `OutputConst` for string constants.
+					let Some(kstring) = constant.as_knstring() else {
+						unsafe { bug_unchecked!("OutputConst of a non-string constant") }
+					};
+
+					Self::write_output(self.env, kstring.as_str())?;
+					self.stack.push(Value::NULL);
+				}
 				Opcode::Length => {
 					let value = unsafe { arg![0] }.kn_length(self.env)?.into();
 					unsafe {
@@ -437,7 +1078,7 @@ impl<'prog, 'src, 'path, 'env, 'gc> Vm<'prog, 'src, 'path, 'env, 'gc> {
 					let boxed = List::boxed(unsafe { arg![0] }, self.env.gc());
 
 					unsafe {
-						boxed.with_inner(|inner| end!().write(inner.into()));
+						end!().write(boxed.as_value());
 						self.stack.set_len(self.stack.len() + 1);
 					}
 				}
@@ -494,17 +1135,15 @@ impl<'prog, 'src, 'path, 'env, 'gc> Vm<'prog, 'src, 'path, 'env, 'gc> {
 					self.stack.set_len(self.stack.len() + 1);
 				},
 				Opcode::Lth => {
-					let value = (unsafe { arg![0] }.kn_compare(&unsafe { arg![1] }, "<", self.env)?
-						== Ordering::Less)
-						.into();
+					let (lhs, rhs) = unsafe { (arg![0], arg![1]) };
+					let value = (Self::compare(lhs, rhs, "<", self.env)? == Ordering::Less).into();
 					unsafe {
 						push_no_resize!(value);
 					}
 				}
 				Opcode::Gth => {
-					let value = (unsafe { arg![0] }.kn_compare(&unsafe { arg![1] }, ">", self.env)?
-						== Ordering::Greater)
-						.into();
+					let (lhs, rhs) = unsafe { (arg![0], arg![1]) };
+					let value = (Self::compare(lhs, rhs, ">", self.env)? == Ordering::Greater).into();
 					unsafe {
 						push_no_resize!(value);
 					}
@@ -543,23 +1182,33 @@ impl<'prog, 'src, 'path, 'env, 'gc> Vm<'prog, 'src, 'path, 'env, 'gc> {
 						let seed = unsafe { last!() }.to_integer(self.env)?;
 						self.env.seed_random(seed);
 					}
-					_ => todo!("{:?}", offset),
+					_ if offset == super::opcode::DynamicAssignment::Prompt as _ => {
+						let value = unsafe { last!() };
+
+						// `NULL`/`FALSE` close stdin, `TRUE` resets, and blocks compute each line.
+						if value.is_null() || value.as_boolean() == Some(false) {
+							self.env.prompt().close();
+						} else if value.as_boolean() == Some(true) {
+							self.env.prompt().reset_replacement();
+						} else if let Some(block) = value.as_block() {
+							self.env.prompt().set_block(block);
+						} else {
+							let lines = value.to_knstring(self.env)?;
+							self.env.prompt().add_lines(lines.as_str());
+						}
+					}
+					_ if offset == super::opcode::DynamicAssignment::System as _ => {
+						let result = unsafe { last!() }.to_knstring(self.env)?;
+						self.env.add_to_system(result.as_str().to_owned());
+					}
+					_ => todo_or_error!("dynamic assignment {offset:?}"),
 				},
 
-				// TODO: the `vm` evals in its entirely own vm, which isnt what we wnat
 				#[cfg(feature = "extensions")]
 				Opcode::Eval => {
-					let program = unsafe { arg![0] }.to_knstring(self.env)?;
-					let parser = crate::parser::Parser::new(
-						&mut self.env,
-						crate::parser::source_location::ProgramSource::Eval,
-						program.as_str(),
-					)?;
-					let program = parser.parse_program()?;
-					let value = Vm::new(&program, self.env).run_entire_program_without_argv()?;
-					unsafe {
-						push_no_resize!(value);
-					}
+					let source = unsafe { arg![0] }.to_knstring(self.env)?;
+					let value = self.eval(source.as_str())?;
+					self.stack.push(value);
 				}
 
 				#[cfg(feature = "extensions")]
@@ -567,10 +1216,9 @@ impl<'prog, 'src, 'path, 'env, 'gc> Vm<'prog, 'src, 'path, 'env, 'gc> {
 					let variable_name = unsafe { arg![0] }.to_knstring(self.env)?;
 
 					let varname = VariableName::new(&variable_name, self.env.opts())
-						.map_err(|err| crate::Error::Todo(err.to_string()))?;
+						.map_err(crate::Error::InvalidVariable)?;
 
-					let value = if let Some(compiletime_variable_offset) =
-						self.program.variable_index(&varname)
+					let value = if let Some(compiletime_variable_offset) = self.variable_index(&varname)
 					{
 						// SAFETY: `variable_index` ensures it always returns a valid index., i think
 						unsafe { self.get_variable(compiletime_variable_offset)? }
@@ -583,6 +1231,144 @@ impl<'prog, 'src, 'path, 'env, 'gc> Vm<'prog, 'src, 'path, 'env, 'gc> {
 					};
 					self.stack.push(value);
 				}
+
+				#[cfg(feature = "extensions")]
+				Opcode::Base => {
+					let integer = unsafe { arg![0] }.to_integer(self.env)?;
+					let base = unsafe { arg![1] }.to_integer(self.env)?;
+					let digits = integer.to_base(base)?;
+					let string = KnString::new(digits, self.env.opts(), self.env.gc())?;
+					self.stack.push(string.as_value());
+				}
+
+				#[cfg(feature = "extensions")]
+				Opcode::FromBase => {
+					let base = unsafe { arg![1] }.to_integer(self.env)?; // read before `to_knstring` allocates
+					let string = unsafe { arg![0] }.to_knstring(self.env)?;
+					let integer = crate::value::Integer::from_base(string.as_str(), base, self.env.opts())?;
+					self.stack.push(integer.into());
+				}
+
+				#[cfg(feature = "extensions")]
+				Opcode::Bitwise => {
+					use super::opcode::BitwiseOp;
+
+					let lhs = unsafe { arg![0] }.to_integer(self.env)?;
+					let rhs = unsafe { arg![1] }.to_integer(self.env)?;
+
+					let result = match offset {
+						_ if offset == BitwiseOp::And as _ => lhs.bitwise_and(rhs),
+						_ if offset == BitwiseOp::Or as _ => lhs.bitwise_or(rhs),
+						_ if offset == BitwiseOp::Xor as _ => lhs.bitwise_xor(rhs),
+						_ if offset == BitwiseOp::ShiftLeft as _ => lhs.shift_left(rhs, self.env.opts())?,
+						_ => lhs.shift_right(rhs, self.env.opts())?,
+					};
+
+					self.stack.push(result.into());
+				}
+
+				#[cfg(feature = "extensions")]
+				Opcode::Cmp => {
+					let lhs = unsafe { arg![0] };
+					let rhs = unsafe { arg![1] };
+					let ordering = Self::compare(lhs, rhs, "XCMP", self.env)?;
+					self.stack.push(crate::value::Integer::from(ordering).into());
+				}
+
+				#[cfg(feature = "extensions")]
+				Opcode::Apply => {
+					let callable = unsafe { arg![0] };
+					let args = unsafe { arg![1] }.to_list(self.env)?;
+
+					// `args` stays rooted for the duration of the call, and the result is pushed
+					// onto the stack before anything else is allocated.
+					let result = if let Some(block) = callable.as_block() {
+						self.apply(block, args.as_value(), &args)?
+					} else if let Some(native) = callable.as_native_fn() {
+						let args = args.iter().collect::<Vec<_>>();
+						self.env.call_native_fn(native, &args)?.get()
+					} else {
+						return Err(Error::TypeError {
+							type_name: callable.type_name(),
+							function: "XAPPLY",
+						});
+					};
+					self.stack.push(result);
+				}
+
+				#[cfg(feature = "extensions")]
+				Opcode::Assert => {
+					if !unsafe { arg![0] }.to_boolean(self.env)? {
+						let message = unsafe { arg![1] }.to_knstring(self.env)?;
+						return Err(Error::AssertionFailed { message: message.as_str().to_owned() });
+					}
+
+					#[cfg(feature = "stats")]
+					{
+						self.stats.assertions += 1;
+					}
+					self.stack.push(Value::NULL);
+				}
+
+				#[cfg(feature = "extensions")]
+				Opcode::System => {
+					let command = unsafe { arg![0] }.to_knstring(self.env)?;
+					let result = self.env.run_command(command.as_str())?;
+					self.stack.push(result.as_value());
+				}
+
+				#[cfg(feature = "extensions")]
+				Opcode::Unary => {
+					use super::opcode::UnaryOp;
+
+					let arg = unsafe { arg![0] };
+
+					match offset {
+						_ if offset == UnaryOp::TypeOf as _ => {
+							let name =
+								KnString::new(arg.type_name().to_string(), self.env.opts(), self.env.gc())?;
+							self.stack.push(name.as_value());
+						}
+						_ if offset == UnaryOp::ToBoolean as _ => {
+							let boolean = arg.to_boolean(self.env)?;
+							self.stack.push(boolean.into());
+						}
+						_ if offset == UnaryOp::ToInteger as _ => {
+							let integer = arg.to_integer(self.env)?;
+							self.stack.push(integer.into());
+						}
+						_ if offset == UnaryOp::ToString as _ => {
+							let string = arg.to_knstring(self.env)?;
+							self.stack.push(string.as_value());
+						}
+						_ if offset == UnaryOp::ToList as _ => {
+							let list = arg.to_list(self.env)?;
+							self.stack.push(list.as_value());
+						}
+						_ if offset == UnaryOp::Shuffle as _ => {
+							let list = arg.to_list(self.env)?;
+							let gc = self.env.gc();
+							let shuffled = list.shuffled(self.env.rng(), gc);
+							self.stack.push(shuffled.as_value());
+						}
+						_ if offset == UnaryOp::Choice as _ => {
+							let list = arg.to_list(self.env)?;
+							let choice = list.choose(self.env.rng())?;
+							self.stack.push(choice);
+						}
+						_ if offset == UnaryOp::SplitLines as _ => {
+							let string = arg.to_knstring(self.env)?;
+							let lines = string.split_lines(self.env.gc());
+							self.stack.push(lines.as_value());
+						}
+						_ if offset == UnaryOp::SplitWhitespace as _ => {
+							let string = arg.to_knstring(self.env)?;
+							let words = string.split_whitespace(self.env.gc());
+							self.stack.push(words.as_value());
+						}
+						_ => unreachable!("invalid UnaryOp offset {offset}"),
+					}
+				}
 			}
 		}
 	}
@@ -594,6 +1380,13 @@ impl<'prog, 'src, 'path, 'env, 'gc> Vm<'prog, 'src, 'path, 'env, 'gc> {
 
 	// SAFETY: the `offset` must be a valid variable offset
 	unsafe fn get_variable(&mut self, offset: usize) -> crate::Result<Value<'gc>> {
+		// The value could end up stored anywhere, so it can't be modified in place anymore.
+		self.ownership[offset] = Ownership::Shared;
+		unsafe { self.read_variable(offset) }
+	}
+
+	// SAFETY: the `offset` must be a valid variable offset
+	unsafe fn read_variable(&self, offset: usize) -> crate::Result<Value<'gc>> {
 		debug_assert!(offset <= self.variables.len());
 
 		let value = *unsafe { self.variables.get_unchecked(offset) };
@@ -613,14 +1406,58 @@ impl<'prog, 'src, 'path, 'env, 'gc> Vm<'prog, 'src, 'path, 'env, 'gc> {
 	}
 
 	// SAFETY: the `offset` must be a valid variable offset
+	/// Writes `string` to `env`'s output, as per `OUTPUT`.
+	fn write_output(env: &mut Environment<'gc>, string: &str) -> crate::Result<()> {
+		use std::io::Write;
+
+		let newline = env.opts().io.newline.as_str();
+		let output = env.output();
+
+		// Flushing is handled by `output`'s flush policy.
+		if let Some(stripped) = string.strip_suffix('\\') {
+			write!(output, "{stripped}")
+		} else {
+			write!(output, "{string}{newline}")
+		}
+		.map_err(|err| env.output_error("OUTPUT", err))
+	}
+
+	/// Compares `lhs` and `rhs` for `<` and `>`.
+	///
+	/// Integers and strings compared against the same type are done directly, without converting
+	/// (and rooting) `rhs`; everything else goes through [`Value::kn_compare`].
+	#[inline]
+	fn compare(
+		lhs: Value<'gc>,
+		rhs: Value<'gc>,
+		function: &'static str,
+		env: &mut Environment<'gc>,
+	) -> crate::Result<Ordering> {
+		if let (Some(lhs), Some(rhs)) = (lhs.as_integer(), rhs.as_integer()) {
+			return Ok(lhs.cmp(&rhs));
+		}
+
+		if let (Some(lhs), Some(rhs)) = (lhs.as_knstring(), rhs.as_knstring()) {
+			return Ok(lhs.as_str().cmp(rhs.as_str()));
+		}
+
+		lhs.kn_compare(&rhs, function, env)
+	}
+
 	unsafe fn set_variable(&mut self, offset: usize, value: Value<'gc>) {
 		debug_assert!(offset <= self.variables.len());
+		self.ownership[offset] = Ownership::Shared;
 
 		// TODO: rework how stacktraces work
 		#[cfg(feature = "stacktrace")]
-		if let Some(ref block) = value.as_block() {
+		if let Some(block) = value.as_block().filter(|&b| self.program.declares(b)) {
 			let varname = self.program.variable_name(offset);
-			self.known_blocks.insert(block.inner().0, varname.clone());
+			self.known_blocks.insert(block, varname.clone());
+		}
+
+		#[cfg(feature = "stats")]
+		if !std::mem::replace(&mut self.assigned_variables[offset], true) {
+			self.stats.variables_used += 1;
 		}
 
 		#[cfg(feature = "check-variables")]
@@ -629,3 +1466,11 @@ impl<'prog, 'src, 'path, 'env, 'gc> Vm<'prog, 'src, 'path, 'env, 'gc> {
 		*unsafe { self.variables.get_unchecked_mut(offset) } = value
 	}
 }
+
+// Copies `slice` into a new one of length `len`, filling any new elements with `fill`.
+#[cfg(feature = "extensions")]
+fn resized<T: Copy>(slice: &[T], len: usize, fill: T) -> Box<[T]> {
+	let mut resized = slice.to_vec();
+	resized.resize(len, fill);
+	resized.into_boxed_slice()
+}
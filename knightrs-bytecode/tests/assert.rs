@@ -0,0 +1,48 @@
+//! `XASSERT`, which raises an error with a message if its condition is falsey.
+
+#![cfg(feature = "extensions")]
+
+use knightrs_bytecode::env::Evaluation;
+use knightrs_bytecode::{Environment, Gc, Options};
+
+// Evaluates `source` with `XASSERT` enabled, passing the result to `check`.
+fn evaluate(source: &str, check: impl FnOnce(Evaluation<'_>)) {
+	let mut opts = Options::default();
+	opts.extensions.functions.assert = true;
+
+	// SAFETY: nothing allocated by the `gc` escapes it.
+	unsafe { Gc::default().run(|gc| check(Environment::new(opts, gc).evaluate(source))) }
+}
+
+#[test]
+fn passing_assertions_return_null() {
+	evaluate(r#"; XASSERT TRUE "unused" : DUMP XASSERT 1 "unused""#, |evaluation| {
+		assert!(evaluation.value.is_ok());
+		assert_eq!(evaluation.stdout_lossy(), "null");
+	});
+}
+
+#[test]
+fn failing_assertions_include_their_message() {
+	evaluate("; = a 3\n; OUTPUT a\n: XASSERT (? a 4) + \"a is \" a", |evaluation| {
+		assert_eq!(evaluation.stdout_lossy(), "3\n");
+
+		let err = evaluation.value.unwrap_err();
+		assert!(err.to_string().contains("assertion failed: a is 3"), "{err}");
+
+		#[cfg(feature = "stacktrace")]
+		match err {
+			knightrs_bytecode::Error::Stacktrace(_, location) => assert_eq!(location.lineno, 3),
+			_ => panic!("the error has no location: {err:?}"),
+		}
+	});
+}
+
+#[test]
+#[cfg(feature = "stats")]
+fn passing_assertions_are_counted() {
+	evaluate(r#"; XASSERT 1 "" ; XASSERT TRUE "" ; XASSERT "a" "" : XASSERT 0 """#, |evaluation| {
+		assert!(evaluation.value.is_err());
+		assert_eq!(evaluation.stats.assertions, 3);
+	});
+}
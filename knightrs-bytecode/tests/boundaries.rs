@@ -0,0 +1,154 @@
+//! Lengths and indices near the limits of `usize`, `i32`, and `i64` (well, the 63 bits that
+//! integers actually have), which should be reported as errors rather than panicking or wrapping.
+//!
+//! The `usize` limits are reached by calling `try_get` and `try_set` directly, as on 64-bit targets
+//! `GET` and `SET` can't pass lengths that large. Checks which depend on how wide `usize` is are
+//! gated on `target_pointer_width`.
+
+use knightrs_bytecode::parser::{source_location::ProgramSource, Parser};
+use knightrs_bytecode::value::{Integer, KnString, List, ToKnString, Value};
+use knightrs_bytecode::vm::Vm;
+use knightrs_bytecode::{Environment, Error, Gc, Options};
+
+// Runs `func` with a fresh `Gc`, which is shut down (freeing everything in it) afterwards.
+fn with_gc(func: impl FnOnce(&Gc)) {
+	// SAFETY: none of the tests let anything allocated by the `gc` escape.
+	unsafe { Gc::default().run(func) }
+}
+
+// Runs `source` with `opts`, returning what it evaluates to, converted to a string.
+fn run(source: &str, opts: &Options) -> Result<String, Error> {
+	let mut result = None;
+	with_gc(|gc| {
+		let mut env = Environment::new(opts.clone(), gc);
+		let program = Parser::new(&mut env, ProgramSource::ExprFlag, source)
+			.and_then(|parser| parser.parse_program())
+			.unwrap();
+
+		result = Some(
+			Vm::new(&program, &mut env)
+				.run_entire_program_without_argv()
+				.and_then(|value| value.to_knstring(&mut env))
+				.map(|string| string.as_str().to_owned()),
+		);
+	});
+	result.unwrap()
+}
+
+// Errors from running programs have stacktraces attached, so they're checked by their messages.
+fn is_out_of_bounds<T>(result: Result<T, Error>) -> bool {
+	result.is_err_and(|err| err.to_string().contains("is out of bounds"))
+}
+
+fn is_domain_error<T>(result: Result<T, Error>) -> bool {
+	result.is_err_and(|err| err.to_string().contains("domain error"))
+}
+
+#[test]
+fn lengths_fit_in_integers() {
+	let opts = Options::default();
+	let max = Integer::max(&opts).inner();
+
+	for len in [0, 1, i32::MAX as usize, i32::MAX as usize + 1, u32::MAX as usize] {
+		let int = Integer::from_usize(len, &opts).ok().map(Integer::inner);
+		assert_eq!(int, i64::try_from(len).ok());
+	}
+
+	// Only when `usize` is wider than integers are there lengths which don't fit.
+	#[cfg(target_pointer_width = "64")]
+	{
+		let max = usize::try_from(max).unwrap();
+		assert_eq!(Integer::from_usize(max, &opts).ok().map(Integer::inner), Some(max as i64));
+		assert!(Integer::from_usize(max + 1, &opts).is_err());
+		assert!(Integer::from_usize(usize::MAX, &opts).is_err());
+	}
+
+	#[cfg(not(target_pointer_width = "64"))]
+	assert!(Integer::from_usize(usize::MAX, &opts).is_ok());
+}
+
+#[test]
+#[cfg(feature = "compliance")]
+fn lengths_fit_in_i32_integers() {
+	let mut opts = Options::default();
+	opts.compliance.i32_integer = true;
+
+	let max = Integer::from_usize(i32::MAX as usize, &opts).unwrap();
+	assert_eq!(max.inner(), i32::MAX.into());
+	assert!(Integer::from_usize(i32::MAX as usize + 1, &opts).is_err());
+	assert!(Integer::from_usize(u32::MAX as usize, &opts).is_err());
+	assert!(Integer::from_usize(usize::MAX, &opts).is_err());
+}
+
+#[test]
+fn usize_ranges_dont_overflow() {
+	with_gc(|gc| {
+		let string = KnString::new_unvalidated("abc".to_owned(), gc);
+		let list = List::from_slice_unvalidated(&[Value::TRUE; 3], gc);
+		let opts = Options::default();
+
+		for (start, len) in [(usize::MAX, 1), (1, usize::MAX), (usize::MAX, usize::MAX)] {
+			assert!(is_domain_error(string.try_get(start, len, gc)), "{start} {len}");
+			assert!(is_domain_error(list.try_get(start, len, gc)), "{start} {len}");
+			assert!(is_domain_error(string.try_set(start, len, &string, &opts, gc)));
+			assert!(is_domain_error(list.try_set(start, len, &list, &opts, gc)));
+		}
+
+		for (start, len) in [(usize::MAX, 0), (0, usize::MAX), (4, 0), (3, 1)] {
+			assert!(is_out_of_bounds(string.try_get(start, len, gc)), "{start} {len}");
+			assert!(is_out_of_bounds(list.try_get(start, len, gc)), "{start} {len}");
+			assert!(is_out_of_bounds(string.try_set(start, len, &string, &opts, gc)));
+			assert!(is_out_of_bounds(list.try_set(start, len, &list, &opts, gc)));
+		}
+
+		// Ranges that end exactly at the end are fine.
+		assert_eq!(string.try_get(3, 0, gc).unwrap().as_str(), "");
+		assert_eq!(string.try_set(0, 3, &string, &opts, gc).unwrap().as_str(), "abc");
+		assert_eq!(list.try_get(1, 2, gc).unwrap().len(), 2);
+		assert_eq!(list.try_set(3, 0, &list, &opts, gc).unwrap().len(), 6);
+	})
+}
+
+#[test]
+fn get_and_set_at_integer_limits() {
+	let opts = Options::default();
+	let max = Integer::max(&opts).inner();
+
+	for container in [r#""abc""#, "+@123"] {
+		// Positions past `u32::MAX` only fit in a `usize` on 64-bit targets.
+		let wide = if cfg!(target_pointer_width = "64") { max } else { i32::MAX.into() };
+
+		for (start, len) in [(wide, 1), (1, wide), (wide, wide), (3, wide), (i32::MAX.into(), 1)] {
+			let get = format!("GET {container} {start} {len}");
+			let set = format!("SET {container} {start} {len} {container}");
+			assert!(is_out_of_bounds(run(&get, &opts)), "{get}");
+			assert!(is_out_of_bounds(run(&set, &opts)), "{set}");
+		}
+
+		// Negative positions and lengths are never valid, no matter how large.
+		let min = format!("(- ~{max} 1)");
+		for (start, len) in [(&*min, "1"), ("0", &*min), ("~1", "1"), ("0", "~1")] {
+			let get = format!("GET {container} {start} {len}");
+			let set = format!("SET {container} {start} {len} {container}");
+			assert!(is_domain_error(run(&get, &opts)), "{get}");
+			assert!(is_domain_error(run(&set, &opts)), "{set}");
+		}
+
+		assert_eq!(run(&format!("LENGTH GET {container} 3 0"), &opts).ok().as_deref(), Some("0"));
+		assert_eq!(run(&format!("LENGTH SET {container} 0 3 @"), &opts).ok().as_deref(), Some("0"));
+	}
+}
+
+#[test]
+#[cfg(feature = "extensions")]
+fn negative_indices_at_integer_limits() {
+	let mut opts = Options::default();
+	opts.extensions.negative_indexing = true;
+	let max = Integer::max(&opts).inner();
+
+	assert_eq!(run(r#"GET "abc" ~3 1"#, &opts).ok().as_deref(), Some("a"));
+	for start in [format!("~{max}"), format!("(- ~{max} 1)"), "~4".to_owned()] {
+		let get = format!(r#"GET "abc" {start} 1"#);
+		assert!(is_domain_error(run(&get, &opts)), "{get}");
+	}
+}
@@ -0,0 +1,116 @@
+//! Tests for how custom types take part in equality and hashing.
+//!
+//! Run via `cargo test -p knightrs-bytecode --features custom-types --test custom_types`.
+#![cfg(feature = "custom-types")]
+
+use knightrs_bytecode::value::{Custom, CustomType, Integer, List, Value};
+use knightrs_bytecode::{Environment, Gc, Options};
+use std::hash::{BuildHasher, Hash, Hasher, RandomState};
+
+// A temperature, which is equal to integers of the same value, and to other temperatures.
+#[derive(Debug)]
+struct Celsius(i64);
+
+impl<'gc> CustomType<'gc> for Celsius {
+	fn eql(&self, rhs: Value<'gc>) -> bool {
+		if let Some(custom) = rhs.as_custom() {
+			return format!("{custom:?}") == format!("{self:?}");
+		}
+
+		rhs.as_integer().is_some_and(|int| int.inner() == self.0)
+	}
+
+	fn hash(&self, mut state: &mut dyn Hasher) {
+		Value::from(Integer::new_unvalidated(self.0)).hash(&mut state);
+	}
+}
+
+// Claims to be equal to everything that's equal to `1`, so it hashes the same as `1` does.
+#[derive(Debug)]
+struct LikeOne;
+
+impl<'gc> CustomType<'gc> for LikeOne {
+	fn eql(&self, rhs: Value<'gc>) -> bool {
+		rhs == Value::from(Integer::new_unvalidated(1))
+	}
+
+	fn hash(&self, mut state: &mut dyn Hasher) {
+		Value::from(Integer::new_unvalidated(1)).hash(&mut state);
+	}
+}
+
+fn with_gc(func: impl FnOnce(&Gc)) {
+	// SAFETY: none of the tests let anything allocated by the `gc` escape.
+	unsafe { Gc::default().run(func) }
+}
+
+fn hash_of(value: Value<'_>, state: &RandomState) -> u64 {
+	let mut hasher = state.build_hasher();
+	value.hash(&mut hasher);
+	hasher.finish()
+}
+
+#[test]
+fn equality_is_symmetric() {
+	with_gc(|gc| {
+		let mut env = Environment::new(Options::default(), gc);
+		let one = Value::from(Integer::new_unvalidated(1));
+		let two = Value::from(Integer::new_unvalidated(2));
+		let celsius = Value::from(*Custom::new(Celsius(1), gc));
+		let like_one = Value::from(*Custom::new(LikeOne, gc));
+
+		for (lhs, rhs, equal) in [
+			(celsius, one, true),
+			(celsius, two, false),
+			(celsius, celsius, true),
+			(celsius, Value::from(*Custom::new(Celsius(1), gc)), true),
+			(celsius, Value::from(*Custom::new(Celsius(2), gc)), false),
+			(like_one, one, true),
+			(like_one, two, false),
+			(like_one, like_one, true),
+			(like_one, Value::from(*Custom::new(LikeOne, gc)), true),
+			// `Celsius` doesn't agree that it's equal to `LikeOne`.
+			(like_one, celsius, false),
+		] {
+			assert_eq!(lhs == rhs, equal, "{lhs:?} == {rhs:?}");
+			assert_eq!(rhs == lhs, equal, "{rhs:?} == {lhs:?}");
+			assert_eq!(lhs.kn_equals(&rhs, &mut env).unwrap(), equal, "? {lhs:?} {rhs:?}");
+			assert_eq!(rhs.kn_equals(&lhs, &mut env).unwrap(), equal, "? {rhs:?} {lhs:?}");
+		}
+	})
+}
+
+#[test]
+fn customs_in_lists_are_compared() {
+	with_gc(|gc| {
+		let mut env = Environment::new(Options::default(), gc);
+		let one = Value::from(Integer::new_unvalidated(1));
+		let celsius = Value::from(*Custom::new(Celsius(1), gc));
+
+		let ints = List::from_slice_unvalidated(&[Value::TRUE, one], gc).as_value();
+		let customs = List::from_slice_unvalidated(&[Value::TRUE, celsius], gc).as_value();
+
+		assert!(ints == customs && customs == ints);
+		assert!(ints.kn_equals(&customs, &mut env).unwrap());
+		assert!(customs.kn_equals(&ints, &mut env).unwrap());
+	})
+}
+
+#[test]
+fn equal_values_hash_the_same() {
+	with_gc(|gc| {
+		let state = RandomState::new();
+		let one = Value::from(Integer::new_unvalidated(1));
+		let celsius = Value::from(*Custom::new(Celsius(1), gc));
+		let other = Value::from(*Custom::new(Celsius(1), gc));
+		let like_one = Value::from(*Custom::new(LikeOne, gc));
+
+		assert_eq!(hash_of(celsius, &state), hash_of(one, &state));
+		assert_eq!(hash_of(celsius, &state), hash_of(other, &state));
+		assert_eq!(hash_of(like_one, &state), hash_of(one, &state));
+
+		let ints = List::from_slice_unvalidated(&[one, one], gc).as_value();
+		let customs = List::from_slice_unvalidated(&[celsius, other], gc).as_value();
+		assert_eq!(hash_of(ints, &state), hash_of(customs, &state));
+	})
+}
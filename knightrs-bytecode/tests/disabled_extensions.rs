@@ -0,0 +1,37 @@
+//! Extensions which are enabled stay off when `compliance.disable_all_extensions` is set.
+
+#![cfg(all(feature = "extensions", feature = "compliance"))]
+
+use knightrs_bytecode::testing::run;
+use knightrs_bytecode::Options;
+
+// `opts`, and then `opts` with every extension disabled.
+fn enabled_and_disabled(opts: Options) -> [Options; 2] {
+	let mut disabled = opts.clone();
+	disabled.compliance.disable_all_extensions = true;
+	[opts, disabled]
+}
+
+#[test]
+fn length_of_anything() {
+	let mut opts = Options::default();
+	opts.compliance.strict_conversions = true;
+	opts.extensions.builtin_fns.length_of_anything = true;
+	let [enabled, disabled] = enabled_and_disabled(opts);
+
+	assert_eq!(run("OUTPUT LENGTH TRUE", "", enabled).stdout, "1\n");
+	assert!(run("OUTPUT LENGTH TRUE", "", disabled).error.is_some());
+}
+
+#[test]
+fn extension_names() {
+	let mut opts = Options::default();
+	opts.extensions.functions.type_of = true;
+	opts.extensions.case_insensitive_extensions = true;
+	opts.extensions.extension_aliases.insert("KIND".to_string(), "TYPEOF".to_string());
+	let [enabled, disabled] = enabled_and_disabled(opts);
+
+	let source = "; OUTPUT Xtypeof 1 : OUTPUT XKind TRUE";
+	assert_eq!(run(source, "", enabled).stdout, "Integer\nBoolean\n");
+	assert!(run(source, "", disabled).error.is_some());
+}
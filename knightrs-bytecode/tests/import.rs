@@ -0,0 +1,54 @@
+//! `XIMPORT`, which compiles files in place with every variable in them prefixed.
+
+#![cfg(feature = "extensions")]
+
+use knightrs_bytecode::parser::{source_location::ProgramSource, ParseErrorKind, Parser};
+use knightrs_bytecode::vm::Vm;
+use knightrs_bytecode::{Environment, Error, Gc, Options};
+
+const MODULE: &str = "; = square BLOCK * x x : = x 3";
+
+// Runs `source`, where `XIMPORT` of any file gives `MODULE`, and returns what it output.
+fn run(source: &str) -> Result<String, String> {
+	let mut opts = Options::default();
+	opts.extensions.functions.import = true;
+	let mut stdout = Vec::new();
+
+	// SAFETY: nothing allocated by the `gc` escapes it.
+	let result = unsafe {
+		Gc::default().run(|gc| {
+			let mut env = Environment::new(opts, gc);
+			env.set_stdout(&mut stdout);
+			env.set_read_file(|_| Ok(MODULE.to_string()));
+
+			let program = Parser::new(&mut env, ProgramSource::ExprFlag, source)
+				.and_then(|parser| parser.parse_program())?;
+			Vm::new(&program, &mut env).run_entire_program_without_argv().and(Ok(()))
+		})
+	};
+
+	result.map_err(|err: Error| err.to_string())?;
+	Ok(String::from_utf8(stdout).unwrap())
+}
+
+#[test]
+fn imported_variables_are_prefixed() {
+	let output = run(r#"; XIMPORT "m.kn" "m" ; = x 5 : OUTPUT + m_x CALL m_square"#);
+	assert_eq!(output.unwrap(), "12\n");
+}
+
+#[test]
+fn each_import_is_separate() {
+	let source =
+		r#"; XIMPORT "m.kn" "a" ; XIMPORT "m.kn" "b" ; = b_x 4 : OUTPUT + a_x CALL b_square"#;
+	assert_eq!(run(source).unwrap(), "19\n");
+}
+
+#[test]
+fn prefixes_must_be_lowercase_variable_names() {
+	for prefix in ["", "M", "m-1", "m n"] {
+		let err = run(&format!(r#"XIMPORT "m.kn" "{prefix}""#)).unwrap_err();
+		let expected = ParseErrorKind::InvalidImportPrefix(prefix.to_string()).to_string();
+		assert!(err.contains(&expected), "{prefix:?}: {err}");
+	}
+}
@@ -0,0 +1,192 @@
+//! Tests for the unsafe parts of the crate: allocating strings and lists, marking and sweeping them,
+//! and packing values into (and out of) their tagged representation.
+//!
+//! These are small enough to be run under [Miri](https://github.com/rust-lang/miri), which is what
+//! they're for, via:
+//! ```sh
+//! cargo +nightly miri test -p knightrs-bytecode --features miri-compat --test miri
+//! ```
+//! (`miri-compat` keeps the `Gc` from preallocating thousands of values each time it's made.) They
+//! also run as part of a normal `cargo test`.
+
+use knightrs_bytecode::parser::{source_location::ProgramSource, Parser};
+use knightrs_bytecode::value::{Integer, KnString, List, Value};
+use knightrs_bytecode::vm::Vm;
+use knightrs_bytecode::{Environment, Gc, Options};
+
+// Long enough that it isn't embedded within its `ValueInner`, and has to be allocated separately.
+const LONG: &str = "this string is far too long to fit in a single value";
+
+// Runs `func` with a fresh `Gc`, which is shut down (freeing everything in it) afterwards.
+fn with_gc(func: impl FnOnce(&Gc)) {
+	// SAFETY: none of the tests let anything allocated by the `gc` escape.
+	unsafe { Gc::default().run(func) }
+}
+
+fn string_of(value: Value<'_>) -> String {
+	value.as_knstring().expect("not a string").as_str().to_owned()
+}
+
+#[test]
+fn string_round_trips() {
+	with_gc(|gc| {
+		for source in ["", "a", "hello", LONG] {
+			let string = KnString::new_unvalidated(source.to_owned(), gc);
+			assert_eq!(string.as_str(), source);
+
+			let value = string.as_value();
+			assert_eq!(string_of(value), source);
+			assert!(value.as_list().is_none() && value.as_integer().is_none());
+		}
+	})
+}
+
+#[test]
+fn list_round_trips() {
+	with_gc(|gc| {
+		let one = Value::from(Integer::new_unvalidated(1));
+		let long = KnString::new_unvalidated(LONG.to_owned(), gc);
+
+		// Lists of one element are embedded within their `ValueInner`; longer ones aren't.
+		for len in [1, 2, 50] {
+			let mut elements = vec![one; len];
+			elements[len - 1] = long.as_value();
+
+			let list = List::from_slice_unvalidated(&elements, gc);
+			assert_eq!(list.len(), len);
+			assert!(list.iter().take(len - 1).all(|element| element.as_integer() == one.as_integer()));
+			assert_eq!(string_of(list.get(len - 1).unwrap()), LONG);
+
+			let value = list.as_value();
+			assert_eq!(value.as_list().unwrap().len(), len);
+			assert!(value.as_knstring().is_none());
+		}
+	})
+}
+
+#[test]
+fn immediate_values_round_trip() {
+	let opts = Options::default();
+	let small = [0, 1, -1, 12345].map(Integer::new_unvalidated);
+	for int in small.into_iter().chain([Integer::max(&opts), Integer::min(&opts)]) {
+		let value = Value::from(int);
+		assert_eq!(value.as_integer().map(Integer::inner), Some(int.inner()));
+		assert!(value.as_boolean().is_none() && value.as_knstring().is_none());
+	}
+
+	assert_eq!(Value::from(true).as_boolean(), Some(true));
+	assert_eq!(Value::from(false).as_boolean(), Some(false));
+	assert!(Value::NULL.is_null() && Value::NULL.as_list().is_none());
+	assert!(Value::TRUE.as_integer().is_none() && Value::TRUE.as_knstring().is_none());
+}
+
+#[test]
+fn blocks_round_trip() {
+	with_gc(|gc| {
+		let mut env = Environment::new(Options::default(), gc);
+		let program = Parser::new(&mut env, ProgramSource::ExprFlag, "; BLOCK 1 : BLOCK 2")
+			.and_then(|parser| parser.parse_program())
+			.unwrap();
+
+		let value = Vm::new(&program, &mut env).run_entire_program_without_argv().unwrap();
+		let block = value.as_block().unwrap();
+		assert_eq!(block.program(), program.id());
+		assert_eq!(Value::from(block).as_block(), Some(block));
+		assert!(value.as_integer().is_none() && value.as_list().is_none());
+	})
+}
+
+#[test]
+fn rooted_values_survive_collection() {
+	with_gc(|gc| {
+		let long = KnString::new_unvalidated(LONG.to_owned(), gc);
+		let inner = List::from_slice_unvalidated(&[long.as_value(), Value::TRUE], gc);
+
+		// Only the outer list is rooted, so everything else has to be found by marking it.
+		let outer = List::from_slice_unvalidated(&[inner.as_value(), long.as_value()], gc);
+		drop((long, inner));
+
+		// SAFETY: `outer` is rooted, and nothing else is used afterwards.
+		unsafe { gc.mark_and_sweep() };
+		unsafe { gc.mark_and_sweep() };
+
+		let nested = outer.get(0).unwrap().as_list().unwrap();
+		assert_eq!(string_of(nested.get(0).unwrap()), LONG);
+		assert_eq!(nested.get(1).unwrap().as_boolean(), Some(true));
+		assert_eq!(string_of(outer.get(1).unwrap()), LONG);
+	})
+}
+
+#[test]
+fn unrooted_values_are_swept() {
+	with_gc(|gc| {
+		let kept = KnString::new_unvalidated(LONG.to_owned(), gc);
+
+		// Make more values than the `Gc` starts out with, so it has to grow.
+		for index in 0..40 {
+			let string = KnString::new_unvalidated(format!("{LONG} {index}"), gc);
+			let list = List::from_slice_unvalidated(&[string.as_value(), kept.as_value()], gc);
+			assert_eq!(list.len(), 2);
+		}
+
+		// SAFETY: only `kept` is used afterwards, which is rooted. Everything swept here is freed
+		// again by the `Gc`'s shutdown, which mustn't double-free them.
+		unsafe { gc.mark_and_sweep() };
+
+		// The `Gc` is still usable after a sweep.
+		let after = KnString::new_unvalidated(format!("{LONG}!"), gc);
+		assert_eq!(after.as_str(), format!("{LONG}!"));
+		assert_eq!(kept.as_str(), LONG);
+	})
+}
+
+// Runs `source` in a new `Environment`, returning the address of the string it evaluates to.
+fn string_address(source: &str, gc: &Gc) -> usize {
+	let mut env = Environment::new(Options::default(), gc);
+	let program = Parser::new(&mut env, ProgramSource::ExprFlag, source)
+		.and_then(|parser| parser.parse_program())
+		.unwrap();
+	let value = Vm::new(&program, &mut env).run_entire_program_without_argv().unwrap();
+	value.as_knstring().unwrap().as_str().as_ptr() as usize
+}
+
+#[test]
+fn short_string_literals_are_shared() {
+	with_gc(|gc| {
+		let short = string_address("'hello'", gc);
+		assert_eq!(short, string_address("; 1 'hello'", gc));
+
+		let long = format!("'{LONG}{LONG}'");
+		assert_ne!(string_address(&long, gc), string_address(&long, gc));
+	});
+
+	// Each `Gc` has its own pool, which is freed along with it (which Miri checks for leaks).
+	with_gc(|gc| {
+		string_address("'hello'", gc);
+	});
+}
+
+#[test]
+fn programs_run() {
+	with_gc(|gc| {
+		let mut env = Environment::new(Options::default(), gc);
+		let source = r#"
+			; = list @
+			; = i 0
+			; WHILE < i 10
+				; = list + list ,(* "ab" i)
+				: = i + i 1
+			: ^ SET list 0 2 ,"x" "-"
+		"#;
+
+		let program = Parser::new(&mut env, ProgramSource::ExprFlag, source)
+			.and_then(|parser| parser.parse_program())
+			.unwrap();
+		let value = Vm::new(&program, &mut env).run_entire_program_without_argv().unwrap();
+		assert_eq!(
+			string_of(value),
+			"x-abab-ababab-abababab-ababababab-abababababab-ababababababab-abababababababab-\
+			 ababababababababab"
+		);
+	})
+}
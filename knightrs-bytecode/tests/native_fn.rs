@@ -0,0 +1,49 @@
+//! Native functions which allocate what they return, run with a `Gc` small enough that it
+//! collects constantly.
+//!
+//! Run via `cargo test -p knightrs-bytecode --features custom-types --test native_fn`.
+#![cfg(feature = "custom-types")]
+
+use knightrs_bytecode::gc::GcOptions;
+use knightrs_bytecode::parser::{source_location::ProgramSource, Parser};
+use knightrs_bytecode::value::KnString;
+use knightrs_bytecode::vm::Vm;
+use knightrs_bytecode::{Environment, Gc, Options, Value};
+
+// Runs `source`, where `$` is a native function that returns a freshly allocated string, and
+// returns what it output.
+fn run(source: &str) -> String {
+	let mut stdout = Vec::new();
+	let mut gc_opts = GcOptions::default();
+	gc_opts.starting_cap = 1;
+
+	// SAFETY: nothing allocated by the `gc` escapes it.
+	unsafe {
+		Gc::new(gc_opts).run(|gc| {
+			let mut env = Environment::new(Options::default(), gc);
+			env.set_stdout(&mut stdout);
+
+			let greet = env.register_native_fn(|_, env| {
+				let string = "a string which is long enough that it has to be allocated";
+				let string = KnString::new(string.to_string(), env.opts(), env.gc())?;
+				Ok(string.as_value().root(env.gc()))
+			});
+			env.add_parse_fn(move |parser| {
+				Ok(parser.advance_if(|c| c == '$').map(|_| Value::from(greet).root(parser.gc())))
+			});
+
+			let program = Parser::new(&mut env, ProgramSource::ExprFlag, source)
+				.and_then(|parser| parser.parse_program())
+				.unwrap();
+			Vm::new(&program, &mut env).run_entire_program_without_argv().unwrap();
+		})
+	}
+
+	String::from_utf8(stdout).unwrap()
+}
+
+#[test]
+fn results_survive_collections() {
+	let output = run("; = i 0 : WHILE < i 100 ; = i + i 1 : OUTPUT LENGTH + CALL $ CALL $");
+	assert_eq!(output, "114\n".repeat(100));
+}
@@ -0,0 +1,97 @@
+//! Programs which fail in every way we could think of, none of which should panic.
+
+#![cfg(feature = "embedded")]
+
+use knightrs_bytecode::{testing, Options};
+
+fn assert_no_panics(programs: &[impl AsRef<str>], opts: &Options) {
+	for program in programs.iter().map(AsRef::as_ref) {
+		let result = std::panic::catch_unwind(|| testing::run(program, "", opts.clone()));
+		assert!(result.is_ok(), "{program:?} panicked");
+	}
+}
+
+fn programs() -> Vec<String> {
+	let mut programs: Vec<String> = [
+		"QUIT 99999999999",
+		"QUIT - 0 1",
+		"* \"a\" 999999999999",
+		"* @ 999999999999",
+		"GET \"abc\" 2 4611686018427387903",
+		"SET @ 4611686018427387903 1 @",
+		"GET @ 0 - 0 1",
+		"+ 1 BLOCK 1",
+		"OUTPUT BLOCK 1",
+		"! BLOCK 1",
+		", BLOCK 1",
+		"ASCII 99999999",
+		"ASCII - 0 1",
+		"[ \"\"",
+		"] @",
+		"/ 1 0",
+		"% 1 0",
+		"^ 0 - 0 1",
+		"+ 0 \"99999999999999999999999\"",
+		"* 4611686018427387903 4611686018427387903",
+		"9999999999999999999999",
+		"\"abc",
+		")",
+		"(",
+		"",
+		"- \"a\" \"b\"",
+		"CALL 3",
+		"VALUE \"\"",
+		"EVAL \"EVAL\"",
+		"= OUTPUT 1",
+		"XBREAK",
+		"XCONTINUE",
+		"XNOPE 1",
+		"CALL EVAL \"BLOCK 1\"",
+		"; = f BLOCK 1 : EVAL \"CALL f\"",
+	]
+	.map(String::from)
+	.into();
+
+	programs.push("! ".repeat(100) + "TRUE");
+	programs
+}
+
+#[test]
+fn defaults_dont_panic() {
+	assert_no_panics(&programs(), &Options::default());
+}
+
+#[test]
+fn extensions_dont_panic() {
+	let mut extensions = Options::default();
+	let flags = ["extensions.syntax.control-flow", "extensions.functions.eval"];
+	extensions.apply_str_flags(&flags).unwrap();
+	assert_no_panics(&programs(), &extensions);
+}
+
+// Extensions that aren't implemented yet only stop panicking with `deny-panics`.
+#[test]
+#[cfg(feature = "deny-panics")]
+fn unimplemented_extensions_dont_panic() {
+	let mut unimplemented = Options::default();
+	unimplemented.extensions.syntax.string_interpolation = true;
+	unimplemented.extensions.builtin_fns.string = true;
+	unimplemented.extensions.builtin_fns.list = true;
+	unimplemented.extensions.builtin_fns.integer = true;
+	unimplemented.extensions.breaking.negate_reverses_collections = true;
+
+	let programs = [
+		"OUTPUT 1.5",
+		"`a`",
+		"- \"a\" \"b\"",
+		"- @ @",
+		"* @ BLOCK 1",
+		"/ \"a\" \"b\"",
+		"/ @ BLOCK 1",
+		"% @ BLOCK 1",
+		"[ 12",
+		"] 12",
+		"~ 1",
+	];
+	assert_no_panics(&programs, &unimplemented);
+}
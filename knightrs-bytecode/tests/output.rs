@@ -0,0 +1,120 @@
+//! `OUTPUT`'s newlines, and when what it writes is flushed.
+
+use knightrs_bytecode::env::{FlushPolicy, Newline, Output};
+use knightrs_bytecode::parser::{source_location::ProgramSource, Parser};
+use knightrs_bytecode::testing::run;
+use knightrs_bytecode::vm::Vm;
+use knightrs_bytecode::{Environment, Gc, Options};
+use std::cell::RefCell;
+use std::io::{self, BufRead, Read, Write};
+use std::rc::Rc;
+
+#[test]
+fn crlf_newlines() {
+	let mut opts = Options::default();
+	opts.io.newline = Newline::CrLf;
+
+	let outcome = run(r#"; OUTPUT "a" ; OUTPUT "b\" : OUTPUT 1"#, "", opts);
+	assert_eq!(outcome.stdout, "a\r\nb1\r\n");
+}
+
+// A stdout whose contents can be inspected while something's writing to it.
+#[derive(Clone, Default)]
+struct Shared(Rc<RefCell<Vec<u8>>>);
+
+impl Shared {
+	fn contents(&self) -> String {
+		String::from_utf8(self.0.borrow().clone()).unwrap()
+	}
+}
+
+impl Write for Shared {
+	fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+		self.0.borrow_mut().extend_from_slice(bytes);
+		Ok(bytes.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+// Writes `"a"`, `"b\n"`, and then `"c"` under `policy`, returning what was written to stdout after
+// each, and then after the `Output` is dropped.
+fn visible_after_writes(policy: FlushPolicy) -> Vec<String> {
+	let stdout = Shared::default();
+	let mut output = Output::new(stdout.clone());
+	output.set_flush_policy(policy);
+
+	let mut visible = Vec::new();
+	for chunk in ["a", "b\n", "c"] {
+		output.write_all(chunk.as_bytes()).unwrap();
+		visible.push(stdout.contents());
+	}
+
+	drop(output);
+	visible.push(stdout.contents());
+	visible
+}
+
+#[test]
+fn flush_policies() {
+	assert_eq!(visible_after_writes(FlushPolicy::EveryOutput), ["a", "ab\n", "ab\nc", "ab\nc"]);
+	assert_eq!(visible_after_writes(FlushPolicy::Line), ["", "ab\n", "ab\n", "ab\nc"]);
+	assert_eq!(visible_after_writes(FlushPolicy::OnExit), ["", "", "", "ab\nc"]);
+	assert_eq!(visible_after_writes(FlushPolicy::Never), ["", "", "", "ab\nc"]);
+}
+
+// A stdin with no lines, which records what had been written to `stdout` when it was first read.
+struct Snapshot {
+	stdout: Shared,
+	seen: Rc<RefCell<Option<String>>>,
+}
+
+impl Read for Snapshot {
+	fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+		Ok(0)
+	}
+}
+
+impl BufRead for Snapshot {
+	fn fill_buf(&mut self) -> io::Result<&[u8]> {
+		self.seen.borrow_mut().get_or_insert_with(|| self.stdout.contents());
+		Ok(&[])
+	}
+
+	fn consume(&mut self, _: usize) {}
+}
+
+// Returns what had been written to stdout when `PROMPT` was run under `policy`.
+fn visible_at_prompt(policy: FlushPolicy) -> String {
+	let mut opts = Options::default();
+	opts.io.flush = policy;
+	let stdout = Shared::default();
+	let seen = Rc::new(RefCell::new(None));
+
+	// SAFETY: nothing allocated by the `gc` escapes it.
+	unsafe {
+		Gc::default().run(|gc| {
+			let mut env = Environment::new(opts, gc);
+			env.set_stdout(stdout.clone());
+			env.set_stdin(Snapshot { stdout: stdout.clone(), seen: seen.clone() });
+
+			let program = Parser::new(&mut env, ProgramSource::ExprFlag, r#"; OUTPUT "a\" PROMPT"#)
+				.and_then(|parser| parser.parse_program())
+				.unwrap();
+			Vm::new(&program, &mut env).run_entire_program_without_argv().unwrap();
+		})
+	}
+
+	let seen = seen.borrow_mut().take();
+	seen.expect("PROMPT read from stdin")
+}
+
+#[test]
+fn only_never_skips_flushing_before_prompt() {
+	for policy in [FlushPolicy::EveryOutput, FlushPolicy::Line, FlushPolicy::OnExit] {
+		assert_eq!(visible_at_prompt(policy), "a", "{policy:?}");
+	}
+	assert_eq!(visible_at_prompt(FlushPolicy::Never), "");
+}
@@ -0,0 +1,62 @@
+//! Programs only hold onto their ids while they're alive, and never run blocks that they didn't
+//! declare, even ones which have their id.
+
+use knightrs_bytecode::parser::{source_location::ProgramSource, Parser};
+use knightrs_bytecode::program::Program;
+use knightrs_bytecode::value::Block;
+use knightrs_bytecode::vm::Vm;
+use knightrs_bytecode::{Environment, Error, Gc, Options};
+use std::collections::HashSet;
+
+fn parse<'gc>(env: &mut Environment<'gc>, source: &'static str) -> Program<'static, 'static, 'gc> {
+	Parser::new(env, ProgramSource::ExprFlag, source)
+		.and_then(|parser| parser.parse_program())
+		.unwrap()
+}
+
+fn first_block(program: &Program<'_, '_, '_>) -> Block {
+	program.constants().iter().find_map(|constant| constant.as_block()).unwrap()
+}
+
+#[test]
+fn live_programs_have_different_ids() {
+	// SAFETY: nothing allocated by the `gc` escapes it.
+	unsafe {
+		Gc::default().run(|gc| {
+			let mut env = Environment::new(Options::default(), gc);
+			let programs = (0..1000).map(|_| parse(&mut env, "BLOCK 1")).collect::<Vec<_>>();
+			let ids = programs.iter().map(Program::id).collect::<HashSet<_>>();
+			assert_eq!(ids.len(), programs.len());
+
+			// Dropping programs frees their ids up, and more programs can still be made.
+			drop(programs);
+			for _ in 0..1000 {
+				let program = parse(&mut env, "BLOCK 1");
+				assert!(program.declares(first_block(&program)));
+			}
+		})
+	}
+}
+
+#[test]
+fn blocks_with_the_same_id_must_start_at_a_block() {
+	// SAFETY: nothing allocated by the `gc` escapes it.
+	unsafe {
+		Gc::default().run(|gc| {
+			let mut env = Environment::new(Options::default(), gc);
+			let program = parse(&mut env, "BLOCK 1");
+			let other = parse(&mut env, "; = a 1 ; = b 2 BLOCK + a b");
+
+			// A block with `program`'s id which doesn't start where any of its blocks do, as if
+			// `other` had been dropped and its id given to `program`.
+			let stray = Block::try_new(first_block(&other).inner(), program.id()).unwrap();
+			assert!(program.declares(first_block(&program)));
+			assert!(!program.declares(stray));
+			assert!(!program.declares(first_block(&other)));
+
+			let mut vm = Vm::new(&program, &mut env);
+			assert!(matches!(vm.run(stray), Err(Error::ForeignBlock)));
+			assert!(vm.run(first_block(&program)).is_ok());
+		})
+	}
+}
@@ -0,0 +1,96 @@
+//! `RANDOM`'s range under each option, and seeding it via `XSRAND` and `= RANDOM`.
+//!
+//! Programs here are seeded, so what `RANDOM` returns is the same each time they're run.
+
+#![cfg(all(feature = "extensions", feature = "embedded"))]
+
+use knightrs_bytecode::testing::run;
+use knightrs_bytecode::value::Integer;
+use knightrs_bytecode::Options;
+
+const DRAWS: usize = 1000;
+
+fn options() -> Options {
+	let mut opts = Options::default();
+	opts.extensions.functions.srand = true;
+	opts.extensions.builtin_fns.assign_to_random = true;
+	opts
+}
+
+// Runs `seed` (which should seed `RANDOM`), and then returns `DRAWS` random numbers.
+fn draws(seed: &str, opts: &Options) -> Vec<i64> {
+	let source = format!("; {seed} ; = i 0 : WHILE < i {DRAWS} ; OUTPUT RANDOM : = i + i 1");
+	let outcome = run(&source, "", opts.clone());
+	assert_eq!(outcome.error, None, "{source}");
+
+	outcome.stdout.lines().map(|line| line.parse().unwrap()).collect()
+}
+
+#[test]
+fn seeding_is_deterministic() {
+	let opts = options();
+	let seeded = draws("XSRAND 42", &opts);
+
+	assert_eq!(seeded.len(), DRAWS);
+	assert_eq!(seeded, draws("XSRAND 42", &opts));
+	assert_eq!(seeded, draws("= RANDOM 42", &opts));
+	assert_ne!(seeded, draws("XSRAND 43", &opts));
+
+	// Reseeding restarts the sequence.
+	let outcome = run("; XSRAND 7 ; = a RANDOM ; XSRAND 7 : OUTPUT ? a RANDOM", "", opts);
+	assert_eq!(outcome.stdout, "true\n");
+}
+
+#[test]
+fn xsrand_returns_its_seed() {
+	let outcome = run("; OUTPUT XSRAND 12 : OUTPUT = RANDOM 34", "", options());
+	assert_eq!(outcome.stdout, "12\n34\n");
+}
+
+#[test]
+fn random_is_nonnegative_by_default() {
+	let opts = options();
+	let max = Integer::max(&opts).inner();
+	let draws = draws("XSRAND 1", &opts);
+
+	assert!(draws.iter().all(|&draw| (0..=max).contains(&draw)));
+	assert!(draws.iter().any(|&draw| draw > 0x7FFF), "the range shouldn't be limited");
+}
+
+#[test]
+fn random_can_be_negative() {
+	let mut opts = options();
+	opts.extensions.breaking.random_can_be_negative = true;
+	let (min, max) = (Integer::min(&opts).inner(), Integer::max(&opts).inner());
+	let draws = draws("XSRAND 1", &opts);
+
+	assert!(draws.iter().all(|&draw| (min..=max).contains(&draw)));
+	assert!(draws.iter().any(|&draw| draw < 0));
+	assert!(draws.iter().any(|&draw| draw > 0));
+}
+
+#[test]
+#[cfg(feature = "compliance")]
+fn limit_rand_range_takes_precedence() {
+	for can_be_negative in [false, true] {
+		let mut opts = options();
+		opts.compliance.limit_rand_range = true;
+		opts.extensions.breaking.random_can_be_negative = can_be_negative;
+		let draws = draws("XSRAND 1", &opts);
+
+		assert!(draws.iter().all(|&draw| (0..=0x7FFF).contains(&draw)), "{can_be_negative}");
+		assert!(draws.iter().any(|&draw| draw > 0x3FFF), "{can_be_negative}");
+	}
+}
+
+#[test]
+#[cfg(feature = "compliance")]
+fn i32_integers_limit_random() {
+	let mut opts = options();
+	opts.compliance.i32_integer = true;
+	opts.extensions.breaking.random_can_be_negative = true;
+	let draws = draws("XSRAND 1", &opts);
+
+	assert!(draws.iter().all(|&draw| i32::try_from(draw).is_ok()));
+	assert!(draws.iter().any(|&draw| draw < 0));
+}
@@ -0,0 +1,27 @@
+//! Environments which read from the process's stdin shouldn't stop other threads from using it.
+
+use knightrs_bytecode::{Environment, Gc, Options};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn environments_dont_hold_stdin_locked() {
+	// SAFETY: nothing allocated by the `gc` escapes it.
+	unsafe {
+		Gc::default().run(|gc| {
+			let _env = Environment::new(Options::default(), gc);
+
+			// If the environment held the lock, this would block until it was dropped.
+			let (sender, receiver) = mpsc::channel();
+			thread::spawn(move || {
+				drop(std::io::stdin().lock());
+				let _ = sender.send(());
+			});
+
+			receiver
+				.recv_timeout(Duration::from_secs(10))
+				.expect("another thread couldn't lock stdin while an environment existed");
+		})
+	}
+}
@@ -0,0 +1,149 @@
+//! Checking programs' output against what it's expected to be, under every engine.
+
+use crate::{Engine, Outcome, Profile};
+use std::fmt::{self, Display, Formatter, Write};
+
+/// Runs `source` under every [`Engine`] with the [default profile](Profile::Default), and checks
+/// that its output is `expected`.
+///
+/// This is the same as [`golden_with_profile`] with [`Profile::Default`].
+///
+/// # Examples
+/// ```
+/// # use knightrs_conformance::{golden, Engine};
+/// golden("; DUMP 1 : OUTPUT 'hi'", "1hi\n").unwrap();
+///
+/// let mismatch = golden("; OUTPUT 1 ; OUTPUT 2 : OUTPUT 3", "1\n4\n3\n").unwrap_err();
+/// assert_eq!(mismatch.engine, Engine::Ast);
+/// assert_eq!(mismatch.diff(), "  1\n- 4\n+ 2\n  3\n");
+/// ```
+pub fn golden(source: &str, expected: &str) -> Result<Outcome, GoldenMismatch> {
+	golden_with_profile(source, expected, Profile::Default)
+}
+
+/// Runs `source` under every [`Engine`] with `profile` (and an empty stdin), and checks that what
+/// each one writes via `OUTPUT` and `DUMP` is `expected`.
+///
+/// Before they're compared, `\r\n`s in both the output and `expected` are replaced with `\n`s, so
+/// golden files don't depend on how they were checked out. Programs which exit via `QUIT` are
+/// fine, as long as their output matches; programs which error never match.
+///
+/// On success, the [`Outcome`] is returned (with its `stdout` normalized); every engine's is the
+/// same. Otherwise, the returned [`GoldenMismatch`] is for the first engine that didn't match, and
+/// displays a diff of its output, along with the error (if any).
+pub fn golden_with_profile(
+	source: &str,
+	expected: &str,
+	profile: Profile,
+) -> Result<Outcome, GoldenMismatch> {
+	let expected = normalize_newlines(expected);
+	let mut matched = Outcome::default();
+
+	for engine in Engine::ALL {
+		let mut outcome = engine.run(source, "", profile);
+		outcome.stdout = normalize_newlines(&outcome.stdout);
+
+		if outcome.stdout != expected || outcome.error.is_some() {
+			return Err(GoldenMismatch { engine, expected, outcome });
+		}
+
+		matched = outcome;
+	}
+
+	Ok(matched)
+}
+
+fn normalize_newlines(text: &str) -> String {
+	text.replace("\r\n", "\n")
+}
+
+/// The error returned by [`golden`] and [`golden_with_profile`] when a program's output isn't what
+/// was expected under an engine, or the program errored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoldenMismatch {
+	/// The engine whose output didn't match.
+	pub engine: Engine,
+
+	/// The expected output, with its newlines normalized.
+	pub expected: String,
+
+	/// What actually happened, with its `stdout`'s newlines normalized.
+	pub outcome: Outcome,
+}
+
+impl GoldenMismatch {
+	/// Returns a line-by-line diff of the expected and actual output.
+	///
+	/// Lines that are only in the expected output start with `- `, lines only in the actual output
+	/// start with `+ `, and lines in both start with two spaces. A line that's missing its trailing
+	/// newline is followed by `\ no newline at end`.
+	#[must_use]
+	pub fn diff(&self) -> String {
+		let expected = self.expected.split_inclusive('\n').collect::<Vec<_>>();
+		let actual = self.outcome.stdout.split_inclusive('\n').collect::<Vec<_>>();
+
+		// `common[i][j]` is the length of the longest common subsequence of `expected[i..]` and
+		// `actual[j..]`.
+		let mut common = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+		for i in (0..expected.len()).rev() {
+			for j in (0..actual.len()).rev() {
+				common[i][j] = if expected[i] == actual[j] {
+					common[i + 1][j + 1] + 1
+				} else {
+					common[i + 1][j].max(common[i][j + 1])
+				};
+			}
+		}
+
+		let mut diff = String::new();
+		let mut line = |prefix: char, line: &str| {
+			let _ = match line.strip_suffix('\n') {
+				Some(line) => writeln!(diff, "{prefix} {line}"),
+				None => writeln!(diff, "{prefix} {line}\n\\ no newline at end"),
+			};
+		};
+
+		let (mut i, mut j) = (0, 0);
+		while i < expected.len() || j < actual.len() {
+			let removed =
+				j == actual.len() || (i < expected.len() && common[i + 1][j] >= common[i][j + 1]);
+
+			if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+				line(' ', expected[i]);
+				i += 1;
+				j += 1;
+			} else if removed {
+				line('-', expected[i]);
+				i += 1;
+			} else {
+				line('+', actual[j]);
+				j += 1;
+			}
+		}
+
+		diff
+	}
+}
+
+impl Display for GoldenMismatch {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		if self.expected == self.outcome.stdout {
+			write!(f, "{:?}'s output matched, but ", self.engine)?;
+		} else {
+			writeln!(f, "{:?}'s output didn't match (- expected, + actual):", self.engine)?;
+			write!(f, "{}", self.diff().trim_end_matches('\n'))?;
+
+			if self.outcome.error.is_some() {
+				writeln!(f)?;
+			}
+		}
+
+		if let Some(error) = &self.outcome.error {
+			write!(f, "the program errored: {error}")?;
+		}
+
+		Ok(())
+	}
+}
+
+impl std::error::Error for GoldenMismatch {}
@@ -0,0 +1,508 @@
+//! Knight programs, along with what they should output, that every engine in this workspace is
+//! checked against.
+//!
+//! `tests/conformance.rs` runs each [`Case`] in [`CORPUS`] under every [`Engine`] and [`Profile`],
+//! and checks that it writes what it should and exits with the right status. Since the engines are
+//! developed separately, this is what keeps them from drifting apart.
+//!
+//! Some differences are known (and accepted for now). Those are recorded in the case with
+//! [`diverges!`], and then the test checks that the engine _doesn't_ conform, so that the
+//! divergence can't silently go away (or linger after it's been fixed).
+//!
+//! For checking other programs against their expected output under every engine, see [`golden`].
+
+mod golden;
+pub use golden::{golden, golden_with_profile, GoldenMismatch};
+
+/// An engine that Knight programs can be run with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Engine {
+	/// The tree-walking interpreter, `knightrs`.
+	Ast,
+
+	/// The bytecode interpreter, `knightrs-bytecode`.
+	Bytecode,
+}
+
+impl Engine {
+	/// Every engine.
+	pub const ALL: [Self; 2] = [Self::Ast, Self::Bytecode];
+
+	/// Runs `source` with `self` under `profile`, using `stdin` as the input to `PROMPT`.
+	///
+	/// Nothing is read from the real stdin or written to the real stdout, and `QUIT` doesn't exit
+	/// the process.
+	#[must_use]
+	pub fn run(self, source: &str, stdin: &str, profile: Profile) -> Outcome {
+		match self {
+			Self::Ast => {
+				let outcome = knightrs::testing::run(source, stdin, &profile.ast_flags());
+				Outcome { stdout: outcome.stdout, status: outcome.status, error: outcome.error }
+			}
+			Self::Bytecode => {
+				let opts = profile.bytecode_options();
+				let outcome = knightrs_bytecode::testing::run(source, stdin, opts);
+				Outcome { stdout: outcome.stdout, status: outcome.status, error: outcome.error }
+			}
+		}
+	}
+}
+
+/// The observable result of running a program via [`Engine::run`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Outcome {
+	/// Everything that was written by `OUTPUT` and `DUMP`.
+	pub stdout: String,
+
+	/// The exit status: `0` if the program finished normally, the status passed to `QUIT`, or `1` if
+	/// an error occurred.
+	pub status: i32,
+
+	/// The error's message, if the program didn't finish normally (or via `QUIT`).
+	pub error: Option<String>,
+}
+
+/// The options that a program is run with.
+///
+/// Each engine has its own options type, so profiles are just names, which [`Profile::ast_flags`]
+/// and [`Profile::bytecode_options`] give the meaning of for each engine. Programs in the corpus
+/// are well-defined, so they should behave the same regardless of the profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+	/// The engine's default options.
+	Default,
+
+	/// Every compliance check is enabled.
+	StrictCompliance,
+
+	/// The extensions that both engines support are enabled.
+	Extensions,
+
+	/// Both [`StrictCompliance`](Self::StrictCompliance) and [`Extensions`](Self::Extensions).
+	StrictExtensions,
+}
+
+impl Profile {
+	/// Every profile.
+	pub const ALL: [Self; 4] =
+		[Self::Default, Self::StrictCompliance, Self::Extensions, Self::StrictExtensions];
+
+	/// Whether extensions are enabled in this profile.
+	#[must_use]
+	pub const fn has_extensions(self) -> bool {
+		matches!(self, Self::Extensions | Self::StrictExtensions)
+	}
+
+	/// Whether every compliance check is enabled in this profile.
+	#[must_use]
+	pub const fn is_strict(self) -> bool {
+		matches!(self, Self::StrictCompliance | Self::StrictExtensions)
+	}
+
+	/// The [`knightrs`] flags that this profile means.
+	#[must_use]
+	pub fn ast_flags(self) -> knightrs::env::Flags {
+		let mut flags = knightrs::env::Flags::default();
+
+		if self.is_strict() {
+			let compliance = &mut flags.compliance;
+			compliance.knight_encoding = true;
+			compliance.check_overflow = true;
+			compliance.i32_integer = true;
+			compliance.check_quit_bounds = true;
+			compliance.forbid_trailing_tokens = true;
+			compliance.verify_variable_names = true;
+			compliance.check_call_arg = true;
+			compliance.limit_rand_range = true;
+			compliance.check_equals_params = true;
+			compliance.check_container_length = true;
+			compliance.check_integer_function_bounds = true;
+			compliance.strict_conversions = true;
+		}
+
+		if self.has_extensions() {
+			let extensions = &mut flags.extensions;
+			extensions.functions.eval = true;
+			extensions.functions.value = true;
+			extensions.functions.xapply = true;
+			extensions.functions.xtypeof = true;
+			extensions.functions.split = true;
+			extensions.functions.assert = true;
+			extensions.functions.xsrand = true;
+			extensions.functions.xrandom = true;
+			extensions.negative_indexing = true;
+		}
+
+		flags
+	}
+
+	/// The [`knightrs_bytecode`] options that this profile means.
+	#[must_use]
+	pub fn bytecode_options(self) -> knightrs_bytecode::Options {
+		let mut opts = knightrs_bytecode::Options::default();
+
+		if self.is_strict() {
+			opts.check_variables = true;
+			opts.check_parens = true;
+
+			let compliance = &mut opts.compliance;
+			compliance.check_container_length = true;
+			compliance.i32_integer = true;
+			compliance.check_overflow = true;
+			compliance.check_integer_function_bounds = true;
+			compliance.variable_name_length = true;
+			compliance.variable_count = true;
+			compliance.forbid_trailing_tokens = true;
+			compliance.forbid_unbalanced_parens = true;
+			compliance.check_parens = true;
+			compliance.strict_blocks = true;
+			compliance.no_block_conversions = true;
+			compliance.limit_rand_range = true;
+			compliance.check_quit_status_codes = true;
+			compliance.strict_conversions = true;
+		}
+
+		if self.has_extensions() {
+			let extensions = &mut opts.extensions;
+			extensions.functions.eval = true;
+			extensions.functions.value = true;
+			extensions.functions.apply = true;
+			extensions.functions.type_of = true;
+			extensions.functions.split = true;
+			extensions.functions.assert = true;
+			extensions.functions.srand = true;
+			extensions.functions.random = true;
+			extensions.negative_indexing = true;
+		}
+
+		opts
+	}
+}
+
+/// A known difference between what an engine does, and what a [`Case`] expects.
+///
+/// These are normally made via [`diverges!`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Divergence {
+	/// The engine which diverges.
+	pub engine: Engine,
+
+	/// The profiles that the engine diverges under. If empty, it diverges under all of them.
+	pub profiles: &'static [Profile],
+
+	/// Why the engine diverges.
+	pub reason: &'static str,
+}
+
+impl Divergence {
+	/// Whether `engine` is expected to diverge when run under `profile`.
+	#[must_use]
+	pub fn applies_to(&self, engine: Engine, profile: Profile) -> bool {
+		self.engine == engine && (self.profiles.is_empty() || self.profiles.contains(&profile))
+	}
+}
+
+/// Creates a [`Divergence`].
+///
+/// The engine is named first, optionally followed by `in` and the `|`-separated profiles it
+/// diverges under (it's all of them otherwise), and then the reason.
+///
+/// # Examples
+/// ```
+/// # use knightrs_conformance::{diverges, Engine, Profile};
+/// let divergence = diverges!(Bytecode in Default | Extensions: "some reason");
+/// assert!(divergence.applies_to(Engine::Bytecode, Profile::Extensions));
+/// assert!(!divergence.applies_to(Engine::Bytecode, Profile::StrictCompliance));
+/// assert!(!divergence.applies_to(Engine::Ast, Profile::Default));
+///
+/// let everywhere = diverges!(Ast: "some other reason");
+/// assert!(Profile::ALL.iter().all(|&profile| everywhere.applies_to(Engine::Ast, profile)));
+/// ```
+#[macro_export]
+macro_rules! diverges {
+	($engine:ident $(in $($profile:ident)|+)?: $reason:expr) => {
+		$crate::Divergence {
+			engine: $crate::Engine::$engine,
+			profiles: &[$($($crate::Profile::$profile),+)?],
+			reason: $reason,
+		}
+	};
+}
+
+/// A program in the [`CORPUS`], and what it should do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Case {
+	/// A short description of the program, which is shown when it fails.
+	pub name: &'static str,
+
+	/// The program's source code.
+	pub source: &'static str,
+
+	/// What `PROMPT` reads from.
+	pub stdin: &'static str,
+
+	/// What the program should write via `OUTPUT` and `DUMP`.
+	pub stdout: &'static str,
+
+	/// What status the program should exit with.
+	pub status: i32,
+
+	/// Whether the program uses extensions, and so is only run under profiles which
+	/// [have them](Profile::has_extensions).
+	pub needs_extensions: bool,
+
+	/// Engines which are known not to do what this case expects.
+	pub divergences: &'static [Divergence],
+}
+
+impl Case {
+	/// Creates a case named `name`, which runs `source` with no stdin, and should write `stdout` and
+	/// then exit successfully.
+	#[must_use]
+	pub const fn new(name: &'static str, source: &'static str, stdout: &'static str) -> Self {
+		Self { name, source, stdin: "", stdout, status: 0, needs_extensions: false, divergences: &[] }
+	}
+
+	/// Runs the program with `stdin` as its stdin.
+	#[must_use]
+	pub const fn stdin(mut self, stdin: &'static str) -> Self {
+		self.stdin = stdin;
+		self
+	}
+
+	/// Expects the program to exit with `status`.
+	#[must_use]
+	pub const fn status(mut self, status: i32) -> Self {
+		self.status = status;
+		self
+	}
+
+	/// Only runs the program under profiles which have extensions.
+	#[must_use]
+	pub const fn needs_extensions(mut self) -> Self {
+		self.needs_extensions = true;
+		self
+	}
+
+	/// Records that engines are known to diverge from this case; see [`diverges!`].
+	#[must_use]
+	pub const fn diverges(mut self, divergences: &'static [Divergence]) -> Self {
+		self.divergences = divergences;
+		self
+	}
+}
+
+/// Every program that the engines are checked against.
+pub const CORPUS: &[Case] = &[
+	// Integers
+	Case::new("arithmetic", "OUTPUT + 1 * 2 - 7 3", "9\n"),
+	Case::new("division truncates", "; OUTPUT / 7 2 : OUTPUT / - 0 7 2", "3\n-3\n"),
+	Case::new("remainder and power", "; OUTPUT % 7 3 : OUTPUT ^ 2 10", "1\n1024\n"),
+	Case::new("negation", "; OUTPUT ~ 5 : OUTPUT ~ - 0 5", "-5\n5\n"),
+	Case::new("integer literals", "; OUTPUT 007 : OUTPUT 0", "7\n0\n"),
+	Case::new(
+		"integer comparisons",
+		"; OUTPUT < 1 2 ; OUTPUT > 1 2 : OUTPUT ? 2 2",
+		"true\nfalse\ntrue\n",
+	),
+	// Strings
+	Case::new("empty output", r#"OUTPUT """#, "\n"),
+	Case::new("concatenation", r#"; OUTPUT + "a" 12 : OUTPUT + "" TRUE"#, "a12\ntrue\n"),
+	Case::new("repetition", r#"; OUTPUT * "ab" 3 : OUTPUT * "ab" 0"#, "ababab\n\n"),
+	Case::new("repeating empty text", r#"OUTPUT * "" 2000000000"#, "\n"),
+	Case::new("string to integer", r#"; OUTPUT + 1 "  42abc" : OUTPUT + 1 "-3" "#, "43\n-2\n"),
+	Case::new(
+		"string comparisons",
+		r#"; OUTPUT < "abc" "abd" ; OUTPUT > "b" "abc" : OUTPUT < "" "a""#,
+		"true\ntrue\ntrue\n",
+	),
+	Case::new("string equality", r#"; OUTPUT ? 1 "1" : OUTPUT ? "1" "1""#, "false\ntrue\n"),
+	Case::new("trailing backslash", r#"; OUTPUT "a\" : OUTPUT "b""#, "ab\n"),
+	Case::new("LENGTH", r#"; OUTPUT LENGTH "hello" : OUTPUT LENGTH """#, "5\n0\n"),
+	Case::new("ASCII", r#"; OUTPUT ASCII 65 : OUTPUT ASCII "abc""#, "A\n97\n"),
+	Case::new(
+		"string GET and SET",
+		r#"; OUTPUT GET "hello" 1 3 : OUTPUT SET "hello" 0 1 "J""#,
+		"ell\nJello\n",
+	),
+	Case::new("string SET that resizes", r#"OUTPUT SET "hello" 1 3 """#, "ho\n"),
+	Case::new(
+		"SET within a SET of the same variable",
+		r#"; = s + "abc" "" ; = s SET s 0 0 "" ; = s SET s 1 1 (; = s SET s 0 1 "Y" "Z") : OUTPUT s"#,
+		"aZc\n",
+	),
+	Case::new("string head and tail", r#"; OUTPUT [ "abc" : OUTPUT ] "abc""#, "a\nbc\n"),
+	// Lists
+	Case::new("empty list", "DUMP @", "[]"),
+	Case::new("list output", "; = l + ,1 ,2 ; OUTPUT l : DUMP l", "1\n2\n[1, 2]"),
+	Case::new("nested lists", "DUMP ,,1", "[[1]]"),
+	Case::new(
+		"list repetition and join",
+		r#"; OUTPUT ^ * ,"ab" 3 "-" : OUTPUT ^ @ "x""#,
+		"ab-ab-ab\n\n",
+	),
+	Case::new(
+		"list GET and SET",
+		"; DUMP GET + ,1 + ,2 ,3 1 2 : DUMP SET + ,1 ,2 1 1 ,9",
+		"[2, 3][1, 9]",
+	),
+	Case::new("list head and tail", "; DUMP [ + ,1 ,2 : DUMP ] + ,1 ,2", "1[2]"),
+	Case::new(
+		"list comparisons",
+		"; OUTPUT < ,1 + ,1 ,2 : OUTPUT ? + ,1 ,2 + ,1 ,2",
+		"true\ntrue\n",
+	),
+	Case::new(
+		"nested list comparisons",
+		"; OUTPUT < ,,1 ,,2 ; OUTPUT > ,,,1 ,,,0 ; OUTPUT < ,,1 ,,1 : OUTPUT > ,,1 ,,1",
+		"true\ntrue\nfalse\nfalse\n",
+	),
+	Case::new(
+		"nested list comparisons of different lengths",
+		"; OUTPUT < ,,1 + ,,1 ,,0 ; OUTPUT > ,+ ,1 ,0 ,,1 : OUTPUT < ,@ ,,0",
+		"true\ntrue\ntrue\n",
+	),
+	Case::new(
+		"nested list equality",
+		"; OUTPUT ? + ,,1 ,,2 + ,,1 ,,2 ; OUTPUT ? ,,1 ,1 ; OUTPUT ? ,,1 ,,2 : OUTPUT ? ,@ ,@",
+		"true\nfalse\nfalse\ntrue\n",
+	),
+	Case::new("conversions to lists", r#"; DUMP + @ "ab" : DUMP + @ 123"#, r#"["a", "b"][1, 2, 3]"#),
+	Case::new("lists to strings", r#"; OUTPUT + "" + ,1 ,2 : OUTPUT + "" @"#, "1\n2\n\n"),
+	// Other types
+	Case::new("DUMP", r#"; DUMP NULL ; DUMP TRUE ; DUMP 12 : DUMP + "'" '"'"#, r#"nulltrue12"'\"""#),
+	Case::new("DUMP escapes quotes", r#"; DUMP 'say "hi"' : DUMP "it's""#, r#""say \"hi\"""it's""#),
+	Case::new("DUMP escapes backslashes", r#"; DUMP 'a\b' : DUMP '\'"#, r#""a\\b""\\""#),
+	Case::new("DUMP escapes whitespace", "DUMP \"1\n2\t3\r4 5\"", r#""1\n2\t3\r4 5""#),
+	Case::new("DUMP escapes strings in lists", r#"DUMP + ,'"' ,"\""#, r#"["\"", "\\"]"#),
+	Case::new("booleans to integers", "; OUTPUT + 0 TRUE : OUTPUT + 0 FALSE", "1\n0\n"),
+	Case::new("not", r#"; OUTPUT ! "" ; OUTPUT ! 0 : OUTPUT ! @"#, "true\ntrue\ntrue\n"),
+	Case::new("RANDOM isn't negative", "OUTPUT ! < RANDOM 0", "true\n"),
+	// Control flow
+	Case::new(
+		"short circuiting",
+		r#"; & FALSE OUTPUT "no" ; | TRUE OUTPUT "no" : OUTPUT "done""#,
+		"done\n",
+	),
+	Case::new("logical results", r#"; DUMP & 0 "x" : DUMP | "" 3"#, "03"),
+	Case::new("IF", r#"; OUTPUT IF 0 "yes" "no" : OUTPUT IF "0" "yes" "no""#, "no\nyes\n"),
+	Case::new(
+		"WHILE",
+		r#"; = i 0 ; WHILE < i 3 ; OUTPUT i : = i + i 1 : OUTPUT "done""#,
+		"0\n1\n2\ndone\n",
+	),
+	Case::new("blocks", "; = add BLOCK + a b ; = a 3 ; = b 4 : OUTPUT CALL add", "7\n"),
+	Case::new(
+		"recursion",
+		"; = f BLOCK IF < n 1 0 ; = n - n 1 : + 1 CALL f ; = n 10 : OUTPUT CALL f",
+		"10\n",
+	),
+	Case::new("QUIT", r#"; OUTPUT "bye" ; QUIT 3 : OUTPUT "unreachable""#, "bye\n").status(3),
+	// Input
+	Case::new("PROMPT", "; OUTPUT PROMPT : OUTPUT PROMPT", "a\nb\n").stdin("a\nb\r\n"),
+	Case::new("PROMPT at end of input", "DUMP PROMPT", "null"),
+	// Syntax
+	Case::new("comments and parens", "# hi\n(OUTPUT (+ 1 2)) # bye", "3\n"),
+	Case::new("identifiers", "; = _a1 3 : OUTPUT _a1", "3\n"),
+	Case::new("word functions", r#"OUTPUT LENGTHY "abc""#, "3\n"),
+	Case::new("whitespace", "\t; \n= a\r\n1\n:OUTPUT a", "1\n"),
+	// Token boundaries
+	Case::new("comment at the end", "OUTPUT 1 # a comment right at the end", "1\n"),
+	Case::new("comment without a space", "OUTPUT 2#no space before it", "2\n"),
+	Case::new("comment between arguments", "OUTPUT # a comment\n3", "3\n"),
+	Case::new("shebang", "#!/usr/bin/env knight\nOUTPUT 4", "4\n"),
+	Case::new("only comments", "# first line\n\n# and the third\nOUTPUT 5 #", "5\n"),
+	Case::new("`#` in a string", r##"OUTPUT "#not a comment""##, "#not a comment\n"),
+	Case::new("`#` after a string", "OUTPUT '# nor this'# but this is", "# nor this\n"),
+	Case::new("tabs and carriage returns", "OUTPUT\t\r\n6", "6\n"),
+	Case::new("no space before strings", r#"OUTPUT"no space""#, "no space\n"),
+	Case::new("quotes in strings", r#"OUTPUT'"'"#, "\"\n"),
+	Case::new("newlines in strings", "OUTPUT \"line\nbreak\"", "line\nbreak\n"),
+	Case::new("no space before parens", "OUTPUT(+ 1 2)", "3\n"),
+	Case::new("`:` before an argument", "OUTPUT :7", "7\n"),
+	Case::new("repeated `:`", ": : : OUTPUT 8", "8\n"),
+	Case::new("`:` at the end", "OUTPUT 10 :", "10\n").diverges(&[diverges!(
+		Bytecode in StrictCompliance | StrictExtensions:
+			"a trailing `:` is a trailing token, which are forbidden"
+	)]),
+	Case::new("symbols before numbers", "OUTPUT+1 2", "3\n"),
+	Case::new("numbers before variables", "; = a 5 OUTPUT + 12a", "17\n"),
+	Case::new("digits in variables", "; = a_1 2 OUTPUT a_1", "2\n"),
+	Case::new("`_` as a variable", "; = _ 11 OUTPUT _", "11\n"),
+	Case::new("variable prefixes", "; = abc 1 ; = ab 2 OUTPUT + abc ab", "3\n"),
+	Case::new("`_` in word functions", "O_U_T 12", "12\n"),
+	Case::new("single-letter word functions", "OUTPUT T", "true\n"),
+	Case::new("word functions end at non-uppercase", "OUTPUT FALSE_TRUE", "false\n"),
+	Case::new("no spaces at all", r#"OUTPUT+"1"2"#, "12\n"),
+	Case::new("`-` isn't a sign", "OUTPUT -3 1", "2\n"),
+	// Extensions
+	Case::new("EVAL", r#"; EVAL "= a 3" : OUTPUT a"#, "3\n").needs_extensions(),
+	Case::new("VALUE", r#"; = a 4 : OUTPUT VALUE "a""#, "4\n").needs_extensions().diverges(&[
+		diverges!(Ast: "VALUE returns the variable itself, which OUTPUT can't convert to a string"),
+	]),
+	Case::new("EVAL'd blocks", r#"; EVAL "= f BLOCK 1" : OUTPUT CALL f"#, "1\n").needs_extensions(),
+	Case::new("EVAL returning a block", r#"; = g EVAL "BLOCK 7" : OUTPUT CALL g"#, "7\n")
+		.needs_extensions(),
+	Case::new("EVAL calling a block", r#"; = f BLOCK 1 : OUTPUT EVAL "CALL f""#, "1\n")
+		.needs_extensions(),
+	Case::new("XAPPLY", "; = f BLOCK + _1 * _2 LENGTH _0 : OUTPUT XAPPLY f + ,3 ,4", "11\n")
+		.needs_extensions(),
+	Case::new("XAPPLY's arguments are global", "; XAPPLY BLOCK NULL ,5 : OUTPUT _1", "5\n")
+		.needs_extensions(),
+	Case::new(
+		"XTYPEOF",
+		r#"; OUTPUT XTYPEOF NULL ; OUTPUT XTYPEOF TRUE ; OUTPUT XTYPEOF 1 ; OUTPUT XTYPEOF "a"
+		   : OUTPUT XTYPEOF @"#,
+		"Null\nBoolean\nInteger\nString\nList\n",
+	)
+	.needs_extensions(),
+	Case::new("XSPLITLINES", "DUMP XSPLITLINES \"a\nb\r\n\n c \n\"", r#"["a", "b", "", " c "]"#)
+		.needs_extensions(),
+	Case::new("XSPLITWS", "DUMP XSPLITWS \" a\t\tb\r\nc  \"", r#"["a", "b", "c"]"#)
+		.needs_extensions(),
+	Case::new("splitting empty strings", r#"; DUMP XSPLITLINES "" : DUMP XSPLITWS "  ""#, "[][]")
+		.needs_extensions(),
+	Case::new(
+		"splitting non-strings",
+		"; DUMP XSPLITLINES 12 : DUMP XSPLITWS TRUE",
+		r#"["12"]["true"]"#,
+	)
+	.needs_extensions(),
+	Case::new("passing XASSERT", r#"; OUTPUT XASSERT TRUE "unused" : OUTPUT "after""#, "\nafter\n")
+		.needs_extensions(),
+	Case::new(
+		"failing XASSERT",
+		r#"; OUTPUT "before" ; XASSERT 0 "oops" : OUTPUT "after""#,
+		"before\n",
+	)
+	.status(1)
+	.needs_extensions(),
+	Case::new(
+		"XASSERT runs its message",
+		r#"; XASSERT 1 (; OUTPUT "message" "") : XASSERT "" (OUTPUT "failed")"#,
+		"message\nfailed\n",
+	)
+	.status(1)
+	.needs_extensions(),
+	Case::new(
+		"XSHUFFLE and XCHOICE",
+		"; OUTPUT XSHUFFLE ,1 ; OUTPUT XSHUFFLE @ : OUTPUT XCHOICE ,1",
+		"1\n\n1\n",
+	)
+	.needs_extensions(),
+	Case::new(
+		"seeded XSHUFFLE and XCHOICE",
+		"; XSRAND 12 ; OUTPUT XSHUFFLE +@123456789 : OUTPUT XCHOICE +@123456789",
+		"1\n4\n2\n3\n7\n8\n9\n6\n5\n2\n",
+	)
+	.needs_extensions(),
+	Case::new("XCHOICE of an empty list", "; OUTPUT 1 : XCHOICE @", "1\n")
+		.status(1)
+		.needs_extensions(),
+	Case::new(
+		"XTYPEOF of blocks",
+		"; = a 1 ; OUTPUT XTYPEOF BLOCK a ; OUTPUT XTYPEOF BLOCK 1 : OUTPUT XTYPEOF BLOCK + a 1",
+		"Block\nBlock\nBlock\n",
+	)
+	.needs_extensions(),
+];
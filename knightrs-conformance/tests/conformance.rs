@@ -0,0 +1,36 @@
+//! Runs the [`CORPUS`] under every engine and profile.
+
+use knightrs_conformance::{Engine, Profile, CORPUS};
+
+#[test]
+fn corpus_conforms() {
+	let mut failures = Vec::new();
+
+	for case in CORPUS {
+		for engine in Engine::ALL {
+			for profile in Profile::ALL {
+				if case.needs_extensions && !profile.has_extensions() {
+					continue;
+				}
+
+				let outcome = engine.run(case.source, case.stdin, profile);
+				let conforms = outcome.stdout == case.stdout && outcome.status == case.status;
+				let whence = format!("{:?} ({engine:?}, {profile:?})", case.name);
+
+				match case.divergences.iter().find(|div| div.applies_to(engine, profile)) {
+					None if !conforms => failures.push(format!(
+						"{whence}: expected {:?} (status {}), got {:?} (status {}, error {:?})",
+						case.stdout, case.status, outcome.stdout, outcome.status, outcome.error
+					)),
+					Some(divergence) if conforms => failures.push(format!(
+						"{whence}: conforms, but is marked as diverging ({})",
+						divergence.reason
+					)),
+					_ => {}
+				}
+			}
+		}
+	}
+
+	assert!(failures.is_empty(), "{} failure(s):\n{}", failures.len(), failures.join("\n"));
+}
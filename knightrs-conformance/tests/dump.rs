@@ -0,0 +1,30 @@
+//! `DUMP` of strings with characters outside of the Knight encoding, which aren't allowed in the
+//! corpus, as they're undefined behaviour.
+
+use knightrs_bytecode::strings::Encoding;
+use knightrs_conformance::{Engine, Profile};
+
+// Runs `source` under every engine, allowing any characters in strings.
+fn run_unchecked(source: &str) -> Vec<(Engine, String)> {
+	let ast = knightrs::testing::run(source, "", &Profile::Default.ast_flags());
+
+	let mut opts = Profile::Default.bytecode_options();
+	opts.encoding = Encoding::Utf8;
+	let bytecode = knightrs_bytecode::testing::run(source, "", opts);
+
+	vec![(Engine::Ast, ast.stdout), (Engine::Bytecode, bytecode.stdout)]
+}
+
+#[test]
+fn other_control_characters_arent_escaped() {
+	for (engine, stdout) in run_unchecked("DUMP \"\x01\x1f\x7f\"") {
+		assert_eq!(stdout, "\"\x01\x1f\x7f\"", "{engine:?}");
+	}
+}
+
+#[test]
+fn control_characters_next_to_escapes() {
+	for (engine, stdout) in run_unchecked("DUMP \"\x00\\\n\x1b\"") {
+		assert_eq!(stdout, "\"\x00\\\\\\n\x1b\"", "{engine:?}");
+	}
+}
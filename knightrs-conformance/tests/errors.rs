@@ -0,0 +1,20 @@
+//! What the engines' errors say. The corpus only checks that programs fail, as each engine
+//! describes errors its own way (eg `domain error: ...` vs `an domain error occurred: ...`), but
+//! the details that they give for the same failure should be the same.
+
+use knightrs_conformance::{Engine, Profile};
+
+// Asserts that running `source` fails with an error containing `detail` with every engine.
+fn assert_fails_with(source: &str, detail: &str) {
+	for engine in Engine::ALL {
+		let error = engine.run(source, "", Profile::Extensions).error;
+		let error = error.unwrap_or_else(|| panic!("{engine:?}: {source:?} didn't fail"));
+		assert!(error.contains(detail), "{engine:?}: {source:?} failed with {error:?}");
+	}
+}
+
+#[test]
+fn empty_xchoice() {
+	assert_fails_with("XCHOICE @", "XCHOICE: empty list given");
+	assert_fails_with(r#"XCHOICE """#, "XCHOICE: empty list given");
+}
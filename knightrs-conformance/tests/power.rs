@@ -0,0 +1,69 @@
+//! `^` for every combination of signs. Knight only defines it for nonnegative exponents, so these
+//! aren't in the corpus, but both engines define the rest the same way.
+
+use knightrs_conformance::{Engine, Profile};
+
+// Knight doesn't have negative literals, so they're written as subtractions from zero.
+fn literal(int: i64) -> String {
+	if int < 0 {
+		format!("- 0 {}", -int)
+	} else {
+		int.to_string()
+	}
+}
+
+// What `^ base exponent` outputs when run with `engine` under `profile`, or `None` if it fails.
+fn power(engine: Engine, base: i64, exponent: i64, profile: Profile) -> Option<i64> {
+	let source = format!("OUTPUT ^ {} {}", literal(base), literal(exponent));
+	let outcome = engine.run(&source, "", profile);
+	outcome.error.is_none().then(|| outcome.stdout.trim_end().parse().unwrap())
+}
+
+#[test]
+fn every_sign_combination() {
+	// Each base raised to `-3` through `3`.
+	for (base, expected) in [
+		(-3, [Some(0), Some(0), Some(0), Some(1), Some(-3), Some(9), Some(-27)]),
+		(-2, [Some(0), Some(0), Some(0), Some(1), Some(-2), Some(4), Some(-8)]),
+		(-1, [Some(-1), Some(1), Some(-1), Some(1), Some(-1), Some(1), Some(-1)]),
+		(0, [None, None, None, Some(1), Some(0), Some(0), Some(0)]),
+		(1, [Some(1), Some(1), Some(1), Some(1), Some(1), Some(1), Some(1)]),
+		(2, [Some(0), Some(0), Some(0), Some(1), Some(2), Some(4), Some(8)]),
+		(3, [Some(0), Some(0), Some(0), Some(1), Some(3), Some(9), Some(27)]),
+	] {
+		for (exponent, expected) in (-3..=3).zip(expected) {
+			for engine in Engine::ALL {
+				let actual = power(engine, base, exponent, Profile::Default);
+				assert_eq!(actual, expected, "{engine:?}: {base} ^ {exponent}");
+			}
+		}
+	}
+}
+
+#[test]
+fn huge_exponents() {
+	// Only `-1`, `0`, and `1` can be raised to exponents this large.
+	for (base, exponent, expected) in [
+		(-1, 1 << 40, Some(1)),
+		(-1, (1 << 40) + 1, Some(-1)),
+		(0, 1 << 40, Some(0)),
+		(1, 1 << 40, Some(1)),
+		(2, 1 << 40, None),
+	] {
+		for engine in Engine::ALL {
+			let actual = power(engine, base, exponent, Profile::Default);
+			assert_eq!(actual, expected, "{engine:?}: {base} ^ {exponent}");
+		}
+	}
+}
+
+#[test]
+fn negative_exponents_when_checking_bounds() {
+	for base in -3..=3 {
+		for engine in Engine::ALL {
+			let profile = Profile::StrictCompliance;
+			assert_eq!(power(engine, base, -1, profile), None, "{engine:?}: {base} ^ -1");
+			assert_eq!(power(engine, base, 2, profile), Some(base * base), "{engine:?}: {base} ^ 2");
+		}
+	}
+}
@@ -0,0 +1,74 @@
+//! Property tests which feed both engines arbitrary source code, and randomly-generated (but always
+//! valid and terminating) programs, checking that neither panics.
+//!
+//! These are the same inputs as the `fuzz` targets, but run on every `cargo test`, with far fewer
+//! cases. As there, `WHILE`, `BLOCK`, and `CALL` aren't generated, and integer literals are kept
+//! small so `*` can't build up huge strings and lists.
+
+use proptest::prelude::*;
+use proptest::sample::select;
+
+const UNARY: &[&str] = &["QUIT", "OUTPUT", "DUMP", "LENGTH", "!", "~", "ASCII", ",", "[", "]"];
+const BINARY: &[&str] = &["+", "-", "*", "/", "%", "^", "<", ">", "?", "&", "|", ";"];
+const VARIABLES: &[&str] = &["a", "b", "c"];
+
+fn leaf() -> impl Strategy<Value = String> {
+	prop_oneof![
+		select(&["NULL", "TRUE", "FALSE", "PROMPT", "RANDOM"][..]).prop_map(str::to_owned),
+		(0..16u8).prop_map(|int| int.to_string()),
+		"[a-z0-9 \n]{0,8}".prop_map(|text| format!("\"{text}\"")),
+		select(VARIABLES).prop_map(str::to_owned),
+	]
+}
+
+// A valid Knight expression, nested at most six deep.
+fn expression() -> impl Strategy<Value = String> {
+	leaf().prop_recursive(6, 64, 4, |inner| {
+		prop_oneof![
+			(select(UNARY), inner.clone()).prop_map(|(func, arg)| format!("{func} {arg}")),
+			(select(BINARY), inner.clone(), inner.clone())
+				.prop_map(|(func, lhs, rhs)| format!("{func} {lhs} {rhs}")),
+			(select(VARIABLES), inner.clone()).prop_map(|(var, value)| format!("= {var} {value}")),
+			(inner.clone(), inner.clone(), inner.clone())
+				.prop_map(|(cond, ift, iff)| format!("IF {cond} {ift} {iff}")),
+			(inner.clone(), inner.clone(), inner.clone())
+				.prop_map(|(coll, start, len)| format!("GET {coll} {start} {len}")),
+			(inner.clone(), inner.clone(), inner.clone(), inner)
+				.prop_map(|(coll, start, len, repl)| format!("SET {coll} {start} {len} {repl}")),
+		]
+	})
+}
+
+proptest! {
+	// Programs aren't run, as they could loop forever.
+	#[test]
+	fn parsing_never_panics(source in any::<String>()) {
+		let flags = knightrs::env::Flags::default();
+		if let Ok(source) = knightrs::value::text::TextSlice::new(&source, &flags) {
+			let mut env = knightrs::env::Environment::new(&flags);
+			let _ = knightrs::parse::Parser::new(source, &mut env).parse_program();
+		}
+
+		// SAFETY: Nothing allocated within the `gc` escapes.
+		unsafe {
+			knightrs_bytecode::Gc::default().run(|gc| {
+				use knightrs_bytecode::parser::{source_location::ProgramSource, Parser};
+
+				let mut env = knightrs_bytecode::Environment::new(Default::default(), gc);
+				if let Ok(parser) = Parser::new(&mut env, ProgramSource::ExprFlag, &source) {
+					let _ = parser.parse_program();
+				}
+			})
+		}
+	}
+
+	#[test]
+	fn valid_programs_never_panic(expression in expression(), stdin in "[a-z0-9\n]{0,16}") {
+		// Every variable is assigned first, as their values are only checked for when
+		// `check-variables` is on.
+		let source = format!("; = a 1 ; = b \"\" ; = c ,0 {expression}");
+
+		let _ = knightrs::testing::run(&source, &stdin, &Default::default());
+		let _ = knightrs_bytecode::testing::run(&source, &stdin, Default::default());
+	}
+}
@@ -0,0 +1,60 @@
+//! Times a program which outputs small integers in a loop, with and without
+//! `qol.cache_small_integers`, and counts how many allocations it makes.
+//!
+//! Run via `cargo bench -p knightrs --bench small_integers`. Each program is run a handful of
+//! times, and the fastest run is reported, as that's the least affected by whatever else the
+//! machine is doing. What's written is discarded, so only the interpreter is measured.
+
+use knightrs::env::{Environment, Flags};
+use knightrs::value::text::TextSlice;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+const RUNS: usize = 10;
+const SOURCE: &str = "; = i 0 : WHILE < i 300000 ; OUTPUT % i 1000 : = i + i 1";
+
+// The system allocator, but counting how many allocations are made.
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+unsafe impl GlobalAlloc for Counting {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+		unsafe { System.alloc(layout) }
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		unsafe { System.dealloc(ptr, layout) }
+	}
+}
+
+// Parses and runs `SOURCE`, returning how long it took and how many allocations that made.
+fn run(cache_small_integers: bool) -> (Duration, usize) {
+	let mut flags = Flags::default();
+	flags.qol.cache_small_integers = cache_small_integers;
+
+	let mut env = Environment::new(&flags);
+	env.output().set_stdout(std::io::sink());
+	let source = TextSlice::new(SOURCE, &flags).expect("the benchmark is valid Knight");
+
+	let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+	let start = Instant::now();
+	env.play(source).expect("the benchmark runs successfully");
+	(start.elapsed(), ALLOCATIONS.load(Ordering::Relaxed) - allocations)
+}
+
+fn main() {
+	for cache_small_integers in [false, true] {
+		let runs = (0..RUNS).map(|_| run(cache_small_integers)).collect::<Vec<_>>();
+		let fastest = runs.iter().map(|&(time, _)| time).min().unwrap_or_default();
+		let allocations = runs.iter().map(|&(_, allocations)| allocations).max().unwrap_or_default();
+
+		let name = if cache_small_integers { "cached" } else { "uncached" };
+		println!("{name:>10}: {fastest:>12?}, {allocations:>8} allocations");
+	}
+}
@@ -67,6 +67,7 @@ impl Ast {
 impl Runnable for Ast {
 	#[inline]
 	fn run(&self, env: &mut Environment<'_>) -> Result<Value> {
+		env.check_cancelled()?;
 		self.function().run(self.args(), env)
 	}
 }
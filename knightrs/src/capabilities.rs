@@ -0,0 +1,56 @@
+//! Discovering what this build of the interpreter supports.
+
+/// A description of which cargo features this crate was compiled with.
+///
+/// This is returned by [`capabilities`], and is intended for tooling which needs to adapt its
+/// behaviour (or print diagnostics) based on what's available. What's actually _enabled_ at runtime
+/// is controlled by the [`Flags`](crate::env::Flags) an environment was built with, which are
+/// available via [`Environment::flags`](crate::env::Environment::flags).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Capabilities {
+	/// Whether extensions can be enabled (`feature = "extensions"`).
+	pub extensions: bool,
+
+	/// Whether extensions which change how vanilla Knight programs behave can be enabled
+	/// (`feature = "iffy-extensions"`).
+	pub iffy_extensions: bool,
+
+	/// Whether compliance checks can be enabled (`feature = "compliance"`).
+	pub compliance: bool,
+
+	/// Whether embedders can define their own types (`feature = "custom-types"`).
+	pub custom_types: bool,
+
+	/// Whether values are thread-safe (`feature = "multithreaded"`).
+	pub multithreaded: bool,
+
+	/// How values are reclaimed.
+	pub gc_strategy: GcStrategy,
+}
+
+/// The strategy used to reclaim values once they're no longer used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GcStrategy {
+	/// Values are reference counted (via `Rc`, or `Arc` when `multithreaded` is enabled).
+	ReferenceCounting,
+}
+
+/// Returns which cargo features this crate was compiled with.
+///
+/// # Examples
+/// ```
+/// let caps = knightrs::capabilities();
+/// assert_eq!(caps.extensions, cfg!(feature = "extensions"));
+/// ```
+pub const fn capabilities() -> Capabilities {
+	Capabilities {
+		extensions: cfg!(feature = "extensions"),
+		iffy_extensions: cfg!(feature = "iffy-extensions"),
+		compliance: cfg!(feature = "compliance"),
+		custom_types: cfg!(feature = "custom-types"),
+		multithreaded: cfg!(feature = "multithreaded"),
+		gc_strategy: GcStrategy::ReferenceCounting,
+	}
+}
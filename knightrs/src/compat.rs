@@ -0,0 +1,205 @@
+//! A migration layer for code written against the old `Environment<I, O>` API.
+//!
+//! Before [`env::Environment`] took a [`Builder`](env::Builder) with [`Flags`], environments were
+//! generic over their stdin and stdout, and were built by chaining methods on the builder:
+//! ```
+//! # use knightrs::compat::Environment;
+//! let mut stdout = Vec::new();
+//! let mut stdin = std::io::Cursor::new("");
+//!
+//! Environment::builder()
+//! 	.stdin(&mut stdin)
+//! 	.stdout(&mut stdout)
+//! 	.disable_system()
+//! 	.build()
+//! 	.run_str("OUTPUT 'hello'")
+//! 	.unwrap();
+//!
+//! assert_eq!(stdout, b"hello\n");
+//! ```
+//! Code like that keeps working by importing [`Environment`] from this module (instead of
+//! `knightrs::Environment`), which adapts it to the current API. It's deprecated: new code should
+//! use [`env::Environment`] directly, as it exposes everything (such as the functions, parsers, and
+//! `PROMPT` replacements) that this doesn't.
+
+// The deprecated types are used throughout their own implementation.
+#![allow(deprecated)]
+
+use crate::env::{self, Flags, Variable};
+use crate::value::text::TextSlice;
+use crate::value::Value;
+use crate::{Error, Result};
+use indexmap::IndexSet;
+use std::io::{self, BufReader};
+
+pub use crate::env::output::Stdout;
+pub use crate::env::prompt::Stdin;
+
+/// An environment that owns its stdin (`I`) and stdout (`O`).
+///
+/// Each [`run_str`](Self::run_str) runs in a fresh [`env::Environment`], but variables are kept
+/// between them, as they were when this type was the environment itself:
+/// ```
+/// # use knightrs::compat::Environment;
+/// let mut stdout = Vec::new();
+/// let mut env = Environment::builder().stdout(&mut stdout).build();
+///
+/// env.run_str("= greeting BLOCK OUTPUT + 'hi ' name").unwrap();
+/// env.run_str("= name 'there'").unwrap();
+/// env.run_str("CALL greeting").unwrap();
+/// drop(env);
+///
+/// assert_eq!(stdout, b"hi there\n");
+/// ```
+#[must_use]
+#[deprecated(note = "use `knightrs::env::Environment` instead")]
+pub struct Environment<I = BufReader<io::Stdin>, O = io::Stdout> {
+	stdin: I,
+	stdout: O,
+	flags: Flags,
+	#[cfg(feature = "extensions")]
+	system: bool,
+	variables: IndexSet<Variable>,
+}
+
+/// A builder for an [`Environment`]; see [`Environment::builder`].
+#[must_use]
+#[deprecated(note = "use `knightrs::env::Builder` instead")]
+pub struct Builder<I = BufReader<io::Stdin>, O = io::Stdout> {
+	stdin: I,
+	stdout: O,
+	flags: Flags,
+	#[cfg(feature = "extensions")]
+	system: bool,
+}
+
+impl Default for Builder {
+	/// Creates a new [`Builder`] which uses the process's stdin and stdout.
+	fn default() -> Self {
+		Self {
+			stdin: BufReader::new(io::stdin()),
+			stdout: io::stdout(),
+			flags: Flags::default(),
+			#[cfg(feature = "extensions")]
+			system: true,
+		}
+	}
+}
+
+impl<I, O> Builder<I, O> {
+	/// Sets the stdin, which is used when `PROMPT` is run.
+	pub fn stdin<I2: Stdin>(self, stdin: I2) -> Builder<I2, O> {
+		Builder {
+			stdin,
+			stdout: self.stdout,
+			flags: self.flags,
+			#[cfg(feature = "extensions")]
+			system: self.system,
+		}
+	}
+
+	/// Sets the stdout, which is used when `OUTPUT` and `DUMP` are run.
+	pub fn stdout<O2: Stdout>(self, stdout: O2) -> Builder<I, O2> {
+		Builder {
+			stdin: self.stdin,
+			stdout,
+			flags: self.flags,
+			#[cfg(feature = "extensions")]
+			system: self.system,
+		}
+	}
+
+	/// Sets the flags; this wasn't in the old API, as they used to be chosen by features.
+	pub fn flags(self, flags: Flags) -> Self {
+		Self { flags, ..self }
+	}
+
+	/// Makes `$` and `USE` return errors instead of running commands and reading files.
+	///
+	/// Without the `extensions` feature, neither exists, so this does nothing.
+	pub fn disable_system(self) -> Self {
+		#[cfg(feature = "extensions")]
+		return Self { system: false, ..self };
+
+		#[cfg(not(feature = "extensions"))]
+		self
+	}
+
+	/// Finishes the builder and creates the environment.
+	pub fn build(self) -> Environment<I, O> {
+		Environment {
+			stdin: self.stdin,
+			stdout: self.stdout,
+			flags: self.flags,
+			#[cfg(feature = "extensions")]
+			system: self.system,
+			variables: IndexSet::default(),
+		}
+	}
+}
+
+impl Environment {
+	/// Creates a new [`Environment`] which uses the process's stdin and stdout.
+	pub fn new() -> Self {
+		Self::builder().build()
+	}
+
+	/// A shorthand function for creating [`Builder`]s.
+	pub fn builder() -> Builder {
+		Builder::default()
+	}
+}
+
+impl Default for Environment {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<I, O> Drop for Environment<I, O> {
+	fn drop(&mut self) {
+		// Variables can reference themselves, so they have to be cleared to not leak; see the
+		// `Drop` impl for `env::Environment`.
+		for var in self.variables.iter() {
+			var.assign(Value::Null);
+		}
+	}
+}
+
+impl<I: Stdin, O: Stdout> Environment<I, O> {
+	/// Gets the flags that programs are run with.
+	pub fn flags(&self) -> &Flags {
+		&self.flags
+	}
+
+	/// Gets the stdin.
+	pub fn stdin(&mut self) -> &mut I {
+		&mut self.stdin
+	}
+
+	/// Gets the stdout.
+	pub fn stdout(&mut self) -> &mut O {
+		&mut self.stdout
+	}
+
+	/// Parses and executes `source` as knight code.
+	pub fn run_str(&mut self, source: &str) -> Result<Value> {
+		let mut builder = env::Builder::new(&self.flags);
+		builder.stdin(&mut self.stdin);
+		builder.stdout(&mut self.stdout);
+
+		#[cfg(feature = "extensions")]
+		if !self.system {
+			builder.system(|_, _, _| Err(Error::Custom("`$` is disabled".into())));
+			builder.read_file(|_, _| Err(Error::Custom("`USE` is disabled".into())));
+		}
+
+		let mut env = builder.build();
+		env.replace_variables(std::mem::take(&mut self.variables));
+		let result = TextSlice::new(source, &self.flags)
+			.map_err(Error::from)
+			.and_then(|source| env.play(source));
+		self.variables = env.replace_variables(IndexSet::default());
+		result
+	}
+}
@@ -12,7 +12,7 @@ if #[cfg(feature = "multithreaded")] {
 	pub type RefCount<T> = std::rc::Rc<T>;
 }}
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct Mutable<T>(
 	#[cfg(feature = "multithreaded")] std::sync::RwLock<T>,
 	#[cfg(not(feature = "multithreaded"))] std::cell::RefCell<T>,
@@ -2,16 +2,19 @@
 
 use crate::function::Function;
 use crate::parse::{ParseFn, Parser};
-use crate::value::{Integer, Runnable, TextSlice, Value};
+use crate::value::{Integer, Runnable, Text, TextSlice, Value};
 use crate::Result;
+use indexmap::IndexSet;
 use rand::{rngs::StdRng, SeedableRng};
 use std::collections::HashSet;
 
 cfg_if! {
 if #[cfg(feature = "extensions")] {
-	use crate::value::{Text, List};
+	use crate::value::List;
 	use crate::function::ExtensionFunction;
-	use std::collections::VecDeque;
+	use include::Includes;
+	use std::collections::{HashMap, VecDeque};
+	use std::path::PathBuf;
 
 	type System<'e> =
 		dyn FnMut(&TextSlice, Option<&TextSlice>, &Flags) -> Result<Text> + 'e + Send + Sync;
@@ -21,35 +24,60 @@ if #[cfg(feature = "extensions")] {
 }}
 
 mod builder;
+pub mod evaluation;
 pub mod flags;
+pub mod function_table;
+#[cfg(feature = "extensions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+pub mod include;
 pub mod output;
 pub mod prompt;
 pub mod variable;
 
 pub use builder::Builder;
+pub use knight_strings::CancelHandle;
+pub use evaluation::{Evaluation, Stats, Warning};
 pub use flags::Flags;
+pub use function_table::{FunctionTable, SharedFunctionTable};
 use output::Output;
 use prompt::Prompt;
-pub use variable::Variable;
+pub use variable::{Variable, VariableVisibility};
+
+// How a child environment's variables relate to its parent's.
+enum ParentVariables<'e> {
+	// There's no parent, or the parent's variables are invisible.
+	None,
+
+	// Variables the parent has looked up are read until the child assigns them.
+	ReadThrough(&'e IndexSet<Variable>),
+
+	// Variables are the parent's, with this prefix (which includes the trailing `_`).
+	Prefixed(&'e mut IndexSet<Variable>, Text),
+}
+
+// What kind of child environment to create; the `ParentVariables` is made from this, as it needs
+// to borrow from the parent.
+enum ChildScope {
+	Isolated,
+	ReadThrough,
+	Prefixed(Text),
+}
 
 /// The environment hosts all relevant information for Knight programs.
 ///
 /// <todo: details>
 pub struct Environment<'e> {
 	flags: &'e Flags,
-	variables: HashSet<Variable>,
+	// Insertion-ordered, so that `variables` is deterministic.
+	variables: IndexSet<Variable>,
+	parent: ParentVariables<'e>,
 	prompt: Prompt<'e>,
 	output: Output<'e>,
-	functions: HashSet<Function>,
 	rng: StdRng,
+	cancel: CancelHandle,
 
-	// Parsers are only modifiable when the `extensions` feature is enabled. Otherwise, the normal
-	// set of parsers is loaded up.
-	parsers: Vec<ParseFn>,
-
-	// A List of extension functions.
-	#[cfg(feature = "extensions")]
-	extensions: HashSet<ExtensionFunction>,
+	// Shared with child environments, and with other environments given the same table.
+	functions: SharedFunctionTable,
 
 	// A queue of things that'll be read from for `` ` `` instead of stdin.
 	#[cfg(feature = "extensions")]
@@ -61,8 +89,16 @@ pub struct Environment<'e> {
 	#[cfg(feature = "extensions")]
 	read_file: Box<ReadFile<'e>>,
 
+	// Search paths, the resolver, and cached programs for `USE` and `XIMPORT`.
+	#[cfg(feature = "extensions")]
+	includes: Includes<'e>,
+
 	#[cfg(feature = "extensions")]
 	callstack: Vec<List>,
+
+	// How many `EVAL`s are currently running.
+	#[cfg(feature = "extensions")]
+	eval_depth: usize,
 }
 
 impl Drop for Environment<'_> {
@@ -102,6 +138,85 @@ impl<'e> Environment<'e> {
 		Parser::new(source, self).parse_program()?.run(self)
 	}
 
+	/// Parses and executes `source` as [`EVAL`](crate::function::EVAL) does.
+	///
+	/// This is the same as [`play`](Self::play), except that it enforces [`max_eval_length`](
+	/// flags::Extensions::max_eval_length) and [`max_eval_depth`](flags::Extensions::max_eval_depth).
+	#[cfg(feature = "extensions")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+	pub fn eval(&mut self, source: &TextSlice) -> Result<Value> {
+		let limits = &self.flags.extensions;
+
+		if let Some(max) = limits.max_eval_length.filter(|&max| max < source.len()) {
+			return Err(crate::Error::EvalTooLong { len: source.len(), max });
+		}
+
+		if let Some(max) = limits.max_eval_depth.filter(|&max| max <= self.eval_depth) {
+			return Err(crate::Error::EvalTooDeep { max });
+		}
+
+		self.eval_depth += 1;
+		let result = self.play(source);
+		self.eval_depth -= 1;
+		result
+	}
+
+	/// Parses and executes `source`, returning its result along with everything it wrote to stdout.
+	///
+	/// This is a convenience for the common case of running a snippet and inspecting all of its
+	/// effects. Output is captured for the duration of the call, and so isn't written to stdout.
+	///
+	/// # Examples
+	/// ```
+	/// use knightrs::env::{Environment, Warning};
+	/// use knightrs::value::TextSlice;
+	///
+	/// let mut env = Environment::default();
+	/// let source = TextSlice::new("; = a 3 : OUTPUT + a b", env.flags()).unwrap();
+	/// let evaluation = env.evaluate(source);
+	///
+	/// assert_eq!(evaluation.stdout_lossy(), "");
+	/// assert!(evaluation.value.is_err()); // `b` was never assigned
+	/// assert_eq!(evaluation.warnings, [Warning::NeverAssigned("b".to_string())]);
+	/// assert_eq!(evaluation.stats.variables_used, 1);
+	/// ```
+	pub fn evaluate(&mut self, source: &TextSlice) -> Evaluation {
+		use std::time::{Duration, Instant};
+
+		let capture = output::Capture::default();
+		let previous = self.output.replace_stdout(Box::new(capture.clone()));
+
+		let start = Instant::now();
+		let program = Parser::new(source, self).parse_program();
+		let parse_time = start.elapsed();
+
+		let mut warnings = Vec::new();
+		let mut stats = evaluation::Stats::default();
+
+		let (value, run_time) = match program {
+			Ok(program) => {
+				let start = Instant::now();
+				let value = program.run(self);
+				let run_time = start.elapsed();
+
+				for (variable, assigned) in evaluation::variables_in(&program) {
+					if !assigned {
+						warnings.push(Warning::NeverAssigned(variable.name().to_string()));
+					}
+					if variable.fetch().is_some() {
+						stats.variables_used += 1;
+					}
+				}
+
+				(value, run_time)
+			}
+			Err(err) => (Err(err.into()), Duration::ZERO),
+		};
+
+		self.output.replace_stdout(previous);
+		Evaluation { value, stdout: capture.take(), warnings, parse_time, run_time, stats }
+	}
+
 	/// Parses and executes `source` as knight code.
 	#[cfg(feature = "extensions")]
 	pub fn play_with_args(&mut self, source: &TextSlice, args: List) -> Result<Value> {
@@ -119,14 +234,22 @@ impl<'e> Environment<'e> {
 	#[must_use]
 	#[inline]
 	pub fn functions(&self) -> &HashSet<Function> {
-		&self.functions
+		self.functions.functions()
 	}
 
 	/// Gets the list of currently defined parsers for `self`.
 	#[must_use]
 	#[inline]
 	pub fn parsers(&self) -> &[ParseFn] {
-		&self.parsers
+		self.functions.parsers()
+	}
+
+	/// Gets the [`FunctionTable`] for `self`, so that it can be shared with other environments via
+	/// [`Builder::function_table`].
+	#[must_use]
+	#[inline]
+	pub fn function_table(&self) -> &SharedFunctionTable {
+		&self.functions
 	}
 
 	/// Gets the [`Prompt`] type, which handles reading lines from stdin.
@@ -143,6 +266,42 @@ impl<'e> Environment<'e> {
 		&mut self.output
 	}
 
+	/// Gets a [`CancelHandle`], which can be used to stop `self`'s programs from other threads.
+	///
+	/// After [`cancel`](CancelHandle::cancel) is called, the program stops the next time a function
+	/// is run, returning [`Error::Interrupted`](crate::Error::Interrupted). Child environments
+	/// (such as the ones [`USE`](crate::function::USE) runs files in) share their parent's handle.
+	///
+	/// # Examples
+	/// ```
+	/// # use knightrs::{env::Environment, value::Text, Error};
+	/// let mut env = Environment::default();
+	/// let handle = env.cancel_handle();
+	///
+	/// std::thread::spawn(move || {
+	///     std::thread::sleep(std::time::Duration::from_millis(10));
+	///     handle.cancel();
+	/// });
+	///
+	/// let source = Text::new("WHILE TRUE NULL".to_string(), env.flags()).unwrap();
+	/// assert!(matches!(env.play(&source), Err(Error::Interrupted)));
+	/// ```
+	#[must_use]
+	pub fn cancel_handle(&self) -> CancelHandle {
+		self.cancel.clone()
+	}
+
+	/// Returns [`Error::Interrupted`](crate::Error::Interrupted) if `self`'s [`CancelHandle`] has
+	/// been cancelled.
+	#[inline]
+	pub fn check_cancelled(&self) -> Result<()> {
+		if self.cancel.take() {
+			return Err(crate::Error::Interrupted);
+		}
+
+		Ok(())
+	}
+
 	/// Fetches the variable corresponding to `name`, creating one if it's the first time that name
 	/// has been requested.
 	pub fn lookup(
@@ -154,17 +313,170 @@ impl<'e> Environment<'e> {
 			return Ok(var.clone());
 		}
 
-		let variable = Variable::new(name.into(), self.flags())?;
+		let variable = match self.parent {
+			ParentVariables::None => Variable::new(name.into(), self.flags())?,
+			ParentVariables::ReadThrough(parent) => match parent.get(name) {
+				Some(parent) => Variable::with_fallback(parent.clone()),
+				None => Variable::new(name.into(), self.flags())?,
+			},
+			ParentVariables::Prefixed(ref mut parent, ref prefix) => {
+				let name = prefix
+					.concat(name, self.flags)
+					.expect("variable names are nowhere near the maximum text length");
+
+				if let Some(var) = parent.get(&*name) {
+					return Ok(var.clone());
+				}
+
+				let variable = Variable::new(name, self.flags)?;
+				parent.insert(variable.clone());
+				return Ok(variable);
+			}
+		};
 		self.variables.insert(variable.clone());
 		Ok(variable)
 	}
 
+	/// Gets every variable that's been looked up, along with its value (or `None` if it hasn't been
+	/// assigned yet).
+	///
+	/// This is useful for extracting results after [`play`](Self::play)ing a program. Variables are
+	/// returned in the order they were first looked up (which, for variables in a program, is the
+	/// order they first appear in it).
+	pub fn variables(&self) -> impl Iterator<Item = (&Text, Option<Value>)> + '_ {
+		self.variables.iter().map(|var| (var.name(), var.fetch()))
+	}
+
+	/// Takes the value of the variable `name` out of `self`, leaving the variable unassigned.
+	///
+	/// Returns `None` if `name` was never looked up, or if it was never assigned to.
+	pub fn take_variable(&mut self, name: &TextSlice) -> Option<Value> {
+		self.variables.get(name)?.take()
+	}
+
+	// Swaps out every variable for `variables`. This is used by `compat`, which keeps variables
+	// alive across the environments it builds for each program.
+	pub(crate) fn replace_variables(&mut self, variables: IndexSet<Variable>) -> IndexSet<Variable> {
+		std::mem::replace(&mut self.variables, variables)
+	}
+
+	/// Clears every variable, so `self` can be reused to run another program.
+	///
+	/// Only state that belongs to the previous program is cleared: along with the variables, this
+	/// removes any `OUTPUT` redirection (as it's bound to a variable). Everything configured by the
+	/// host persists, such as the [flags](Self::flags), stdin and stdout, the functions, parsers,
+	/// and extensions, and the random number generator. Lines queued up for `PROMPT` and results
+	/// for `$` also persist; use [`Prompt::reset_replacement`] and [`clear_system_results`](
+	/// Self::clear_system_results) to clear those as well.
+	///
+	/// Any [`Variable`]s obtained before the reset are unassigned, and are no longer returned by
+	/// [`lookup`](Self::lookup). As such, previously parsed programs shouldn't be rerun.
+	pub fn reset(&mut self) {
+		// Unassign everything first, as variables may reference each other (see the `Drop` impl).
+		for var in self.variables.drain(..) {
+			var.take();
+		}
+
+		#[cfg(feature = "extensions")]
+		{
+			self.output.clear_redirection();
+			self.callstack.clear();
+			self.includes.cache.clear();
+		}
+	}
+
+	/// Creates a child environment, which shares `self`'s flags, functions, parsers, and IO, but
+	/// has its own variables.
+	///
+	/// `visibility` determines whether the child can read `self`'s variables; regardless, the
+	/// child's assignments are never visible to `self`. State which is tied to the parent's
+	/// program, such as `PROMPT` replacements, `OUTPUT` redirection, and queued `$` results, isn't
+	/// inherited. The child's random number generator is seeded from `self`'s.
+	pub fn child(&mut self, visibility: VariableVisibility) -> Environment<'_> {
+		self.child_with(match visibility {
+			VariableVisibility::Isolated => ChildScope::Isolated,
+			VariableVisibility::ReadThrough => ChildScope::ReadThrough,
+		})
+	}
+
+	/// Creates a child environment (see [`child`](Self::child)) whose variables are actually
+	/// `self`'s variables, named with `prefix` and an `_` in front. That is, `foo` in the child is
+	/// `{prefix}_foo` in `self`. This is used by `XIMPORT`.
+	///
+	/// If `self` is itself a prefixed child, the prefixes are combined, so nested modules' variables
+	/// are always visible to the outermost environment.
+	pub fn prefixed_child(&mut self, prefix: &TextSlice) -> crate::Result<Environment<'_>> {
+		let underscore = TextSlice::new("_", self.flags)?;
+		let mut prefix = prefix.concat(underscore, self.flags)?;
+
+		if let ParentVariables::Prefixed(_, ref outer) = self.parent {
+			prefix = outer.concat(&prefix, self.flags)?;
+		}
+
+		Ok(self.child_with(ChildScope::Prefixed(prefix)))
+	}
+
+	fn child_with(&mut self, scope: ChildScope) -> Environment<'_> {
+		let parent = match scope {
+			ChildScope::Isolated => ParentVariables::None,
+			ChildScope::ReadThrough => ParentVariables::ReadThrough(&self.variables),
+			// A prefixed child of a prefixed child uses the outermost environment's variables.
+			ChildScope::Prefixed(prefix) => match self.parent {
+				ParentVariables::Prefixed(ref mut variables, _) => {
+					ParentVariables::Prefixed(variables, prefix)
+				}
+				_ => ParentVariables::Prefixed(&mut self.variables, prefix),
+			},
+		};
+
+		Environment {
+			flags: self.flags,
+			variables: IndexSet::default(),
+			parent,
+			prompt: self.prompt.child(),
+			output: self.output.child(),
+			functions: self.functions.clone(),
+			rng: StdRng::from_rng(&mut self.rng).expect("seeding from another rng can't fail"),
+			cancel: self.cancel.clone(),
+
+			#[cfg(feature = "extensions")]
+			system_results: VecDeque::new(),
+
+			#[cfg(feature = "extensions")]
+			system: Box::new(|command, stdin, flags| (self.system)(command, stdin, flags)),
+
+			#[cfg(feature = "extensions")]
+			read_file: Box::new(|filename, flags| (self.read_file)(filename, flags)),
+
+			#[cfg(feature = "extensions")]
+			includes: Includes::new(
+				self.includes.search_paths.clone(),
+				Box::new(|filename, search_paths, flags| {
+					(self.includes.resolve)(filename, search_paths, flags)
+				}),
+			),
+
+			#[cfg(feature = "extensions")]
+			callstack: Vec::new(),
+
+			#[cfg(feature = "extensions")]
+			eval_depth: self.eval_depth,
+		}
+	}
+
 	/// Gets a random [`Integer`].
 	#[must_use]
 	#[inline]
 	pub fn random(&mut self) -> Integer {
 		Integer::random(&mut self.rng, self.flags)
 	}
+
+	/// Gets the random number generator used by [`random`](Self::random).
+	#[must_use]
+	#[inline]
+	pub fn rng(&mut self) -> &mut StdRng {
+		&mut self.rng
+	}
 }
 
 #[cfg(feature = "extensions")]
@@ -174,7 +486,26 @@ impl Environment<'_> {
 	#[must_use]
 	#[inline]
 	pub fn extensions(&self) -> &HashSet<ExtensionFunction> {
-		&self.extensions
+		self.functions.extensions()
+	}
+
+	/// Gets the alternative names for extension functions, mapped to the names they resolve to.
+	///
+	/// See [`Builder::extension_aliases`] for details.
+	#[must_use]
+	#[inline]
+	pub fn extension_aliases(&self) -> &HashMap<Text, Text> {
+		self.functions.extension_aliases()
+	}
+
+	/// Looks up the extension function named `name` (e.g. `XSRAND`).
+	///
+	/// If [`case_insensitive_extensions`](crate::env::flags::Extensions::case_insensitive_extensions)
+	/// is enabled, `name` is uppercased first. Then, if `name` is an [alias](Self::extension_aliases),
+	/// the extension it refers to is returned.
+	#[must_use]
+	pub fn lookup_extension(&self, name: &TextSlice) -> Option<&ExtensionFunction> {
+		self.functions.lookup_extension(name, self.flags)
 	}
 
 	/// Seeds the random number generator.
@@ -195,6 +526,12 @@ impl Environment<'_> {
 		self.system_results.push_back(output);
 	}
 
+	/// Removes every result added via [`add_to_system`](Self::add_to_system).
+	#[inline]
+	pub fn clear_system_results(&mut self) {
+		self.system_results.clear();
+	}
+
 	/// Gets the next result from within system.
 	#[must_use]
 	#[inline]
@@ -208,6 +545,66 @@ impl Environment<'_> {
 		(self.read_file)(filename, self.flags)
 	}
 
+	/// Gets the directories that `USE` and `XIMPORT` search for relative filenames.
+	///
+	/// See [`include::default_resolve`] for details.
+	#[must_use]
+	#[inline]
+	pub fn include_paths(&mut self) -> &mut Vec<PathBuf> {
+		&mut self.includes.search_paths
+	}
+
+	/// Forgets every file that's been compiled by `USE` and `XIMPORT`, so that they're read again
+	/// the next time they're included.
+	#[inline]
+	pub fn clear_include_cache(&mut self) {
+		self.includes.cache.clear();
+	}
+
+	/// Resolves `filename` with the include resolver, and then reads and parses it. If it was
+	/// already compiled (with the same `prefix`) and hasn't been modified since, the cached program
+	/// is returned instead.
+	fn load_include(&mut self, filename: &TextSlice, prefix: Option<&TextSlice>) -> Result<Value> {
+		let resolved = self.includes.resolve(filename, self.flags)?;
+		let key = (resolved.path, prefix.map(Text::from));
+
+		if let Some(program) = resolved.modified.and_then(|when| self.includes.cached(&key, when)) {
+			return Ok(program);
+		}
+
+		let path = Text::new(key.0.to_string_lossy(), self.flags)?;
+		let contents = self.read_file(&path)?;
+		let program = match prefix {
+			Some(prefix) => {
+				Parser::new(&contents, &mut self.prefixed_child(prefix)?).parse_program()?
+			}
+			None => Parser::new(&contents, self).parse_program()?,
+		};
+
+		if let Some(modified) = resolved.modified {
+			self.includes.cache.insert(key, (modified, program.clone()));
+		}
+
+		Ok(program)
+	}
+
+	/// Runs the file `filename`, as `USE` does.
+	///
+	/// Relative filenames are looked up in the [include paths](Self::include_paths), and the
+	/// compiled file is cached; see [`Builder::resolve_include`] for details.
+	pub fn use_file(&mut self, filename: &TextSlice) -> Result<Value> {
+		self.load_include(filename, None)?.run(self)
+	}
+
+	/// Runs the file `filename` with all its variables prefixed by `prefix` and an `_`, as
+	/// `XIMPORT` does.
+	///
+	/// Files are looked up and cached the same way as [`use_file`](Self::use_file).
+	pub fn import_file(&mut self, filename: &TextSlice, prefix: &TextSlice) -> Result<Value> {
+		let program = self.load_include(filename, Some(prefix))?;
+		program.run(&mut self.prefixed_child(prefix)?)
+	}
+
 	#[inline]
 	pub fn callstack(&mut self) -> &mut Vec<List> {
 		&mut self.callstack
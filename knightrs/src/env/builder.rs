@@ -6,19 +6,22 @@ pub struct Builder<'e> {
 	flags: &'e Flags,
 	prompt: Prompt<'e>,
 	output: Output<'e>,
-	functions: HashSet<Function>,
 
-	// While not feature gated to extensions, it's only modifiable with extensions.
-	parsers: Vec<ParseFn>,
-
-	#[cfg(feature = "extensions")]
-	extensions: HashSet<ExtensionFunction>,
+	// Only created when it's first needed, so environments given a shared table don't also build
+	// their own.
+	functions: Option<SharedFunctionTable>,
 
 	#[cfg(feature = "extensions")]
 	system: Option<Box<System<'e>>>,
 
 	#[cfg(feature = "extensions")]
 	read_file: Option<Box<ReadFile<'e>>>,
+
+	#[cfg(feature = "extensions")]
+	include_paths: Vec<PathBuf>,
+
+	#[cfg(feature = "extensions")]
+	resolve_include: Option<Box<include::Resolve<'e>>>,
 }
 
 impl Default for Builder<'_> {
@@ -35,17 +38,19 @@ impl<'e> Builder<'e> {
 			flags,
 			prompt: Prompt::new(flags),
 			output: Output::new(flags),
-			functions: Function::default_set(&flags),
-			parsers: crate::parse::default(&flags),
-
-			#[cfg(feature = "extensions")]
-			extensions: ExtensionFunction::default_set(&flags),
+			functions: None,
 
 			#[cfg(feature = "extensions")]
 			system: None,
 
 			#[cfg(feature = "extensions")]
 			read_file: None,
+
+			#[cfg(feature = "extensions")]
+			include_paths: Vec::new(),
+
+			#[cfg(feature = "extensions")]
+			resolve_include: None,
 		}
 	}
 
@@ -56,16 +61,45 @@ impl<'e> Builder<'e> {
 		self.prompt.set_stdin(stdin);
 	}
 
+	/// Sets a function that refills stdin when it reaches end of file; see
+	/// [`Prompt::set_eof_hook`](super::prompt::Prompt::set_eof_hook).
+	pub fn eof_hook<F>(&mut self, hook: F)
+	where
+		F: FnMut() -> Option<String> + 'e + Send + Sync,
+	{
+		self.prompt.set_eof_hook(hook);
+	}
+
 	/// Sets the stdout, which is used when `OUTPUT` and `DUMP` are run.
 	pub fn stdout<S: super::output::Stdout + 'e>(&mut self, stdout: S) {
 		self.output.set_stdout(stdout);
 	}
 
+	/// Sets the stream that `DUMP` writes to, instead of stdout.
+	pub fn dump_stream<S: super::output::Stdout + 'e>(&mut self, stream: S) {
+		self.output.set_dump_stream(stream);
+	}
+
+	/// Sets the [`FunctionTable`] the environment uses, instead of building a new one.
+	///
+	/// The table is shared, not copied, unless it's modified via this builder afterwards (e.g. with
+	/// [`Builder::functions`]), in which case the builder gets its own copy of it.
+	pub fn function_table(&mut self, table: SharedFunctionTable) {
+		self.functions = Some(table);
+	}
+
+	// Gets the table to modify, copying it if it's shared.
+	fn function_table_mut(&mut self) -> &mut FunctionTable {
+		let flags = self.flags;
+		let table = self.functions.get_or_insert_with(|| FunctionTable::new(flags).into());
+		SharedFunctionTable::make_mut(table)
+	}
+
 	/// Gets a mutable set of normal (i.e. non-`X`) functions.
 	///
 	/// See [`Builder::extensions`] for extension functions.
 	pub fn functions(&mut self) -> &mut HashSet<Function> {
-		&mut self.functions
+		self.function_table_mut().functions_mut()
 	}
 
 	/// Gets a list of extension (i.e. `X`) functions.
@@ -74,14 +108,42 @@ impl<'e> Builder<'e> {
 	#[cfg(feature = "extensions")]
 	#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
 	pub fn extensions(&mut self) -> &mut HashSet<ExtensionFunction> {
-		&mut self.extensions
+		self.function_table_mut().extensions_mut()
+	}
+
+	/// Gets a mutable map of alternative names for extension functions.
+	///
+	/// Keys are the alias (e.g. `XSEED`), and values are the [full name](Function::full_name) of the
+	/// extension they refer to (e.g. `XSRAND`). This lets renamed extensions keep their old names
+	/// working. Aliases are only resolved once, so an alias can't refer to another alias.
+	///
+	/// # Examples
+	/// ```
+	/// # use knightrs::{env::{Builder, Flags}, value::{Text, TextSlice, Value}};
+	/// let mut flags = Flags::default();
+	/// flags.extensions.functions.xsrand = true;
+	///
+	/// let mut builder = Builder::new(&flags);
+	/// builder.extension_aliases().insert(
+	/// 	Text::new("XSEED", &flags).unwrap(),
+	/// 	Text::new("XSRAND", &flags).unwrap(),
+	/// );
+	///
+	/// let mut env = builder.build();
+	/// let source = TextSlice::new("; XSEED 1 XSRAND 1", &flags).unwrap();
+	/// assert_eq!(env.play(source).unwrap(), Value::Null);
+	/// ```
+	#[cfg(feature = "extensions")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+	pub fn extension_aliases(&mut self) -> &mut HashMap<Text, Text> {
+		self.function_table_mut().extension_aliases_mut()
 	}
 
 	/// Gets a list of parse functions, which can be used to modify how parsing is done.
 	#[cfg(feature = "extensions")]
 	#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
 	pub fn parse_fns(&mut self) -> &mut Vec<ParseFn> {
-		&mut self.parsers
+		self.function_table_mut().parsers_mut()
 	}
 
 	/// Configure what happens when `$` is run.
@@ -104,6 +166,37 @@ impl<'e> Builder<'e> {
 		self.read_file = Some(Box::new(func) as Box<_>);
 	}
 
+	/// Gets the directories that `USE` and `XIMPORT` search for relative filenames.
+	///
+	/// If empty (the default), relative filenames are relative to the current directory. See
+	/// [`include::default_resolve`] for details.
+	#[cfg(feature = "extensions")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+	pub fn include_paths(&mut self) -> &mut Vec<PathBuf> {
+		&mut self.include_paths
+	}
+
+	/// Configure how `USE` and `XIMPORT` find the files they're given.
+	///
+	/// `func` is passed the filename, the [include paths](Self::include_paths), and the flags. The
+	/// [`path`](include::ResolvedInclude::path) it returns is then given to [`read_file`](
+	/// Self::read_file), so the two can be used together to provide a virtual filesystem. Compiled
+	/// files are cached by their path, and only reused while their [`modified`](
+	/// include::ResolvedInclude::modified) time stays the same.
+	///
+	/// If not set, [`include::default_resolve`] is used.
+	#[cfg(feature = "extensions")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+	pub fn resolve_include<F>(&mut self, func: F)
+	where
+		F: FnMut(&TextSlice, &[PathBuf], &Flags) -> crate::Result<include::ResolvedInclude>
+			+ 'e
+			+ Send
+			+ Sync,
+	{
+		self.resolve_include = Some(Box::new(func) as Box<_>);
+	}
+
 	/// Finishes the builder and creates the given environment.
 	///
 	/// Any values not set use their default values.
@@ -111,16 +204,16 @@ impl<'e> Builder<'e> {
 		Environment {
 			flags: self.flags,
 
-			variables: HashSet::default(),
+			variables: IndexSet::default(),
+			parent: ParentVariables::None,
 			prompt: self.prompt,
 			output: self.output,
-			functions: self.functions,
-			parsers: self.parsers,
+			functions: self
+				.functions
+				.unwrap_or_else(|| SharedFunctionTable::new(FunctionTable::new(self.flags))),
 
 			rng: StdRng::from_entropy(),
-
-			#[cfg(feature = "extensions")]
-			extensions: self.extensions,
+			cancel: CancelHandle::default(),
 
 			#[cfg(feature = "extensions")]
 			system: self.system.unwrap_or_else(|| {
@@ -145,11 +238,20 @@ impl<'e> Builder<'e> {
 				Box::new(|filename, flags| Ok(Text::new(std::fs::read_to_string(&**filename)?, flags)?))
 			}),
 
+			#[cfg(feature = "extensions")]
+			includes: include::Includes::new(
+				self.include_paths,
+				self.resolve_include.unwrap_or_else(|| Box::new(include::default_resolve)),
+			),
+
 			#[cfg(feature = "extensions")]
 			system_results: Default::default(),
 
 			#[cfg(feature = "extensions")]
 			callstack: Vec::default(),
+
+			#[cfg(feature = "extensions")]
+			eval_depth: 0,
 		}
 	}
 }
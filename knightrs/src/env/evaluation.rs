@@ -0,0 +1,95 @@
+//! The result of [`Environment::evaluate`](super::Environment::evaluate).
+
+use super::Variable;
+use crate::value::Value;
+use indexmap::IndexMap;
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
+
+/// Everything that happened when running a program via
+/// [`Environment::evaluate`](super::Environment::evaluate).
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct Evaluation {
+	/// The value the program returned, or the error that occurred while parsing or running it.
+	pub value: crate::Result<Value>,
+
+	/// Everything the program wrote to stdout via `OUTPUT` (and `DUMP`, unless it has its own
+	/// [stream](super::output::Output::set_dump_stream)).
+	pub stdout: Vec<u8>,
+
+	/// Suspicious things about the program, which didn't stop it from running. This is empty if it
+	/// couldn't be parsed.
+	pub warnings: Vec<Warning>,
+
+	/// How long parsing the program took.
+	pub parse_time: Duration,
+
+	/// How long running the program took. This is zero if it couldn't be parsed.
+	pub run_time: Duration,
+
+	/// Statistics about running the program. These are all zero if it couldn't be parsed.
+	pub stats: Stats,
+}
+
+impl Evaluation {
+	/// Returns [`Evaluation::stdout`] as a string, replacing invalid UTF-8 sequences.
+	pub fn stdout_lossy(&self) -> std::borrow::Cow<'_, str> {
+		String::from_utf8_lossy(&self.stdout)
+	}
+}
+
+/// Something suspicious about a program, which didn't stop it from running.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Warning {
+	/// The program uses the variable, but never assigns to it with `=`.
+	///
+	/// The variable may still have a value, eg if it was assigned by an `EVAL` or by an earlier
+	/// program run in the same environment.
+	NeverAssigned(String),
+}
+
+impl Display for Warning {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		match self {
+			Self::NeverAssigned(name) => write!(f, "variable `{name}` is never assigned"),
+		}
+	}
+}
+
+/// Statistics about a program run via [`Environment::evaluate`](super::Environment::evaluate).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Stats {
+	/// How many of the variables the program uses have a value once it's finished.
+	pub variables_used: usize,
+}
+
+// Returns every variable that `program` uses, in the order they first appear, along with whether
+// it's ever the target of `=`.
+pub(super) fn variables_in(program: &Value) -> IndexMap<Variable, bool> {
+	fn visit(value: &Value, variables: &mut IndexMap<Variable, bool>) {
+		match value {
+			Value::Variable(variable) => {
+				variables.entry(variable.clone()).or_insert(false);
+			}
+			Value::Ast(ast) => {
+				if let (true, Some(Value::Variable(variable))) =
+					(ast.function().full_name() == "=", ast.args().first())
+				{
+					variables.insert(variable.clone(), true);
+				}
+
+				for arg in ast.args() {
+					visit(arg, variables);
+				}
+			}
+			_ => {}
+		}
+	}
+
+	let mut variables = IndexMap::new();
+	visit(program, &mut variables);
+	variables
+}
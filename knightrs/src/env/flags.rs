@@ -1,5 +1,7 @@
 //! Flags to change how the Knight interpreter works at runtime.
 
+use knight_strings::IntegerOverflow;
+
 /// A set of flags that can be toggled to change how the interpreter runs.
 ///
 /// Normally, all flags default to `false`. However, if `strict-compliance` is enabled, then the
@@ -22,6 +24,30 @@ pub struct Flags {
 	#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
 	#[cfg_attr(feature = "clap", command(flatten))]
 	pub extensions: Extensions,
+
+	/// Quality-of-life flags, which don't change what programs do.
+	#[cfg_attr(feature = "clap", command(flatten))]
+	pub qol: QualityOfLife,
+
+	/// Flags for how [`OUTPUT`](crate::function::OUTPUT) writes to stdout.
+	#[cfg_attr(feature = "clap", command(flatten))]
+	pub io: Io,
+
+	/// The longest [`Text`](crate::value::Text) or [`List`](crate::value::List) that `*` is allowed
+	/// to create by repetition.
+	///
+	/// This is always checked, regardless of [`Compliance::check_container_length`], so programs
+	/// like `* (+,1,1) 99999999999` error out instead of attempting absurd allocations. It defaults
+	/// to [`i32::MAX`], which is the longest container the Knight spec requires.
+	#[cfg_attr(feature = "clap", arg(long, default_value_t = DEFAULT_MAX_REPETITION_LENGTH))]
+	pub max_repetition_length: usize,
+
+	/// What happens when a string containing an out-of-bounds integer is converted to an integer.
+	///
+	/// This is ignored (and overflowing is always an error) when [`Compliance::check_overflow`] is
+	/// enabled.
+	#[cfg_attr(feature = "clap", arg(long, value_enum, default_value_t = IntegerOverflow::Wrap))]
+	pub string_to_integer_overflow: IntegerOverflow,
 }
 
 impl Default for Flags {
@@ -44,6 +70,8 @@ const STRICT_COMPLIANCE: bool = cfg!(feature = "strict-compliance");
 #[cfg(feature = "extensions")]
 const ALL_EXTENSIONS: bool = cfg!(feature = "all-extensions");
 
+const DEFAULT_MAX_REPETITION_LENGTH: usize = knight_strings::MAX_LEN;
+
 // We need this as a `static` because `env::Builder::default` needs to take a reference to a Flag.
 pub(crate) static DEFAULT: Flags = Flags {
 	#[cfg(feature = "compliance")]
@@ -58,7 +86,9 @@ pub(crate) static DEFAULT: Flags = Flags {
 		limit_rand_range: STRICT_COMPLIANCE,
 		check_equals_params: STRICT_COMPLIANCE,
 		check_container_length: STRICT_COMPLIANCE,
+		max_container_length: None,
 		check_integer_function_bounds: STRICT_COMPLIANCE,
+		strict_conversions: STRICT_COMPLIANCE,
 	},
 	#[cfg(feature = "extensions")]
 	extensions: Extensions {
@@ -81,6 +111,16 @@ pub(crate) static DEFAULT: Flags = Flags {
 			xrange: ALL_EXTENSIONS,
 			xget: ALL_EXTENSIONS,
 			xset: ALL_EXTENSIONS,
+			xbase: ALL_EXTENSIONS,
+			xbitwise: ALL_EXTENSIONS,
+			xcmp: ALL_EXTENSIONS,
+			ximport: ALL_EXTENSIONS,
+			xapply: ALL_EXTENSIONS,
+			xtypeof: ALL_EXTENSIONS,
+			xconvert: ALL_EXTENSIONS,
+			xrandom: ALL_EXTENSIONS,
+			split: ALL_EXTENSIONS,
+			assert: ALL_EXTENSIONS,
 		},
 		types: Types {
 			boolean: ALL_EXTENSIONS,
@@ -93,13 +133,137 @@ pub(crate) static DEFAULT: Flags = Flags {
 			negating_a_list_inverts_it: cfg!(feature = "all-iffy-extensions"),
 			unassigned_variables_default_to_null: cfg!(feature = "all-iffy-extensions"),
 			negative_random_integers: cfg!(feature = "all-iffy-extensions"),
+			euclidean_remainder: cfg!(feature = "all-iffy-extensions"),
 		},
 		negative_indexing: ALL_EXTENSIONS,
+		negative_ranges: ALL_EXTENSIONS,
 		list_literal: ALL_EXTENSIONS,
 		block_params: ALL_EXTENSIONS,
+		// Not enabled by `all-extensions`, as it rejects otherwise-valid Knight programs.
+		strict_keywords: false,
+		// Not enabled by `all-extensions`, as it changes how `X` followed by lowercase letters parses.
+		case_insensitive_extensions: false,
+		max_eval_depth: None,
+		max_eval_length: None,
+	},
+	qol: QualityOfLife {
+		dump_to_stderr: false,
+		ignore_broken_pipe: false,
+		cache_small_integers: false,
 	},
+	io: Io { newline: Newline::Lf, flush: FlushPolicy::EveryOutput },
+	max_repetition_length: DEFAULT_MAX_REPETITION_LENGTH,
+	string_to_integer_overflow: IntegerOverflow::Wrap,
 };
 
+/// Flags which make the interpreter nicer to use, without changing what programs do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "clap", derive(clap::Args))]
+#[cfg_attr(feature = "clap", command(next_line_help = false))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct QualityOfLife {
+	/// Write [`DUMP`](crate::function::DUMP)'s output to stderr instead of stdout, so debugging
+	/// doesn't interfere with a program's output.
+	///
+	/// This is only used as the default; see [`Output::set_dump_stream`](
+	/// crate::env::output::Output::set_dump_stream).
+	#[cfg_attr(feature = "clap", arg(long))]
+	pub dump_to_stderr: bool,
+
+	/// When [`OUTPUT`](crate::function::OUTPUT) or [`DUMP`](crate::function::DUMP) fails because
+	/// stdout was closed (ie `EPIPE`, such as when piping into `head`), quit successfully instead of
+	/// raising an error, like most Unix tools do.
+	#[cfg_attr(feature = "clap", arg(long))]
+	pub ignore_broken_pipe: bool,
+
+	/// Reuse the same [`Text`](crate::value::Text)s when converting integers in
+	/// [`Integer::SMALL_TEXTS`](crate::value::Integer::SMALL_TEXTS) to text, instead of allocating
+	/// new ones each time. This speeds up programs which `OUTPUT` lots of small numbers.
+	#[cfg_attr(feature = "clap", arg(long))]
+	pub cache_small_integers: bool,
+}
+
+impl Default for QualityOfLife {
+	#[inline]
+	fn default() -> Self {
+		DEFAULT.qol
+	}
+}
+
+/// Flags for how [`OUTPUT`](crate::function::OUTPUT) writes to stdout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "clap", derive(clap::Args))]
+#[cfg_attr(feature = "clap", command(next_line_help = false))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Io {
+	/// What `OUTPUT` ends lines with. The Knight spec requires [`Newline::Lf`].
+	#[cfg_attr(feature = "clap", arg(long, value_enum, default_value_t = Newline::Lf))]
+	pub newline: Newline,
+
+	/// When `OUTPUT`'s writes are flushed to stdout.
+	///
+	/// This is only read when the [`Environment`](crate::env::Environment) is created, and doesn't
+	/// apply to stdouts given to [`Output::set_stdout`](crate::env::output::Output::set_stdout).
+	#[cfg_attr(feature = "clap", arg(long, value_enum, default_value_t = FlushPolicy::EveryOutput))]
+	pub flush: FlushPolicy,
+}
+
+impl Default for Io {
+	#[inline]
+	fn default() -> Self {
+		DEFAULT.io
+	}
+}
+
+/// What [`OUTPUT`](crate::function::OUTPUT) ends lines with.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Newline {
+	/// `\n`, as the Knight spec requires.
+	#[default]
+	Lf,
+
+	/// `\r\n`, for Windows-style line endings.
+	CrLf,
+}
+
+impl Newline {
+	/// Gets the characters that end a line.
+	#[must_use]
+	pub const fn as_str(self) -> &'static str {
+		match self {
+			Self::Lf => "\n",
+			Self::CrLf => "\r\n",
+		}
+	}
+}
+
+/// When [`OUTPUT`](crate::function::OUTPUT)'s writes are flushed to stdout.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum FlushPolicy {
+	/// Flush after every `OUTPUT`. This is the slowest, but always keeps stdout up-to-date.
+	#[default]
+	EveryOutput,
+
+	/// Flush whenever a newline is written, as stdout normally does.
+	Line,
+
+	/// Buffer stdout, and only flush when the buffer fills up, before `PROMPT`, and when the
+	/// program exits.
+	OnExit,
+
+	/// Buffer stdout, and only flush when the buffer fills up and when the program exits. Unlike
+	/// [`FlushPolicy::OnExit`], the buffer isn't flushed before `PROMPT`.
+	Never,
+}
+
 cfg_if! {
 if #[cfg(feature = "compliance")] {
 	/// Flags related to catching undefined behaviour in Knight programs.
@@ -168,10 +332,18 @@ if #[cfg(feature = "compliance")] {
 		pub check_equals_params: bool,
 
 		/// Ensures that the length of [`Text`](crate::value::Text)s and [`List`](crate::value::List)s
-		/// are no larger than [`i32::MAX`].
+		/// are no larger than [`i32::MAX`] (or [`max_container_length`](
+		/// Compliance::max_container_length), if it's set).
 		#[cfg_attr(feature = "clap", arg(long))]
 		pub check_container_length: bool,
 
+		/// A custom maximum length for [`check_container_length`](
+		/// Compliance::check_container_length).
+		///
+		/// When `None`, [`knight_strings::MAX_LEN`] (ie [`i32::MAX`]) is used, as per the Knight spec.
+		#[cfg_attr(feature = "clap", arg(long))]
+		pub max_container_length: Option<usize>,
+
 		/// Ensures that [`Integer::power`](crate::value::Integer::power) and [`Integer::remainder`](
 		/// crate::value::Integer::remainder) and are called with valid arguments only.
 		///
@@ -180,6 +352,13 @@ if #[cfg(feature = "compliance")] {
 		/// and modulo by zero are checked.
 		#[cfg_attr(feature = "clap", arg(long))]
 		pub check_integer_function_bounds: bool,
+
+		/// Ensures that all conversions are strictly spec-conformant.
+		///
+		/// Without this, negative integer -> list conversions, and boolean -> list conversions are
+		/// defined.
+		#[cfg_attr(feature = "clap", arg(long))]
+		pub strict_conversions: bool,
 	}
 
 	impl Default for Compliance {
@@ -188,6 +367,19 @@ if #[cfg(feature = "compliance")] {
 			DEFAULT.compliance
 		}
 	}
+
+	impl Compliance {
+		/// Returns whether a [`Text`](crate::value::Text) or [`List`](crate::value::List) of length
+		/// `len` is allowed.
+		///
+		/// This is always true unless [`check_container_length`](Compliance::check_container_length)
+		/// is enabled.
+		#[inline]
+		pub const fn is_valid_container_length(&self, len: usize) -> bool {
+			!self.check_container_length
+				|| knight_strings::is_valid_container_length(len, self.max_container_length)
+		}
+	}
 }} // compliance
 
 cfg_if! {
@@ -228,11 +420,42 @@ if #[cfg(feature = "extensions")] {
 		#[cfg_attr(feature = "clap", arg(long))]
 		pub negative_indexing: bool,
 
+		/// [`XRANGE`](crate::function::XRANGE) counts down when its start is greater than its stop,
+		/// instead of it being an error.
+		#[cfg_attr(feature = "clap", arg(long))]
+		pub negative_ranges: bool,
+
 		/// Enables the list literal syntax
 		///
 		/// For example, `{ TRUE FALSE NULL }` desugars to `++, TRUE, FALSE ,NULL`.
 		#[cfg_attr(feature = "clap", arg(long))]
 		pub list_literal: bool,
+
+		/// Keywords must either be a single letter or spelled out entirely (e.g. `O` or `OUTPUT`, but
+		/// not `OUT`), and unknown uppercase keywords are parse errors.
+		///
+		/// Normally, everything after the first letter of a keyword is ignored.
+		#[cfg_attr(feature = "clap", arg(long))]
+		pub strict_keywords: bool,
+
+		/// Extension function names are matched case-insensitively, so `Xsrand` is the same as
+		/// `XSRAND`.
+		///
+		/// Normally, only uppercase letters and `_` are part of an extension's name.
+		#[cfg_attr(feature = "clap", arg(long))]
+		pub case_insensitive_extensions: bool,
+
+		/// The most [`EVAL`](crate::function::EVAL)s that can be nested inside each other.
+		///
+		/// When `None`, `EVAL` can nest until the interpreter runs out of stack.
+		#[cfg_attr(feature = "clap", arg(long))]
+		pub max_eval_depth: Option<usize>,
+
+		/// The longest source code, in bytes, that [`EVAL`](crate::function::EVAL) will run.
+		///
+		/// When `None`, source code of any length is allowed.
+		#[cfg_attr(feature = "clap", arg(long))]
+		pub max_eval_length: Option<usize>,
 	}
 
 	impl Default for Extensions {
@@ -293,6 +516,52 @@ if #[cfg(feature = "extensions")] {
 		/// Enables the [`XSET`](crate::function::XSET) (accessed as `XG`) function.
 		#[cfg_attr(feature = "clap", arg(long))]
 		pub xset: bool,
+
+		/// Enables the [`XBASE`](crate::function::XBASE) and [`XFROMBASE`](
+		/// crate::function::XFROMBASE) functions.
+		#[cfg_attr(feature = "clap", arg(long))]
+		pub xbase: bool,
+
+		/// Enables the [`XBAND`](crate::function::XBAND), [`XBOR`](crate::function::XBOR),
+		/// [`XBXOR`](crate::function::XBXOR), [`XSHL`](crate::function::XSHL), and [`XSHR`](
+		/// crate::function::XSHR) functions.
+		#[cfg_attr(feature = "clap", arg(long))]
+		pub xbitwise: bool,
+
+		/// Enables the [`XCMP`](crate::function::XCMP) function.
+		#[cfg_attr(feature = "clap", arg(long))]
+		pub xcmp: bool,
+
+		/// Enables the [`XIMPORT`](crate::function::XIMPORT) function.
+		#[cfg_attr(feature = "clap", arg(long))]
+		pub ximport: bool,
+
+		/// Enables the [`XAPPLY`](crate::function::XAPPLY) function.
+		#[cfg_attr(feature = "clap", arg(long))]
+		pub xapply: bool,
+
+		/// Enables the [`XTYPEOF`](crate::function::XTYPEOF) function.
+		#[cfg_attr(feature = "clap", arg(long))]
+		pub xtypeof: bool,
+
+		/// Enables the [`XBOOL`](crate::function::XBOOL), [`XINT`](crate::function::XINT),
+		/// [`XSTR`](crate::function::XSTR), and [`XLIST`](crate::function::XLIST) functions.
+		#[cfg_attr(feature = "clap", arg(long))]
+		pub xconvert: bool,
+
+		/// Enables the [`XSHUFFLE`](crate::function::XSHUFFLE) and [`XCHOICE`](
+		/// crate::function::XCHOICE) functions.
+		#[cfg_attr(feature = "clap", arg(long))]
+		pub xrandom: bool,
+
+		/// Enables the [`XSPLITLINES`](crate::function::XSPLITLINES) and [`XSPLITWS`](
+		/// crate::function::XSPLITWS) functions.
+		#[cfg_attr(feature = "clap", arg(long))]
+		pub split: bool,
+
+		/// Enables the [`XASSERT`](crate::function::XASSERT) function.
+		#[cfg_attr(feature = "clap", arg(long))]
+		pub assert: bool,
 	}
 
 	impl Default for Functions {
@@ -433,6 +702,14 @@ if #[cfg(feature = "iffy-extensions")] {
 		/// `RANDOM` can return negative integers
 		#[cfg_attr(feature = "clap", arg(long))]
 		pub negative_random_integers: bool,
+
+		/// `%` uses the Euclidean remainder (which is never negative) instead of truncating, so
+		/// `% ~7 3` is `2` rather than `-1`.
+		///
+		/// This only matters for negative operands, which [`check_integer_function_bounds`](
+		/// Compliance::check_integer_function_bounds) rejects regardless of this flag.
+		#[cfg_attr(feature = "clap", arg(long))]
+		pub euclidean_remainder: bool,
 	}
 
 	impl Default for Iffy {
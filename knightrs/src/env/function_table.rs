@@ -0,0 +1,156 @@
+//! Sets of functions and parsers which can be shared between environments.
+
+use super::Flags;
+use crate::containers::RefCount;
+use crate::function::Function;
+use crate::parse::ParseFn;
+use std::collections::HashSet;
+
+#[cfg(feature = "extensions")]
+use {
+	crate::function::ExtensionFunction,
+	crate::value::{Text, TextSlice},
+	std::collections::HashMap,
+};
+
+/// The functions, extension functions, and parsers that an [`Environment`](super::Environment)
+/// knows about.
+///
+/// Normally, every environment builds its own table. When lots of environments are created with the
+/// same functions (such as in a plugin host), a table can instead be built once, made into a
+/// [`SharedFunctionTable`], and given to each environment via [`Builder::function_table`](
+/// super::Builder::function_table). Environments never modify their table, so it's shared
+/// (including with their child environments) rather than copied.
+///
+/// # Examples
+/// ```
+/// # use knightrs::env::{Builder, Flags, FunctionTable, SharedFunctionTable};
+/// # use knightrs::value::{TextSlice, Value};
+/// let flags = Flags::default();
+/// let table = SharedFunctionTable::new(FunctionTable::new(&flags));
+///
+/// for _ in 0..10 {
+///     let mut builder = Builder::new(&flags);
+///     builder.function_table(table.clone());
+///
+///     let mut env = builder.build();
+///     let source = TextSlice::new("< 1 2", &flags).unwrap();
+///     assert_eq!(env.play(source).unwrap(), Value::from(true));
+/// }
+/// ```
+#[derive(Clone)]
+pub struct FunctionTable {
+	pub(super) functions: HashSet<Function>,
+
+	// While not feature gated to extensions, it's only modifiable with extensions.
+	pub(super) parsers: Vec<ParseFn>,
+
+	#[cfg(feature = "extensions")]
+	pub(super) extensions: HashSet<ExtensionFunction>,
+
+	#[cfg(feature = "extensions")]
+	pub(super) extension_aliases: HashMap<Text, Text>,
+}
+
+/// A [`FunctionTable`] which can be shared between environments.
+///
+/// This is an [`Arc`](std::sync::Arc) when the `multithreaded` feature is enabled (in which case
+/// it's `Send + Sync`, and so can be shared across threads), and an [`Rc`](std::rc::Rc) otherwise.
+pub type SharedFunctionTable = RefCount<FunctionTable>;
+
+impl FunctionTable {
+	/// Creates a new [`FunctionTable`] with the default functions and parsers for `flags`.
+	///
+	/// Extension functions are only included if they're enabled in `flags`. Note that the table
+	/// isn't tied to `flags`, so environments using it will have these functions regardless of
+	/// their own flags.
+	#[must_use]
+	pub fn new(flags: &Flags) -> Self {
+		Self {
+			functions: Function::default_set(flags),
+			parsers: crate::parse::default(flags),
+
+			#[cfg(feature = "extensions")]
+			extensions: ExtensionFunction::default_set(flags),
+
+			#[cfg(feature = "extensions")]
+			extension_aliases: HashMap::new(),
+		}
+	}
+
+	/// Gets the set of normal (i.e. non-`X`) functions.
+	#[must_use]
+	#[inline]
+	pub fn functions(&self) -> &HashSet<Function> {
+		&self.functions
+	}
+
+	/// Gets a mutable set of normal (i.e. non-`X`) functions.
+	#[inline]
+	pub fn functions_mut(&mut self) -> &mut HashSet<Function> {
+		&mut self.functions
+	}
+
+	/// Gets the list of parse functions.
+	#[must_use]
+	#[inline]
+	pub fn parsers(&self) -> &[ParseFn] {
+		&self.parsers
+	}
+
+	/// Gets a mutable list of parse functions, which can be used to modify how parsing is done.
+	#[cfg(feature = "extensions")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+	#[inline]
+	pub fn parsers_mut(&mut self) -> &mut Vec<ParseFn> {
+		&mut self.parsers
+	}
+}
+
+#[cfg(feature = "extensions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+impl FunctionTable {
+	/// Gets the set of extension (i.e. `X`) functions.
+	#[must_use]
+	#[inline]
+	pub fn extensions(&self) -> &HashSet<ExtensionFunction> {
+		&self.extensions
+	}
+
+	/// Gets a mutable set of extension (i.e. `X`) functions.
+	#[inline]
+	pub fn extensions_mut(&mut self) -> &mut HashSet<ExtensionFunction> {
+		&mut self.extensions
+	}
+
+	/// Gets the alternative names for extension functions; see
+	/// [`Builder::extension_aliases`](super::Builder::extension_aliases).
+	#[must_use]
+	#[inline]
+	pub fn extension_aliases(&self) -> &HashMap<Text, Text> {
+		&self.extension_aliases
+	}
+
+	/// Gets a mutable map of alternative names for extension functions; see
+	/// [`Builder::extension_aliases`](super::Builder::extension_aliases).
+	#[inline]
+	pub fn extension_aliases_mut(&mut self) -> &mut HashMap<Text, Text> {
+		&mut self.extension_aliases
+	}
+
+	/// Looks up the extension function named `name`; see
+	/// [`Environment::lookup_extension`](super::Environment::lookup_extension).
+	#[must_use]
+	pub fn lookup_extension(&self, name: &TextSlice, flags: &Flags) -> Option<&ExtensionFunction> {
+		let uppercased;
+		let mut name = name;
+
+		if flags.extensions.case_insensitive_extensions {
+			uppercased = name.to_uppercase();
+			name = TextSlice::new(&uppercased, flags).ok()?;
+		}
+
+		let name = self.extension_aliases.get(name).map_or(name, |alias| &**alias);
+		self.extensions.get(name)
+	}
+}
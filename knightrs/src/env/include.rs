@@ -0,0 +1,101 @@
+//! Resolving and caching the files run by [`USE`](crate::function::USE) and
+//! [`XIMPORT`](crate::function::XIMPORT).
+
+use super::Flags;
+use crate::value::{Text, TextSlice, Value};
+use crate::Result;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Where a file that's being included was found.
+///
+/// This is returned by an [`Environment`](super::Environment)'s include resolver; see
+/// [`Builder::resolve_include`](super::Builder::resolve_include) for how to override it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct ResolvedInclude {
+	/// The file's path. This is passed to [`read_file`](super::Environment::read_file), and is
+	/// what compiled files are cached by, so it should be canonical.
+	pub path: PathBuf,
+
+	/// When the file was last modified.
+	///
+	/// The compiled file is cached, and reused for as long as this doesn't change. If it's `None`,
+	/// the file is read and parsed every time it's included. (Resolvers for virtual filesystems can
+	/// use this as a version, rather than an actual modification time.)
+	pub modified: Option<SystemTime>,
+}
+
+impl ResolvedInclude {
+	/// Creates a new [`ResolvedInclude`].
+	#[must_use]
+	pub const fn new(path: PathBuf, modified: Option<SystemTime>) -> Self {
+		Self { path, modified }
+	}
+}
+
+pub(super) type Resolve<'e> =
+	dyn FnMut(&TextSlice, &[PathBuf], &Flags) -> Result<ResolvedInclude> + 'e + Send + Sync;
+
+/// The include resolver that's used unless [`Builder::resolve_include`](
+/// super::Builder::resolve_include) is given a different one.
+///
+/// If `filename` is relative, the first directory in `search_paths` containing it is used (or the
+/// current directory, if `search_paths` is empty). The path is then canonicalized, and its
+/// modification time looked up.
+///
+/// If `filename` doesn't exist on disk, it's returned unchanged and without a modification time,
+/// so that a custom [`read_file`](super::Builder::read_file) can still handle it.
+pub fn default_resolve(
+	filename: &TextSlice,
+	search_paths: &[PathBuf],
+	_: &Flags,
+) -> Result<ResolvedInclude> {
+	let filename = Path::new(&**filename);
+
+	let found = if filename.is_absolute() || search_paths.is_empty() {
+		filename.canonicalize().ok()
+	} else {
+		search_paths.iter().find_map(|dir| dir.join(filename).canonicalize().ok())
+	};
+
+	let Some(path) = found else {
+		return Ok(ResolvedInclude::new(filename.to_path_buf(), None));
+	};
+
+	let modified = std::fs::metadata(&path).and_then(|metadata| metadata.modified()).ok();
+	Ok(ResolvedInclude::new(path, modified))
+}
+
+// The include state of an `Environment`.
+pub(super) struct Includes<'e> {
+	pub(super) search_paths: Vec<PathBuf>,
+	pub(super) resolve: Box<Resolve<'e>>,
+
+	// Keyed by the resolved path and the `XIMPORT` prefix (if any), as the variables in a compiled
+	// file depend on its prefix.
+	pub(super) cache: HashMap<(PathBuf, Option<Text>), (SystemTime, Value)>,
+}
+
+impl<'e> Includes<'e> {
+	pub(super) fn new(search_paths: Vec<PathBuf>, resolve: Box<Resolve<'e>>) -> Self {
+		Self { search_paths, resolve, cache: HashMap::new() }
+	}
+
+	pub(super) fn resolve(
+		&mut self,
+		filename: &TextSlice,
+		flags: &Flags,
+	) -> Result<ResolvedInclude> {
+		(self.resolve)(filename, &self.search_paths, flags)
+	}
+
+	pub(super) fn cached(
+		&self,
+		key: &(PathBuf, Option<Text>),
+		modified: SystemTime,
+	) -> Option<Value> {
+		self.cache.get(key).filter(|(when, _)| *when == modified).map(|(_, program)| program.clone())
+	}
+}
@@ -1,7 +1,8 @@
 //! How Knight writes to stdout.
 
+use super::flags::FlushPolicy;
 use super::Flags;
-use crate::containers::MaybeSendSync;
+use crate::containers::{MaybeSendSync, Mutable, RefCount};
 use std::io::{self, Write};
 
 /// A trait used for writing to stdout.
@@ -29,7 +30,9 @@ impl<T: Write + MaybeSendSync> Stdout for T {}
 pub struct Output<'e> {
 	default: Box<dyn Stdout + 'e>,
 
-	#[cfg_attr(not(feature = "extensions"), allow(dead_code))]
+	// Where `DUMP` writes to; if `None`, it's the same as everything else.
+	dump: Option<Box<dyn Stdout + 'e>>,
+
 	flags: &'e Flags,
 
 	#[cfg(feature = "extensions")]
@@ -38,14 +41,34 @@ pub struct Output<'e> {
 
 impl<'e> Output<'e> {
 	pub(super) fn new(flags: &'e Flags) -> Self {
+		let default: Box<dyn Stdout> = match flags.io.flush {
+			FlushPolicy::EveryOutput | FlushPolicy::Line => Box::new(io::stdout()),
+			FlushPolicy::OnExit | FlushPolicy::Never => Box::new(io::BufWriter::new(io::stdout())),
+		};
+
 		Self {
-			default: Box::new(io::stdout()),
+			default,
+			dump: if flags.qol.dump_to_stderr { Some(Box::new(io::stderr())) } else { None },
 			flags,
 
 			#[cfg(feature = "extensions")]
 			redirect: None,
 		}
 	}
+
+	// Creates an `Output` for a child environment, which writes to `self`'s stdout and dump stream.
+	// Redirections aren't inherited, as they're bound to the parent's variables.
+	pub(super) fn child(&mut self) -> Output<'_> {
+		Output {
+			default: Box::new(&mut self.default),
+			dump: self.dump.as_mut().map(|dump| Box::new(dump) as Box<dyn Stdout + '_>),
+			flags: self.flags,
+
+			#[cfg(feature = "extensions")]
+			redirect: None,
+		}
+	}
+
 	/// Sets the default stdout.
 	///
 	/// This doesn't affect any pipes which are enabled.
@@ -53,6 +76,45 @@ impl<'e> Output<'e> {
 		self.default = Box::new(stdout);
 	}
 
+	// Replaces the default stdout, returning the old one so it can be restored.
+	pub(super) fn replace_stdout(&mut self, stdout: Box<dyn Stdout + 'e>) -> Box<dyn Stdout + 'e> {
+		std::mem::replace(&mut self.default, stdout)
+	}
+
+	/// Sets the stream that `DUMP` writes to, instead of stdout.
+	///
+	/// By default, this is stdout (or stderr, if [`dump_to_stderr`](
+	/// crate::env::flags::QualityOfLife::dump_to_stderr) is set).
+	pub fn set_dump_stream<S: Stdout + 'e>(&mut self, stream: S) {
+		self.dump = Some(Box::new(stream));
+	}
+
+	/// Makes `DUMP` write to the same place as everything else.
+	pub fn clear_dump_stream(&mut self) {
+		self.dump = None;
+	}
+
+	/// Gets the stream that `DUMP` should write to.
+	pub fn dump_stream(&mut self) -> &mut dyn Write {
+		match self.dump {
+			Some(ref mut dump) => dump,
+			None => self,
+		}
+	}
+
+	/// Converts an error from writing to `self` into an [`Error`](crate::Error).
+	///
+	/// If [`ignore_broken_pipe`](crate::env::flags::QualityOfLife::ignore_broken_pipe) is set and
+	/// `err` is a broken pipe, [`Error::Quit(0)`](crate::Error::Quit) is returned, so the program
+	/// stops successfully and without an error message.
+	pub fn write_error(&self, err: io::Error) -> crate::Error {
+		if self.flags.qol.ignore_broken_pipe && err.kind() == io::ErrorKind::BrokenPipe {
+			return crate::Error::Quit(0);
+		}
+
+		err.into()
+	}
+
 	/// Sets where stdout will be redirected to.
 	#[cfg(feature = "extensions")]
 	pub fn set_redirection(&mut self, variable: super::Variable) {
@@ -92,3 +154,25 @@ impl Write for Output<'_> {
 		self.default.flush()
 	}
 }
+
+// A `Stdout` which collects everything written to it, for `Environment::evaluate`. It's shared so
+// the buffer can be retrieved after it's been given to an `Output`.
+#[derive(Default, Clone)]
+pub(super) struct Capture(RefCount<Mutable<Vec<u8>>>);
+
+impl Capture {
+	pub(super) fn take(&self) -> Vec<u8> {
+		std::mem::take(&mut *self.0.write())
+	}
+}
+
+impl Write for Capture {
+	fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+		self.0.write().extend_from_slice(bytes);
+		Ok(bytes.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
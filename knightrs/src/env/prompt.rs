@@ -75,10 +75,15 @@ pub struct Prompt<'e> {
 	default: Box<dyn Stdin + 'e>,
 	flags: &'e Flags,
 
+	// Called when `default` reaches end of file; see `Prompt::set_eof_hook`.
+	eof_hook: Option<Box<EofHook<'e>>>,
+
 	#[cfg(feature = "extensions")]
 	replacement: Option<PromptReplacement>,
 }
 
+type EofHook<'e> = dyn FnMut() -> Option<String> + 'e + Send + Sync;
+
 #[cfg(feature = "extensions")]
 enum PromptReplacement {
 	Eof,
@@ -108,12 +113,46 @@ fn strip_ending(line: &mut String) {
 	}
 }
 
+/// What reading a line for `PROMPT` resulted in.
+///
+/// `PROMPT` returns `NULL` for both [`Eof`](Self::Eof) and [`Closed`](Self::Closed); they're only
+/// distinguished for embedders.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PromptResult {
+	/// A line was read, with its line ending stripped.
+	Line(Text),
+
+	/// Stdin reached end of file, and the [EOF hook](Prompt::set_eof_hook) (if any) didn't provide
+	/// any more input.
+	Eof,
+
+	/// A [replacement](Prompt#replacements) is acting like stdin is at end of file: either
+	/// [`Prompt::eof`] was used, the [buffered lines](Prompt::add_lines) ran out, or a computed
+	/// replacement returned `NULL`. This is never returned without `extensions`.
+	Closed,
+}
+
+impl PromptResult {
+	/// Gets the line that was read, or `None` if there wasn't one.
+	#[must_use]
+	pub fn into_line(self) -> Option<Text> {
+		match self {
+			Self::Line(line) => Some(line),
+			Self::Eof | Self::Closed => None,
+		}
+	}
+}
+
 /// Represents a line read from stdin.
 ///
 /// See [`Prompt::read_line`] for details.
-pub struct Line(Option<ReadLineResultInner>);
+pub struct Line(ReadLineResultInner);
 enum ReadLineResultInner {
 	Text(Text),
+	Eof,
+
+	#[cfg(feature = "extensions")]
+	Closed,
 
 	#[cfg(feature = "extensions")]
 	Ast(Ast),
@@ -122,14 +161,25 @@ enum ReadLineResultInner {
 impl Line {
 	/// Gets the `Text` corresponding to this line. Returns `None` if at eof.
 	pub fn get(self, env: &mut Environment) -> Result<Option<Text>> {
+		self.into_result(env).map(PromptResult::into_line)
+	}
+
+	/// Gets the [`PromptResult`] corresponding to this line, which distinguishes between stdin
+	/// reaching end of file and a replacement acting like it has.
+	pub fn into_result(self, env: &mut Environment) -> Result<PromptResult> {
+		let _ = env;
+
 		match self.0 {
-			None => Ok(None),
-			Some(ReadLineResultInner::Text(text)) => Ok(Some(text)),
+			ReadLineResultInner::Text(text) => Ok(PromptResult::Line(text)),
+			ReadLineResultInner::Eof => Ok(PromptResult::Eof),
+
+			#[cfg(feature = "extensions")]
+			ReadLineResultInner::Closed => Ok(PromptResult::Closed),
 
 			#[cfg(feature = "extensions")]
-			Some(ReadLineResultInner::Ast(ast)) => match ast.run(env)? {
-				Value::Null => Ok(None),
-				other => other.to_text(env).map(Some),
+			ReadLineResultInner::Ast(ast) => match ast.run(env)? {
+				Value::Null => Ok(PromptResult::Closed),
+				other => other.to_text(env).map(PromptResult::Line),
 			},
 		}
 	}
@@ -140,11 +190,26 @@ impl<'e> Prompt<'e> {
 		Self {
 			default: Box::new(io::BufReader::new(io::stdin())),
 			flags,
+			eof_hook: None,
+
+			#[cfg(feature = "extensions")]
+			replacement: None,
+		}
+	}
+
+	// Creates a `Prompt` for a child environment, which reads from `self`'s stdin and uses its EOF
+	// hook. Replacements aren't inherited.
+	pub(super) fn child(&mut self) -> Prompt<'_> {
+		Prompt {
+			default: Box::new(&mut self.default),
+			flags: self.flags,
+			eof_hook: self.eof_hook.as_mut().map(|hook| Box::new(move || hook()) as Box<EofHook<'_>>),
 
 			#[cfg(feature = "extensions")]
 			replacement: None,
 		}
 	}
+
 	/// Sets the default stdin.
 	///
 	/// This doesn't affect any replacements that may have been set.
@@ -152,6 +217,23 @@ impl<'e> Prompt<'e> {
 		self.default = Box::new(stdin);
 	}
 
+	/// Sets a function that's called whenever stdin reaches end of file.
+	///
+	/// If `hook` returns `Some`, it's read as the new stdin, and reading continues from it. This
+	/// lets interactive hosts refill the input on demand. If it returns `None`, the read results in
+	/// [`PromptResult::Eof`].
+	pub fn set_eof_hook<F>(&mut self, hook: F)
+	where
+		F: FnMut() -> Option<String> + 'e + Send + Sync,
+	{
+		self.eof_hook = Some(Box::new(hook));
+	}
+
+	/// Removes the function set by [`set_eof_hook`](Self::set_eof_hook).
+	pub fn clear_eof_hook(&mut self) {
+		self.eof_hook = None;
+	}
+
 	/// Reads a line from stdin.
 	///
 	/// Instead of directly returning the [`Text`] line, this instead returns the [`Line`] type. You
@@ -165,25 +247,30 @@ impl<'e> Prompt<'e> {
 	pub fn read_line(&mut self) -> Result<Line> {
 		#[cfg(feature = "extensions")]
 		match self.replacement.as_mut() {
-			Some(PromptReplacement::Eof) => return Ok(Line(None)),
+			Some(PromptReplacement::Eof) => return Ok(Line(ReadLineResultInner::Closed)),
 			Some(PromptReplacement::Buffered(queue)) => {
-				return Ok(Line(queue.pop_front().map(ReadLineResultInner::Text)))
+				return Ok(Line(
+					queue.pop_front().map_or(ReadLineResultInner::Closed, ReadLineResultInner::Text),
+				))
 			}
 			Some(PromptReplacement::Computed(ast)) => {
-				return Ok(Line(Some(ReadLineResultInner::Ast(ast.clone()))))
+				return Ok(Line(ReadLineResultInner::Ast(ast.clone())))
 			}
 			None => {}
 		}
 
 		let mut line = String::new();
 
-		// If we read an empty line, return null.
-		if self.default.read_line(&mut line)? == 0 {
-			return Ok(Line(None));
+		// If we read an empty line, we're at EOF; see if the hook has any more input for us.
+		while self.default.read_line(&mut line)? == 0 {
+			match self.eof_hook.as_mut().and_then(|hook| hook()) {
+				Some(more) => self.default = Box::new(io::Cursor::new(more.into_bytes())),
+				None => return Ok(Line(ReadLineResultInner::Eof)),
+			}
 		}
 
 		strip_ending(&mut line);
-		Ok(Line(Some(ReadLineResultInner::Text(Text::new(line, self.flags)?))))
+		Ok(Line(ReadLineResultInner::Text(Text::new(line, self.flags)?)))
 	}
 }
 
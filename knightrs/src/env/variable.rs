@@ -5,9 +5,10 @@ use crate::env::{Environment, Flags};
 use crate::parse::{self, Parsable, Parser};
 use crate::value::{NamedType, Runnable, Text, TextSlice, Value};
 use crate::{Error, Result};
+use knight_strings::syntax;
 use std::borrow::Borrow;
 use std::fmt::{self, Debug, Display, Formatter};
-use std::hash::{Hash, Hasher};
+use std::hash::{BuildHasherDefault, Hash, Hasher};
 
 /// Represents a variable within Knight.
 ///
@@ -18,6 +19,9 @@ pub struct Variable(RefCount<Inner>);
 struct Inner {
 	name: Text,
 	value: Mutable<Option<Value>>,
+
+	// The parent environment's variable, which is read from until `self` is assigned to.
+	fallback: Option<Variable>,
 }
 
 impl Debug for Variable {
@@ -61,6 +65,42 @@ impl NamedType for Variable {
 	const TYPENAME: &'static str = "Variable";
 }
 
+// A fast hasher for variable names (FNV-1a), used by the parser to cache lookups. Names are short,
+// and they come from the program itself, so resistance to collision attacks isn't needed.
+pub(crate) struct NameHasher(u64);
+
+pub(crate) type BuildNameHasher = BuildHasherDefault<NameHasher>;
+
+impl Default for NameHasher {
+	fn default() -> Self {
+		Self(0xcbf2_9ce4_8422_2325)
+	}
+}
+
+impl Hasher for NameHasher {
+	fn finish(&self) -> u64 {
+		self.0
+	}
+
+	fn write(&mut self, bytes: &[u8]) {
+		for &byte in bytes {
+			self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3);
+		}
+	}
+}
+
+/// Which of its parent's variables a [child environment](Environment::child) can see.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VariableVisibility {
+	/// The child has its own variables, and can't see any of its parent's.
+	#[default]
+	Isolated,
+
+	/// Variables which the parent has looked up are readable by the child, until the child assigns
+	/// to them. Assignments are never visible to the parent.
+	ReadThrough,
+}
+
 /// Indicates that a a variable name was illegal.
 ///
 /// While the enum itself is not feature gated, every one of its variants requires `compliance` to
@@ -139,7 +179,7 @@ impl Variable {
 				return !matches!(chr, 'a'..='z' | '_' | '0'..='9');
 			}
 
-			!chr.is_lowercase() && chr != '_' && !chr.is_numeric()
+			!syntax::is_variable_char(chr)
 		}) {
 			return Err(IllegalVariableName::IllegalBodyChar(bad));
 		}
@@ -154,7 +194,14 @@ impl Variable {
 		}
 
 		let _ = flags;
-		Ok(Self(Inner { name, value: None.into() }.into()))
+		Ok(Self(Inner { name, value: None.into(), fallback: None }.into()))
+	}
+
+	// Creates a variable which reads from `fallback` until it's assigned to. Since `fallback` is an
+	// existing variable, its name is already known to be valid.
+	pub(crate) fn with_fallback(fallback: Self) -> Self {
+		let name = fallback.name().clone();
+		Self(Inner { name, value: None.into(), fallback: Some(fallback) }.into())
 	}
 
 	/// Fetches the name of the variable.
@@ -171,10 +218,24 @@ impl Variable {
 	}
 
 	/// Fetches the last value assigned to `self`, returning `None` if it haven't been assigned yet.
+	///
+	/// If `self` was created by a [child environment](Environment::child) which can read its
+	/// parent's variables, the parent's variable is fetched until `self` is assigned.
 	#[must_use = "fetching the value of a variable does nothing on its own"]
 	#[inline]
 	pub fn fetch(&self) -> Option<Value> {
-		(self.0).value.read().clone()
+		if let Some(value) = (self.0).value.read().clone() {
+			return Some(value);
+		}
+
+		(self.0).fallback.as_ref().and_then(Self::fetch)
+	}
+
+	/// Removes the last value assigned to `self`, leaving it unassigned. This never affects a
+	/// parent environment's variable.
+	#[inline]
+	pub fn take(&self) -> Option<Value> {
+		(self.0).value.write().take()
 	}
 }
 
@@ -199,16 +260,15 @@ impl Parsable for Variable {
 	type Output = Self;
 
 	fn parse(parser: &mut Parser<'_, '_>) -> parse::Result<Option<Self>> {
-		if parser.peek().map_or(false, |c| !c.is_lowercase() && c != '_') {
+		if parser.peek().map_or(false, |c| !syntax::is_variable_start(c)) {
 			return Ok(None);
 		}
 
-		let Some(ident) = parser.take_while(|c| c.is_lowercase() || c == '_' || c.is_numeric())
-		else {
+		let Some(ident) = parser.take_while(syntax::is_variable_char) else {
 			return Ok(None);
 		};
 
-		match parser.env().lookup(ident) {
+		match parser.lookup_variable(ident) {
 			Ok(value) => Ok(Some(value)),
 			Err(err) => match err {
 				// When there's no compliance issues, there'll be nothing to match.
@@ -1,6 +1,7 @@
 use crate::env::variable::IllegalVariableName;
 use crate::parse::Error as ParseError;
 use crate::value::text::NewTextError;
+use crate::value::Integer;
 use std::fmt::{self, Display, Formatter};
 use std::io;
 
@@ -24,8 +25,9 @@ pub enum Error {
 	/// The correct type was supplied, but some requirements for it weren't met.
 	DomainError(&'static str),
 
-	/// Division/Modulo by zero.
-	DivisionByZero,
+	/// Division/Modulo/Power by zero. `operand` is the other argument: the dividend for `/` and
+	/// `%`, and the exponent for `^`.
+	DivisionByZero { kind: ZeroDivisionKind, operand: Integer },
 
 	/// There was an issue with parsing
 	///
@@ -39,6 +41,9 @@ pub enum Error {
 	/// error is returned; the caller can do what they wish then.
 	Quit(i32),
 
+	/// The program was stopped via a [`CancelHandle`](crate::env::CancelHandle).
+	Interrupted,
+
 	/// Indicates that either `GET` or `SET` were given an index that was out of bounds.
 	IndexOutOfBounds { len: usize, index: usize },
 
@@ -55,12 +60,48 @@ pub enum Error {
 	#[cfg_attr(docsrs, doc(cfg(feature = "compliance")))]
 	IllegalVariableName(IllegalVariableName),
 
+	/// [`EVAL`](crate::function::EVAL)s were nested more than [`max_eval_depth`](
+	/// crate::env::flags::Extensions::max_eval_depth) deep.
+	#[cfg(feature = "extensions")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+	EvalTooDeep { max: usize },
+
+	/// [`EVAL`](crate::function::EVAL) was given source code longer than [`max_eval_length`](
+	/// crate::env::flags::Extensions::max_eval_length).
+	#[cfg(feature = "extensions")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+	EvalTooLong { len: usize, max: usize },
+
+	/// An [`XASSERT`](crate::function::XASSERT)'s condition was false.
+	#[cfg(feature = "extensions")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+	AssertionFailed { message: String },
+
+	/// [`ExtensionFunction::symbolic`](crate::function::ExtensionFunction::symbolic) was given a
+	/// character which would be parsed as something other than a function.
+	#[cfg(feature = "extensions")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+	InvalidFunctionSymbol(char),
+
 	/// An error that doesn't fall into one of the other categories.
 	#[cfg(feature = "extensions")]
 	#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
 	Custom(Box<dyn std::error::Error + Send + Sync>),
 }
 
+/// What kind of operation caused an [`Error::DivisionByZero`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ZeroDivisionKind {
+	/// A `/` by zero.
+	Divide,
+
+	/// A `%` by zero.
+	Remainder,
+
+	/// A `^` with zero as the base and a negative exponent.
+	Power,
+}
+
 /// A type alias for `Result<T, Error>`.
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -126,9 +167,18 @@ impl Display for Error {
 			Self::IoError(err) => write!(f, "an io error occurred: {err}"),
 			Self::DomainError(err) => write!(f, "an domain error occurred: {err}"),
 			Self::TypeError(kind, func) => write!(f, "invalid type {kind} given to {func}"),
-			Self::DivisionByZero => write!(f, "division/modulo by zero"),
+			Self::DivisionByZero { kind: ZeroDivisionKind::Divide, operand } => {
+				write!(f, "division by zero: {operand} / 0")
+			}
+			Self::DivisionByZero { kind: ZeroDivisionKind::Remainder, operand } => {
+				write!(f, "remainder by zero: {operand} % 0")
+			}
+			Self::DivisionByZero { kind: ZeroDivisionKind::Power, operand } => {
+				write!(f, "0 exponentiated by a negative power: 0 ^ {operand}")
+			}
 			Self::ParseError(err) => Display::fmt(&err, f),
 			Self::Quit(status) => write!(f, "quitting with status code {status}"),
+			Self::Interrupted => write!(f, "interrupted"),
 			Self::IntegerOverflow => write!(f, "integer under/overflow"),
 			Self::IndexOutOfBounds { len, index } => {
 				write!(f, "end index {index} is out of bounds for length {len}")
@@ -140,6 +190,22 @@ impl Display for Error {
 			#[cfg(feature = "compliance")]
 			Self::IllegalVariableName(err) => Display::fmt(&err, f),
 
+			#[cfg(feature = "extensions")]
+			Self::EvalTooDeep { max } => write!(f, "EVAL nested too deeply (max depth {max})"),
+
+			#[cfg(feature = "extensions")]
+			Self::EvalTooLong { len, max } => {
+				write!(f, "EVAL source code is too long ({len} > {max} bytes)")
+			}
+
+			#[cfg(feature = "extensions")]
+			Self::AssertionFailed { message } => write!(f, "assertion failed: {message}"),
+
+			#[cfg(feature = "extensions")]
+			Self::InvalidFunctionSymbol(symbol) => {
+				write!(f, "{symbol:?} cannot be used as a function name")
+			}
+
 			#[cfg(feature = "extensions")]
 			Self::Custom(err) => Display::fmt(&err, f),
 		}
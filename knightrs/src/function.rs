@@ -1,6 +1,7 @@
 #![allow(non_snake_case)]
 
 use crate::containers::RefCount;
+use crate::env::flags::FlushPolicy;
 use crate::env::{Environment, Flags};
 use crate::parse::{self, Parsable, Parser};
 use crate::value::text::TextSlice;
@@ -29,9 +30,10 @@ struct Inner {
 
 type AllocFn = dyn Fn(&[Value], &mut Environment<'_>) -> Result<Value> + Send + Sync + 'static;
 
+#[derive(Clone)]
 pub enum FnType {
 	FnPtr(fn(&[Value], &mut Environment<'_>) -> Result<Value>),
-	Alloc(Box<AllocFn>),
+	Alloc(std::sync::Arc<AllocFn>),
 }
 
 impl Eq for Function {}
@@ -76,9 +78,14 @@ impl Parsable for Function {
 		// FIXME: make this parsing part of the extension function itself
 		#[cfg(feature = "extensions")]
 		if parser.peek().map_or(false, |chr| chr == 'X') {
-			let name = parser.strip_keyword_function().unwrap();
+			let name = if parser.env().flags().extensions.case_insensitive_extensions {
+				parser.take_while(|chr| chr.is_alphabetic() || chr == '_')
+			} else {
+				parser.strip_keyword_function()
+			}
+			.unwrap();
 
-			return parser.env().extensions().get(name).cloned().map(|e| Some(e.0)).ok_or_else(|| {
+			return parser.env().lookup_extension(name).cloned().map(|e| Some(e.0)).ok_or_else(|| {
 				parser.error(parse::ErrorKind::UnknownExtensionFunction(name.to_string()))
 			});
 		}
@@ -92,7 +99,7 @@ impl Parsable for Function {
 		};
 
 		if head.is_uppercase() {
-			parser.strip_keyword_function();
+			parser.strip_keyword(function.full_name().as_str())?;
 		} else {
 			parser.advance();
 		}
@@ -121,7 +128,7 @@ impl Function {
 	{
 		Self(RefCount::from(Inner {
 			arity,
-			func: FnType::Alloc(Box::new(func) as _),
+			func: FnType::Alloc(std::sync::Arc::new(func) as _),
 			short_name: Some(full_name.head().unwrap()),
 			full_name,
 		}))
@@ -223,6 +230,37 @@ if #[cfg(feature = "extensions")] {
 	}
 
 	impl ExtensionFunction {
+		/// Creates a [`Function`] which runs this extension, but is called via the single-character
+		/// `symbol` instead of its `X` name.
+		///
+		/// To opt into the symbolic form, add the returned function to [`Builder::functions`](
+		/// crate::env::Builder::functions). The `X` name still works as long as the extension is
+		/// registered.
+		///
+		/// # Errors
+		/// If `symbol` would be parsed as something other than a function (eg it starts a variable,
+		/// keyword, number, string, or comment, or it's whitespace or a paren), an
+		/// [`Error::InvalidFunctionSymbol`] is returned.
+		pub fn symbolic(&self, symbol: char) -> Result<Function> {
+			use knight_strings::syntax;
+
+			if syntax::is_whitespace(symbol)
+				|| syntax::is_paren(symbol)
+				|| syntax::is_variable_char(symbol)
+				|| syntax::is_keyword_char(symbol)
+				|| matches!(symbol, syntax::COMMENT_START | '"' | '\'')
+			{
+				return Err(Error::InvalidFunctionSymbol(symbol));
+			}
+
+			Ok(Function(RefCount::from(Inner {
+				full_name: self.0.full_name().clone(),
+				arity: self.0.arity(),
+				short_name: Some(symbol),
+				func: self.0 .0.func.clone(),
+			})))
+		}
+
 		pub(crate) fn default_set(flags: &Flags) -> HashSet<Self> {
 			let mut map = HashSet::new();
 
@@ -243,6 +281,26 @@ if #[cfg(feature = "extensions")] {
 				xsystem XSYSTEM
 				xget XGET
 				xset XSET
+				xbase XBASE
+				xbase XFROMBASE
+				xbitwise XBAND
+				xbitwise XBOR
+				xbitwise XBXOR
+				xbitwise XSHL
+				xbitwise XSHR
+				xcmp XCMP
+				ximport XIMPORT
+				xapply XAPPLY
+				xtypeof XTYPEOF
+				xconvert XBOOL
+				xconvert XINT
+				xconvert XSTR
+				xconvert XLIST
+				xrandom XSHUFFLE
+				xrandom XCHOICE
+				split XSPLITLINES
+				split XSPLITWS
+				assert XASSERT
 			}
 
 			map
@@ -278,6 +336,11 @@ macro_rules! xfunction {
 /// The `PROMPT` function.
 pub fn PROMPT() -> Function {
 	function!("PROMPT", env, |/* comment for rustfmt */| {
+		// Make sure any prompts that were `OUTPUT`ted are visible before we block on reading.
+		if env.flags().io.flush != FlushPolicy::Never {
+			let _ = env.output().flush();
+		}
+
 		env.prompt().read_line()?.get(env)?.map(Value::from).unwrap_or_default()
 	})
 }
@@ -314,6 +377,16 @@ pub fn TAIL() -> Function {
 
 /// The `BLOCK` function.
 pub fn BLOCK() -> Function {
+	// The NOOP function literally just runs its argument.
+	#[cfg(any(feature = "compliance", feature = "extensions"))]
+	fn NOOP() -> Function {
+		function!(":", env, |arg| {
+			debug_assert!(!matches!(arg, Value::Ast(_)));
+
+			arg.run(env)? // We can't simply `.clone()` the arg in case we're given a variable name.
+		})
+	}
+
 	function!("BLOCK", env, |arg| {
 		// Technically, according to the spec, only the return value from `BLOCK` can be used in `CALL`.
 		// Since this function normally just returns whatever it's argument is, it's impossible to
@@ -322,15 +395,12 @@ pub fn BLOCK() -> Function {
 		// from `BLOCK`, so `CALL` can verify them.
 		#[cfg(feature = "compliance")]
 		if env.flags().compliance.check_call_arg && !matches!(arg, Value::Ast(_)) {
-			// The NOOP function literally just runs its argument.
-			fn NOOP() -> Function {
-				function!(":", env, |arg| {
-					debug_assert!(!matches!(arg, Value::Ast(_)));
-
-					arg.run(env)? // We can't simply `.clone()` the arg in case we're given a variable name.
-				})
-			}
+			return Ok(crate::Ast::new(NOOP(), vec![arg.clone()].into()).into());
+		}
 
+		// Likewise, `XTYPEOF` has to be able to tell that `BLOCK 1` is a block.
+		#[cfg(feature = "extensions")]
+		if env.flags().extensions.functions.xtypeof && !matches!(arg, Value::Ast(_)) {
 			return Ok(crate::Ast::new(NOOP(), vec![arg.clone()].into()).into());
 		}
 
@@ -440,7 +510,8 @@ pub fn LENGTH() -> Function {
 pub fn DUMP() -> Function {
 	function!("DUMP", env, |arg| {
 		let value = arg.run(env)?;
-		write!(env.output(), "{value:?}")?;
+		let output = env.output();
+		value.kn_dump(output.dump_stream()).map_err(|err| output.write_error(err))?;
 		value
 	})
 }
@@ -449,15 +520,16 @@ pub fn DUMP() -> Function {
 pub fn OUTPUT() -> Function {
 	function!("OUTPUT", env, |arg| {
 		let text = arg.run(env)?.to_text(env)?;
+		let io = env.flags().io;
 		let output = env.output();
 
 		if let Some(stripped) = text.strip_suffix('\\') {
-			write!(output, "{stripped}")?
+			write!(output, "{stripped}")
 		} else {
-			writeln!(output, "{text}")?;
+			write!(output, "{text}{}", io.newline.as_str())
 		}
-
-		output.flush()?;
+		.and_then(|()| if io.flush == FlushPolicy::EveryOutput { output.flush() } else { Ok(()) })
+		.map_err(|err| output.write_error(err))?;
 
 		Value::Null
 	})
@@ -594,16 +666,68 @@ pub fn THEN() -> Function {
 /// The `=` function.
 pub fn ASSIGN() -> Function {
 	function!("=", env, |variable, value| {
+		if let Some(ran) = assign_set_in_place(variable, value, env)? {
+			return Ok(ran);
+		}
+
 		let ran = value.run(env)?;
 		variable.assign(ran.clone(), env)?;
 		ran
 	})
 }
 
+// Runs `= foo SET foo start length replacement`, returning `None` if `variable` and `value` aren't
+// of that form.
+//
+// Running `SET` normally would fetch a copy of `foo`'s value, so its contents would always be
+// shared with `foo` and couldn't be modified in place. So instead, the value's taken out of `foo`
+// while it's being modified.
+fn assign_set_in_place(
+	variable: &Value,
+	value: &Value,
+	env: &mut Environment,
+) -> Result<Option<Value>> {
+	let (Value::Variable(target), Value::Ast(ast)) = (variable, value) else {
+		return Ok(None);
+	};
+
+	let [Value::Variable(source), start, length, replacement] = ast.args() else {
+		return Ok(None);
+	};
+
+	if source != target || ast.function().full_name() != "SET" {
+		return Ok(None);
+	}
+
+	let mut container = source.run(env)?;
+	let (start, length) = (start.run(env)?, length.run(env)?);
+	let replacement = replacement.run(env)?;
+
+	// If running the arguments reassigned `foo`, its new value isn't the one being modified, so it
+	// has to be kept around in case `SET` fails.
+	let previous = match target.take() {
+		Some(taken) if taken.ptr_eq(&container) => None,
+		other => Some(other),
+	};
+
+	if let Err(err) = container.set_in_place(&start, &length, replacement, env) {
+		if let Some(previous) = previous.unwrap_or(Some(container)) {
+			target.assign(previous);
+		}
+
+		return Err(err);
+	}
+
+	target.assign(container.clone());
+	Ok(Some(container))
+}
+
 /// The `WHILE` function.
 pub fn WHILE() -> Function {
 	function!("WHILE", env, |condition, body| {
 		while condition.run(env)?.to_boolean(env)? {
+			// Checked here as well as when functions are run, as `WHILE TRUE NULL` doesn't run any.
+			env.check_cancelled()?;
 			body.run(env)?;
 		}
 
@@ -634,7 +758,9 @@ pub fn GET() -> Function {
 pub fn SET() -> Function {
 	function!("SET", env, |source, start, length, replacement| {
 		//
-		source.run(env)?.set(&start.run(env)?, &length.run(env)?, replacement.run(env)?, env)?
+		let mut source = source.run(env)?;
+		source.set_in_place(&start.run(env)?, &length.run(env)?, replacement.run(env)?, env)?;
+		source
 	})
 }
 
@@ -659,6 +785,8 @@ pub fn HANDLE() -> Function {
 
 		match block.run(env) {
 			Ok(value) => value,
+			// Cancelling has to stop the whole program, so it can't be caught.
+			Err(Error::Interrupted) => return Err(Error::Interrupted),
 			Err(err) => {
 				// This is fallible, as the error string might have had something bad.
 				let errmsg = Text::new(err.to_string(), env.flags())?;
@@ -692,9 +820,7 @@ pub fn YEET() -> Function {
 pub fn USE() -> Function {
 	function!("USE", env, |arg| {
 		let filename = arg.run(env)?.to_text(env)?;
-		let contents = env.read_file(&filename)?;
-
-		env.play(&contents)?
+		env.use_file(&filename)?
 	})
 }
 
@@ -704,7 +830,7 @@ pub fn USE() -> Function {
 pub fn EVAL() -> Function {
 	function!("EVAL", env, |val| {
 		let code = val.run(env)?.to_text(env)?;
-		env.play(&code)?
+		env.eval(&code)?
 	})
 }
 
@@ -778,27 +904,16 @@ pub fn XRANGE() -> ExtensionFunction {
 		match start.run(env)? {
 			Value::Integer(start) => {
 				let stop = stop.run(env)?.to_integer(env)?;
-
-				match start <= stop {
-					true => List::new(
-						(i64::from(start)..i64::from(stop))
-							.map(|x| Value::from(crate::value::Integer::try_from(x).unwrap()))
-							.collect::<Vec<Value>>(),
-						env.flags(),
-					)
-					.expect("todo: out of bounds error")
-					.into(),
-
-					false => {
-						// (stop..start).map(Value::from).rev().collect::<List>().into()
-						todo!()
-					}
-				}
+				List::from_range(start, stop, env.flags())?.into()
 			}
 
-			Value::Text(_text) => {
-				// let start = text.get(0).a;
-				todo!()
+			Value::Text(start) => {
+				let stop = stop.run(env)?.to_text(env)?;
+				let (Some(start), Some(stop)) = (start.head(), stop.head()) else {
+					return Err(Error::DomainError("XRANGE: empty text given"));
+				};
+
+				List::from_char_range(start, stop, env.flags())?.into()
 			}
 
 			other => return Err(Error::TypeError(other.typename(), "XRANGE")),
@@ -838,3 +953,323 @@ pub fn XSET() -> ExtensionFunction {
 		// list.get(index).cloned().unwrap_or_default()
 	})
 }
+
+/// **Compiler extension**: XBASE
+///
+/// Converts its first argument to an integer, and then to a string in the base of its second
+/// argument (which must be within `2..=36`).
+#[cfg(feature = "extensions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+pub fn XBASE() -> ExtensionFunction {
+	xfunction!("XBASE", env, |int, base| {
+		let int = int.run(env)?.to_integer(env)?;
+		let base = base.run(env)?.to_integer(env)?;
+
+		Text::new(int.to_base(base)?, env.flags())?.into()
+	})
+}
+
+/// **Compiler extension**: XFROMBASE
+///
+/// Converts its first argument to a string, and then parses it as an integer in the base of its
+/// second argument (which must be within `2..=36`).
+#[cfg(feature = "extensions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+pub fn XFROMBASE() -> ExtensionFunction {
+	xfunction!("XFROMBASE", env, |text, base| {
+		let text = text.run(env)?.to_text(env)?;
+		let base = base.run(env)?.to_integer(env)?;
+
+		crate::value::Integer::from_base(&text, base, env.flags())?.into()
+	})
+}
+
+/// **Compiler extension**: XBAND
+///
+/// Bitwise ANDs its arguments, after converting them to integers.
+#[cfg(feature = "extensions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+pub fn XBAND() -> ExtensionFunction {
+	xfunction!("XBAND", env, |lhs, rhs| {
+		let lhs = lhs.run(env)?.to_integer(env)?;
+		let rhs = rhs.run(env)?.to_integer(env)?;
+
+		lhs.bitwise_and(rhs).into()
+	})
+}
+
+/// **Compiler extension**: XBOR
+///
+/// Bitwise ORs its arguments, after converting them to integers.
+#[cfg(feature = "extensions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+pub fn XBOR() -> ExtensionFunction {
+	xfunction!("XBOR", env, |lhs, rhs| {
+		let lhs = lhs.run(env)?.to_integer(env)?;
+		let rhs = rhs.run(env)?.to_integer(env)?;
+
+		lhs.bitwise_or(rhs).into()
+	})
+}
+
+/// **Compiler extension**: XBXOR
+///
+/// Bitwise XORs its arguments, after converting them to integers.
+#[cfg(feature = "extensions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+pub fn XBXOR() -> ExtensionFunction {
+	xfunction!("XBXOR", env, |lhs, rhs| {
+		let lhs = lhs.run(env)?.to_integer(env)?;
+		let rhs = rhs.run(env)?.to_integer(env)?;
+
+		lhs.bitwise_xor(rhs).into()
+	})
+}
+
+/// **Compiler extension**: XSHL
+///
+/// Shifts its first argument left by its second argument's amount of bits.
+#[cfg(feature = "extensions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+pub fn XSHL() -> ExtensionFunction {
+	xfunction!("XSHL", env, |lhs, rhs| {
+		let lhs = lhs.run(env)?.to_integer(env)?;
+		let rhs = rhs.run(env)?.to_integer(env)?;
+
+		lhs.shift_left(rhs, env.flags())?.into()
+	})
+}
+
+/// **Compiler extension**: XSHR
+///
+/// Arithmetically shifts its first argument right by its second argument's amount of bits.
+#[cfg(feature = "extensions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+pub fn XSHR() -> ExtensionFunction {
+	xfunction!("XSHR", env, |lhs, rhs| {
+		let lhs = lhs.run(env)?.to_integer(env)?;
+		let rhs = rhs.run(env)?.to_integer(env)?;
+
+		lhs.shift_right(rhs, env.flags())?.into()
+	})
+}
+
+/// **Compiler extension**: XIMPORT
+///
+/// Runs the file named by its first argument, like `USE`, except in a [child environment](
+/// Environment::prefixed_child): Every variable in the file is prefixed by its second argument and
+/// an `_`. So, `XIMPORT "math.kn" "math"` makes the file's `sqrt` accessible as `math_sqrt`.
+#[cfg(feature = "extensions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+pub fn XIMPORT() -> ExtensionFunction {
+	xfunction!("XIMPORT", env, |filename, prefix| {
+		let filename = filename.run(env)?.to_text(env)?;
+		let prefix = prefix.run(env)?.to_text(env)?;
+		env.import_file(&filename, &prefix)?
+	})
+}
+
+/// **Compiler extension**: XCMP
+///
+/// Returns `-1`, `0`, or `1` if its first argument is less than, equal to, or greater than its
+/// second argument; comparisons are done the same way as `<` and `>`.
+#[cfg(feature = "extensions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+pub fn XCMP() -> ExtensionFunction {
+	xfunction!("XCMP", env, |lhs, rhs| {
+		let lhs = lhs.run(env)?;
+		let rhs = rhs.run(env)?;
+
+		crate::value::Integer::from(lhs.compare(&rhs, env)?).into()
+	})
+}
+
+/// **Compiler extension**: XAPPLY
+///
+/// Calls its first argument with the elements of its second argument (converted to a list) as
+/// the arguments. The whole list is assigned to the variable `_0`, and each element to `_1`, `_2`,
+/// and so on, the same as `knightrs-bytecode` does. Like all other variables, these are global.
+#[cfg(feature = "extensions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+pub fn XAPPLY() -> ExtensionFunction {
+	use crate::value::ToList;
+
+	xfunction!("XAPPLY", env, |block, args| {
+		let block = block.run(env)?;
+		let args = args.run(env)?.to_list(env)?;
+
+		// Native functions are given their arguments directly.
+		if let Value::NativeFn(native) = block {
+			return native.call_with(&args.iter().cloned().collect::<Vec<_>>(), env);
+		}
+
+		let flags = env.flags();
+		let name = |position: usize| Text::new(format!("_{position}"), flags);
+
+		env.lookup(&name(0)?)?.assign(args.clone().into());
+		for (position, arg) in args.iter().enumerate() {
+			env.lookup(&name(position + 1)?)?.assign(arg.clone());
+		}
+
+		block.call(env)?
+	})
+}
+
+/// **Compiler extension**: XTYPEOF
+///
+/// Returns the name of its argument's type: `Null`, `Boolean`, `Integer`, `String`, `List`, or
+/// `Block` (or `NativeFn`, or the name of a custom type).
+#[cfg(feature = "extensions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+pub fn XTYPEOF() -> ExtensionFunction {
+	xfunction!("XTYPEOF", env, |arg| {
+		let value = arg.run(env)?;
+
+		// Use the names from the spec rather than the ones internal to this crate.
+		let typename = match value {
+			Value::Text(_) => "String",
+			Value::Ast(_) => "Block",
+			_ => value.typename(),
+		};
+
+		Text::new(typename, env.flags())?.into()
+	})
+}
+
+/// **Compiler extension**: XBOOL
+///
+/// Converts its argument to a [`Boolean`](crate::value::Boolean), the same as `!!` does.
+#[cfg(feature = "extensions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+pub fn XBOOL() -> ExtensionFunction {
+	xfunction!("XBOOL", env, |arg| { arg.run(env)?.to_boolean(env)?.into() })
+}
+
+/// **Compiler extension**: XINT
+///
+/// Converts its argument to an [`Integer`](crate::value::Integer), the same as `+ 0` does.
+#[cfg(feature = "extensions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+pub fn XINT() -> ExtensionFunction {
+	xfunction!("XINT", env, |arg| { arg.run(env)?.to_integer(env)?.into() })
+}
+
+/// **Compiler extension**: XSTR
+///
+/// Converts its argument to a [`Text`], the same as `+ ""` does.
+#[cfg(feature = "extensions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+pub fn XSTR() -> ExtensionFunction {
+	xfunction!("XSTR", env, |arg| { arg.run(env)?.to_text(env)?.into() })
+}
+
+/// **Compiler extension**: XLIST
+///
+/// Converts its argument to a [`List`], the same as `+ @` does.
+#[cfg(feature = "extensions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+pub fn XLIST() -> ExtensionFunction {
+	use crate::value::ToList;
+
+	xfunction!("XLIST", env, |arg| { arg.run(env)?.to_list(env)?.into() })
+}
+
+/// **Compiler extension**: XSHUFFLE
+///
+/// Returns its argument, converted to a list, in a random order. This uses the same random number
+/// generator as [`RANDOM`], so it's deterministic after [`XSRAND`].
+#[cfg(feature = "extensions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+pub fn XSHUFFLE() -> ExtensionFunction {
+	use crate::value::ToList;
+	use rand::seq::SliceRandom;
+
+	xfunction!("XSHUFFLE", env, |arg| {
+		let list = arg.run(env)?.to_list(env)?;
+		let mut elements = list.iter().cloned().collect::<Vec<Value>>();
+		elements.shuffle(env.rng());
+		List::new(elements, env.flags())?.into()
+	})
+}
+
+/// **Compiler extension**: XCHOICE
+///
+/// Returns a random element of its argument, after converting it to a list. This uses the same
+/// random number generator as [`RANDOM`], so it's deterministic after [`XSRAND`].
+#[cfg(feature = "extensions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+pub fn XCHOICE() -> ExtensionFunction {
+	use crate::value::ToList;
+	use rand::Rng;
+
+	xfunction!("XCHOICE", env, |arg| {
+		let list = arg.run(env)?.to_list(env)?;
+		if list.is_empty() {
+			return Err(Error::DomainError("XCHOICE: empty list given"));
+		}
+
+		let index = env.rng().gen_range(0..list.len());
+		list.get(index).expect("index is in bounds").clone()
+	})
+}
+
+/// **Compiler extension**: XSPLITLINES
+///
+/// Converts its argument to a text, and returns a list of its lines. Lines end with either `\n` or
+/// `\r\n`, and a trailing line ending doesn't add an empty line at the end.
+#[cfg(feature = "extensions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+pub fn XSPLITLINES() -> ExtensionFunction {
+	use crate::value::ToText;
+
+	xfunction!("XSPLITLINES", env, |arg| {
+		let text = arg.run(env)?.to_text(env)?;
+		// SAFETY: Each line is a part of `text`, so is a valid text too.
+		let lines = text.lines().map(|line| Value::from(unsafe { Text::new_unchecked(line) }));
+
+		// SAFETY: If `text` is within the container bounds, so is the amount of lines in it.
+		unsafe { List::new_unchecked(lines.collect::<Vec<_>>()) }.into()
+	})
+}
+
+/// **Compiler extension**: XSPLITWS
+///
+/// Converts its argument to a text, and returns a list of the parts of it that are separated by
+/// whitespace. Leading and trailing whitespace is ignored, so there are never any empty texts in
+/// the list.
+#[cfg(feature = "extensions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+pub fn XSPLITWS() -> ExtensionFunction {
+	use crate::value::ToText;
+
+	xfunction!("XSPLITWS", env, |arg| {
+		let text = arg.run(env)?.to_text(env)?;
+		// SAFETY: Each word is a part of `text`, so is a valid text too.
+		let words =
+			text.split_whitespace().map(|word| Value::from(unsafe { Text::new_unchecked(word) }));
+
+		// SAFETY: If `text` is within the container bounds, so is the amount of words in it.
+		unsafe { List::new_unchecked(words.collect::<Vec<_>>()) }.into()
+	})
+}
+
+/// **Compiler extension**: XASSERT
+///
+/// Runs both of its arguments, and returns `NULL` if the first is truthy. Otherwise, an
+/// [`Error::AssertionFailed`] is raised, with the second converted to a text as its message.
+#[cfg(feature = "extensions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+pub fn XASSERT() -> ExtensionFunction {
+	use crate::value::ToText;
+
+	xfunction!("XASSERT", env, |condition, message| {
+		let condition = condition.run(env)?.to_boolean(env)?;
+		let message = message.run(env)?.to_text(env)?;
+
+		if !condition {
+			return Err(Error::AssertionFailed { message: message.to_string() });
+		}
+
+		Value::Null
+	})
+}
@@ -6,12 +6,16 @@
 extern crate cfg_if;
 
 mod ast;
+pub mod capabilities;
+pub mod compat;
 mod containers;
 pub mod env;
 mod error;
 pub mod function;
 pub mod parse;
+pub mod testing;
 pub mod value;
 
 pub use ast::Ast;
-pub use error::{Error, Result};
+pub use capabilities::capabilities;
+pub use error::{Error, Result, ZeroDivisionKind};
@@ -427,9 +427,11 @@ fn main() {
 
 	let flags = knightrs::env::Flags::default();
 	// match knightrs::play("utf8", "i64", "wrapping", &arg, &Default::default()) {
-	match knightrs::env::Environment::new(&flags)
-		.play(&knightrs::value::Text::new(arg, &flags).unwrap())
-	{
+	// The environment's dropped before exiting, so that buffered output is flushed.
+	let source = knightrs::value::Text::new(arg, &flags).unwrap();
+	let result = knightrs::env::Environment::new(&flags).play(&source);
+
+	match result {
 		Err(knightrs::Error::Quit(code)) => std::process::exit(code),
 		Err(err) => {
 			eprintln!("error: {err}");
@@ -1,9 +1,12 @@
 //! Parsing Knight code.
 
 use crate::containers::{MaybeSendSync, RefCount};
-use crate::env::{Environment, Flags};
+use crate::env::variable::{BuildNameHasher, IllegalVariableName};
+use crate::env::{Environment, Flags, Variable};
 use crate::value::text::TextSlice;
 use crate::value::Value;
+use knight_strings::syntax;
+use std::collections::HashMap;
 use std::fmt::{self, Display, Formatter};
 
 mod blank;
@@ -21,6 +24,10 @@ pub struct Parser<'s, 'e> {
 	source: &'s TextSlice,
 	env: &'s mut Environment<'e>,
 	line: usize,
+
+	// Every variable that's been parsed so far, so that names which appear lots of times don't go
+	// through `Environment::lookup` (and its slower hash) each time.
+	variables: HashMap<&'s TextSlice, Variable, BuildNameHasher>,
 }
 
 /// A trait that indicates that something can be parsed.
@@ -155,6 +162,20 @@ pub enum ErrorKind {
 	/// An unknown extension name was encountered.
 	UnknownExtensionFunction(String),
 
+	/// An uppercase keyword that doesn't correspond to any known function was encountered.
+	///
+	/// This is only returned when [`strict_keywords`](crate::env::flags::Extensions::strict_keywords)
+	/// is enabled.
+	#[cfg(feature = "extensions")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+	UnknownKeyword {
+		/// The keyword that was parsed.
+		name: String,
+
+		/// The closest known keyword, if any were close enough.
+		suggestion: Option<String>,
+	},
+
 	/// An error which doesn't fit into one of the other categories.
 	#[cfg(feature = "extensions")]
 	#[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
@@ -186,6 +207,14 @@ impl Display for ErrorKind {
 			#[cfg(feature = "extensions")]
 			Self::UnknownExtensionFunction(ref name) => write!(f, "unknown extension {name}"),
 
+			#[cfg(feature = "extensions")]
+			Self::UnknownKeyword { name, suggestion: Some(suggestion) } => {
+				write!(f, "unknown keyword {name} (did you mean {suggestion}?)")
+			}
+
+			#[cfg(feature = "extensions")]
+			Self::UnknownKeyword { name, suggestion: None } => write!(f, "unknown keyword {name}"),
+
 			#[cfg(feature = "extensions")]
 			Self::Custom(err) => Display::fmt(err, f),
 		}
@@ -241,7 +270,7 @@ impl<'s, 'e> Parser<'s, 'e> {
 	/// Create a new `Parser` from the given source.
 	#[must_use]
 	pub fn new(source: &'s TextSlice, env: &'s mut Environment<'e>) -> Self {
-		Self { source, line: 1, env }
+		Self { source, line: 1, env, variables: HashMap::default() }
 	}
 
 	/// Gets the current line number.
@@ -256,6 +285,22 @@ impl<'s, 'e> Parser<'s, 'e> {
 		self.env
 	}
 
+	/// Fetches the variable `name`, like [`Environment::lookup`] does.
+	///
+	/// Variables are cached for the rest of the parse, so looking up the same name again is cheap.
+	pub fn lookup_variable(
+		&mut self,
+		name: &'s TextSlice,
+	) -> std::result::Result<Variable, IllegalVariableName> {
+		if let Some(variable) = self.variables.get(name) {
+			return Ok(variable.clone());
+		}
+
+		let variable = self.env.lookup(name)?;
+		self.variables.insert(name, variable.clone());
+		Ok(variable)
+	}
+
 	/// Creates an error at the current source code position.
 	#[must_use]
 	pub fn error(&self, kind: ErrorKind) -> Error {
@@ -317,10 +362,10 @@ impl<'s, 'e> Parser<'s, 'e> {
 
 		loop {
 			// strip all leading whitespace, if any.
-			self.take_while(|c| c.is_whitespace() || c == ':');
+			self.take_while(|c| syntax::is_whitespace(c) || c == ':');
 
 			// If we're not at the start of a comment, break out
-			if self.advance_if('#').is_none() {
+			if self.advance_if(syntax::COMMENT_START).is_none() {
 				break;
 			}
 
@@ -337,22 +382,69 @@ impl<'s, 'e> Parser<'s, 'e> {
 
 	/// Removes the remainder of a keyword function.
 	pub fn strip_keyword_function(&mut self) -> Option<&'s TextSlice> {
-		self.take_while(|c| c.is_uppercase() || c == '_')
+		self.take_while(syntax::is_keyword_char)
+	}
+
+	/// Removes the keyword `full_name` (e.g. `OUTPUT`), starting at its first character.
+	///
+	/// Normally, everything after a keyword's first character is ignored, so `OUTPUTX` is the same
+	/// as `OUTPUT`. If [`strict_keywords`](crate::env::flags::Extensions::strict_keywords) is
+	/// enabled, the keyword must instead be either just its first character or exactly
+	/// `full_name`, and an [`ErrorKind::UnknownKeyword`] is returned otherwise.
+	pub fn strip_keyword(&mut self, full_name: &str) -> Result<()> {
+		let name = self.strip_keyword_function().unwrap_or_default();
+
+		#[cfg(feature = "extensions")]
+		if self.env.flags().extensions.strict_keywords
+			&& name.len() != 1
+			&& name.as_str() != full_name
+		{
+			return Err(self.unknown_keyword(name.as_str()));
+		}
+
+		let _ = (name, full_name);
+		Ok(())
+	}
+
+	/// Creates an [`ErrorKind::UnknownKeyword`] for `name`, suggesting the closest known keyword.
+	#[cfg(feature = "extensions")]
+	fn unknown_keyword(&self, name: &str) -> Error {
+		let functions = self.env.functions().iter().map(|func| func.full_name().as_str());
+		let extensions = self.env.extensions().iter().map(|func| func.0.full_name().as_str());
+		let literals = ["TRUE", "FALSE", "NULL"].into_iter();
+
+		// Only suggest keywords that are a couple of typos away.
+		let suggestion = functions
+			.chain(extensions)
+			.chain(literals)
+			.filter(|candidate| candidate.starts_with(char::is_uppercase))
+			.map(|candidate| (edit_distance(name, candidate), candidate))
+			.filter(|&(distance, _)| distance <= 2)
+			.min()
+			.map(|(_, candidate)| candidate.to_string());
+
+		self.error(ErrorKind::UnknownKeyword { name: name.to_string(), suggestion })
 	}
 
 	/// Parses a whole program, returning a [`Value`] corresponding to its ast.
 	///
 	/// This will return an [`ErrorKind::TrailingTokens`] if [`forbid_trailing_tokens`](
-	/// crate::env::flags::Compliance::forbid_trailing_tokens) is set.
+	/// crate::env::flags::Compliance::forbid_trailing_tokens) is set and anything other than
+	/// whitespace and comments follows the first expression. (If the trailing token is a `)`, an
+	/// [`ErrorKind::UnmatchedRightParen`] is returned instead.) The trailing tokens themselves are
+	/// never parsed, so the error's line is always where they start.
 	pub fn parse_program(mut self) -> Result<Value> {
 		let ret = self.parse_expression()?;
 
-		// If we forbid any trailing tokens, then see if we could have parsed anything else.
 		#[cfg(feature = "compliance")]
-		if self.env.flags().compliance.forbid_trailing_tokens
-			&& !matches!(self.parse_expression().map_err(|e| e.kind), Err(ErrorKind::EmptySource))
-		{
-			return Err(self.error(ErrorKind::TrailingTokens));
+		if self.env.flags().compliance.forbid_trailing_tokens {
+			self.strip_whitespace_and_comments();
+
+			match self.peek() {
+				None => {}
+				Some(')') => return Err(self.error(ErrorKind::UnmatchedRightParen)),
+				Some(_) => return Err(self.error(ErrorKind::TrailingTokens)),
+			}
 		}
 
 		Ok(ret)
@@ -374,6 +466,15 @@ impl<'s, 'e> Parser<'s, 'e> {
 			}
 		}
 
+		// Nothing matched an uppercase word, so it's an unknown keyword.
+		#[cfg(feature = "extensions")]
+		if self.env.flags().extensions.strict_keywords
+			&& self.peek().map_or(false, char::is_uppercase)
+		{
+			let name = self.strip_keyword_function().unwrap_or_default();
+			return Err(self.unknown_keyword(name.as_str()));
+		}
+
 		Err(
 			self.error(
 				self
@@ -385,3 +486,23 @@ impl<'s, 'e> Parser<'s, 'e> {
 		)
 	}
 }
+
+/// The Levenshtein distance between `lhs` and `rhs`, used for suggesting keywords.
+#[cfg(feature = "extensions")]
+fn edit_distance(lhs: &str, rhs: &str) -> usize {
+	let rhs = rhs.chars().collect::<Vec<_>>();
+	let mut previous = (0..=rhs.len()).collect::<Vec<_>>();
+
+	for (i, lchr) in lhs.chars().enumerate() {
+		let mut current = vec![i + 1];
+
+		for (j, &rchr) in rhs.iter().enumerate() {
+			let substitution = previous[j] + (lchr != rchr) as usize;
+			current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+		}
+
+		previous = current;
+	}
+
+	previous[rhs.len()]
+}
@@ -0,0 +1,57 @@
+//! Helpers for running Knight programs with captured IO.
+//!
+//! These are mainly intended for conformance testing, where the same program is run (with the same
+//! stdin) under different engines and flags, and its stdout and exit status are compared.
+//!
+//! For example, a comment right at the end of a program is ignored:
+//! ```
+//! let outcome = knightrs::testing::run("OUTPUT 1 # a comment", "", &Default::default());
+//! assert_eq!(outcome.stdout, "1\n");
+//! assert_eq!(outcome.status, 0);
+//! ```
+//!
+//! For checking lots of sample programs against their expected output under every engine, see
+//! `knightrs_conformance::golden`.
+
+use crate::env::{Environment, Flags};
+use crate::value::text::TextSlice;
+use crate::Error;
+
+/// The observable result of running a program via [`run`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Outcome {
+	/// Everything that was written by `OUTPUT` and `DUMP`.
+	pub stdout: String,
+
+	/// The exit status: `0` if the program finished normally, the status passed to `QUIT`, or `1` if
+	/// an error occurred.
+	pub status: i32,
+
+	/// The error's message, if the program didn't finish normally (or via `QUIT`).
+	pub error: Option<String>,
+}
+
+/// Runs `source` with `flags`, using `stdin` as the input to `PROMPT`.
+///
+/// Nothing is read from the real stdin or written to the real stdout, and `QUIT` doesn't exit the
+/// process.
+pub fn run(source: &str, stdin: &str, flags: &Flags) -> Outcome {
+	let mut stdout = Vec::new();
+
+	let result = {
+		let mut builder = Environment::builder(flags);
+		builder.stdin(stdin.as_bytes());
+		builder.stdout(&mut stdout);
+
+		let mut env = builder.build();
+		TextSlice::new(source, flags).map_err(Error::from).and_then(|source| env.play(source))
+	};
+
+	let (status, error) = match result {
+		Ok(_) => (0, None),
+		Err(Error::Quit(status)) => (status, None),
+		Err(err) => (1, Some(err.to_string())),
+	};
+
+	Outcome { stdout: String::from_utf8_lossy(&stdout).into_owned(), status, error }
+}
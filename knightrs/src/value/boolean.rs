@@ -20,11 +20,11 @@ impl Parsable for Boolean {
 	type Output = Self;
 
 	fn parse(parser: &mut Parser<'_, '_>) -> parse::Result<Option<Self>> {
-		let Some(which) = parser.advance_if(|chr| chr == 'T' || chr == 'F') else {
+		let Some(which) = parser.peek().filter(|&chr| chr == 'T' || chr == 'F') else {
 			return Ok(None);
 		};
 
-		parser.strip_keyword_function();
+		parser.strip_keyword(if which == 'T' { "TRUE" } else { "FALSE" })?;
 
 		Ok(Some(which == 'T'))
 	}
@@ -52,8 +52,19 @@ impl ToInteger for Boolean {
 
 impl ToList for Boolean {
 	/// Returns an empty list for `false`, and a list with just `self` if true.
+	///
+	/// # Errors
+	/// If [`strict_conversions`](crate::env::flags::Compliance::strict_conversions) is enabled, an
+	/// [`Error::NoConversion`](crate::Error::NoConversion) is returned, as the Knight spec doesn't
+	/// define this conversion.
 	#[inline]
-	fn to_list(&self, _: &mut Environment) -> crate::Result<List> {
+	fn to_list(&self, env: &mut Environment) -> crate::Result<List> {
+		#[cfg(feature = "compliance")]
+		if env.flags().compliance.strict_conversions {
+			return Err(crate::Error::NoConversion { from: "Boolean", to: "List" });
+		}
+
+		let _ = env;
 		if *self {
 			Ok(List::boxed((*self).into()))
 		} else {
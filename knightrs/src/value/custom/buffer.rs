@@ -0,0 +1,169 @@
+use crate::containers::RefCount;
+use crate::env::Environment;
+use crate::value::{Boolean, Custom, CustomType, Integer, List, Text, ToInteger, ToList, Value};
+use crate::{Error, Result};
+use std::hash::{Hash, Hasher};
+
+/// A sequence of bytes, which is usable within Knight.
+///
+/// This is both usable on its own (eg for handing binary data to programs), and is a reference
+/// implementation for [`CustomType`]s. Like strings and lists, buffers are immutable.
+///
+/// Buffers support the following:
+/// - `GET buf start len` and `SET buf start len bytes`: The same as lists, except they return
+///   buffers. (Negative start positions aren't supported.)
+/// - `[ buf`: The first byte, as an integer; `] buf`: Everything but the first byte.
+/// - `+ buf bytes`: Returns a new buffer with `bytes` appended.
+/// - `LENGTH buf`: The amount of bytes in the buffer.
+/// - `? buf other`: Whether `other` is a buffer with the same bytes.
+/// - Conversions: To a list of integers (which is how buffers are iterated over), to a string by
+///   decoding the bytes as UTF-8, and to a boolean indicating whether the buffer is nonempty.
+///
+/// Wherever `bytes` is used above, it's converted to a list, and each element is converted to an
+/// integer, which must be in `0..=255`. As buffers convert to lists of integers, this means
+/// buffers themselves can be used for `bytes`.
+///
+/// # Examples
+/// ```
+/// use knightrs::env::{Environment, Flags};
+/// use knightrs::value::{Buffer, Custom, TextSlice, ToText};
+///
+/// let flags = Flags::default();
+/// let mut env = Environment::new(&flags);
+///
+/// env.lookup(TextSlice::new("buf", &flags).unwrap())
+///    .unwrap()
+///    .assign(Custom::new(Buffer::from(b"hello".to_vec())).into());
+///
+/// let source = r#"
+///    ; = buf SET buf 0 1 ,72
+///    + buf ,33
+/// "#;
+/// let result = env.play(TextSlice::new(source, &flags).unwrap()).unwrap();
+/// assert_eq!(result.to_text(&mut env).unwrap().as_str(), "Hello!");
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Buffer(Vec<u8>);
+
+impl Buffer {
+	/// Gets the bytes of `self`.
+	pub fn as_bytes(&self) -> &[u8] {
+		&self.0
+	}
+}
+
+impl From<Vec<u8>> for Buffer {
+	fn from(bytes: Vec<u8>) -> Self {
+		Self(bytes)
+	}
+}
+
+// Converts `value` to a list, and then each element to a byte.
+fn to_bytes(value: &Value, env: &mut Environment) -> Result<Vec<u8>> {
+	value
+		.to_list(env)?
+		.iter()
+		.map(|byte| {
+			let byte = byte.to_integer(env)?;
+			u8::try_from(i64::from(byte)).or(Err(Error::DomainError("byte isn't within 0..=255")))
+		})
+		.collect()
+}
+
+// Converts `start` and `len` to a range within `buffer`.
+fn to_range(
+	buffer: &Buffer,
+	start: &Value,
+	len: &Value,
+	env: &mut Environment,
+) -> Result<std::ops::Range<usize>> {
+	let start = usize::try_from(start.to_integer(env)?)
+		.or(Err(Error::DomainError("negative start position")))?;
+	let len =
+		usize::try_from(len.to_integer(env)?).or(Err(Error::DomainError("negative length")))?;
+	let end = start.checked_add(len).ok_or(Error::DomainError("end position is too large"))?;
+
+	if buffer.0.len() < end {
+		return Err(Error::IndexOutOfBounds { len: buffer.0.len(), index: end });
+	}
+
+	Ok(start..end)
+}
+
+impl CustomType for Buffer {
+	fn to_custom(self: RefCount<Self>) -> Custom {
+		self.into()
+	}
+
+	fn typename(&self) -> &'static str {
+		"Buffer"
+	}
+
+	fn eql(&self, rhs: &Custom) -> bool {
+		rhs.downcast_ref::<Self>().is_some_and(|rhs| self == rhs)
+	}
+
+	fn hash(&self, mut state: &mut dyn Hasher) {
+		Hash::hash(self, &mut state);
+	}
+
+	fn to_text(self: RefCount<Self>, env: &mut Environment) -> Result<Text> {
+		let string =
+			std::str::from_utf8(&self.0).or(Err(Error::DomainError("buffer isn't valid UTF-8")))?;
+		Ok(Text::new(string, env.flags())?)
+	}
+
+	fn to_boolean(self: RefCount<Self>, _env: &mut Environment) -> Result<Boolean> {
+		Ok(!self.0.is_empty())
+	}
+
+	fn to_list(self: RefCount<Self>, env: &mut Environment) -> Result<List> {
+		List::new(
+			self.0.iter().map(|&byte| Integer::from(byte).into()).collect::<Vec<_>>(),
+			env.flags(),
+		)
+	}
+
+	fn head(self: RefCount<Self>, _env: &mut Environment) -> Result<Value> {
+		let first = self.0.first().ok_or(Error::DomainError("empty buffer for head"))?;
+		Ok(Integer::from(*first).into())
+	}
+
+	fn tail(self: RefCount<Self>, _env: &mut Environment) -> Result<Value> {
+		let rest = self.0.get(1..).ok_or(Error::DomainError("empty buffer for tail"))?;
+		Ok(Custom::new(Self(rest.to_vec())).into())
+	}
+
+	fn length(self: RefCount<Self>, _env: &mut Environment) -> Result<usize> {
+		Ok(self.0.len())
+	}
+
+	fn add(self: RefCount<Self>, rhs: &Value, env: &mut Environment) -> Result<Value> {
+		let mut bytes = self.0.clone();
+		bytes.extend(to_bytes(rhs, env)?);
+		Ok(Custom::new(Self(bytes)).into())
+	}
+
+	fn get(
+		self: RefCount<Self>,
+		start: &Value,
+		len: &Value,
+		env: &mut Environment,
+	) -> Result<Value> {
+		let range = to_range(&self, start, len, env)?;
+		Ok(Custom::new(Self(self.0[range].to_vec())).into())
+	}
+
+	fn set(
+		self: RefCount<Self>,
+		start: &Value,
+		len: &Value,
+		replacement: Value,
+		env: &mut Environment,
+	) -> Result<Value> {
+		let range = to_range(&self, start, len, env)?;
+		let mut bytes = self.0.clone();
+		bytes.splice(range, to_bytes(&replacement, env)?);
+		Ok(Custom::new(Self(bytes)).into())
+	}
+}
@@ -0,0 +1,144 @@
+use crate::containers::RefCount;
+use crate::env::Environment;
+use crate::value::{Boolean, Custom, CustomType, List, Text, ToList, ToText, Value};
+use crate::{Error, Result};
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+
+/// A map from strings to values, which is usable within Knight.
+///
+/// This is both usable on its own, and is a reference implementation for [`CustomType`]s. In
+/// line with Knight tradition, maps are immutable: Functions which "modify" them return new maps.
+///
+/// Maps support the following:
+/// - `GET map key _`: Gets the value associated with `key` (converted to a string). It's an
+///   error if there's no such key. The length is ignored.
+/// - `SET map key _ value`: Returns a new map with `key` associated with `value`.
+/// - `+ map pairs`: Returns a new map with each `[key, value]` pair in `pairs` (converted to a
+///   list) added. Since maps convert to a list of pairs, this can be used to merge maps.
+/// - `LENGTH map`: The amount of entries in the map.
+/// - `? map other`: Whether `other` is a map with the same entries.
+/// - Conversions: To a list of `[key, value]` pairs in key order (which is how maps are iterated
+///   over), and to a boolean indicating whether the map is nonempty.
+///
+/// # Examples
+/// ```
+/// use knightrs::env::{Environment, Flags};
+/// use knightrs::value::{Custom, Map, TextSlice, ToInteger};
+///
+/// let flags = Flags::default();
+/// let mut env = Environment::new(&flags);
+///
+/// // Maps are given to programs by assigning them to variables.
+/// env.lookup(TextSlice::new("map", &flags).unwrap())
+///    .unwrap()
+///    .assign(Custom::new(Map::default()).into());
+///
+/// let source = r#"
+///    ; = map SET map "one" 0 1
+///    ; = map SET map "two" 0 2
+///    ; = pairs +@map
+///    + GET map "two" 0 LENGTH pairs
+/// "#;
+/// let result = env.play(TextSlice::new(source, &flags).unwrap()).unwrap();
+/// assert_eq!(result.to_integer(&mut env).unwrap(), 4);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Hash)]
+pub struct Map(BTreeMap<Text, Value>);
+
+impl Map {
+	/// Gets the value associated with `key`, if there is one.
+	pub fn get(&self, key: &Text) -> Option<&Value> {
+		self.0.get(key)
+	}
+
+	/// Associates `key` with `value`, returning the previous value if there was one.
+	pub fn insert(&mut self, key: Text, value: Value) -> Option<Value> {
+		self.0.insert(key, value)
+	}
+
+	/// Iterates over the entries of `self`, in key order.
+	pub fn iter(&self) -> impl Iterator<Item = (&Text, &Value)> + '_ {
+		self.0.iter()
+	}
+}
+
+impl From<BTreeMap<Text, Value>> for Map {
+	fn from(map: BTreeMap<Text, Value>) -> Self {
+		Self(map)
+	}
+}
+
+impl CustomType for Map {
+	fn to_custom(self: RefCount<Self>) -> Custom {
+		self.into()
+	}
+
+	fn typename(&self) -> &'static str {
+		"Map"
+	}
+
+	fn eql(&self, rhs: &Custom) -> bool {
+		rhs.downcast_ref::<Self>().is_some_and(|rhs| self == rhs)
+	}
+
+	fn hash(&self, mut state: &mut dyn Hasher) {
+		Hash::hash(self, &mut state);
+	}
+
+	fn to_boolean(self: RefCount<Self>, _env: &mut Environment) -> Result<Boolean> {
+		Ok(!self.0.is_empty())
+	}
+
+	fn to_list(self: RefCount<Self>, env: &mut Environment) -> Result<List> {
+		let pairs = self
+			.iter()
+			.map(|(key, value)| List::new([key.clone().into(), value.clone()], env.flags()))
+			.map(|pair| pair.map(Value::from))
+			.collect::<Result<Vec<_>>>()?;
+
+		List::new(pairs, env.flags())
+	}
+
+	fn length(self: RefCount<Self>, _env: &mut Environment) -> Result<usize> {
+		Ok(self.0.len())
+	}
+
+	fn add(self: RefCount<Self>, rhs: &Value, env: &mut Environment) -> Result<Value> {
+		let mut map = (*self).clone();
+
+		for pair in &rhs.to_list(env)? {
+			let pair = pair.to_list(env)?;
+			let (Some(key), Some(value), 2) = (pair.get(0), pair.get(1), pair.len()) else {
+				return Err(Error::DomainError("map entries must be `[key, value]` pairs"));
+			};
+
+			map.insert(key.to_text(env)?, value.clone());
+		}
+
+		Ok(Custom::new(map).into())
+	}
+
+	fn get(
+		self: RefCount<Self>,
+		start: &Value,
+		_len: &Value,
+		env: &mut Environment,
+	) -> Result<Value> {
+		let key = start.to_text(env)?;
+
+		self.0.get(&key).cloned().ok_or_else(|| Error::Custom(format!("unknown key: {key:?}").into()))
+	}
+
+	fn set(
+		self: RefCount<Self>,
+		start: &Value,
+		_len: &Value,
+		replacement: Value,
+		env: &mut Environment,
+	) -> Result<Value> {
+		let mut map = (*self).clone();
+		map.insert(start.to_text(env)?, replacement);
+		Ok(Custom::new(map).into())
+	}
+}
@@ -4,9 +4,16 @@ use crate::value::{
 	Boolean, Integer, List, NamedType, Runnable, Text, ToBoolean, ToInteger, ToList, ToText, Value,
 };
 use crate::{Error, Result};
+use std::any::Any;
 use std::cmp::Ordering;
 use std::hash::{Hash, Hasher};
 
+mod buffer;
+mod map;
+
+pub use buffer::Buffer;
+pub use map::Map;
+
 /// A type that can hold custom data that's not a part of vanilla Knight.
 ///
 /// This is a simple wrapper around a [`Refcount`] of [`CustomType`]. All the meat is within
@@ -18,13 +25,13 @@ impl Eq for Custom {}
 impl PartialEq for Custom {
 	#[inline]
 	fn eq(&self, rhs: &Self) -> bool {
-		RefCount::ptr_eq(&self.0, &rhs.0)
+		RefCount::ptr_eq(&self.0, &rhs.0) || (self.0.eql(rhs) && rhs.0.eql(self))
 	}
 }
 
 impl Hash for Custom {
-	fn hash<H: Hasher>(&self, state: &mut H) {
-		(RefCount::as_ptr(&self.0) as *const u8 as usize).hash(state);
+	fn hash<H: Hasher>(&self, mut state: &mut H) {
+		CustomType::hash(&*self.0, &mut state);
 	}
 }
 
@@ -46,68 +53,10 @@ impl<T: CustomType + 'static> From<RefCount<T>> for Custom {
 /// it's literally as simple as calling `self.into()`.
 ///
 /// # Examples
-/// Here's an example implementation of a map type that would be usable within Knight.
-/// ```
-/// use std::collections::HashMap;
-/// use std::fmt::{self, Debug, Formatter};
-/// use knightrs::{RefCount, Result, Error, Environment};
-/// use knightrs::value::{
-/// 	integer::IntType,
-/// 	text::Encoding,
-/// 	custom::{Custom, CustomType},
-/// 	Value,
-/// };
-///
-/// // Our map type. In line with Knight tradition, we'll be keeping it immutable.
-/// #[derive(Debug)]
-/// pub struct Map(RefCount<HashMap<Value, Value>>);
-///
-/// // Here we're implementing the custom type trait for our map.
-/// // Technically we requiring `I` to be `IntType` and `E` to be `Encoding`
-/// // is a stronger requirement than we need, but for the sake of example,
-/// // let's just roll with it.
-/// impl<I: IntType, E: Encoding> CustomType for Map {
-///    // The required function for all implementations.
-///    fn to_custom(self: RefCount<Self>) -> Custom {
-///       self.into()
-///    }
-///
-///    // The length of a map is how many elements it has.
-///    fn length(self: RefCount<Self>, _env: &mut Environment<'_>) -> Result<usize> {
-///       Ok(self.0.len())
-///    }
-///
-///    // We'll just ignore the length parameter given to us, and use the start
-///    // as the key to index with.
-///    fn get(
-///       self: RefCount<Self>,
-///       start: &Value,
-///       _len: &Value,
-///       _env: &mut Environment<'_>,
-///    ) -> Result<Value> {
-///       self.0
-///          .get(start)
-///          .ok_or_else(|| Error::Custom(format!("unknown key: {start:?}").into()))
-///          .cloned()
-///    }
-///
-///    // Like `get`, we'll be ignoring `len`. We'll be using `replacement`
-///    // as the value to fetch.
-///    fn set(
-///       self: RefCount<Self>,
-///       start: &Value,
-///       _len: &Value,
-///       replacement: Value,
-///       _env: &mut Environment<'_>,
-///    ) -> Result<Value> {
-///       let mut new = (*self.0).clone();
-///       new.insert(start.clone(), replacement);
-///       Ok(Custom::new(Self(new.into())).into())
-///    }
-/// }
-/// ```
+/// [`Map`] and [`Buffer`] are complete implementations, and serve as examples of how to write
+/// custom types.
 #[allow(unused_variables)] // all the `env`s.
-pub trait CustomType: std::fmt::Debug + MaybeSendSync {
+pub trait CustomType: std::fmt::Debug + MaybeSendSync + Any {
 	/// <todo>
 	fn to_custom(self: RefCount<Self>) -> Custom;
 
@@ -180,6 +129,23 @@ pub trait CustomType: std::fmt::Debug + MaybeSendSync {
 		Err(Error::TypeError(self.typename(), "<cmp>"))
 	}
 
+	/// Checks to see if `self` is equal to `rhs`, as per `?` (and [`Value`]'s [`PartialEq`]).
+	///
+	/// This is only called when `rhs` isn't the exact same [`Custom`] as `self`, as identical values
+	/// are always equal. Both `self`'s and `rhs`'s `eql` have to return `true` for them to be equal,
+	/// so that equality is symmetric. The default returns `false`. [`Custom::downcast_ref`] can be
+	/// used to see if `rhs` is the same type as `self`.
+	fn eql(&self, rhs: &Custom) -> bool {
+		false
+	}
+
+	/// Hashes `self`. Any two values which are [`eql`](CustomType::eql) must hash the same.
+	///
+	/// The default hashes the address of `self`, which is only suitable if `eql` isn't overridden.
+	fn hash(&self, state: &mut dyn Hasher) {
+		state.write_usize(self as *const Self as *const u8 as usize);
+	}
+
 	fn assign(self: RefCount<Self>, rhs: Value, env: &mut Environment) -> Result<()> {
 		Err(Error::TypeError(self.typename(), "="))
 	}
@@ -250,6 +216,11 @@ impl Custom {
 		self.0.typename()
 	}
 
+	/// Gets a reference to the underlying data if it's a `T`.
+	pub fn downcast_ref<T: CustomType>(&self) -> Option<&T> {
+		(&*self.0 as &dyn Any).downcast_ref()
+	}
+
 	#[inline]
 	pub fn run(&self, env: &mut Environment) -> Result<Value> {
 		self.0.clone().run(env)
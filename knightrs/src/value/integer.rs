@@ -1,6 +1,7 @@
 use crate::env::{Environment, Flags};
+use crate::error::ZeroDivisionKind;
 use crate::parse::{self, Parsable, Parser};
-use crate::value::{Boolean, List, NamedType, Text, ToBoolean, ToList, ToText};
+use crate::value::{Boolean, List, NamedType, Text, ToBoolean, ToList, ToText, Value};
 use crate::{Error, Result};
 use std::fmt::{self, Debug, Display, Formatter};
 use std::str::FromStr;
@@ -101,6 +102,13 @@ impl Integer {
 	/// The value one.
 	pub const ONE: Self = Self(1);
 
+	/// The most digits an integer can have, as returned by [`Integer::number_of_digits`].
+	pub const MAX_DIGITS: usize = i64::MAX.ilog10() as usize + 1;
+
+	/// The integers whose [`Text`]s are cached when [`cache_small_integers`](
+	/// crate::env::flags::QualityOfLife::cache_small_integers) is enabled.
+	pub const SMALL_TEXTS: std::ops::RangeInclusive<i64> = -99..=999;
+
 	#[inline]
 	pub const fn max(flags: &Flags) -> Self {
 		#[cfg(feature = "compliance")]
@@ -142,6 +150,36 @@ impl Integer {
 		.ok_or(Error::IntegerOverflow)
 	}
 
+	/// Parses out an integer from `source` according to the Knight specifications for string ->
+	/// integer conversions.
+	///
+	/// See [`knight_strings::parse_integer`] for the exact rules. Integers which are out of bounds are
+	/// handled according to [`Flags::string_to_integer_overflow`], except that they're always an
+	/// error when [`check_overflow`](crate::env::flags::Compliance::check_overflow) is enabled.
+	///
+	/// # Errors
+	/// If the integer is out of bounds and overflowing is an error, an [`Error::IntegerOverflow`] is
+	/// returned.
+	pub fn parse_from_str(source: &str, flags: &Flags) -> Result<Self> {
+		#[cfg_attr(not(feature = "compliance"), allow(unused_mut))]
+		let (mut bits, mut overflow) = (i64::BITS, flags.string_to_integer_overflow);
+
+		#[cfg(feature = "compliance")]
+		{
+			if flags.compliance.i32_integer {
+				bits = i32::BITS;
+			}
+
+			if flags.compliance.check_overflow {
+				overflow = knight_strings::IntegerOverflow::Error;
+			}
+		}
+
+		knight_strings::parse_integer(source, bits, overflow)
+			.map(Self)
+			.map_err(|_| Error::IntegerOverflow)
+	}
+
 	fn binary_op<T>(
 		self,
 		rhs: T,
@@ -190,7 +228,7 @@ impl Integer {
 	/// Any errors [`::divide`](IntType::divide) returns are bubbled up.
 	pub fn divide(self, divisor: Self, flags: &Flags) -> Result<Self> {
 		if divisor == 0 {
-			return Err(Error::DivisionByZero);
+			return Err(Error::DivisionByZero { kind: ZeroDivisionKind::Divide, operand: self });
 		}
 
 		self.binary_op(divisor.0, flags, i64::checked_div, i64::wrapping_div)
@@ -206,10 +244,54 @@ impl Integer {
 	///
 	/// Any errors [`::remainder`](IntType::remainder) returns are bubbled up.
 	///
+	/// # Negative operands
+	/// By default, the remainder is truncated (ie it has the same sign as `self`, like Rust's `%`).
+	/// If [`euclidean_remainder`] is enabled, the Euclidean remainder is used instead, which is
+	/// never negative. Both agree when neither operand is negative, which is the only case the
+	/// Knight spec defines.
+	///
+	/// # Examples
+	/// ```
+	/// # use knightrs::{env::Flags, value::Integer};
+	/// let remainder = |lhs: i32, rhs: i32, flags: &Flags| {
+	///     Integer::from(lhs).remainder(Integer::from(rhs), flags).ok().map(i64::from)
+	/// };
+	///
+	/// let mut flags = Flags::default();
+	/// for (lhs, rhs, expected) in [(7, 3, 1), (7, -3, 1), (-7, 3, -1), (-7, -3, -1), (-6, 3, 0)] {
+	///     assert_eq!(remainder(lhs, rhs, &flags), Some(expected), "{lhs} % {rhs}");
+	/// }
+	/// assert_eq!(remainder(7, 0, &flags), None);
+	/// assert_eq!(remainder(-7, 0, &flags), None);
+	///
+	/// // Euclidean remainders are never negative.
+	/// #[cfg(feature = "iffy-extensions")]
+	/// {
+	///     let mut flags = flags;
+	///     flags.extensions.iffy.euclidean_remainder = true;
+	///     let euclidean = [(7, 3, 1), (7, -3, 1), (-7, 3, 2), (-7, -3, 2), (-6, 3, 0)];
+	///     for (lhs, rhs, expected) in euclidean {
+	///         assert_eq!(remainder(lhs, rhs, &flags), Some(expected), "{lhs} % {rhs}");
+	///     }
+	///     assert_eq!(remainder(-7, 0, &flags), None);
+	/// }
+	///
+	/// // Only nonnegative operands are allowed when checking bounds.
+	/// #[cfg(feature = "compliance")]
+	/// {
+	///     flags.compliance.check_integer_function_bounds = true;
+	///     assert_eq!(remainder(7, 3, &flags), Some(1));
+	///     assert_eq!(remainder(-7, 3, &flags), None);
+	///     assert_eq!(remainder(7, -3, &flags), None);
+	///     assert_eq!(remainder(-7, -3, &flags), None);
+	/// }
+	/// ```
+	///
 	/// [`check_integer_function_bounds`]: crate::env::flags::Compliance::check_integer_function_bounds
+	/// [`euclidean_remainder`]: crate::env::flags::Iffy::euclidean_remainder
 	pub fn remainder(self, base: Self, flags: &Flags) -> Result<Self> {
 		if base == 0 {
-			return Err(Error::DivisionByZero);
+			return Err(Error::DivisionByZero { kind: ZeroDivisionKind::Remainder, operand: self });
 		}
 
 		#[cfg(feature = "compliance")]
@@ -223,23 +305,37 @@ impl Integer {
 			}
 		}
 
+		#[cfg(feature = "iffy-extensions")]
+		if flags.extensions.iffy.euclidean_remainder {
+			return self.binary_op(base.0, flags, i64::checked_rem_euclid, i64::wrapping_rem_euclid);
+		}
+
 		self.binary_op(base.0, flags, i64::checked_rem, i64::wrapping_rem)
 	}
 
 	/// Raises `self` to the `exponent`th power.
 	///
+	/// Negative exponents are computed as `1 / (self ^ -exponent)`, truncated towards zero, so:
+	/// - `1` to any power is `1`,
+	/// - `-1` to any power is `1` if the exponent is even, and `-1` if it's odd,
+	/// - `0` to a negative power is a division by zero, and
+	/// - everything else to a negative power is `0`.
+	///
+	/// Anything to the zeroth power (including `0`) is `1`.
+	///
 	/// # Errors
 	/// If the exponent is negative and [`check_integer_function_bounds`] is enabled, then an
 	/// [`Error::DomainError`] is returned.
 	///
-	/// If the exponent is negative, [`check_integer_function_bounds`] isn't enabled, and `self` is
-	/// zero, an [`Error::DivisionByZero`] is returned.
+	/// If the exponent is negative and `self` is zero, an [`Error::DivisionByZero`] is returned.
+	///
+	/// If the exponent is larger than a [`u32`] and `self` isn't `-1`, `0`, or `1` (ie the result
+	/// is guaranteed to overflow), an [`Error::DomainError`] is returned.
 	///
-	/// If `self` is not zero or one, [`check_integer_function_bounds`] is enabled, and the exponent
-	/// is larger than an [`u32`], then an [`Error::DomainError`] is returned.
+	/// If [`check_overflow`](crate::env::flags::Compliance::check_overflow) is enabled, overflows
+	/// yield [`Error::IntegerOverflow`].
 	///
 	/// [`check_integer_function_bounds`]: crate::env::flags::Compliance::check_integer_function_bounds
-	/// If the exponent is negative,
 	pub fn power(self, exponent: Self, flags: &Flags) -> Result<Self> {
 		use std::cmp::Ordering;
 		let _ = flags;
@@ -251,19 +347,24 @@ impl Integer {
 			}
 
 			Ordering::Less => match self.0 {
-				-1 => Ok(if exponent.0 % 2 == 0 { self } else { Self::ONE }),
-				0 => Err(Error::DivisionByZero),
+				-1 => Ok(if exponent.0 % 2 == 0 { Self::ONE } else { self }),
+				0 => Err(Error::DivisionByZero { kind: ZeroDivisionKind::Power, operand: exponent }),
 				1 => Ok(Self::ONE),
 				_ => Ok(Self::ZERO),
 			},
 
 			Ordering::Equal => Ok(Self::ONE),
 
-			Ordering::Greater => {
-				let exp = u32::try_from(exponent).or(Err(Error::DomainError("exponent too large")))?;
+			Ordering::Greater => match u32::try_from(exponent) {
+				Ok(exp) => self.binary_op(exp, flags, i64::checked_pow, i64::wrapping_pow),
 
-				self.binary_op(exp, flags, i64::checked_pow, i64::wrapping_pow)
-			}
+				// The exponent's too large to fit in a `u32`; only `-1`, `0`, and `1` won't overflow.
+				Err(_) => match self.0 {
+					-1 => Ok(if exponent.0 % 2 == 0 { Self::ONE } else { self }),
+					0 | 1 => Ok(self),
+					_ => Err(Error::DomainError("exponent too large")),
+				},
+			},
 		}
 	}
 
@@ -314,39 +415,165 @@ impl Integer {
 		todo!()
 	}
 
+	/// Bitwise ANDs `self` and `rhs`, as per [`XBAND`](crate::function::XBAND).
+	#[cfg(feature = "extensions")]
+	pub fn bitwise_and(self, rhs: Self) -> Self {
+		// Bitwise operations of two in-bounds integers are always in bounds.
+		Self(self.0 & rhs.0)
+	}
+
+	/// Bitwise ORs `self` and `rhs`, as per [`XBOR`](crate::function::XBOR).
+	#[cfg(feature = "extensions")]
+	pub fn bitwise_or(self, rhs: Self) -> Self {
+		Self(self.0 | rhs.0)
+	}
+
+	/// Bitwise XORs `self` and `rhs`, as per [`XBXOR`](crate::function::XBXOR).
+	#[cfg(feature = "extensions")]
+	pub fn bitwise_xor(self, rhs: Self) -> Self {
+		Self(self.0 ^ rhs.0)
+	}
+
+	/// Validates the amount to shift by for [`Integer::shift_left`] and [`Integer::shift_right`].
+	#[cfg(feature = "extensions")]
+	fn shift_amount(amount: Self, flags: &Flags) -> Result<u32> {
+		#[cfg(feature = "compliance")]
+		if flags.compliance.check_integer_function_bounds {
+			let bits = if flags.compliance.i32_integer { i32::BITS } else { i64::BITS };
+
+			if !(0..bits as i64).contains(&amount.0) {
+				return Err(Error::DomainError("shift amount out of bounds"));
+			}
+		}
+
+		let _ = flags;
+		u32::try_from(amount.0).or(Err(Error::DomainError("negative shift amount")))
+	}
+
+	/// Shifts `self` left by `amount` bits, as per [`XSHL`](crate::function::XSHL).
+	///
+	/// # Errors
+	/// An [`Error::DomainError`] is returned if `amount` is negative, or, when
+	/// [`check_integer_function_bounds`] is enabled, if `amount` is at least the amount of bits in
+	/// an integer.
+	///
+	/// If [`check_overflow`](crate::env::flags::Compliance::check_overflow) is enabled, shifting
+	/// bits out yields an [`Error::IntegerOverflow`].
+	///
+	/// [`check_integer_function_bounds`]: crate::env::flags::Compliance::check_integer_function_bounds
+	#[cfg(feature = "extensions")]
+	pub fn shift_left(self, amount: Self, flags: &Flags) -> Result<Self> {
+		let amount = Self::shift_amount(amount, flags)?;
+
+		self.binary_op(
+			amount,
+			flags,
+			|int, amount| int.checked_shl(amount).filter(|shifted| shifted >> amount == int),
+			i64::wrapping_shl,
+		)
+	}
+
+	/// Arithmetically shifts `self` right by `amount` bits, as per [`XSHR`](crate::function::XSHR).
+	///
+	/// # Errors
+	/// The same as [`Integer::shift_left`], except shifting right never overflows.
+	#[cfg(feature = "extensions")]
+	pub fn shift_right(self, amount: Self, flags: &Flags) -> Result<Self> {
+		let amount = Self::shift_amount(amount, flags)?;
+
+		self.binary_op(amount, flags, i64::checked_shr, i64::wrapping_shr)
+	}
+
+	/// Gets the radix corresponding to `base`, which must be within `2..=36`.
+	#[cfg(feature = "extensions")]
+	fn radix(base: Self) -> Result<u32> {
+		match base.0 {
+			2..=36 => Ok(base.0 as u32),
+			_ => Err(Error::DomainError("base must be within 2..=36")),
+		}
+	}
+
+	/// Converts `self` to a string of digits in `base`, as per [`XBASE`](crate::function::XBASE).
+	///
+	/// Digits above `9` are written as lowercase letters, and negative numbers start with `-`.
+	///
+	/// # Errors
+	/// If `base` isn't within `2..=36`, an [`Error::DomainError`] is returned.
+	#[cfg(feature = "extensions")]
+	pub fn to_base(self, base: Self) -> Result<String> {
+		let radix = Self::radix(base)?;
+		let mut magnitude = self.0.unsigned_abs();
+		let mut digits = Vec::new();
+
+		loop {
+			digits.push(char::from_digit((magnitude % radix as u64) as u32, radix).unwrap());
+			magnitude /= radix as u64;
+
+			if magnitude == 0 {
+				break;
+			}
+		}
+
+		if self.0 < 0 {
+			digits.push('-');
+		}
+
+		Ok(digits.into_iter().rev().collect())
+	}
+
+	/// Parses `source` as digits in `base`, as per [`XFROMBASE`](crate::function::XFROMBASE).
+	///
+	/// Leading and trailing whitespace is ignored, and an optional leading `+` or `-` is allowed.
+	///
+	/// # Errors
+	/// If `base` isn't within `2..=36`, or `source` contains invalid digits, an
+	/// [`Error::DomainError`] is returned. If the result doesn't fit within an `Integer` (which
+	/// depends on [`i32_integer`](crate::env::flags::Compliance::i32_integer)), then an
+	/// [`Error::IntegerOverflow`] is returned.
+	#[cfg(feature = "extensions")]
+	pub fn from_base(source: &str, base: Self, flags: &Flags) -> Result<Self> {
+		use std::num::IntErrorKind;
+
+		let radix = Self::radix(base)?;
+		let int = i64::from_str_radix(source.trim(), radix).map_err(|err| match err.kind() {
+			IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => Error::IntegerOverflow,
+			_ => Error::DomainError("invalid digits for base"),
+		})?;
+
+		Self::new(int, flags).ok_or(Error::IntegerOverflow)
+	}
+
 	/// Get a random integer.
 	///
 	/// # Flags
 	/// If the [`limit_rand_range`](crate::env::flags::Compliance::limit_rand_range) flag is enabled,
-	/// then the returned integer will be within the range `0..=0x7FFF`.
+	/// then the returned integer will be within the range `0..=0x7FFF`. This takes precedence over
+	/// all other flags, as it's required for spec compliance.
 	///
-	/// If the [`negative_random_integers`](crate::env::flags::Iffy::negative_random_integers) flag
-	/// is enabled, then the returned integer will be in the range `Self::MIN..=Self::MAX`.
+	/// Otherwise, if the [`negative_random_integers`](
+	/// crate::env::flags::Iffy::negative_random_integers) flag is enabled, then the returned integer
+	/// will be in the range `Self::MIN..=Self::MAX`.
 	///
 	/// If neither of these flags are enabled, the returned integer will be in the range
 	/// `0..Self::MAX`.
 	pub fn random<R: rand::Rng + ?Sized>(rng: &mut R, flags: &Flags) -> Self {
-		let min = match () {
+		let (min, max) = match () {
+			#[cfg(feature = "compliance")]
+			_ if flags.compliance.limit_rand_range => (0, 0x7FFF),
+
 			#[cfg(feature = "iffy-extensions")]
 			_ if flags.extensions.iffy.negative_random_integers => {
 				if flags.compliance.i32_integer {
-					i32::MIN as i64
+					(i32::MIN as i64, i32::MAX as i64)
 				} else {
-					i64::MIN
+					(i64::MIN, i64::MAX)
 				}
 			}
 
-			_ => 0,
-		};
-
-		let max = match () {
 			#[cfg(feature = "compliance")]
-			_ if flags.compliance.limit_rand_range => 0x7FFF,
+			_ if flags.compliance.i32_integer => (0, i32::MAX as i64),
 
-			#[cfg(feature = "compliance")]
-			_ if flags.compliance.i32_integer => i32::MAX as i64,
-
-			_ => i64::MAX,
+			_ => (0, i64::MAX),
 		};
 
 		let _ = flags;
@@ -382,10 +609,25 @@ impl ToBoolean for Integer {
 	}
 }
 
+thread_local! {
+	// The texts for `Integer::SMALL_TEXTS`, for when `cache_small_integers` is enabled.
+	static SMALL_TEXTS: Box<[Text]> = Integer::SMALL_TEXTS
+		.map(|int| unsafe { Text::new_unchecked(int) })
+		.collect();
+}
+
 impl ToText for Integer {
 	/// Returns a string representation of `self`.
+	///
+	/// If [`cache_small_integers`](crate::env::flags::QualityOfLife::cache_small_integers) is
+	/// enabled, integers within [`Integer::SMALL_TEXTS`] return a cached [`Text`].
 	#[inline]
-	fn to_text(&self, _env: &mut Environment) -> Result<Text> {
+	fn to_text(&self, env: &mut Environment) -> Result<Text> {
+		if env.flags().qol.cache_small_integers && Self::SMALL_TEXTS.contains(&self.0) {
+			let index = (self.0 - Self::SMALL_TEXTS.start()) as usize;
+			return Ok(SMALL_TEXTS.with(|texts| texts[index].clone()));
+		}
+
 		// SAFETY: digits are valid in all encodings, and it'll never exceed the length.
 		Ok(unsafe { Text::new_unchecked(self) })
 	}
@@ -395,19 +637,35 @@ impl ToList for Integer {
 	/// Returns a list of all the digits of `self`, when `self` is expressed in base 10.
 	///
 	/// If `self` is negative, all the returned digits are negative.
-	fn to_list(&self, _: &mut Environment) -> Result<List> {
-		if *self == 0 {
-			return Ok(List::boxed(self.clone().into()));
+	///
+	/// # Errors
+	/// If [`strict_conversions`](crate::env::flags::Compliance::strict_conversions) is enabled and
+	/// `self` is negative, an [`Error::DomainError`] is returned.
+	fn to_list(&self, env: &mut Environment) -> Result<List> {
+		#[cfg(feature = "compliance")]
+		if env.flags().compliance.strict_conversions && self.0 < 0 {
+			return Err(Error::DomainError("negative integer for to list encountered"));
 		}
 
+		let _ = env;
+		if (-9..=9).contains(&self.0) {
+			return Ok(List::boxed((*self).into()));
+		}
+
+		// The digits are written to the end of a buffer on the stack (as they're found in reverse
+		// order), so the list's slice can be allocated in one go.
+		let mut digits = [Self::ZERO; Self::MAX_DIGITS];
+		let mut start = digits.len();
 		let mut integer = self.0;
-		let mut digits = Vec::with_capacity(self.number_of_digits());
 
 		while integer != 0 {
-			digits.insert(0, Self(integer % 10).into());
+			start -= 1;
+			digits[start] = Self(integer % 10);
 			integer /= 10;
 		}
 
+		let digits = digits[start..].iter().map(|&digit| digit.into()).collect::<Box<[Value]>>();
+
 		// The maximum amount of digits for an Integer is vastly smaller than `i32::MAX`, so
 		// there's no need to do a check.
 		Ok(unsafe { List::new_unchecked(digits) })
@@ -476,6 +734,14 @@ macro_rules! impl_from_integer {
 impl_integer_from!(bool u8 u16 i8 i16 i32 ; u32 u64 u128 usize i64 i128 isize );
 impl_from_integer!(u8 u16 u32 u64 u128 usize i8 i16 i32 isize; i64 i128);
 
+impl From<std::cmp::Ordering> for Integer {
+	/// Converts `ordering` to `-1`, `0`, or `1`, as per `XCMP`.
+	#[inline]
+	fn from(ordering: std::cmp::Ordering) -> Self {
+		Self(ordering as i64)
+	}
+}
+
 impl TryFrom<char> for Integer {
 	type Error = Error;
 
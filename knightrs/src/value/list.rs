@@ -83,20 +83,21 @@ impl List {
 	/// An empty [`List`].
 	pub const EMPTY: Self = Self(None);
 
-	/// The maximum length for [`List`]s. Only used when `container-length-limit` is enabled.
+	/// The default maximum length for [`List`]s. Only used when `container-length-limit` is enabled.
 	pub const MAX_LEN: usize = i32::MAX as usize;
 
 	/// Creates a new `list` from `slice`.
 	///
 	/// # Errors
-	/// If `container-length-limit` is enabled, and `slice.len()` is larger than [`List::MAX_LEN`],
-	/// then an [`Error::DomainError`] is returned. If `container-length-limit` is not enabled,
+	/// If `container-length-limit` is enabled, and `slice.len()` isn't a [valid container length](
+	/// crate::env::flags::Compliance::is_valid_container_length), then an [`Error::DomainError`] is
+	/// returned. If `container-length-limit` is not enabled,
 	/// this function will always succeed.
 	pub fn new<T: Into<Box<[Value]>>>(slice: T, flags: &Flags) -> Result<Self> {
 		let slice = slice.into();
 
 		#[cfg(feature = "compliance")]
-		if flags.compliance.check_container_length && Self::MAX_LEN < slice.len() {
+		if !flags.compliance.is_valid_container_length(slice.len()) {
 			return Err(Error::DomainError("length of slice is out of bounds"));
 		}
 
@@ -121,6 +122,59 @@ impl List {
 		Self::_new(Inner::Boxed(value))
 	}
 
+	/// Creates a new [`List`] of the integers from `start` up to, but not including, `stop`.
+	///
+	/// If `start` is greater than `stop` and the [`negative_ranges`] extension is enabled, the list
+	/// instead counts down from `start` to, but not including, `stop`.
+	///
+	/// # Errors
+	/// If `start` is greater than `stop` and [`negative_ranges`] isn't enabled, an
+	/// [`Error::DomainError`] is returned. Like [`List::new`], an error is also returned if the
+	/// range is too large.
+	///
+	/// [`negative_ranges`]: crate::env::flags::Extensions::negative_ranges
+	pub fn from_range(start: Integer, stop: Integer, flags: &Flags) -> Result<Self> {
+		let (start, stop) = (i64::from(start), i64::from(stop));
+
+		// Every integer between `start` and `stop` is also a valid integer.
+		let to_value = |int: i64| Value::from(Integer::try_from(int).unwrap());
+
+		if start <= stop {
+			return Self::new((start..stop).map(to_value).collect::<Vec<_>>(), flags);
+		}
+
+		#[cfg(feature = "extensions")]
+		if flags.extensions.negative_ranges {
+			return Self::new((stop + 1..=start).rev().map(to_value).collect::<Vec<_>>(), flags);
+		}
+
+		Err(Error::DomainError("range start is greater than its stop"))
+	}
+
+	/// Creates a new [`List`] of the single-character [`Text`]s from `start` up to, but not
+	/// including, `stop`.
+	///
+	/// This follows the same rules as [`List::from_range`] for when `start` is after `stop`.
+	/// Characters which aren't valid [`Text`]s (such as surrogates) are skipped.
+	///
+	/// # Errors
+	/// The same as [`List::from_range`].
+	pub fn from_char_range(start: char, stop: char, flags: &Flags) -> Result<Self> {
+		let to_value = |chr: char| Text::new(chr, flags).ok().map(Value::from);
+
+		if start <= stop {
+			return Self::new((start..stop).filter_map(to_value).collect::<Vec<_>>(), flags);
+		}
+
+		#[cfg(feature = "extensions")]
+		if flags.extensions.negative_ranges {
+			let chars = (stop..=start).rev().take_while(|&chr| chr != stop);
+			return Self::new(chars.filter_map(to_value).collect::<Vec<_>>(), flags);
+		}
+
+		Err(Error::DomainError("range start is greater than its stop"))
+	}
+
 	/// Returns whether `self` is empty.
 	#[inline]
 	pub fn is_empty(&self) -> bool {
@@ -178,7 +232,8 @@ impl List {
 	///
 	/// # Errors
 	/// If `container-length-limit` not enabled, this method will never fail. I fit is, and
-	/// [`List::MAX_LEN`] is smaller than `self.len() + rhs.len()`, then an [`Error::DomainError`] is
+	/// `self.len() + rhs.len()` isn't a [valid container length](
+	/// crate::env::flags::Compliance::is_valid_container_length), then an [`Error::DomainError`] is
 	/// returned.
 	pub fn concat(&self, rhs: &Self, flags: &Flags) -> Result<Self> {
 		if self.is_empty() {
@@ -190,7 +245,7 @@ impl List {
 		}
 
 		#[cfg(feature = "compliance")]
-		if flags.compliance.check_container_length && Self::MAX_LEN < self.len() + rhs.len() {
+		if !flags.compliance.is_valid_container_length(self.len().saturating_add(rhs.len())) {
 			return Err(Error::DomainError("length of concatenation is out of bounds"));
 		}
 
@@ -198,24 +253,104 @@ impl List {
 		Ok(Self::_new(Inner::Cons(self.clone(), rhs.clone())))
 	}
 
-	/// Returns a new list where `self` is repeated `amount` times.
+	/// Replaces the elements `start..start + len` of `self` with `replacement`, as per `SET`.
+	///
+	/// If nothing else refers to `self`, and it's backed by a single slice, the elements are spliced
+	/// in place instead of copying the whole list.
+	///
+	/// # Errors
+	/// If `start..start + len` is out of bounds, an [`Error::IndexOutOfBounds`] is returned. Any
+	/// errors from [`List::new`] are also returned. `self` is left unchanged if an error occurs.
+	pub fn set_in_place(
+		&mut self,
+		start: usize,
+		len: usize,
+		replacement: &Self,
+		flags: &Flags,
+	) -> Result<()> {
+		let end = start.saturating_add(len);
+		if end > self.len() {
+			return Err(Error::IndexOutOfBounds { len: self.len(), index: end });
+		}
+
+		// Checked up front, so that `self` isn't left half-modified if the new length isn't valid.
+		#[cfg(feature = "compliance")]
+		if !flags.compliance.is_valid_container_length(self.len() - (end - start) + replacement.len())
+		{
+			return Err(Error::DomainError("length of slice is out of bounds"));
+		}
+
+		if let Some(Inner::Slice(slice)) = self.0.as_mut().and_then(RefCount::get_mut) {
+			if end - start == replacement.len() {
+				for (dst, src) in slice[start..end].iter_mut().zip(replacement) {
+					*dst = src.clone();
+				}
+
+				return Ok(());
+			}
+
+			let mut elements = std::mem::take(slice).into_vec();
+			elements.splice(start..end, replacement.iter().cloned());
+			*self = Self::new(elements, flags)?;
+			return Ok(());
+		}
+
+		let mut ret = Vec::new();
+		ret.extend(self.iter().take(start).cloned());
+		ret.extend(replacement.iter().cloned());
+		ret.extend(self.iter().skip(end).cloned());
+		*self = Self::new(ret, flags)?;
+		Ok(())
+	}
+
+	/// Whether `self` and `rhs` are the same list, rather than just equal ones.
+	#[must_use]
+	pub fn ptr_eq(&self, rhs: &Self) -> bool {
+		match (&self.0, &rhs.0) {
+			(Some(lhs), Some(rhs)) => RefCount::ptr_eq(lhs, rhs),
+			_ => false,
+		}
+	}
+
+	/// Lexicographically compares `self` and `rhs`, as per `<` and `>`.
 	///
-	/// This will return `None` if `self.len() * amount` is greater than [`Integer::MAX`].
+	/// Elements are compared pairwise with [`Value::compare`], stopping at the first pair which
+	/// isn't equal; if every pair is equal, the shorter list is smaller.
+	///
+	/// # Errors
+	/// Any errors from comparing elements are returned.
+	pub fn compare(&self, rhs: &Self, env: &mut Environment) -> Result<std::cmp::Ordering> {
+		for (left, right) in self.iter().zip(rhs) {
+			match left.compare(right, env)? {
+				std::cmp::Ordering::Equal => {}
+				other => return Ok(other),
+			}
+		}
+
+		Ok(self.len().cmp(&rhs.len()))
+	}
+
+	/// Returns a new list where `self` is repeated `amount` times.
 	///
 	/// # Errors
-	/// If `container-length-limit` is not enabled, this method will never fail. If it is, and
-	/// [`List::MAX_LEN`] is smaller than `self.len() * amount`, then a [`Error::DomainError`] is
+	/// If `self.len() * amount` is larger than [`Flags::max_repetition_length`], an
+	/// [`Error::DomainError`] is returned. Additionally, if `container-length-limit` is enabled and
+	/// `self.len() * amount` isn't a [valid container length](
+	/// crate::env::flags::Compliance::is_valid_container_length), then a [`Error::DomainError`] is
 	/// returned.
 	pub fn repeat(&self, amount: usize, flags: &Flags) -> Result<Self> {
+		if self.len().checked_mul(amount).map_or(true, |x| flags.max_repetition_length < x) {
+			return Err(Error::DomainError("repetition is too large"));
+		}
+
 		#[cfg(feature = "compliance")]
-		if flags.compliance.check_container_length
-			&& self.len().checked_mul(amount).map_or(true, |x| Self::MAX_LEN < x)
+		if !flags
+			.compliance
+			.is_valid_container_length(self.len().checked_mul(amount).unwrap_or(usize::MAX))
 		{
 			return Err(Error::DomainError("length of repetition is out of bounds"));
 		}
 
-		let _ = flags;
-
 		if self.is_empty() {
 			return Ok(Self::EMPTY);
 		}
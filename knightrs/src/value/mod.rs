@@ -4,6 +4,7 @@
 mod boolean;
 mod integer;
 mod list;
+mod native_fn;
 mod null;
 pub mod text;
 mod value;
@@ -14,10 +15,11 @@ mod custom;
 
 pub use boolean::{Boolean, ToBoolean};
 #[cfg(feature = "custom-types")]
-pub use custom::{Custom, CustomType};
+pub use custom::{Buffer, Custom, CustomType, Map};
 #[doc(inline)]
 pub use integer::{Integer, ToInteger};
 pub use list::{List, ToList};
+pub use native_fn::NativeFn;
 pub use null::Null;
 #[doc(inline)]
 pub use text::{Text, TextSlice, ToText};
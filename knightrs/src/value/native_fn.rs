@@ -0,0 +1,76 @@
+use crate::containers::{MaybeSendSync, RefCount};
+use crate::env::Environment;
+use crate::value::{NamedType, Runnable, Value};
+use crate::Result;
+use std::fmt::{self, Debug, Formatter};
+use std::hash::{Hash, Hasher};
+
+/// A function implemented in Rust, which Knight code can `CALL`.
+///
+/// This is useful for handing callbacks (such as a logging function) to Knight programs: Assign it
+/// to a variable (eg via [`Variable::assign`](crate::env::Variable::assign)), and then the program
+/// can call it like any block.
+///
+/// When called, the function is given the arguments of the current call frame (ie what `$`
+/// accesses), so `CALL`ing lists with [`block_params`] will pass arguments along, and `XAPPLY`
+/// passes it the elements of its list. Without the `extensions` feature, it's always given no
+/// arguments.
+///
+/// [`block_params`]: crate::env::flags::Extensions::block_params
+#[derive(Clone)]
+pub struct NativeFn(RefCount<dyn Body>);
+
+// `MaybeSendSync` isn't an auto trait, so it can't be added to `dyn Fn` directly.
+trait Body: Fn(&[Value], &mut Environment) -> Result<Value> + MaybeSendSync {}
+impl<F: Fn(&[Value], &mut Environment) -> Result<Value> + MaybeSendSync> Body for F {}
+
+impl NativeFn {
+	/// Creates a new [`NativeFn`] from `func`.
+	pub fn new<F>(func: F) -> Self
+	where
+		F: Fn(&[Value], &mut Environment) -> Result<Value> + MaybeSendSync + 'static,
+	{
+		Self(RefCount::new(func))
+	}
+
+	/// Calls `self` with `args`.
+	pub fn call_with(&self, args: &[Value], env: &mut Environment) -> Result<Value> {
+		(self.0)(args, env)
+	}
+}
+
+impl Debug for NativeFn {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		write!(f, "NativeFn({:p})", RefCount::as_ptr(&self.0) as *const u8)
+	}
+}
+
+impl Eq for NativeFn {}
+impl PartialEq for NativeFn {
+	#[inline]
+	fn eq(&self, rhs: &Self) -> bool {
+		RefCount::ptr_eq(&self.0, &rhs.0)
+	}
+}
+
+impl Hash for NativeFn {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		(RefCount::as_ptr(&self.0) as *const u8 as usize).hash(state);
+	}
+}
+
+impl NamedType for NativeFn {
+	const TYPENAME: &'static str = "NativeFn";
+}
+
+impl Runnable for NativeFn {
+	/// Calls `self` with the arguments of the current call frame.
+	fn run(&self, env: &mut Environment) -> Result<Value> {
+		#[cfg(feature = "extensions")]
+		let args = env.callstack().last().map(|args| args.iter().cloned().collect::<Vec<_>>());
+		#[cfg(not(feature = "extensions"))]
+		let args = None::<Vec<Value>>;
+
+		self.call_with(args.as_deref().unwrap_or_default(), env)
+	}
+}
@@ -25,11 +25,11 @@ impl Parsable for Null {
 	type Output = Self;
 
 	fn parse(parser: &mut Parser<'_, '_>) -> parse::Result<Option<Self>> {
-		if parser.advance_if('N').is_none() {
+		if parser.peek() != Some('N') {
 			return Ok(None);
 		}
 
-		parser.strip_keyword_function();
+		parser.strip_keyword("NULL")?;
 
 		Ok(Some(Self))
 	}
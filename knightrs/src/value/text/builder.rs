@@ -2,8 +2,8 @@ use super::*;
 
 /// A builder for [`Text`]s.
 ///
-/// Since [`Text`]s are immutable, this builder allows you to create one from different sources
-/// without having to recheck the encoding's validity.
+/// Since [`Text`]s are immutable, this builder allows you to create one from different sources,
+/// and then validate the result once in [`Builder::finish`].
 #[must_use]
 #[derive(Default, Debug, PartialEq, Eq)]
 pub struct Builder(String);
@@ -21,6 +21,24 @@ impl Builder {
 		Self(String::with_capacity(cap))
 	}
 
+	/// Reserves capacity for at least `additional` more bytes.
+	#[inline]
+	pub fn reserve(&mut self, additional: usize) {
+		self.0.reserve(additional);
+	}
+
+	/// Returns the length of the builder so far, in bytes.
+	#[inline]
+	pub fn len(&self) -> usize {
+		self.0.len()
+	}
+
+	/// Returns whether nothing has been pushed to the builder yet.
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+
 	/// Adds the given `text` to the end of the builder.
 	#[inline]
 	pub fn push(&mut self, text: &TextSlice) {
@@ -28,23 +46,95 @@ impl Builder {
 	}
 
 	/// Adds the given `chr` to the end of the builder.
+	///
+	/// `chr` isn't validated until [`Builder::finish`] is called.
 	#[inline]
 	pub fn push_char(&mut self, chr: char) {
 		self.0.push(chr);
 	}
 
+	/// Adds `text` to the end of the builder `amount` times.
+	///
+	/// # Errors
+	/// If [`check_container_length`](crate::env::flags::Compliance::check_container_length) is
+	/// enabled, and the repeated text would be too large, an error is returned before anything is
+	/// allocated.
+	pub fn push_repeated(
+		&mut self,
+		text: &TextSlice,
+		amount: usize,
+		flags: &Flags,
+	) -> Result<(), NewTextError> {
+		let additional = text.len().checked_mul(amount);
+
+		#[cfg(feature = "compliance")]
+		if flags.compliance.check_container_length {
+			match additional.and_then(|additional| additional.checked_add(self.len())) {
+				Some(len) if flags.compliance.is_valid_container_length(len) => {}
+				Some(len) => return Err(NewTextError::LengthTooLong(len)),
+				None => return Err(NewTextError::LengthTooLong(usize::MAX)),
+			}
+		}
+
+		let _ = flags;
+
+		// Repeating empty text is a no-op, no matter how large `amount` is.
+		if text.is_empty() {
+			return Ok(());
+		}
+
+		self.reserve(additional.expect("capacity overflow"));
+		for _ in 0..amount {
+			self.push(text);
+		}
+
+		Ok(())
+	}
+
 	/// Finishes constructing the [`Text`] and returns it.
 	///
-	/// Note that there's no `finish_unchecked`. You can simply do [`Text::new_unchecked`] for that.
+	/// This is the only place the contents are validated, so pushing lots of pieces is only ever a
+	/// single pass over the result. Note that there's no `finish_unchecked`. You can simply do
+	/// [`Text::new_unchecked`] for that.
 	///
 	/// # Results
 	/// If [`check_container_length`](crate::env::flags::Compliance::check_container_length) is
-	/// enabled, and the resulting [`Text`] is too large, an error is returned.
+	/// enabled, and the resulting [`Text`] is too large, an error is returned. Likewise, if
+	/// [`knight_encoding`](crate::env::flags::Compliance::knight_encoding) is enabled and a pushed
+	/// char isn't [valid](is_valid_character), an error is returned.
 	#[inline]
 	pub fn finish(self, flags: &Flags) -> Result<Text, NewTextError> {
-		// SAFETY: We know that `self` is comprised of only valid `E`s because it was constructed only
-		// with `E`s. Additionally, since `E: Encoding` is required for creating all `TextSlice`s and
-		// `Characters` (which is how `self.0` is constructed), its contents are a valid `E`.
-		unsafe { Text::new_len_unchecked(self.0, flags) }
+		validate(&self.0, flags)?;
+
+		// SAFETY: We just validated `self.0`.
+		Ok(unsafe { Text::new_unchecked(self.0) })
+	}
+}
+
+impl<'a> Extend<&'a TextSlice> for Builder {
+	fn extend<T: IntoIterator<Item = &'a TextSlice>>(&mut self, iter: T) {
+		for text in iter {
+			self.push(text);
+		}
+	}
+}
+
+impl Extend<char> for Builder {
+	fn extend<T: IntoIterator<Item = char>>(&mut self, iter: T) {
+		self.0.extend(iter);
+	}
+}
+
+impl<'a> FromIterator<&'a TextSlice> for Builder {
+	fn from_iter<T: IntoIterator<Item = &'a TextSlice>>(iter: T) -> Self {
+		let mut builder = Self::new();
+		builder.extend(iter);
+		builder
+	}
+}
+
+impl FromIterator<char> for Builder {
+	fn from_iter<T: IntoIterator<Item = char>>(iter: T) -> Self {
+		Self(iter.into_iter().collect())
 	}
 }
@@ -1,5 +1,4 @@
 mod builder;
-mod character;
 mod text;
 mod textslice;
 
@@ -9,7 +8,7 @@ pub trait ToText {
 
 use crate::env::{Environment, Flags};
 pub use builder::Builder;
-pub use character::Character;
+pub use knight_strings::Character;
 pub use text::*;
 pub use textslice::*;
 
@@ -22,9 +21,10 @@ pub use textslice::*;
 pub const fn is_valid_character(chr: char, flags: &Flags) -> bool {
 	#[cfg(feature = "compliance")]
 	if flags.compliance.knight_encoding {
-		return matches!(chr, '\r' | '\n' | '\t' | ' '..='~');
+		return knight_strings::Encoding::Knight.is_char_valid(chr);
 	}
 
+	let _ = (chr, flags);
 	true
 }
 
@@ -43,8 +43,8 @@ impl Iterator for Chars<'_> {
 	}
 }
 
-/// The maximum length of a [`Text`]/[`TextSlice`] when `check_container_length` is enabled.
-pub const MAX_LEN: usize = i32::MAX as usize;
+/// The default maximum length of a [`Text`]/[`TextSlice`] when `check_container_length` is enabled.
+pub const MAX_LEN: usize = knight_strings::MAX_LEN;
 
 /// Problems that can occur when [creating `Text`](Text::new)s.
 ///
@@ -82,7 +82,7 @@ impl std::fmt::Display for NewTextError {
 
 		match *self {
 			#[cfg(feature = "compliance")]
-			Self::LengthTooLong(len) => write!(f, "length {len} longer than max {MAX_LEN}"),
+			Self::LengthTooLong(len) => write!(f, "length {len} is longer than the maximum"),
 			#[cfg(feature = "compliance")]
 			Self::IllegalChar { chr, idx } => write!(f, "illegal char {chr:?} found at index {idx}"),
 		}
@@ -93,7 +93,7 @@ impl std::error::Error for NewTextError {}
 
 const fn validate_len(data: &str, flags: &Flags) -> Result<(), NewTextError> {
 	#[cfg(feature = "compliance")]
-	if flags.compliance.check_container_length && MAX_LEN < data.len() {
+	if !flags.compliance.is_valid_container_length(data.len()) {
 		return Err(NewTextError::LengthTooLong(data.len()));
 	}
 
@@ -107,12 +107,10 @@ fn validate(data: &str, flags: &Flags) -> Result<(), NewTextError> {
 		validate_len(data, flags)?;
 
 		if flags.compliance.knight_encoding {
-			for (idx, chr) in data.chars().enumerate() {
-				if !is_valid_character(chr, flags) {
-					// Since everything's a byte, the byte index is the same as the char index.
-					return Err(NewTextError::IllegalChar { chr, idx });
-				}
-			}
+			// Since every valid char is a single byte, the byte index is the same as the char index.
+			knight_strings::Encoding::Knight
+				.validate(data)
+				.map_err(|err| NewTextError::IllegalChar { chr: err.character, idx: err.position })?;
 		}
 	}
 
@@ -8,6 +8,14 @@ use std::fmt::{self, Debug, Display, Formatter};
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Text(RefCount<TextSlice>);
 
+thread_local! {
+	// Every ASCII character as a `Text`, for `Text::from_char_unchecked`. Characters which aren't
+	// valid in the current encoding are in here too, but they're never handed out, as callers have to
+	// have validated their character first.
+	static ASCII_CHARS: [Text; 128] =
+		std::array::from_fn(|byte| unsafe { Text::new_unchecked(char::from(byte as u8)) });
+}
+
 impl Debug for Text {
 	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
 		Debug::fmt(&***self, f)
@@ -64,6 +72,22 @@ impl Text {
 		Self(RefCount::from(Box::from_raw(Box::into_raw(boxed) as *mut TextSlice)))
 	}
 
+	/// Creates a new [`Text`] containing just `chr`, without validating it.
+	///
+	/// Single-character texts are common (they're returned by `ASCII`, `GET`, `[`, and converting
+	/// texts to lists), so ASCII characters are cached per-thread and don't need an allocation.
+	///
+	/// # Safety
+	/// The same as [`Text::new_unchecked`]: if `compliance` is enabled, `chr` must be a valid
+	/// [`Character`](super::Character).
+	pub unsafe fn from_char_unchecked(chr: char) -> Self {
+		if chr.is_ascii() {
+			return ASCII_CHARS.with(|chars| chars[chr as usize].clone());
+		}
+
+		Self::new_unchecked(chr)
+	}
+
 	/// Creates a new [`Text`] without validating that the contents are of the right encoding.
 	pub unsafe fn new_len_unchecked<T>(inp: T, flags: &Flags) -> Result<Self, NewTextError>
 	where
@@ -76,6 +100,53 @@ impl Text {
 	}
 }
 
+impl Text {
+	/// Replaces the bytes `start..start + len` of `self` with `replacement`, as per `SET`.
+	///
+	/// If nothing else refers to `self`, and `replacement` is the same length as what it replaces,
+	/// `self` is modified in place instead of being copied.
+	///
+	/// # Errors
+	/// If `start..start + len` is out of bounds, an [`Error::IndexOutOfBounds`](
+	/// crate::Error::IndexOutOfBounds) is returned. Any errors from [`Builder::finish`](
+	/// super::Builder::finish) are also returned. `self` is left unchanged if an error occurs.
+	pub fn set_in_place(
+		&mut self,
+		start: usize,
+		len: usize,
+		replacement: &TextSlice,
+		flags: &Flags,
+	) -> crate::Result<()> {
+		let end = start.saturating_add(len);
+		if self.get(..start).is_none() || self.get(end..).is_none() {
+			return Err(crate::Error::IndexOutOfBounds { len: self.len(), index: end });
+		}
+
+		if replacement.len() == len {
+			if let Some(slice) = RefCount::get_mut(&mut self.0) {
+				// SAFETY: `start..end` is on char boundaries (as `get` succeeded), and we're replacing it
+				// with a valid `TextSlice` of the same length, so `slice` stays valid.
+				let bytes = unsafe { slice.as_mut_str().as_bytes_mut() };
+				bytes[start..end].copy_from_slice(replacement.as_bytes());
+				return Ok(());
+			}
+		}
+
+		let (prefix, suffix) = (self.get(..start).unwrap(), self.get(end..).unwrap());
+		let mut builder =
+			super::Builder::with_capacity(prefix.len() + replacement.len() + suffix.len());
+		builder.extend([prefix, replacement, suffix]);
+		*self = builder.finish(flags)?;
+		Ok(())
+	}
+
+	/// Whether `self` and `rhs` are the same text, rather than just equal ones.
+	#[must_use]
+	pub fn ptr_eq(&self, rhs: &Self) -> bool {
+		RefCount::ptr_eq(&self.0, &rhs.0)
+	}
+}
+
 impl std::borrow::Borrow<TextSlice> for Text {
 	fn borrow(&self) -> &TextSlice {
 		self
@@ -84,8 +155,14 @@ impl std::borrow::Borrow<TextSlice> for Text {
 
 impl From<&TextSlice> for Text {
 	fn from(text: &TextSlice) -> Self {
-		// SAFETY: `text` is already valid.
-		unsafe { Self::new_unchecked(text) }
+		// SAFETY: `text` is already valid, and single bytes are always ASCII characters.
+		unsafe {
+			if let &[byte] = text.as_bytes() {
+				return Self::from_char_unchecked(char::from(byte));
+			}
+
+			Self::new_unchecked(text)
+		}
 	}
 }
 
@@ -129,15 +206,15 @@ impl ToText for Text {
 
 impl ToInteger for Text {
 	#[inline]
-	fn to_integer(&self, _: &mut Environment) -> crate::Result<Integer> {
-		Ok(self.parse().unwrap_or_default())
+	fn to_integer(&self, env: &mut Environment) -> crate::Result<Integer> {
+		Integer::parse_from_str(self, env.flags())
 	}
 }
 
 impl ToList for Text {
 	fn to_list(&self, _: &mut Environment) -> crate::Result<List> {
 		let chars =
-			self.chars().map(|c| unsafe { Self::new_unchecked(c) }.into()).collect::<Vec<_>>();
+			self.chars().map(|c| unsafe { Self::from_char_unchecked(c) }.into()).collect::<Vec<_>>();
 
 		// SAFETY: If `self` is within the container bounds, so is the length of its chars.
 		Ok(unsafe { List::new_unchecked(chars) })
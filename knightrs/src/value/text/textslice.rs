@@ -56,6 +56,14 @@ impl TextSlice {
 		&*(inp as *const str as *const Self)
 	}
 
+	/// Gets mutable access to the underlying `str`.
+	///
+	/// # Safety
+	/// Whatever's written must keep `self` a valid [`TextSlice`].
+	pub(super) unsafe fn as_mut_str(&mut self) -> &mut str {
+		&mut self.0
+	}
+
 	/// Tries to create a new [`TextSlice`], returning an error if not possible.
 	pub fn new<'s>(inp: &'s str, flags: &Flags) -> Result<&'s Self, NewTextError> {
 		validate(inp, flags)?;
@@ -104,7 +112,9 @@ impl TextSlice {
 	}
 
 	pub fn repeat(&self, amount: usize, flags: &Flags) -> Result<Text, NewTextError> {
-		unsafe { Text::new_len_unchecked((**self).repeat(amount), flags) }
+		let mut builder = super::Builder::new();
+		builder.push_repeated(self, amount, flags)?;
+		builder.finish(flags)
 	}
 
 	#[cfg(feature = "extensions")]
@@ -1,10 +1,11 @@
 use crate::env::{Environment, Variable};
 use crate::value::{
-	Boolean, Integer, List, NamedType, Null, Runnable, Text, ToBoolean, ToInteger, ToList, ToText,
+	Boolean, Integer, List, NamedType, NativeFn, Null, Runnable, Text, ToBoolean, ToInteger, ToList,
+	ToText,
 };
 use crate::{Ast, Error, Result};
 use std::cmp::Ordering;
-use std::fmt::{self, Debug, Formatter};
+use std::fmt::{self, Debug, Display, Formatter};
 
 /// A Value within Knight.
 #[derive(Default, Clone, PartialEq, Hash)]
@@ -32,6 +33,9 @@ pub enum Value {
 	/// Represents a block of code.
 	Ast(Ast),
 
+	/// Represents a function implemented in Rust.
+	NativeFn(NativeFn),
+
 	/// Represents a custom type.
 	#[cfg(feature = "custom-types")]
 	#[cfg_attr(docsrs, doc(cfg(feature = "custom-types")))]
@@ -44,10 +48,11 @@ impl Debug for Value {
 			Self::Null => Debug::fmt(&Null, f),
 			Self::Boolean(boolean) => Debug::fmt(boolean, f),
 			Self::Integer(integer) => Debug::fmt(integer, f),
-			Self::Text(text) => Debug::fmt(text, f),
+			Self::Text(text) => Display::fmt(&knight_strings::dump(text), f),
 			Self::List(list) => Debug::fmt(list, f),
 			Self::Variable(variable) => Debug::fmt(variable, f),
 			Self::Ast(ast) => Debug::fmt(ast, f),
+			Self::NativeFn(native) => Debug::fmt(native, f),
 
 			#[cfg(feature = "custom-types")]
 			Self::Custom(custom) => Debug::fmt(custom, f),
@@ -97,6 +102,13 @@ impl From<Ast> for Value {
 	}
 }
 
+impl From<NativeFn> for Value {
+	#[inline]
+	fn from(native: NativeFn) -> Self {
+		Self::NativeFn(native)
+	}
+}
+
 impl From<List> for Value {
 	#[inline]
 	fn from(list: List) -> Self {
@@ -185,6 +197,7 @@ impl Runnable for Value {
 		match self {
 			Self::Variable(variable) => variable.run(env),
 			Self::Ast(ast) => ast.run(env),
+			Self::NativeFn(native) => native.run(env),
 
 			#[cfg(feature = "custom-types")]
 			Self::Custom(custom) => custom.run(env),
@@ -205,6 +218,7 @@ impl Value {
 			Self::Text(_) => Text::TYPENAME,
 			Self::List(_) => List::TYPENAME,
 			Self::Ast(_) => Ast::TYPENAME,
+			Self::NativeFn(_) => NativeFn::TYPENAME,
 			Self::Variable(_) => Variable::TYPENAME,
 
 			#[cfg(feature = "custom-types")]
@@ -216,19 +230,40 @@ impl Value {
 	///
 	/// # Errors
 	/// If [`check_call_arg`](crate::env::flags::Compliance::check_call_arg) is enabled and `self`
-	/// isn't a [`Value::Ast`], This will return a `TypeError`. Errors that result from calling
+	/// isn't a [`Value::Ast`] (or a [`Value::NativeFn`]), This will return a `TypeError`. Errors that result from calling
 	/// [`run`](Self::run) are also propogated.
 	pub fn call(&self, env: &mut Environment) -> Result<Self> {
 		// When ensuring that `CALL` is only given values returned from `BLOCK`, we must ensure that
 		// all arguments are `Value::Ast`s.
 		#[cfg(feature = "compliance")]
-		if env.flags().compliance.check_call_arg && !matches!(self, Value::Ast(_)) {
+		if env.flags().compliance.check_call_arg
+			&& !matches!(self, Value::Ast(_) | Value::NativeFn(_))
+		{
 			return Err(Error::TypeError(self.typename(), "CALL"));
 		}
 
 		self.run(env)
 	}
 
+	/// Writes the debug representation of `self` to `out`, as [`DUMP`](crate::function::DUMP) does.
+	///
+	/// # Errors
+	/// Any errors from `out` are returned.
+	pub fn kn_dump<W: std::io::Write + ?Sized>(&self, out: &mut W) -> std::io::Result<()> {
+		write!(out, "{self:?}")
+	}
+
+	/// Returns the debug representation of `self`, as [`DUMP`](crate::function::DUMP) would write
+	/// it.
+	///
+	/// This is useful for comparing values against what the Knight spec expects `DUMP` to print,
+	/// without having to capture stdout.
+	#[must_use]
+	pub fn dump_to_string(&self, env: &Environment) -> String {
+		let _ = env;
+		format!("{self:?}")
+	}
+
 	/// Gets the first element of `self`.
 	///
 	/// # Extensions
@@ -245,7 +280,7 @@ impl Value {
 			Self::Text(text) => text
 				.head()
 				.ok_or(Error::DomainError("empty text"))
-				.map(|chr| unsafe { Text::new_unchecked(chr) }.into()),
+				.map(|chr| unsafe { Text::from_char_unchecked(chr) }.into()),
 
 			#[cfg(feature = "extensions")]
 			Self::Integer(integer) if env.flags().extensions.types.integer => Ok(integer.head().into()),
@@ -296,7 +331,7 @@ impl Value {
 		match self {
 			Self::Integer(integer) => Ok({
 				let chr = integer.chr(env.flags())?;
-				unsafe { Text::new_unchecked(chr) }.into()
+				unsafe { Text::from_char_unchecked(chr) }.into()
 			}),
 			Self::Text(text) => Ok(text.ord()?.into()),
 
@@ -358,7 +393,8 @@ impl Value {
 				let amount = usize::try_from(rhs.to_integer(env)?)
 					.or(Err(Error::DomainError("repetition count is negative")))?;
 
-				if amount.checked_mul(lstr.len()).map_or(true, |c| isize::MAX as usize <= c) {
+				let max = env.flags().max_repetition_length;
+				if amount.checked_mul(lstr.len()).map_or(true, |c| max < c) {
 					return Err(Error::DomainError("repetition is too large"));
 				}
 
@@ -483,28 +519,26 @@ impl Value {
 		}
 	}
 
+	/// Compares `self` with `rhs`, as per `<`, `>`, and `XCMP`.
+	///
+	/// `rhs` is converted to `self`'s type first, unless they're already the same type.
 	pub fn compare(&self, rhs: &Self, env: &mut Environment) -> Result<Ordering> {
-		match self {
-			Value::Integer(integer) => Ok(integer.cmp(&rhs.to_integer(env)?)),
-			Value::Boolean(boolean) => Ok(boolean.cmp(&rhs.to_boolean(env)?)),
-			Value::Text(text) => Ok(text.cmp(&rhs.to_text(env)?)),
-			Value::List(list) => {
-				let rhs = rhs.to_list(env)?;
-
-				for (left, right) in list.iter().zip(&rhs) {
-					match left.compare(right, env)? {
-						Ordering::Equal => {}
-						other => return Ok(other),
-					}
-				}
-
-				Ok(list.len().cmp(&rhs.len()))
-			}
+		match (self, rhs) {
+			// Values of the same type don't need to convert `rhs` at all.
+			(Value::Integer(lhs), Value::Integer(rhs)) => Ok(lhs.cmp(rhs)),
+			(Value::Boolean(lhs), Value::Boolean(rhs)) => Ok(lhs.cmp(rhs)),
+			(Value::Text(lhs), Value::Text(rhs)) => Ok(lhs.cmp(rhs)),
+			(Value::List(lhs), Value::List(rhs)) => lhs.compare(rhs, env),
+
+			(Value::Integer(integer), _) => Ok(integer.cmp(&rhs.to_integer(env)?)),
+			(Value::Boolean(boolean), _) => Ok(boolean.cmp(&rhs.to_boolean(env)?)),
+			(Value::Text(text), _) => Ok(text.cmp(&rhs.to_text(env)?)),
+			(Value::List(list), _) => list.compare(&rhs.to_list(env)?, env),
 
 			#[cfg(feature = "custom-types")]
-			Self::Custom(custom) => custom.compare(rhs, env),
+			(Self::Custom(custom), _) => custom.compare(rhs, env),
 
-			other => Err(Error::TypeError(other.typename(), "<cmp>")),
+			(other, _) => Err(Error::TypeError(other.typename(), "<cmp>")),
 		}
 	}
 
@@ -519,7 +553,9 @@ impl Value {
 						}
 						Ok(())
 					}
-					Value::Ast(_) | Value::Variable(_) => Err(Error::TypeError(value.typename(), "?")),
+					Value::Ast(_) | Value::NativeFn(_) | Value::Variable(_) => {
+						Err(Error::TypeError(value.typename(), "?"))
+					}
 					_ => Ok(()),
 				}
 			}
@@ -615,13 +651,14 @@ impl Value {
 		let start = fix_len(self, start.to_integer(env)?, env)?;
 		let len =
 			usize::try_from(len.to_integer(env)?).or(Err(Error::DomainError("negative length")))?;
+		let end = start.checked_add(len).ok_or(Error::DomainError("end position is too large"))?;
 
 		match self {
-			Self::List(list) => list.try_get(start..start + len).map(Self::from),
+			Self::List(list) => list.try_get(start..end).map(Self::from),
 
 			Self::Text(text) => text
-				.get(start..start + len)
-				.ok_or(Error::IndexOutOfBounds { len: text.len(), index: start + len })
+				.get(start..end)
+				.ok_or(Error::IndexOutOfBounds { len: text.len(), index: end })
 				.map(ToOwned::to_owned)
 				.map(Self::from),
 
@@ -636,9 +673,26 @@ impl Value {
 		replacement: Self,
 		env: &mut Environment,
 	) -> Result<Self> {
+		let mut ret = self.clone();
+		ret.set_in_place(start, len, replacement, env)?;
+		Ok(ret)
+	}
+
+	/// The same as [`Value::set`], except `self` is modified instead of returning a new value.
+	///
+	/// If nothing else refers to `self`'s contents, they're modified in place instead of copied.
+	/// `self` is left unchanged if an error occurs.
+	pub fn set_in_place(
+		&mut self,
+		start: &Self,
+		len: &Self,
+		replacement: Self,
+		env: &mut Environment,
+	) -> Result<()> {
 		#[cfg(feature = "custom-types")]
 		if let Self::Custom(custom) = self {
-			return custom.set(start, len, replacement, env);
+			*self = custom.set(start, len, replacement, env)?;
+			return Ok(());
 		}
 
 		let start = fix_len(self, start.to_integer(env)?, env)?;
@@ -647,28 +701,27 @@ impl Value {
 
 		match self {
 			Self::List(list) => {
-				// OPTIMIZE ME: cons?
 				let replacement = replacement.to_list(env)?;
-				let mut ret = Vec::new();
-
-				ret.extend(list.iter().take(start).cloned());
-				ret.extend(replacement.iter().cloned());
-				ret.extend(list.iter().skip((start) + len).cloned());
-
-				List::new(ret, env.flags()).map(Self::from)
+				list.set_in_place(start, len, &replacement, env.flags())
 			}
 			Self::Text(text) => {
 				let replacement = replacement.to_text(env)?;
-
-				// lol, todo, optimize me
-				let mut builder = Text::builder();
-				builder.push(text.get(..start).unwrap());
-				builder.push(&replacement);
-				builder.push(text.get(start + len..).unwrap());
-				Ok(builder.finish(env.flags())?.into())
+				text.set_in_place(start, len, &replacement, env.flags())
 			}
 
-			other => return Err(Error::TypeError(other.typename(), "SET")),
+			other => Err(Error::TypeError(other.typename(), "SET")),
+		}
+	}
+
+	/// Whether `self` and `rhs` are the same text or list, rather than just equal ones.
+	///
+	/// Other types are never considered the same.
+	#[must_use]
+	pub fn ptr_eq(&self, rhs: &Self) -> bool {
+		match (self, rhs) {
+			(Self::Text(lhs), Self::Text(rhs)) => lhs.ptr_eq(rhs),
+			(Self::List(lhs), Self::List(rhs)) => lhs.ptr_eq(rhs),
+			_ => false,
 		}
 	}
 }
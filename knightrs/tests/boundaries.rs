@@ -0,0 +1,86 @@
+//! `GET` and `SET` with positions and lengths near the limits of `i32`, `i64`, and `usize`, which
+//! should be reported as errors rather than panicking or wrapping.
+
+use knightrs::env::{Environment, Flags};
+use knightrs::value::text::TextSlice;
+use knightrs::value::Value;
+use knightrs::Error;
+
+fn play(flags: &Flags, source: &str) -> knightrs::Result<Value> {
+	let mut env = Environment::new(flags);
+	let source = TextSlice::new(source, env.flags()).unwrap();
+	env.play(source)
+}
+
+// The `GET` and `SET` programs for `start` and `len` on each kind of container.
+fn programs(start: &str, len: &str) -> Vec<String> {
+	[r#""abc""#, "+@123"]
+		.into_iter()
+		.flat_map(|container| {
+			[
+				format!("GET {container} {start} {len}"),
+				format!("SET {container} {start} {len} {container}"),
+			]
+		})
+		.collect()
+}
+
+#[test]
+fn get_and_set_at_integer_and_usize_limits() {
+	let flags = Flags::default();
+	let min = format!("(- ~{} 1)", i64::MAX);
+
+	// The largest position that's both an integer and a `usize`, which is `i64::MAX` on 64-bit
+	// targets and `usize::MAX` on narrower ones.
+	let max = i64::try_from(usize::MAX).unwrap_or(i64::MAX).to_string();
+
+	for (start, len) in [(&*max, "1"), ("1", &*max), (&*max, &*max), ("3", &*max), ("4", "0")] {
+		for program in programs(start, len) {
+			let result = play(&flags, &program);
+			assert!(matches!(result, Err(Error::IndexOutOfBounds { .. })), "{program}: {result:?}");
+		}
+	}
+
+	for (start, len) in [(&*min, "1"), ("0", &*min), ("~1", "1"), ("0", "~1")] {
+		for program in programs(start, len) {
+			let result = play(&flags, &program);
+			assert!(matches!(result, Err(Error::DomainError(_))), "{program}: {result:?}");
+		}
+	}
+
+	// Ranges that end exactly at the end are fine.
+	for program in programs("3", "0").into_iter().chain(programs("0", "3")) {
+		assert!(play(&flags, &program).is_ok(), "{program}");
+	}
+}
+
+#[test]
+#[cfg(feature = "compliance")]
+fn get_and_set_at_i32_limits() {
+	let mut flags = Flags::default();
+	flags.compliance.i32_integer = true;
+	let max = i32::MAX.to_string();
+
+	for (start, len) in [(&*max, "1"), ("1", &*max), (&*max, &*max)] {
+		for program in programs(start, len) {
+			let result = play(&flags, &program);
+			assert!(matches!(result, Err(Error::IndexOutOfBounds { .. })), "{program}: {result:?}");
+		}
+	}
+}
+
+#[test]
+#[cfg(feature = "extensions")]
+fn negative_indices_at_i64_limits() {
+	let mut flags = Flags::default();
+	flags.extensions.negative_indexing = true;
+
+	assert_eq!(play(&flags, r#"GET "abc" ~3 1"#).unwrap(), play(&flags, r#""a""#).unwrap());
+
+	for start in [format!("~{}", i64::MAX), format!("(- ~{} 1)", i64::MAX), "~4".to_owned()] {
+		for program in programs(&start, "1") {
+			let result = play(&flags, &program);
+			assert!(matches!(result, Err(Error::DomainError(_))), "{program}: {result:?}");
+		}
+	}
+}
@@ -0,0 +1,63 @@
+//! `OUTPUT`'s newlines, and when what it writes is flushed.
+
+use knightrs::env::flags::{FlushPolicy, Newline};
+use knightrs::env::{Environment, Flags};
+use knightrs::testing::run;
+use knightrs::value::text::TextSlice;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn crlf_newlines() {
+	let mut flags = Flags::default();
+	flags.io.newline = Newline::CrLf;
+
+	let outcome = run(r#"; OUTPUT "a" ; OUTPUT "b\" : OUTPUT 1"#, "", &flags);
+	assert_eq!(outcome.stdout, "a\r\nb1\r\n");
+}
+
+// A stdout which records what had been written to it each time it was flushed.
+#[derive(Clone, Default)]
+struct Flushes {
+	written: Arc<Mutex<Vec<u8>>>,
+	flushes: Arc<Mutex<Vec<String>>>,
+}
+
+impl Write for Flushes {
+	fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+		self.written.lock().unwrap().extend_from_slice(bytes);
+		Ok(bytes.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		let written = String::from_utf8(self.written.lock().unwrap().clone()).unwrap();
+		self.flushes.lock().unwrap().push(written);
+		Ok(())
+	}
+}
+
+// Runs a program which outputs before and after a `PROMPT` under `policy`, returning what had been
+// written each time stdout was flushed.
+fn flushes(policy: FlushPolicy) -> Vec<String> {
+	let mut flags = Flags::default();
+	flags.io.flush = policy;
+	let stdout = Flushes::default();
+
+	let mut env = Environment::new(&flags);
+	env.output().set_stdout(stdout.clone());
+	env.prompt().set_stdin(&b"line\n"[..]);
+
+	let source = TextSlice::new(r#"; OUTPUT "a" ; PROMPT : OUTPUT "b""#, &flags).unwrap();
+	env.play(source).unwrap();
+
+	let flushes = stdout.flushes.lock().unwrap().clone();
+	flushes
+}
+
+#[test]
+fn flush_policies() {
+	assert_eq!(flushes(FlushPolicy::EveryOutput), ["a\n", "a\n", "a\nb\n"]);
+	assert_eq!(flushes(FlushPolicy::Line), ["a\n"]);
+	assert_eq!(flushes(FlushPolicy::OnExit), ["a\n"]);
+	assert!(flushes(FlushPolicy::Never).is_empty());
+}
@@ -0,0 +1,72 @@
+//! `= foo SET foo ...` should modify `foo`'s value in place when nothing else refers to it, and
+//! copy it otherwise.
+
+use knightrs::env::{Environment, Flags};
+use knightrs::value::text::TextSlice;
+use knightrs::value::Value;
+
+fn play(env: &mut Environment, source: &str) -> Value {
+	let source = TextSlice::new(source, env.flags()).unwrap();
+	env.play(source).unwrap()
+}
+
+// The address of the contents of the variable `name`, which changes only when they're copied.
+fn address_of(env: &mut Environment, name: &str) -> usize {
+	let name = TextSlice::new(name, env.flags()).unwrap();
+
+	match env.lookup(name).unwrap().fetch().unwrap() {
+		Value::Text(text) => text.as_str().as_ptr() as usize,
+		Value::List(list) => list.iter().next().unwrap() as *const Value as usize,
+		other => panic!("{other:?} isn't a container"),
+	}
+}
+
+#[test]
+fn text_is_modified_in_place() {
+	let flags = Flags::default();
+	let mut env = Environment::new(&flags);
+
+	play(&mut env, r#"= s * "ab" 3"#);
+	let before = address_of(&mut env, "s");
+
+	play(&mut env, r#"= s SET s 1 2 "XY""#);
+	assert_eq!(before, address_of(&mut env, "s"));
+	assert_eq!(play(&mut env, "s"), play(&mut env, r#""aXYbab""#));
+
+	// `t` refers to the same text, so `s` has to be copied.
+	play(&mut env, r#"; = t s : = s SET s 0 1 "Z""#);
+	assert_ne!(before, address_of(&mut env, "s"));
+	assert_eq!(play(&mut env, "s"), play(&mut env, r#""ZXYbab""#));
+	assert_eq!(play(&mut env, "t"), play(&mut env, r#""aXYbab""#));
+}
+
+#[test]
+fn list_is_modified_in_place() {
+	let flags = Flags::default();
+	let mut env = Environment::new(&flags);
+
+	// `SET` always returns a list that's backed by a single slice, which is what's modified in place.
+	play(&mut env, "= l SET (+ ,1 ,2) 0 0 @");
+	let before = address_of(&mut env, "l");
+
+	play(&mut env, "; = i 0 : WHILE (< i 100) ; = l SET l 0 1 ,i : = i + i 1");
+	assert_eq!(before, address_of(&mut env, "l"));
+	assert_eq!(play(&mut env, "l"), play(&mut env, "+ ,99 ,2"));
+
+	// `m` refers to the same list, so `l` has to be copied.
+	play(&mut env, "; = m l : = l SET l 1 1 ,3");
+	assert_ne!(before, address_of(&mut env, "l"));
+	assert_eq!(play(&mut env, "l"), play(&mut env, "+ ,99 ,3"));
+	assert_eq!(play(&mut env, "m"), play(&mut env, "+ ,99 ,2"));
+}
+
+#[test]
+fn failed_set_leaves_the_variable_alone() {
+	let flags = Flags::default();
+	let mut env = Environment::new(&flags);
+
+	play(&mut env, r#"= s * "ab" 2"#);
+	let source = TextSlice::new(r#"= s SET s 10 1 "X""#, env.flags()).unwrap();
+	assert!(env.play(source).is_err());
+	assert_eq!(play(&mut env, "s"), play(&mut env, r#""abab""#));
+}
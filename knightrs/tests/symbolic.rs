@@ -0,0 +1,31 @@
+//! Extension functions which are given single-character names via `ExtensionFunction::symbolic`.
+
+#![cfg(feature = "extensions")]
+
+use knightrs::env::{Builder, Flags};
+use knightrs::function::XREVERSE;
+use knightrs::value::text::TextSlice;
+use knightrs::Error;
+
+#[test]
+fn symbols_can_be_called() {
+	for symbol in ['$', '`', 'λ'] {
+		let flags = Flags::default();
+		let mut builder = Builder::new(&flags);
+		builder.functions().insert(XREVERSE().symbolic(symbol).unwrap());
+		let mut env = builder.build();
+
+		let source = format!("{symbol} +@123");
+		let result = env.play(TextSlice::new(&source, &flags).unwrap()).unwrap();
+		let expected = env.play(TextSlice::new("+@321", &flags).unwrap()).unwrap();
+		assert_eq!(result, expected, "{symbol:?}");
+	}
+}
+
+#[test]
+fn symbols_which_arent_functions_are_rejected() {
+	for symbol in ['a', '_', 'Z', '1', ' ', '\n', '(', ')', '#', '"', '\''] {
+		let result = XREVERSE().symbolic(symbol);
+		assert!(matches!(result, Err(Error::InvalidFunctionSymbol(s)) if s == symbol), "{symbol:?}");
+	}
+}